@@ -0,0 +1,8 @@
+CREATE TABLE templates
+(
+    id_template       TEXT                                NOT NULL
+        CONSTRAINT templates_pk PRIMARY KEY,
+    name              TEXT                                NOT NULL,
+    content           TEXT                                NOT NULL,
+    created_date_time TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,2 @@
+DROP TABLE time_entries;
+DROP TABLE task_estimates;
\ No newline at end of file
@@ -0,0 +1,19 @@
+CREATE TABLE time_entries
+(
+    id_time_entry     TEXT      NOT NULL
+        CONSTRAINT time_entries_pk PRIMARY KEY,
+    task_id           TEXT      NOT NULL,
+    list_id           TEXT      NOT NULL,
+    start_date_time   TIMESTAMP NOT NULL,
+    end_date_time     TIMESTAMP
+);
+
+CREATE INDEX time_entries_task_id_index ON time_entries (task_id);
+CREATE INDEX time_entries_list_id_index ON time_entries (list_id);
+
+CREATE TABLE task_estimates
+(
+    id_task  TEXT    NOT NULL
+        CONSTRAINT task_estimates_pk PRIMARY KEY,
+    estimate INTEGER NOT NULL
+);
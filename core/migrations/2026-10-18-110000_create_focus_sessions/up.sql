@@ -0,0 +1,13 @@
+CREATE TABLE focus_sessions
+(
+    id_focus_session  TEXT      NOT NULL
+        CONSTRAINT focus_sessions_pk PRIMARY KEY,
+    task_id           TEXT      NOT NULL,
+    list_id           TEXT      NOT NULL,
+    start_date_time   TIMESTAMP NOT NULL,
+    end_date_time     TIMESTAMP NOT NULL,
+    completed         BOOLEAN   DEFAULT false NOT NULL
+);
+
+CREATE INDEX focus_sessions_task_id_index ON focus_sessions (task_id);
+CREATE INDEX focus_sessions_start_date_time_index ON focus_sessions (start_date_time);
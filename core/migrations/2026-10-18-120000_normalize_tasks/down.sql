@@ -0,0 +1,105 @@
+CREATE TABLE old_tasks
+(
+    id_task                 TEXT                                NOT NULL
+        CONSTRAINT tasks_pk PRIMARY KEY,
+    parent                  TEXT                                NOT NULL,
+    title                   TEXT                                NOT NULL,
+    notes                   TEXT                                NOT NULL,
+    priority                INTEGER   DEFAULT 1                 NOT NULL,
+    favorite                BOOLEAN   DEFAULT false             NOT NULL,
+    status                  INTEGER   DEFAULT 1                 NOT NULL,
+    completion_date         TIMESTAMP,
+    due_date                TIMESTAMP,
+    reminder_date           TIMESTAMP,
+    created_date_time       TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_modified_date_time TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    sub_tasks               TEXT      DEFAULT "[]"              NOT NULL,
+    tags                    TEXT      DEFAULT "[]"              NOT NULL,
+    today                   BOOLEAN   DEFAULT false             NOT NULL,
+    deletion_date           TIMESTAMP,
+    recurrence              TEXT
+);
+
+CREATE TEMPORARY VIEW task_json AS
+SELECT tasks.*,
+       (SELECT json_group_array(name)
+        FROM (SELECT tags.name
+              FROM task_tags
+                       JOIN tags ON tags.id_tag = task_tags.id_tag
+              WHERE task_tags.id_task = tasks.id_task
+              ORDER BY task_tags.position))                AS tag_names,
+       substr(iif(recurrence & 1, ', Mon', '') ||
+              iif(recurrence & 2, ', Tue', '') ||
+              iif(recurrence & 4, ', Wed', '') ||
+              iif(recurrence & 8, ', Thu', '') ||
+              iif(recurrence & 16, ', Fri', '') ||
+              iif(recurrence & 32, ', Sat', '') ||
+              iif(recurrence & 64, ', Sun', ''), 3) AS recurrence_days
+FROM tasks;
+
+INSERT INTO old_tasks
+SELECT task.id_task,
+       task.parent,
+       task.title,
+       task.notes,
+       task.priority,
+       task.favorite,
+       task.status,
+       task.completion_date,
+       task.due_date,
+       task.reminder_date,
+       task.created_date_time,
+       task.last_modified_date_time,
+       (SELECT json_group_array(json_object(
+               'id', id_task,
+               'parent', parent,
+               'title', title,
+               'favorite', json(iif(favorite, 'true', 'false')),
+               'today', json(iif(today, 'true', 'false')),
+               'status', iif(status = 1, 'Completed', 'NotStarted'),
+               'priority', CASE priority
+                               WHEN 1 THEN 'Normal'
+                               WHEN 2 THEN 'High'
+                               ELSE 'Low' END,
+               'sub_tasks', json('[]'),
+               'tags', json(tag_names),
+               'notes', notes,
+               'completion_date',
+               strftime('%Y-%m-%dT%H:%M:%fZ', completion_date),
+               'deletion_date', strftime('%Y-%m-%dT%H:%M:%fZ', deletion_date),
+               'due_date', strftime('%Y-%m-%dT%H:%M:%fZ', due_date),
+               'reminder_date', strftime('%Y-%m-%dT%H:%M:%fZ', reminder_date),
+               'recurrence', json_object(
+                       'monday', json(iif(recurrence & 1, 'true', 'false')),
+                       'tuesday', json(iif(recurrence & 2, 'true', 'false')),
+                       'wednesday', json(iif(recurrence & 4, 'true', 'false')),
+                       'thursday', json(iif(recurrence & 8, 'true', 'false')),
+                       'friday', json(iif(recurrence & 16, 'true', 'false')),
+                       'saturday', json(iif(recurrence & 32, 'true', 'false')),
+                       'sunday', json(iif(recurrence & 64, 'true', 'false'))),
+               'created_date_time',
+               strftime('%Y-%m-%dT%H:%M:%fZ', created_date_time),
+               'last_modified_date_time',
+               strftime('%Y-%m-%dT%H:%M:%fZ', last_modified_date_time)))
+        FROM (SELECT *
+              FROM task_json
+              WHERE task_json.parent_task = task.id_task
+              ORDER BY position)),
+       task.tag_names,
+       task.today,
+       task.deletion_date,
+       task.recurrence_days
+FROM task_json AS task
+WHERE task.parent_task IS NULL;
+
+DROP VIEW task_json;
+DROP TABLE task_tags;
+DROP TABLE tags;
+DROP TABLE tasks;
+
+ALTER TABLE old_tasks RENAME TO tasks;
+
+CREATE TRIGGER remove_tasks_on_list_delete BEFORE DELETE ON lists BEGIN
+DELETE FROM tasks
+WHERE tasks.parent = old.id_list;
+END;
@@ -0,0 +1,184 @@
+-- Tasks move to a table of their own so the list can be a foreign key,
+-- sub-tasks become rows and tags get their own tables.
+DROP TRIGGER IF EXISTS remove_tasks_on_list_delete;
+
+ALTER TABLE tasks RENAME TO old_tasks;
+
+-- Tasks whose list is gone get one back instead of being dropped.
+INSERT INTO lists (id_list, name, description)
+SELECT DISTINCT parent, 'Recovered tasks', ''
+FROM old_tasks
+WHERE parent NOT IN (SELECT id_list FROM lists);
+
+CREATE TABLE tasks
+(
+    id_task                 TEXT                                NOT NULL
+        CONSTRAINT tasks_pk PRIMARY KEY,
+    parent                  TEXT                                NOT NULL
+        REFERENCES lists (id_list) ON DELETE CASCADE,
+    parent_task             TEXT
+        REFERENCES tasks (id_task) ON DELETE CASCADE,
+    position                INTEGER   DEFAULT 0                 NOT NULL,
+    title                   TEXT                                NOT NULL,
+    favorite                BOOLEAN   DEFAULT false             NOT NULL,
+    today                   BOOLEAN   DEFAULT false             NOT NULL,
+    status                  INTEGER   DEFAULT 0                 NOT NULL,
+    priority                INTEGER   DEFAULT 0                 NOT NULL,
+    notes                   TEXT      DEFAULT ''                NOT NULL,
+    completion_date         TIMESTAMP,
+    deletion_date           TIMESTAMP,
+    due_date                TIMESTAMP,
+    reminder_date           TIMESTAMP,
+    recurrence              INTEGER   DEFAULT 0                 NOT NULL,
+    created_date_time       TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_modified_date_time TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX tasks_parent_index ON tasks (parent);
+CREATE INDEX tasks_parent_task_index ON tasks (parent_task);
+CREATE INDEX tasks_due_date_index ON tasks (due_date);
+CREATE INDEX tasks_status_index ON tasks (status);
+
+CREATE TABLE tags
+(
+    id_tag INTEGER NOT NULL
+        CONSTRAINT tags_pk PRIMARY KEY AUTOINCREMENT,
+    name   TEXT    NOT NULL
+        CONSTRAINT tags_name_unique UNIQUE
+);
+
+CREATE TABLE task_tags
+(
+    id_task  TEXT    NOT NULL
+        REFERENCES tasks (id_task) ON DELETE CASCADE,
+    id_tag   INTEGER NOT NULL
+        REFERENCES tags (id_tag) ON DELETE CASCADE,
+    position INTEGER DEFAULT 0 NOT NULL,
+    CONSTRAINT task_tags_pk PRIMARY KEY (id_task, id_tag)
+);
+
+CREATE INDEX task_tags_id_tag_index ON task_tags (id_tag);
+
+-- Recurrence was a list of day names, it is now a bit per day starting
+-- with Monday.
+INSERT INTO tasks (id_task, parent, title, favorite, today, status, priority,
+                   notes, completion_date, deletion_date, due_date,
+                   reminder_date, recurrence, created_date_time,
+                   last_modified_date_time)
+SELECT id_task,
+       parent,
+       title,
+       favorite,
+       today,
+       status,
+       priority,
+       ifnull(notes, ''),
+       completion_date,
+       deletion_date,
+       due_date,
+       reminder_date,
+       (instr(ifnull(recurrence, ''), 'Mon') > 0) * 1
+           + (instr(ifnull(recurrence, ''), 'Tue') > 0) * 2
+           + (instr(ifnull(recurrence, ''), 'Wed') > 0) * 4
+           + (instr(ifnull(recurrence, ''), 'Thu') > 0) * 8
+           + (instr(ifnull(recurrence, ''), 'Fri') > 0) * 16
+           + (instr(ifnull(recurrence, ''), 'Sat') > 0) * 32
+           + (instr(ifnull(recurrence, ''), 'Sun') > 0) * 64,
+       created_date_time,
+       last_modified_date_time
+FROM old_tasks;
+
+-- Sub-tasks were serialized tasks, ids that are missing or taken are
+-- replaced by new ones.
+WITH sub_tasks AS (SELECT old_tasks.id_task           AS parent_task,
+                          old_tasks.parent            AS parent,
+                          old_tasks.created_date_time AS created_date_time,
+                          sub_task.key                AS position,
+                          sub_task.value              AS value,
+                          json_extract(sub_task.value, '$.id') AS id
+                   FROM old_tasks,
+                        json_each(old_tasks.sub_tasks) AS sub_task
+                   WHERE json_valid(old_tasks.sub_tasks)
+                     AND sub_task.type = 'object'),
+     numbered AS (SELECT *, row_number() OVER (PARTITION BY id) AS occurrence
+                  FROM sub_tasks)
+INSERT
+INTO tasks (id_task, parent, parent_task, position, title, favorite, today,
+            status, priority, notes, completion_date, deletion_date, due_date,
+            reminder_date, recurrence, created_date_time,
+            last_modified_date_time)
+SELECT CASE
+           WHEN ifnull(id, '') = '' OR occurrence > 1
+               OR id IN (SELECT id_task FROM old_tasks)
+               THEN lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) ||
+                          '-4' || substr(hex(randomblob(2)), 2) || '-' ||
+                          substr('89ab', 1 + (abs(random()) % 4), 1) ||
+                          substr(hex(randomblob(2)), 2) || '-' ||
+                          hex(randomblob(6)))
+           ELSE id
+           END,
+       parent,
+       parent_task,
+       position,
+       ifnull(json_extract(value, '$.title'), ''),
+       ifnull(json_extract(value, '$.favorite'), false),
+       ifnull(json_extract(value, '$.today'), false),
+       ifnull(json_extract(value, '$.status') = 'Completed', false),
+       CASE json_extract(value, '$.priority')
+           WHEN 'Normal' THEN 1
+           WHEN 'High' THEN 2
+           ELSE 0
+           END,
+       ifnull(json_extract(value, '$.notes'), ''),
+       strftime('%Y-%m-%d %H:%M:%f', json_extract(value, '$.completion_date')),
+       strftime('%Y-%m-%d %H:%M:%f', json_extract(value, '$.deletion_date')),
+       strftime('%Y-%m-%d %H:%M:%f', json_extract(value, '$.due_date')),
+       strftime('%Y-%m-%d %H:%M:%f', json_extract(value, '$.reminder_date')),
+       ifnull(json_extract(value, '$.recurrence.monday'), false) * 1
+           + ifnull(json_extract(value, '$.recurrence.tuesday'), false) * 2
+           + ifnull(json_extract(value, '$.recurrence.wednesday'), false) * 4
+           + ifnull(json_extract(value, '$.recurrence.thursday'), false) * 8
+           + ifnull(json_extract(value, '$.recurrence.friday'), false) * 16
+           + ifnull(json_extract(value, '$.recurrence.saturday'), false) * 32
+           + ifnull(json_extract(value, '$.recurrence.sunday'), false) * 64,
+       ifnull(strftime('%Y-%m-%d %H:%M:%f',
+                       json_extract(value, '$.created_date_time')),
+              created_date_time),
+       ifnull(strftime('%Y-%m-%d %H:%M:%f',
+                       json_extract(value, '$.last_modified_date_time')),
+              created_date_time)
+FROM numbered;
+
+-- Tags of tasks and of their sub-tasks, in the order they were in.
+CREATE TEMPORARY TABLE task_tag_names AS
+SELECT old_tasks.id_task AS id_task,
+       tag.key           AS position,
+       tag.value         AS name
+FROM old_tasks,
+     json_each(old_tasks.tags) AS tag
+WHERE json_valid(old_tasks.tags)
+UNION ALL
+SELECT tasks.id_task, tag.key, tag.value
+FROM old_tasks,
+     json_each(old_tasks.sub_tasks) AS sub_task
+         JOIN tasks
+              ON tasks.parent_task = old_tasks.id_task
+                  AND tasks.position = sub_task.key,
+     json_each(sub_task.value, '$.tags') AS tag
+WHERE json_valid(old_tasks.sub_tasks)
+  AND sub_task.type = 'object';
+
+INSERT OR IGNORE INTO tags (name)
+SELECT DISTINCT name
+FROM task_tag_names
+WHERE typeof(name) = 'text'
+  AND name != '';
+
+INSERT OR IGNORE INTO task_tags (id_task, id_tag, position)
+SELECT task_tag_names.id_task, tags.id_tag, task_tag_names.position
+FROM task_tag_names
+         JOIN tags ON tags.name = task_tag_names.name;
+
+DROP TABLE task_tag_names;
+
+DROP TABLE old_tasks;
@@ -0,0 +1 @@
+DROP TRIGGER remove_time_on_task_delete;
@@ -0,0 +1,14 @@
+-- Time is also tracked for tasks of other services, which have no row in
+-- tasks, so entries and estimates can't reference it. They are removed
+-- along with the local tasks they belong to instead, lists deleted with
+-- their tasks included.
+DELETE FROM time_entries
+WHERE list_id IN (SELECT id_list FROM lists)
+  AND task_id NOT IN (SELECT id_task FROM tasks);
+
+CREATE TRIGGER remove_time_on_task_delete AFTER DELETE ON tasks BEGIN
+DELETE FROM time_entries
+WHERE time_entries.task_id = old.id_task;
+DELETE FROM task_estimates
+WHERE task_estimates.id_task = old.id_task;
+END;
@@ -0,0 +1,126 @@
+use std::fmt::Display;
+
+use reqwest::StatusCode;
+
+use crate::services::accounts::SignInRequired;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// What went wrong in a service, so frontends can react to each failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+	/// The list, task or file doesn't exist.
+	NotFound(String),
+	/// The account was signed out or must be signed in to again.
+	Unauthorized(String),
+	/// The service couldn't be reached.
+	Network(String),
+	/// The item already exists or was changed elsewhere.
+	Conflict(String),
+	/// Reading or writing the database, files or keyring failed.
+	Storage(String),
+	/// Stored or received data couldn't be understood.
+	InvalidData(String),
+	/// The service can't do this.
+	Unsupported(String),
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Error::NotFound(message) => write!(f, "Not found: {message}"),
+			Error::Unauthorized(message) => write!(f, "Unauthorized: {message}"),
+			Error::Network(message) => write!(f, "Network error: {message}"),
+			Error::Conflict(message) => write!(f, "Conflict: {message}"),
+			Error::Storage(message) => write!(f, "Storage error: {message}"),
+			Error::InvalidData(message) => write!(f, "Invalid data: {message}"),
+			Error::Unsupported(message) => write!(f, "Unsupported: {message}"),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl From<diesel::result::Error> for Error {
+	fn from(error: diesel::result::Error) -> Self {
+		match error {
+			diesel::result::Error::NotFound => Error::NotFound(error.to_string()),
+			diesel::result::Error::DatabaseError(
+				diesel::result::DatabaseErrorKind::UniqueViolation,
+				_,
+			) => Error::Conflict(error.to_string()),
+			diesel::result::Error::DeserializationError(_) => {
+				Error::InvalidData(error.to_string())
+			},
+			_ => Error::Storage(error.to_string()),
+		}
+	}
+}
+
+impl From<reqwest::Error> for Error {
+	fn from(error: reqwest::Error) -> Self {
+		match error.status() {
+			Some(StatusCode::UNAUTHORIZED) => Error::Unauthorized(error.to_string()),
+			Some(StatusCode::NOT_FOUND) => Error::NotFound(error.to_string()),
+			Some(StatusCode::CONFLICT | StatusCode::PRECONDITION_FAILED) => {
+				Error::Conflict(error.to_string())
+			},
+			_ if error.is_decode() => Error::InvalidData(error.to_string()),
+			_ => Error::Network(error.to_string()),
+		}
+	}
+}
+
+impl From<std::io::Error> for Error {
+	fn from(error: std::io::Error) -> Self {
+		match error.kind() {
+			std::io::ErrorKind::NotFound => Error::NotFound(error.to_string()),
+			std::io::ErrorKind::AlreadyExists => Error::Conflict(error.to_string()),
+			std::io::ErrorKind::InvalidData => Error::InvalidData(error.to_string()),
+			_ => Error::Storage(error.to_string()),
+		}
+	}
+}
+
+impl From<serde_json::Error> for Error {
+	fn from(error: serde_json::Error) -> Self {
+		Error::InvalidData(error.to_string())
+	}
+}
+
+impl From<graph_rs_sdk::GraphFailure> for Error {
+	fn from(error: graph_rs_sdk::GraphFailure) -> Self {
+		Error::Network(error.to_string())
+	}
+}
+
+/// Most of done_core still uses [`anyhow`] internally, its errors are sorted
+/// by the error they wrap, and count as storage errors otherwise.
+impl From<anyhow::Error> for Error {
+	fn from(error: anyhow::Error) -> Self {
+		let error = match error.downcast::<Error>() {
+			Ok(error) => return error,
+			Err(error) => error,
+		};
+		let message = format!("{error:#}");
+		if error.is::<SignInRequired>() {
+			return Error::Unauthorized(message);
+		}
+		let error = match error.downcast::<reqwest::Error>() {
+			Ok(error) => return error.into(),
+			Err(error) => error,
+		};
+		let error = match error.downcast::<diesel::result::Error>() {
+			Ok(error) => return error.into(),
+			Err(error) => error,
+		};
+		let error = match error.downcast::<std::io::Error>() {
+			Ok(error) => return error.into(),
+			Err(error) => error,
+		};
+		if error.is::<serde_json::Error>() {
+			return Error::InvalidData(message);
+		}
+		Error::Storage(message)
+	}
+}
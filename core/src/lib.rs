@@ -1,5 +1,8 @@
+mod error;
 pub mod models;
 pub(crate) mod schema;
 pub mod service;
 pub mod services;
 pub(crate) mod task_service;
+
+pub use error::{Error, Result};
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use crate::service::Service;
+
+/// An account signed in to a service, a service can have several.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+	/// The id the service gives the account.
+	pub id: String,
+	pub display_name: String,
+	pub service: Service,
+	/// Whether the lists of this account are shown.
+	#[serde(default = "enabled")]
+	pub enabled: bool,
+	/// Whether the credentials of the account stopped working, until the user
+	/// signs in to it again.
+	#[serde(default)]
+	pub needs_sign_in: bool,
+}
+
+fn enabled() -> bool {
+	true
+}
+
+impl Account {
+	pub fn new(id: &str, display_name: &str, service: Service) -> Self {
+		Self {
+			id: id.to_string(),
+			display_name: display_name.to_string(),
+			service,
+			enabled: true,
+			needs_sign_in: false,
+		}
+	}
+}
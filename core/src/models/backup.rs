@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupSettings {
+	pub enabled: bool,
+	pub interval_hours: u32,
+	pub keep: u32,
+}
+
+impl Default for BackupSettings {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			interval_hours: 24,
+			keep: 7,
+		}
+	}
+}
+
+/// A copy of the local database taken at a point in time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+	pub path: PathBuf,
+	pub created_date_time: DateTime<Utc>,
+	pub task_count: Option<i64>,
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::service::Service;
+
+use super::{list::List, task::Task};
+
+/// Version of the bundle written by [`ListBundle::to_json`], bump it whenever
+/// the format changes in a way older readers can't parse.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// One list and its tasks in a file of its own, to hand a list to someone
+/// else. Ids are kept so importing a newer bundle of the same list updates
+/// the earlier import instead of duplicating it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListBundle {
+	pub version: u32,
+	pub exported_date_time: DateTime<Utc>,
+	pub list: List,
+	pub tasks: Vec<Task>,
+}
+
+/// How a bundle is imported into a service.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BundleImport {
+	/// Creates the list the first time, afterwards updates the tasks changed
+	/// in the bundle since they were last imported, matched by id.
+	Update,
+	/// Creates another list with new ids, unrelated to earlier imports.
+	Copy,
+}
+
+/// What importing a bundle did.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BundleReport {
+	pub list: String,
+	pub created: usize,
+	pub updated: usize,
+	pub unchanged: usize,
+}
+
+impl ListBundle {
+	/// Reads a list and its tasks from the service it belongs to.
+	pub async fn export(list: &List) -> Result<Self> {
+		let tasks = list
+			.service
+			.get_service()
+			.read_tasks_from_list(list.id.clone())
+			.await?;
+		// Whose account the list was in means nothing to whoever imports it.
+		let list = List {
+			account: None,
+			..list.clone()
+		};
+		Ok(Self {
+			version: BUNDLE_VERSION,
+			exported_date_time: Utc::now(),
+			list,
+			tasks,
+		})
+	}
+
+	pub fn to_json(&self) -> Result<String> {
+		serde_json::to_string_pretty(self).context("Failed to serialize list.")
+	}
+
+	pub fn from_json(json: &str) -> Result<Self> {
+		let bundle: Self =
+			serde_json::from_str(json).context("Failed to parse list.")?;
+		if bundle.version > BUNDLE_VERSION {
+			bail!(
+				"List version {} is newer than the supported version {BUNDLE_VERSION}.",
+				bundle.version
+			);
+		}
+		Ok(bundle)
+	}
+
+	/// Whether the list was imported into `service` before, so it can be
+	/// updated.
+	pub async fn imported(&self, service: Service) -> bool {
+		service
+			.get_service()
+			.read_list(self.list.id.clone())
+			.await
+			.is_ok()
+	}
+
+	/// Imports the list and its tasks into `service`.
+	pub async fn import(
+		&self,
+		service: Service,
+		mode: BundleImport,
+	) -> Result<BundleReport> {
+		let mut report = BundleReport {
+			list: self.list.name.clone(),
+			..Default::default()
+		};
+		let mut provider = service.get_service();
+		let mut list = self.list.clone();
+		list.service = service;
+
+		if mode == BundleImport::Copy {
+			list.id = Uuid::new_v4().to_string();
+			let list = provider.create_list(list).await?;
+			for task in &self.tasks {
+				if let Err(err) = provider.create_task(task.copy_to(&list.id)).await {
+					if let Err(err) = provider.delete_list(list.id.clone()).await {
+						tracing::error!("Failed to delete {}: {err}", list.name);
+					}
+					return Err(err.into());
+				}
+				report.created += 1;
+			}
+			return Ok(report);
+		}
+
+		let local: HashMap<String, Task> =
+			if provider.read_list(list.id.clone()).await.is_ok() {
+				provider.update_list(list.clone()).await?;
+				provider
+					.read_tasks_from_list(list.id.clone())
+					.await?
+					.into_iter()
+					.map(|task| (task.id.clone(), task))
+					.collect()
+			} else {
+				// Services that assign ids of their own won't recognize the
+				// list when it's imported again.
+				list.id = provider.create_list(list.clone()).await?.id;
+				HashMap::new()
+			};
+		// Tasks only in the local list are kept, they may have been added
+		// after importing.
+		for task in &self.tasks {
+			let mut task = task.clone();
+			task.parent = list.id.clone();
+			match local.get(&task.id) {
+				Some(existing)
+					if task.last_modified_date_time
+						> existing.last_modified_date_time =>
+				{
+					provider.update_task(task).await?;
+					report.updated += 1;
+				},
+				Some(_) => report.unchanged += 1,
+				None => {
+					provider.create_task(task).await?;
+					report.created += 1;
+				},
+			}
+		}
+		Ok(report)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::Duration;
+
+	use crate::service::Services;
+
+	use super::*;
+
+	fn bundle(list: &List, tasks: Vec<Task>) -> ListBundle {
+		ListBundle {
+			version: BUNDLE_VERSION,
+			exported_date_time: Utc::now(),
+			list: list.clone(),
+			tasks,
+		}
+	}
+
+	fn task(list: &List, title: &str) -> Task {
+		let mut task = Task::new(title.into(), list.id.clone());
+		task.last_modified_date_time = Utc::now() - Duration::days(1);
+		task
+	}
+
+	async fn titles(list: &List) -> Vec<String> {
+		let mut titles: Vec<String> = Service::Computer
+			.get_service()
+			.read_tasks_from_list(list.id.clone())
+			.await
+			.unwrap()
+			.into_iter()
+			.map(|task| task.title)
+			.collect();
+		titles.sort();
+		titles
+	}
+
+	#[tokio::test]
+	async fn updates_merge_tasks_by_id() {
+		Services::init("dev.edfloreshz.Done.Scratch");
+		let list = List::new("Shared", Service::Microsoft);
+		let (kept, changed) = (task(&list, "Kept"), task(&list, "Draft"));
+		let first = bundle(&list, vec![kept.clone(), changed.clone()]);
+		assert!(!first.imported(Service::Computer).await);
+
+		let report = first
+			.import(Service::Computer, BundleImport::Update)
+			.await
+			.unwrap();
+		assert_eq!(
+			(report.created, report.updated, report.unchanged),
+			(2, 0, 0)
+		);
+		assert!(first.imported(Service::Computer).await);
+
+		// Tasks added locally after importing stay.
+		let mut provider = Service::Computer.get_service();
+		provider.create_task(task(&list, "Local")).await.unwrap();
+
+		let mut newer = changed.clone();
+		newer.title = "Final".into();
+		newer.last_modified_date_time = Utc::now();
+		let second = bundle(&list, vec![kept, newer, task(&list, "Added")]);
+		let report = second
+			.import(Service::Computer, BundleImport::Update)
+			.await
+			.unwrap();
+		assert_eq!(
+			(report.created, report.updated, report.unchanged),
+			(1, 1, 1)
+		);
+		assert_eq!(titles(&list).await, vec!["Added", "Final", "Kept", "Local"]);
+
+		// An older bundle doesn't undo what changed since.
+		let report = first
+			.import(Service::Computer, BundleImport::Update)
+			.await
+			.unwrap();
+		assert_eq!(
+			(report.created, report.updated, report.unchanged),
+			(0, 0, 2)
+		);
+		assert_eq!(titles(&list).await, vec!["Added", "Final", "Kept", "Local"]);
+
+		provider.delete_list(list.id).await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn copies_get_new_ids() {
+		Services::init("dev.edfloreshz.Done.Scratch");
+		let list = List::new(&Uuid::new_v4().to_string(), Service::Microsoft);
+		let original = task(&list, "Task");
+		let report = bundle(&list, vec![original.clone()])
+			.import(Service::Computer, BundleImport::Copy)
+			.await
+			.unwrap();
+		assert_eq!(report.created, 1);
+
+		let mut provider = Service::Computer.get_service();
+		let copy = provider
+			.read_lists()
+			.await
+			.unwrap()
+			.into_iter()
+			.find(|copy| copy.name == list.name)
+			.unwrap();
+		assert_ne!(copy.id, list.id);
+		assert_eq!(copy.service, Service::Computer);
+		let tasks = provider
+			.read_tasks_from_list(copy.id.clone())
+			.await
+			.unwrap();
+		assert_eq!(tasks.len(), 1);
+		assert_ne!(tasks[0].id, original.id);
+		provider.delete_list(copy.id).await.unwrap();
+	}
+
+	#[test]
+	fn newer_bundles_are_refused() {
+		let list = List::new("Shared", Service::Computer);
+		let mut bundle = bundle(&list, vec![]);
+		bundle.version = BUNDLE_VERSION + 1;
+		assert!(ListBundle::from_json(&bundle.to_json().unwrap()).is_err());
+		bundle.version = BUNDLE_VERSION;
+		assert_eq!(
+			ListBundle::from_json(&bundle.to_json().unwrap()).unwrap(),
+			bundle
+		);
+	}
+}
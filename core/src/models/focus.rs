@@ -0,0 +1,166 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PomodoroSettings {
+	pub work_minutes: u32,
+	pub short_break_minutes: u32,
+	pub long_break_minutes: u32,
+	pub sessions_before_long_break: u32,
+}
+
+impl Default for PomodoroSettings {
+	fn default() -> Self {
+		Self {
+			work_minutes: 25,
+			short_break_minutes: 5,
+			long_break_minutes: 15,
+			sessions_before_long_break: 4,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FocusPhase {
+	Work,
+	ShortBreak,
+	LongBreak,
+}
+
+/// Pomodoro state machine, alternates work phases with short breaks and
+/// takes a long break after every few work sessions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FocusTimer {
+	pub settings: PomodoroSettings,
+	pub phase: FocusPhase,
+	pub remaining: Duration,
+	pub completed_sessions: u32,
+	pub phase_start_date_time: DateTime<Utc>,
+	pub paused: bool,
+}
+
+impl FocusTimer {
+	pub fn new(settings: PomodoroSettings) -> Self {
+		Self {
+			settings,
+			phase: FocusPhase::Work,
+			remaining: Self::duration_of(&settings, FocusPhase::Work),
+			completed_sessions: 0,
+			phase_start_date_time: Utc::now(),
+			paused: false,
+		}
+	}
+
+	fn duration_of(settings: &PomodoroSettings, phase: FocusPhase) -> Duration {
+		let minutes = match phase {
+			FocusPhase::Work => settings.work_minutes,
+			FocusPhase::ShortBreak => settings.short_break_minutes,
+			FocusPhase::LongBreak => settings.long_break_minutes,
+		};
+		Duration::minutes(minutes.max(1) as i64)
+	}
+
+	/// Advances the timer, returns the phase that finished if any.
+	pub fn tick(&mut self, elapsed: Duration) -> Option<FocusPhase> {
+		if self.paused {
+			return None;
+		}
+		self.remaining = self.remaining - elapsed;
+		if self.remaining <= Duration::zero() {
+			Some(self.skip())
+		} else {
+			None
+		}
+	}
+
+	/// Ends the current phase right away and returns it.
+	pub fn skip(&mut self) -> FocusPhase {
+		let finished = self.phase;
+		self.phase = match finished {
+			FocusPhase::Work => {
+				self.completed_sessions += 1;
+				let cycle = self.settings.sessions_before_long_break.max(1);
+				if self.completed_sessions.is_multiple_of(cycle) {
+					FocusPhase::LongBreak
+				} else {
+					FocusPhase::ShortBreak
+				}
+			},
+			FocusPhase::ShortBreak | FocusPhase::LongBreak => FocusPhase::Work,
+		};
+		self.remaining = Self::duration_of(&self.settings, self.phase);
+		self.phase_start_date_time = Utc::now();
+		finished
+	}
+
+	pub fn toggle_pause(&mut self) {
+		self.paused = !self.paused;
+	}
+}
+
+/// A work phase spent on a task, `completed` is false when the phase was
+/// stopped before it finished.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FocusSession {
+	pub id: String,
+	pub task_id: String,
+	pub list_id: String,
+	pub start_date_time: DateTime<Utc>,
+	pub end_date_time: DateTime<Utc>,
+	pub completed: bool,
+}
+
+impl FocusSession {
+	pub fn new(
+		task_id: String,
+		list_id: String,
+		start_date_time: DateTime<Utc>,
+		completed: bool,
+	) -> Self {
+		Self {
+			id: Uuid::new_v4().to_string(),
+			task_id,
+			list_id,
+			start_date_time,
+			end_date_time: Utc::now(),
+			completed,
+		}
+	}
+
+	pub fn duration(&self) -> Duration {
+		self.end_date_time - self.start_date_time
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FocusStatistics {
+	pub completed_sessions: usize,
+	pub interrupted_sessions: usize,
+	pub focused_time: Duration,
+}
+
+impl Default for FocusStatistics {
+	fn default() -> Self {
+		Self {
+			completed_sessions: 0,
+			interrupted_sessions: 0,
+			focused_time: Duration::zero(),
+		}
+	}
+}
+
+impl FromIterator<FocusSession> for FocusStatistics {
+	fn from_iter<T: IntoIterator<Item = FocusSession>>(iter: T) -> Self {
+		let mut statistics = Self::default();
+		for session in iter {
+			if session.completed {
+				statistics.completed_sessions += 1;
+			} else {
+				statistics.interrupted_sessions += 1;
+			}
+			statistics.focused_time = statistics.focused_time + session.duration();
+		}
+		statistics
+	}
+}
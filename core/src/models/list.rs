@@ -14,6 +14,33 @@ pub struct List {
 	pub description: String,
 	pub icon: Option<String>,
 	pub service: Service,
+	#[serde(default)]
+	pub sharing: Sharing,
+	/// The name of the account a list belongs to, for services with several.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub account: Option<String>,
+}
+
+/// Who a list is shared with, only some services share lists.
+#[derive(
+	Clone,
+	Copy,
+	Default,
+	Debug,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Serialize,
+	Deserialize,
+)]
+pub enum Sharing {
+	#[default]
+	Private,
+	/// Owned by the user and shared with others.
+	SharedByMe,
+	/// Owned by someone else, it can't be renamed or deleted.
+	SharedWithMe,
 }
 
 impl FromIterator<List> for List {
@@ -34,6 +61,8 @@ impl List {
 			service,
 			description: String::new(),
 			icon: Some("✍️".to_string()),
+			sharing: Sharing::Private,
+			account: None,
 		}
 	}
 }
@@ -48,6 +77,12 @@ impl From<TodoTaskList> for List {
 			description: String::new(),
 			icon,
 			service: Service::Microsoft,
+			sharing: match (task.is_shared, task.is_owner) {
+				(false, _) => Sharing::Private,
+				(true, true) => Sharing::SharedByMe,
+				(true, false) => Sharing::SharedWithMe,
+			},
+			account: None,
 		}
 	}
 }
@@ -60,8 +95,8 @@ impl From<List> for TodoTaskList {
 		Self {
 			id: list.id,
 			display_name,
-			is_owner: true,
-			is_shared: false,
+			is_owner: list.sharing != Sharing::SharedWithMe,
+			is_shared: list.sharing != Sharing::Private,
 			wellknown_list_name: Default::default(),
 		}
 	}
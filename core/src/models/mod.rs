@@ -2,8 +2,28 @@ pub mod task;
 
 pub mod list;
 
+pub mod smart_list;
+
 pub mod priority;
 
 pub mod status;
 
 pub mod recurrence;
+
+pub mod template;
+
+pub mod time_entry;
+
+pub mod focus;
+
+pub mod statistics;
+
+pub mod backup;
+
+pub mod workspace;
+
+pub mod bundle;
+
+pub mod account;
+
+pub mod profile;
@@ -1,4 +1,4 @@
-use crate::services::microsoft::models::importance::TaskImportance;
+use crate::{services::microsoft::models::importance::TaskImportance, Error};
 use serde::{Deserialize, Serialize};
 
 #[derive(
@@ -21,13 +21,15 @@ pub enum Priority {
 	High = 2,
 }
 
-impl From<i32> for Priority {
-	fn from(value: i32) -> Self {
+impl TryFrom<i32> for Priority {
+	type Error = Error;
+
+	fn try_from(value: i32) -> Result<Self, Self::Error> {
 		match value {
-			0 => Priority::Low,
-			1 => Priority::Normal,
-			2 => Priority::High,
-			_ => panic!("Invalid value for Priority"),
+			0 => Ok(Priority::Low),
+			1 => Ok(Priority::Normal),
+			2 => Ok(Priority::High),
+			_ => Err(Error::InvalidData(format!("{value} is not a priority."))),
 		}
 	}
 }
@@ -0,0 +1,69 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_NAME: &str = "Default";
+
+/// A named set of a local database, services and preferences, to keep work
+/// and personal tasks apart.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+	pub name: String,
+}
+
+impl Profile {
+	pub fn new(name: &str) -> Self {
+		Self {
+			name: name.trim().to_string(),
+		}
+	}
+
+	/// The profile that existed before profiles did.
+	pub fn is_default(&self) -> bool {
+		self.slug() == Self::default().slug()
+	}
+
+	/// The name reduced to lowercase letters, digits and underscores, which
+	/// is safe in paths and application ids.
+	pub fn slug(&self) -> String {
+		let slug: String = self
+			.name
+			.chars()
+			.map(|c| {
+				if c.is_ascii_alphanumeric() {
+					c.to_ascii_lowercase()
+				} else {
+					'_'
+				}
+			})
+			.collect();
+		// Parts of an application id can't start with a digit.
+		if slug.starts_with(|c: char| c.is_ascii_digit()) {
+			format!("_{slug}")
+		} else {
+			slug
+		}
+	}
+
+	/// The id the data of the profile is stored under. The default profile
+	/// keeps the application id, so its data stays where it was.
+	pub fn app_id(&self, app_id: &str) -> String {
+		if self.is_default() {
+			app_id.to_string()
+		} else {
+			format!("{app_id}.{}", self.slug())
+		}
+	}
+}
+
+impl Default for Profile {
+	fn default() -> Self {
+		Self::new(DEFAULT_NAME)
+	}
+}
+
+impl Display for Profile {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.name)
+	}
+}
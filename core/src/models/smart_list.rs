@@ -0,0 +1,37 @@
+use chrono::{DateTime, Duration, Utc};
+
+use super::{status::Status, task::Task};
+
+/// Lists that gather tasks from every list of a service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmartList {
+	All,
+	Today,
+	Starred,
+	Next7Days,
+	Done,
+}
+
+impl SmartList {
+	/// Whether a task belongs in this list.
+	pub fn contains(&self, task: &Task) -> bool {
+		match self {
+			SmartList::All => true,
+			SmartList::Today => {
+				task.today
+					|| task
+						.due_date
+						.is_some_and(|due| due.date_naive() == Utc::now().date_naive())
+			},
+			SmartList::Starred => task.favorite,
+			SmartList::Next7Days => task.due_date.is_some_and(is_within_next_7_days),
+			SmartList::Done => task.status == Status::Completed,
+		}
+	}
+}
+
+fn is_within_next_7_days(date: DateTime<Utc>) -> bool {
+	let now = Utc::now();
+	let next_7_days = now + Duration::days(7);
+	date >= now && date <= next_7_days
+}
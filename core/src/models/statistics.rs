@@ -0,0 +1,322 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+
+use crate::service::Service;
+
+use super::{list::List, priority::Priority, status::Status, task::Task};
+
+/// Task counts for a single list, priority or tag.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Breakdown {
+	pub name: String,
+	pub total: usize,
+	pub completed: usize,
+	pub overdue: usize,
+}
+
+impl Breakdown {
+	fn new(name: impl Into<String>) -> Self {
+		Self {
+			name: name.into(),
+			..Default::default()
+		}
+	}
+
+	fn count(&mut self, task: &Task, now: DateTime<Utc>) {
+		self.total += 1;
+		if task.status == Status::Completed {
+			self.completed += 1;
+		}
+		if is_overdue(task, now) {
+			self.overdue += 1;
+		}
+	}
+}
+
+/// Productivity statistics computed from the tasks of one or more services.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statistics {
+	pub generated_date_time: DateTime<Utc>,
+	pub total: usize,
+	pub completed: usize,
+	pub overdue: usize,
+	/// Completed tasks per day of completion.
+	pub completed_per_day: BTreeMap<NaiveDate, usize>,
+	/// Completed tasks per week, keyed by the Monday starting the week.
+	pub completed_per_week: BTreeMap<NaiveDate, usize>,
+	/// Average time from creation to completion of completed tasks.
+	pub average_lead_time: Option<Duration>,
+	pub by_list: Vec<Breakdown>,
+	pub by_priority: Vec<Breakdown>,
+	pub by_tag: Vec<Breakdown>,
+	/// Services left out because their tasks couldn't be read.
+	pub skipped: Vec<Service>,
+}
+
+impl Statistics {
+	/// Computes the statistics of the given tasks, `lists` is used to name the
+	/// per list breakdown.
+	pub fn new(lists: &[List], tasks: &[Task], now: DateTime<Utc>) -> Self {
+		let mut completed_per_day = BTreeMap::new();
+		let mut completed_per_week = BTreeMap::new();
+		let mut lead_times = vec![];
+		let mut by_list: Vec<Breakdown> = lists
+			.iter()
+			.map(|list| Breakdown::new(list.name.as_str()))
+			.collect();
+		let mut by_priority: Vec<Breakdown> =
+			[Priority::Low, Priority::Normal, Priority::High]
+				.iter()
+				.map(|priority| Breakdown::new(priority.as_str_name()))
+				.collect();
+		let mut by_tag: BTreeMap<String, Breakdown> = BTreeMap::new();
+
+		for task in tasks {
+			if let Some(index) = lists.iter().position(|list| list.id == task.parent)
+			{
+				by_list[index].count(task, now);
+			}
+			by_priority[priority_index(task.priority)].count(task, now);
+			for tag in &task.tags {
+				by_tag
+					.entry(tag.clone())
+					.or_insert_with(|| Breakdown::new(tag.as_str()))
+					.count(task, now);
+			}
+
+			if task.status != Status::Completed {
+				continue;
+			}
+			if let Some(completion_date) = task.completion_date {
+				let day = completion_date.date_naive();
+				let week =
+					day - Duration::days(day.weekday().num_days_from_monday() as i64);
+				*completed_per_day.entry(day).or_insert(0) += 1;
+				*completed_per_week.entry(week).or_insert(0) += 1;
+				lead_times.push(completion_date - task.created_date_time);
+			}
+		}
+
+		let average_lead_time = (!lead_times.is_empty()).then(|| {
+			let total = lead_times
+				.iter()
+				.fold(Duration::zero(), |total, lead_time| total + *lead_time);
+			total / lead_times.len() as i32
+		});
+
+		Self {
+			generated_date_time: now,
+			total: tasks.len(),
+			completed: tasks
+				.iter()
+				.filter(|task| task.status == Status::Completed)
+				.count(),
+			overdue: tasks.iter().filter(|task| is_overdue(task, now)).count(),
+			completed_per_day,
+			completed_per_week,
+			average_lead_time,
+			by_list,
+			by_priority,
+			by_tag: by_tag.into_values().collect(),
+			skipped: vec![],
+		}
+	}
+
+	/// Reads every list and task of the given services and computes their
+	/// statistics, services that are not available are skipped. Those that
+	/// fail to be read are skipped too and listed in `skipped`.
+	pub async fn collect(services: &[Service]) -> Self {
+		let mut lists = vec![];
+		let mut tasks = vec![];
+		let mut skipped = vec![];
+		for service in services {
+			if *service == Service::Smart {
+				continue;
+			}
+			match read_service(*service).await {
+				Ok(Some((service_lists, service_tasks))) => {
+					lists.extend(service_lists);
+					tasks.extend(service_tasks);
+				},
+				Ok(None) => (),
+				Err(err) => {
+					tracing::error!("Skipping {service} in statistics: {err}");
+					skipped.push(*service);
+				},
+			}
+		}
+		Self {
+			skipped,
+			..Self::new(&lists, &tasks, Utc::now())
+		}
+	}
+
+	/// Completed tasks for each of the last `days` days, oldest first.
+	pub fn last_days(&self, days: i64) -> Vec<(NaiveDate, usize)> {
+		let today = self.generated_date_time.date_naive();
+		(0..days)
+			.rev()
+			.map(|offset| {
+				let day = today - Duration::days(offset);
+				(day, self.completed_per_day.get(&day).copied().unwrap_or(0))
+			})
+			.collect()
+	}
+}
+
+impl Display for Statistics {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "Tasks: {}", self.total)?;
+		writeln!(f, "Completed: {}", self.completed)?;
+		writeln!(f, "Overdue: {}", self.overdue)?;
+		match self.average_lead_time {
+			Some(lead_time) => writeln!(
+				f,
+				"Average lead time: {}h {:02}m",
+				lead_time.num_hours(),
+				lead_time.num_minutes() % 60
+			)?,
+			None => writeln!(f, "Average lead time: -")?,
+		}
+		if !self.skipped.is_empty() {
+			let skipped: Vec<String> =
+				self.skipped.iter().map(ToString::to_string).collect();
+			writeln!(f, "Not read: {}", skipped.join(", "))?;
+		}
+		writeln!(f, "\nCompleted per week:")?;
+		for (week, count) in &self.completed_per_week {
+			writeln!(f, "  {week}: {count}")?;
+		}
+		for (title, breakdowns) in [
+			("By list", &self.by_list),
+			("By priority", &self.by_priority),
+			("By tag", &self.by_tag),
+		] {
+			writeln!(f, "\n{title}:")?;
+			for breakdown in breakdowns {
+				writeln!(
+					f,
+					"  {}: {} total, {} completed, {} overdue",
+					breakdown.name,
+					breakdown.total,
+					breakdown.completed,
+					breakdown.overdue
+				)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Reads the lists and tasks of a service, `None` if it isn't available.
+async fn read_service(
+	service: Service,
+) -> Result<Option<(Vec<List>, Vec<Task>)>> {
+	let mut provider = service.get_service();
+	if !provider.available() {
+		return Ok(None);
+	}
+	let mut lists = vec![];
+	let mut tasks = vec![];
+	for list in provider.read_lists().await? {
+		tasks.extend(provider.read_tasks_from_list(list.id.clone()).await?);
+		lists.push(list);
+	}
+	Ok(Some((lists, tasks)))
+}
+
+/// The position of a priority in `Statistics::by_priority`.
+fn priority_index(priority: Priority) -> usize {
+	match priority {
+		Priority::Low => 0,
+		Priority::Normal => 1,
+		Priority::High => 2,
+	}
+}
+
+fn is_overdue(task: &Task, now: DateTime<Utc>) -> bool {
+	task.status != Status::Completed
+		&& task.due_date.is_some_and(|due_date| due_date < now)
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+
+	use super::*;
+
+	fn date(day: u32, hour: u32) -> DateTime<Utc> {
+		Utc.with_ymd_and_hms(2024, 5, day, hour, 0, 0).unwrap()
+	}
+
+	fn completed(parent: &str, created: u32, completed: u32) -> Task {
+		let mut task = Task::new("Done".into(), parent.into());
+		task.status = Status::Completed;
+		task.created_date_time = date(created, 8);
+		task.completion_date = Some(date(completed, 8));
+		task
+	}
+
+	fn pending(parent: &str, due: u32) -> Task {
+		let mut task = Task::new("Pending".into(), parent.into());
+		task.due_date = Some(date(due, 8));
+		task
+	}
+
+	#[test]
+	fn completed_tasks_are_counted_per_day_and_week() {
+		// 2024-05-06 is a Monday.
+		let tasks = vec![
+			completed("list", 1, 6),
+			completed("list", 1, 6),
+			completed("list", 1, 8),
+			completed("list", 1, 13),
+		];
+		let statistics = Statistics::new(&[], &tasks, date(14, 12));
+
+		let day = |day| date(day, 0).date_naive();
+		assert_eq!(statistics.completed, 4);
+		assert_eq!(statistics.completed_per_day.get(&day(6)), Some(&2));
+		assert_eq!(statistics.completed_per_day.get(&day(8)), Some(&1));
+		assert_eq!(statistics.completed_per_week.get(&day(6)), Some(&3));
+		assert_eq!(statistics.completed_per_week.get(&day(13)), Some(&1));
+		assert_eq!(statistics.last_days(2), vec![(day(13), 1), (day(14), 0)]);
+	}
+
+	#[test]
+	fn lead_time_is_averaged_over_completed_tasks() {
+		let tasks = vec![
+			completed("list", 1, 2),
+			completed("list", 1, 4),
+			pending("list", 20),
+		];
+		let statistics = Statistics::new(&[], &tasks, date(10, 0));
+		assert_eq!(statistics.average_lead_time, Some(Duration::days(2)));
+
+		let statistics = Statistics::new(&[], &[pending("list", 20)], date(10, 0));
+		assert_eq!(statistics.average_lead_time, None);
+	}
+
+	#[test]
+	fn overdue_tasks_are_counted_per_list_and_priority() {
+		let list = List::new("Work", Service::Computer);
+		let mut late = pending(&list.id, 5);
+		late.priority = Priority::High;
+		let mut done_late = completed(&list.id, 1, 9);
+		done_late.due_date = Some(date(5, 8));
+		let tasks = vec![late, done_late, pending(&list.id, 20)];
+		let statistics = Statistics::new(&[list], &tasks, date(10, 0));
+
+		assert_eq!(statistics.total, 3);
+		assert_eq!(statistics.overdue, 1);
+		assert_eq!(statistics.by_list[0].overdue, 1);
+		assert_eq!(statistics.by_list[0].total, 3);
+		let high = &statistics.by_priority[priority_index(Priority::High)];
+		assert_eq!(high.name, "HIGH");
+		assert_eq!((high.total, high.overdue), (1, 1));
+		let low = &statistics.by_priority[priority_index(Priority::Low)];
+		assert_eq!((low.total, low.completed, low.overdue), (2, 1, 0));
+	}
+}
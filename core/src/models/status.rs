@@ -1,4 +1,4 @@
-use crate::services::microsoft::models::status::TaskStatus;
+use crate::{services::microsoft::models::status::TaskStatus, Error};
 use serde::{Deserialize, Serialize};
 
 #[derive(
@@ -20,12 +20,14 @@ pub enum Status {
 	Completed = 1,
 }
 
-impl From<i32> for Status {
-	fn from(value: i32) -> Self {
+impl TryFrom<i32> for Status {
+	type Error = Error;
+
+	fn try_from(value: i32) -> Result<Self, Self::Error> {
 		match value {
-			0 => Self::NotStarted,
-			1 => Status::Completed,
-			_ => panic!("Invalid value for Status"),
+			0 => Ok(Self::NotStarted),
+			1 => Ok(Status::Completed),
+			_ => Err(Error::InvalidData(format!("{value} is not a status."))),
 		}
 	}
 }
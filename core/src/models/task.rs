@@ -4,10 +4,14 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::services::microsoft::models::{
-	body::{BodyType, ItemBody},
-	checklist_item::ChecklistItem,
-	task::TodoTask,
+use crate::{
+	services::microsoft::models::{
+		body::{BodyType, ItemBody},
+		checklist_item::ChecklistItem,
+		date_time_zone::DateTimeTimeZone,
+		task::TodoTask,
+	},
+	Error,
 };
 
 use super::{priority::Priority, recurrence::Recurrence, status::Status};
@@ -67,41 +71,61 @@ impl Task {
 			last_modified_date_time: now,
 		}
 	}
+
+	/// A copy of the task in another list, it and its sub-tasks get new ids
+	/// since ids are unique across lists.
+	pub fn copy_to(&self, parent: &str) -> Self {
+		let mut task = self.clone();
+		task.id = Uuid::new_v4().to_string();
+		task.parent = parent.to_string();
+		for sub_task in &mut task.sub_tasks {
+			sub_task.id = Uuid::new_v4().to_string();
+			sub_task.parent = task.id.clone();
+		}
+		task
+	}
 }
 
-impl From<TodoTask> for Task {
-	fn from(task: TodoTask) -> Self {
-		Self {
+fn date_time(
+	date: Option<DateTimeTimeZone>,
+) -> Result<Option<DateTime<Utc>>, Error> {
+	date.map(DateTime::<Utc>::try_from).transpose()
+}
+
+fn timestamp(date: &str) -> Result<DateTime<Utc>, Error> {
+	DateTime::<Utc>::from_str(date)
+		.map_err(|err| Error::InvalidData(format!("Invalid date {date}: {err}")))
+}
+
+impl TryFrom<TodoTask> for Task {
+	type Error = Error;
+
+	fn try_from(task: TodoTask) -> Result<Self, Self::Error> {
+		let reminder_date = date_time(task.reminder_date_time)?;
+		Ok(Self {
 			id: task.id,
 			parent: String::new(),
 			title: task.title,
 			favorite: false,
-			today: task.reminder_date_time.is_some()
-				&& Into::<DateTime<Utc>>::into(
-					task.reminder_date_time.clone().unwrap(),
-				) == Utc::now(),
+			today: reminder_date == Some(Utc::now()),
 			status: task.status.into(),
 			priority: task.importance.into(),
 			sub_tasks: task
 				.checklist_items
 				.unwrap_or_default()
-				.iter()
-				.map(|item| item.clone().into())
-				.collect(),
+				.into_iter()
+				.map(Task::try_from)
+				.collect::<Result<_, _>>()?,
 			tags: vec![],
 			notes: task.body.content,
-			completion_date: task.completed_date_time.map(|date| date.into()),
+			completion_date: date_time(task.completed_date_time)?,
 			deletion_date: None,
-			due_date: task.due_date_time.map(|date| date.into()),
-			reminder_date: task.reminder_date_time.map(|date| date.into()),
+			due_date: date_time(task.due_date_time)?,
+			reminder_date,
 			recurrence: task.recurrence.unwrap_or_default().into(),
-			created_date_time: DateTime::<Utc>::from_str(&task.created_date_time)
-				.unwrap(),
-			last_modified_date_time: DateTime::<Utc>::from_str(
-				&task.last_modified_date_time,
-			)
-			.unwrap(),
-		}
+			created_date_time: timestamp(&task.created_date_time)?,
+			last_modified_date_time: timestamp(&task.last_modified_date_time)?,
+		})
 	}
 }
 
@@ -139,9 +163,15 @@ impl From<Task> for TodoTask {
 	}
 }
 
-impl From<ChecklistItem> for Task {
-	fn from(value: ChecklistItem) -> Self {
-		Self {
+impl TryFrom<ChecklistItem> for Task {
+	type Error = Error;
+
+	fn try_from(value: ChecklistItem) -> Result<Self, Self::Error> {
+		let created_date_time = match &value.created_date_time {
+			Some(date) => timestamp(date)?,
+			None => Utc::now(),
+		};
+		Ok(Self {
 			id: value.id,
 			title: value.display_name,
 			status: if value.is_checked {
@@ -149,12 +179,9 @@ impl From<ChecklistItem> for Task {
 			} else {
 				Status::NotStarted
 			},
-			created_date_time: DateTime::<Utc>::from_str(
-				&value.created_date_time.unwrap_or_default(),
-			)
-			.unwrap(),
+			created_date_time,
 			..Default::default()
-		}
+		})
 	}
 }
 
@@ -0,0 +1,218 @@
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::service::Service;
+
+use super::{
+	list::List, priority::Priority, recurrence::Recurrence, task::Task,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Template {
+	pub id: String,
+	pub name: String,
+	pub content: TemplateContent,
+	pub created_date_time: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TemplateContent {
+	Task(TaskTemplate),
+	List(ListTemplate),
+}
+
+/// A reusable task, dates are stored as offsets in seconds from the
+/// start of the day the task was created.
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskTemplate {
+	pub title: String,
+	pub notes: String,
+	pub favorite: bool,
+	pub priority: Priority,
+	pub tags: Vec<String>,
+	pub recurrence: Recurrence,
+	pub due_offset: Option<i64>,
+	pub reminder_offset: Option<i64>,
+	pub sub_tasks: Vec<TaskTemplate>,
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListTemplate {
+	pub name: String,
+	pub description: String,
+	pub icon: Option<String>,
+	pub tasks: Vec<TaskTemplate>,
+}
+
+impl Template {
+	pub fn from_task(task: &Task) -> Self {
+		Self::new(
+			task.title.clone(),
+			TemplateContent::Task(TaskTemplate::from(task)),
+		)
+	}
+
+	pub fn from_list(list: &List, tasks: &[Task]) -> Self {
+		Self::new(
+			list.name.clone(),
+			TemplateContent::List(ListTemplate::from_list(list, tasks)),
+		)
+	}
+
+	fn new(name: String, content: TemplateContent) -> Self {
+		Self {
+			id: Uuid::new_v4().to_string(),
+			name,
+			content,
+			created_date_time: Utc::now(),
+		}
+	}
+
+	pub fn is_task(&self) -> bool {
+		matches!(self.content, TemplateContent::Task(_))
+	}
+
+	pub fn is_list(&self) -> bool {
+		matches!(self.content, TemplateContent::List(_))
+	}
+}
+
+impl From<&Task> for TaskTemplate {
+	fn from(task: &Task) -> Self {
+		let start = start_of_day(task.created_date_time);
+		Self {
+			title: task.title.clone(),
+			notes: task.notes.clone(),
+			favorite: task.favorite,
+			priority: task.priority,
+			tags: task.tags.clone(),
+			recurrence: task.recurrence,
+			due_offset: task.due_date.map(|date| (date - start).num_seconds()),
+			reminder_offset: task
+				.reminder_date
+				.map(|date| (date - start).num_seconds()),
+			sub_tasks: task.sub_tasks.iter().map(TaskTemplate::from).collect(),
+		}
+	}
+}
+
+impl TaskTemplate {
+	/// Creates a new task inside `parent`, computing its dates relative to `day`.
+	pub fn instantiate(&self, parent: String, day: DateTime<Utc>) -> Task {
+		let start = start_of_day(day);
+		let mut task = Task::new(self.title.clone(), parent);
+		task.notes = self.notes.clone();
+		task.favorite = self.favorite;
+		task.priority = self.priority;
+		task.tags = self.tags.clone();
+		task.recurrence = self.recurrence;
+		task.due_date = self
+			.due_offset
+			.map(|offset| start + Duration::seconds(offset));
+		task.reminder_date = self
+			.reminder_offset
+			.map(|offset| start + Duration::seconds(offset));
+		task.sub_tasks = self
+			.sub_tasks
+			.iter()
+			.map(|sub_task| sub_task.instantiate(String::new(), day))
+			.collect();
+		task
+	}
+}
+
+impl ListTemplate {
+	pub fn from_list(list: &List, tasks: &[Task]) -> Self {
+		Self {
+			name: list.name.clone(),
+			description: list.description.clone(),
+			icon: list.icon.clone(),
+			tasks: tasks
+				.iter()
+				.filter(|task| task.parent == list.id)
+				.map(TaskTemplate::from)
+				.collect(),
+		}
+	}
+
+	/// Creates a new list named `name` for `service` along with its tasks,
+	/// the tasks must be re-parented if the service assigns a new list id.
+	pub fn instantiate(
+		&self,
+		name: &str,
+		service: Service,
+		day: DateTime<Utc>,
+	) -> (List, Vec<Task>) {
+		let mut list = List::new(name, service);
+		list.description = self.description.clone();
+		list.icon = self.icon.clone().or(list.icon);
+		let tasks = self
+			.tasks
+			.iter()
+			.map(|task| task.instantiate(list.id.clone(), day))
+			.collect();
+		(list, tasks)
+	}
+}
+
+fn start_of_day(date: DateTime<Utc>) -> DateTime<Utc> {
+	date.date_naive().and_time(NaiveTime::MIN).and_utc()
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+
+	use super::*;
+
+	fn day(day: u32, hour: u32) -> DateTime<Utc> {
+		Utc.with_ymd_and_hms(2024, 5, day, hour, 0, 0).unwrap()
+	}
+
+	#[test]
+	fn task_dates_move_with_the_day() {
+		let mut task = Task::new("Pay rent".into(), "list".into());
+		task.created_date_time = day(1, 9);
+		task.due_date = Some(day(3, 0));
+		task.reminder_date = Some(day(2, 18));
+		task.tags = vec!["home".into()];
+		let template = TaskTemplate::from(&task);
+
+		let new = template.instantiate("other".into(), day(10, 15));
+		assert_eq!(new.title, "Pay rent");
+		assert_eq!(new.parent, "other");
+		assert_eq!(new.tags, vec!["home".to_string()]);
+		assert_eq!(new.due_date, Some(day(12, 0)));
+		assert_eq!(new.reminder_date, Some(day(11, 18)));
+		assert_ne!(new.id, task.id);
+	}
+
+	#[test]
+	fn sub_tasks_are_instantiated() {
+		let mut task = Task::new("Trip".into(), "list".into());
+		task.sub_tasks = vec![Task::new("Pack".into(), String::new())];
+		let new = TaskTemplate::from(&task).instantiate("list".into(), day(1, 0));
+		assert_eq!(new.sub_tasks.len(), 1);
+		assert_eq!(new.sub_tasks[0].title, "Pack");
+	}
+
+	#[test]
+	fn list_tasks_are_parented_to_the_new_list() {
+		let list = List::new("Groceries", Service::Computer);
+		let tasks = vec![
+			Task::new("Milk".into(), list.id.clone()),
+			Task::new("Elsewhere".into(), "other".into()),
+		];
+		let template = ListTemplate::from_list(&list, &tasks);
+		assert_eq!(template.tasks.len(), 1);
+
+		let (new, tasks) =
+			template.instantiate("Weekly groceries", Service::Computer, day(1, 0));
+		assert_eq!(new.name, "Weekly groceries");
+		assert_ne!(new.id, list.id);
+		assert_eq!(tasks.len(), 1);
+		assert_eq!(tasks[0].title, "Milk");
+		assert_eq!(tasks[0].parent, new.id);
+	}
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::task::Task;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeEntry {
+	pub id: String,
+	pub task_id: String,
+	pub list_id: String,
+	pub start_date_time: DateTime<Utc>,
+	pub end_date_time: Option<DateTime<Utc>>,
+}
+
+impl TimeEntry {
+	pub fn new(task_id: String, list_id: String) -> Self {
+		Self {
+			id: Uuid::new_v4().to_string(),
+			task_id,
+			list_id,
+			start_date_time: Utc::now(),
+			end_date_time: None,
+		}
+	}
+
+	pub fn is_running(&self) -> bool {
+		self.end_date_time.is_none()
+	}
+
+	/// Time tracked by this entry, running entries are counted until now.
+	pub fn duration(&self) -> Duration {
+		self.end_date_time.unwrap_or_else(Utc::now) - self.start_date_time
+	}
+
+	/// Time tracked by this entry that falls inside the given range.
+	pub fn duration_within(
+		&self,
+		from: Option<DateTime<Utc>>,
+		to: Option<DateTime<Utc>>,
+	) -> Duration {
+		let start =
+			from.map_or(self.start_date_time, |from| from.max(self.start_date_time));
+		let end = self.end_date_time.unwrap_or_else(Utc::now);
+		let end = to.map_or(end, |to| to.min(end));
+		(end - start).max(Duration::zero())
+	}
+}
+
+/// The time tracked for a task, as shown next to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskTime {
+	/// Time of the finished entries.
+	pub tracked: Duration,
+	/// When the running timer started, if one is.
+	pub running_since: Option<DateTime<Utc>>,
+	/// The estimate in minutes.
+	pub estimate: Option<i32>,
+}
+
+impl TaskTime {
+	/// Time tracked so far, the running timer included.
+	pub fn total(&self) -> Duration {
+		self.tracked
+			+ self
+				.running_since
+				.map_or(Duration::zero(), |since| Utc::now() - since)
+	}
+}
+
+impl Default for TaskTime {
+	fn default() -> Self {
+		Self {
+			tracked: Duration::zero(),
+			running_since: None,
+			estimate: None,
+		}
+	}
+}
+
+/// Time entries recorded within a range, optionally for a single list.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeReport {
+	pub list_id: Option<String>,
+	pub from: Option<DateTime<Utc>>,
+	pub to: Option<DateTime<Utc>>,
+	pub entries: Vec<TimeEntry>,
+}
+
+impl TimeReport {
+	pub fn total(&self) -> Duration {
+		self
+			.entries
+			.iter()
+			.map(|entry| entry.duration_within(self.from, self.to))
+			.fold(Duration::zero(), |total, duration| total + duration)
+	}
+
+	/// Tracked time per task id, in the order tasks were first tracked.
+	pub fn totals_by_task(&self) -> Vec<(String, Duration)> {
+		let mut totals: Vec<(String, Duration)> = vec![];
+		for entry in &self.entries {
+			let duration = entry.duration_within(self.from, self.to);
+			match totals.iter_mut().find(|(id, _)| id == &entry.task_id) {
+				Some((_, total)) => *total = *total + duration,
+				None => totals.push((entry.task_id.clone(), duration)),
+			}
+		}
+		totals
+	}
+
+	/// Writes every entry as a CSV row, task titles are taken from `tasks`.
+	pub fn to_csv(&self, tasks: &[Task]) -> String {
+		let titles: HashMap<&str, &str> = tasks
+			.iter()
+			.map(|task| (task.id.as_str(), task.title.as_str()))
+			.collect();
+		let mut csv = String::from("task,task_id,list_id,start,end,seconds\n");
+		for entry in &self.entries {
+			let row = [
+				titles
+					.get(entry.task_id.as_str())
+					.copied()
+					.unwrap_or_default(),
+				&entry.task_id,
+				&entry.list_id,
+				&entry.start_date_time.to_rfc3339(),
+				&entry
+					.end_date_time
+					.map(|date| date.to_rfc3339())
+					.unwrap_or_default(),
+				&entry
+					.duration_within(self.from, self.to)
+					.num_seconds()
+					.to_string(),
+			]
+			.map(escape_csv_field);
+			csv.push_str(&row.join(","));
+			csv.push('\n');
+		}
+		csv
+	}
+}
+
+fn escape_csv_field(field: &str) -> String {
+	if field.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+
+	use super::*;
+
+	fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+		Utc.with_ymd_and_hms(2024, 5, 1, hour, minute, 0).unwrap()
+	}
+
+	fn entry(
+		task_id: &str,
+		start: DateTime<Utc>,
+		end: DateTime<Utc>,
+	) -> TimeEntry {
+		TimeEntry {
+			id: Uuid::new_v4().to_string(),
+			task_id: task_id.into(),
+			list_id: "list".into(),
+			start_date_time: start,
+			end_date_time: Some(end),
+		}
+	}
+
+	#[test]
+	fn entries_are_clipped_to_the_range() {
+		let entry = entry("a", at(9, 0), at(11, 0));
+		assert_eq!(entry.duration(), Duration::hours(2));
+		assert_eq!(
+			entry.duration_within(Some(at(10, 0)), None),
+			Duration::hours(1)
+		);
+		assert_eq!(
+			entry.duration_within(None, Some(at(9, 30))),
+			Duration::minutes(30)
+		);
+		assert_eq!(
+			entry.duration_within(Some(at(12, 0)), Some(at(13, 0))),
+			Duration::zero()
+		);
+	}
+
+	#[test]
+	fn totals_add_up_per_task() {
+		let report = TimeReport {
+			list_id: None,
+			from: Some(at(9, 0)),
+			to: Some(at(12, 0)),
+			entries: vec![
+				entry("b", at(8, 0), at(10, 0)),
+				entry("a", at(10, 0), at(10, 30)),
+				entry("b", at(11, 0), at(13, 0)),
+			],
+		};
+		assert_eq!(report.total(), Duration::minutes(150));
+		assert_eq!(
+			report.totals_by_task(),
+			vec![
+				("b".to_string(), Duration::hours(2)),
+				("a".to_string(), Duration::minutes(30)),
+			]
+		);
+	}
+
+	#[test]
+	fn running_timers_count_until_now() {
+		let time = TaskTime {
+			tracked: Duration::minutes(10),
+			running_since: Some(Utc::now() - Duration::minutes(5)),
+			estimate: None,
+		};
+		let total = time.total();
+		assert!(total >= Duration::minutes(15) && total < Duration::minutes(16));
+	}
+
+	#[test]
+	fn exports_entries_as_csv() {
+		let mut task = Task::new("Write \"report\", draft".into(), "list".into());
+		task.id = "a".into();
+		let report = TimeReport {
+			list_id: Some("list".into()),
+			from: None,
+			to: None,
+			entries: vec![
+				entry("a", at(9, 0), at(9, 45)),
+				entry("gone", at(10, 0), at(10, 1)),
+			],
+		};
+		assert_eq!(
+			report.to_csv(&[task]),
+			"task,task_id,list_id,start,end,seconds\n\
+			\"Write \"\"report\"\", draft\",a,list,2024-05-01T09:00:00+00:00,2024-05-01T09:45:00+00:00,2700\n\
+			,gone,list,2024-05-01T10:00:00+00:00,2024-05-01T10:01:00+00:00,60\n"
+		);
+	}
+}
@@ -0,0 +1,286 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::service::Service;
+
+use super::{list::List, task::Task};
+
+/// Version of the workspace document written by [`Workspace::to_json`],
+/// bump it whenever the format changes in a way older readers can't parse.
+pub const WORKSPACE_VERSION: u32 = 1;
+
+/// Every list and task of one or more services, as written by an export.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workspace {
+	pub version: u32,
+	pub exported_date_time: DateTime<Utc>,
+	pub lists: Vec<WorkspaceList>,
+	/// Services that couldn't be read, their lists are missing.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub skipped: Vec<Service>,
+}
+
+/// A list together with its tasks, `list.service` records where it was
+/// exported from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceList {
+	pub list: List,
+	pub tasks: Vec<Task>,
+}
+
+/// What an import created, or would create when it is a dry run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+	pub dry_run: bool,
+	pub lists: Vec<(String, usize)>,
+	pub tasks: usize,
+	pub sub_tasks: usize,
+	/// Services missing from the workspace because they couldn't be read.
+	pub skipped: Vec<Service>,
+}
+
+impl Workspace {
+	/// Reads every list and task of the given services, services that are not
+	/// available are skipped. Those that fail to be read are skipped too and
+	/// recorded in `skipped`.
+	pub async fn export(services: &[Service]) -> Self {
+		let mut lists = vec![];
+		let mut skipped = vec![];
+		for service in services {
+			if *service == Service::Smart {
+				continue;
+			}
+			match read_service(*service).await {
+				Ok(service_lists) => lists.extend(service_lists),
+				Err(err) => {
+					tracing::error!("Skipping {service} in the workspace: {err}");
+					skipped.push(*service);
+				},
+			}
+		}
+		Self {
+			version: WORKSPACE_VERSION,
+			exported_date_time: Utc::now(),
+			lists,
+			skipped,
+		}
+	}
+
+	pub fn to_json(&self) -> Result<String> {
+		serde_json::to_string_pretty(self).context("Failed to serialize workspace.")
+	}
+
+	pub fn from_json(json: &str) -> Result<Self> {
+		let workspace: Self =
+			serde_json::from_str(json).context("Failed to parse workspace.")?;
+		if workspace.version > WORKSPACE_VERSION {
+			bail!(
+				"Workspace version {} is newer than the supported version {WORKSPACE_VERSION}.",
+				workspace.version
+			);
+		}
+		Ok(workspace)
+	}
+
+	/// Recreates every list and task into `service` with new ids, nothing is
+	/// written when `dry_run` is set. A list that fails halfway is deleted
+	/// again, the lists imported before it are kept.
+	pub async fn import(
+		&self,
+		service: Service,
+		dry_run: bool,
+	) -> Result<ImportReport> {
+		let mut report = ImportReport {
+			dry_run,
+			skipped: self.skipped.clone(),
+			..Default::default()
+		};
+		let mut provider = service.get_service();
+		for workspace_list in &self.lists {
+			let tasks = &workspace_list.tasks;
+			report
+				.lists
+				.push((workspace_list.list.name.clone(), tasks.len()));
+			report.tasks += tasks.len();
+			report.sub_tasks +=
+				tasks.iter().map(|task| task.sub_tasks.len()).sum::<usize>();
+			if dry_run {
+				continue;
+			}
+
+			let mut list = workspace_list.list.clone();
+			list.id = Uuid::new_v4().to_string();
+			list.service = service;
+			let list = provider.create_list(list).await?;
+			for task in tasks {
+				if let Err(err) = provider.create_task(task.copy_to(&list.id)).await {
+					if let Err(err) = provider.delete_list(list.id.clone()).await {
+						tracing::error!("Failed to delete {}: {err}", list.name);
+					}
+					return Err(err.into());
+				}
+			}
+		}
+		Ok(report)
+	}
+}
+
+/// Reads the lists of a service with their tasks, nothing if it isn't
+/// available.
+async fn read_service(service: Service) -> Result<Vec<WorkspaceList>> {
+	let mut provider = service.get_service();
+	if !provider.available() {
+		return Ok(vec![]);
+	}
+	let mut lists = vec![];
+	for list in provider.read_lists().await? {
+		let tasks = provider.read_tasks_from_list(list.id.clone()).await?;
+		lists.push(WorkspaceList { list, tasks });
+	}
+	Ok(lists)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::service::Services;
+
+	use super::*;
+
+	/// Creates a local list with a task that has a sub-task.
+	async fn local_list() -> (List, Task) {
+		Services::init("dev.edfloreshz.Done.Scratch");
+		let mut provider = Service::Computer.get_service();
+		let list = provider
+			.create_list(List::new(&Uuid::new_v4().to_string(), Service::Computer))
+			.await
+			.unwrap();
+		let mut task = Task::new("Plan".into(), list.id.clone());
+		task.sub_tasks = vec![Task::new("Book".into(), task.id.clone())];
+		provider.create_task(task.clone()).await.unwrap();
+		(list, task)
+	}
+
+	async fn lists_named(name: &str) -> Vec<List> {
+		Service::Computer
+			.get_service()
+			.read_lists()
+			.await
+			.unwrap()
+			.into_iter()
+			.filter(|list| list.name == name)
+			.collect()
+	}
+
+	#[tokio::test]
+	async fn export_round_trips_through_json() {
+		let (list, task) = local_list().await;
+		let workspace =
+			Workspace::export(&[Service::Smart, Service::Computer]).await;
+		assert!(workspace.skipped.is_empty());
+		let exported = workspace
+			.lists
+			.iter()
+			.find(|exported| exported.list.id == list.id)
+			.unwrap();
+		assert_eq!(exported.tasks.len(), 1);
+		assert_eq!(exported.tasks[0].id, task.id);
+		assert_eq!(exported.tasks[0].sub_tasks.len(), 1);
+
+		let json = workspace.to_json().unwrap();
+		assert!(!json.contains("skipped"));
+		assert_eq!(Workspace::from_json(&json).unwrap(), workspace);
+
+		Service::Computer
+			.get_service()
+			.delete_list(list.id)
+			.await
+			.unwrap();
+	}
+
+	#[test]
+	fn skipped_services_are_kept_in_the_document() {
+		let workspace = Workspace {
+			version: WORKSPACE_VERSION,
+			exported_date_time: Utc::now(),
+			lists: vec![],
+			skipped: vec![Service::Microsoft],
+		};
+		let read = Workspace::from_json(&workspace.to_json().unwrap()).unwrap();
+		assert_eq!(read.skipped, vec![Service::Microsoft]);
+	}
+
+	#[test]
+	fn newer_documents_are_refused() {
+		let json = format!(
+			r#"{{"version":{},"exported_date_time":"2024-05-01T00:00:00Z","lists":[]}}"#,
+			WORKSPACE_VERSION + 1
+		);
+		assert!(Workspace::from_json(&json).is_err());
+	}
+
+	#[tokio::test]
+	async fn dry_run_reports_without_writing() {
+		let (list, task) = local_list().await;
+		let workspace = Workspace {
+			version: WORKSPACE_VERSION,
+			exported_date_time: Utc::now(),
+			lists: vec![WorkspaceList {
+				list: list.clone(),
+				tasks: vec![task],
+			}],
+			skipped: vec![Service::Microsoft],
+		};
+
+		let report = workspace.import(Service::Computer, true).await.unwrap();
+		assert!(report.dry_run);
+		assert_eq!(report.lists, vec![(list.name.clone(), 1)]);
+		assert_eq!((report.tasks, report.sub_tasks), (1, 1));
+		assert_eq!(report.skipped, vec![Service::Microsoft]);
+		assert_eq!(lists_named(&list.name).await.len(), 1);
+
+		Service::Computer
+			.get_service()
+			.delete_list(list.id)
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	async fn import_copies_lists_with_new_ids() {
+		let (list, task) = local_list().await;
+		let workspace = Workspace::export(&[Service::Computer]).await;
+		let workspace = Workspace {
+			lists: workspace
+				.lists
+				.into_iter()
+				.filter(|exported| exported.list.id == list.id)
+				.collect(),
+			..workspace
+		};
+
+		let report = workspace.import(Service::Computer, false).await.unwrap();
+		assert!(!report.dry_run);
+		assert_eq!((report.tasks, report.sub_tasks), (1, 1));
+
+		let mut provider = Service::Computer.get_service();
+		let copy = lists_named(&list.name)
+			.await
+			.into_iter()
+			.find(|copy| copy.id != list.id)
+			.unwrap();
+		let tasks = provider
+			.read_tasks_from_list(copy.id.clone())
+			.await
+			.unwrap();
+		assert_eq!(tasks.len(), 1);
+		assert_ne!(tasks[0].id, task.id);
+		assert_eq!(tasks[0].title, task.title);
+		assert_eq!(tasks[0].sub_tasks.len(), 1);
+		assert_ne!(tasks[0].sub_tasks[0].id, task.sub_tasks[0].id);
+
+		provider.delete_list(list.id).await.unwrap();
+		provider.delete_list(copy.id).await.unwrap();
+	}
+}
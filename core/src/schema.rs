@@ -1,5 +1,16 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+		focus_sessions (id_focus_session) {
+				id_focus_session -> Text,
+				task_id -> Text,
+				list_id -> Text,
+				start_date_time -> Timestamp,
+				end_date_time -> Timestamp,
+				completed -> Bool,
+		}
+}
+
 diesel::table! {
 		lists (id_list) {
 				id_list -> Text,
@@ -9,26 +20,80 @@ diesel::table! {
 		}
 }
 
+diesel::table! {
+		tags (id_tag) {
+				id_tag -> Integer,
+				name -> Text,
+		}
+}
+
+diesel::table! {
+		task_tags (id_task, id_tag) {
+				id_task -> Text,
+				id_tag -> Integer,
+				position -> Integer,
+		}
+}
+
 diesel::table! {
 		tasks (id_task) {
 				id_task -> Text,
 				parent -> Text,
+				parent_task -> Nullable<Text>,
+				position -> Integer,
 				title -> Text,
 				favorite -> Bool,
 				today -> Bool,
 				status -> Integer,
 				priority -> Integer,
-				sub_tasks -> Text,
-				tags -> Text,
 				notes -> Text,
 				completion_date -> Nullable<Timestamp>,
 				deletion_date -> Nullable<Timestamp>,
 				due_date -> Nullable<Timestamp>,
 				reminder_date -> Nullable<Timestamp>,
-				recurrence -> Text,
+				recurrence -> Integer,
 				created_date_time -> Timestamp,
 				last_modified_date_time -> Timestamp,
 		}
 }
 
-diesel::allow_tables_to_appear_in_same_query!(lists, tasks,);
+diesel::table! {
+		task_estimates (id_task) {
+				id_task -> Text,
+				estimate -> Integer,
+		}
+}
+
+diesel::table! {
+		templates (id_template) {
+				id_template -> Text,
+				name -> Text,
+				content -> Text,
+				created_date_time -> Timestamp,
+		}
+}
+
+diesel::table! {
+		time_entries (id_time_entry) {
+				id_time_entry -> Text,
+				task_id -> Text,
+				list_id -> Text,
+				start_date_time -> Timestamp,
+				end_date_time -> Nullable<Timestamp>,
+		}
+}
+
+diesel::joinable!(task_tags -> tags (id_tag));
+diesel::joinable!(task_tags -> tasks (id_task));
+diesel::joinable!(tasks -> lists (parent));
+
+diesel::allow_tables_to_appear_in_same_query!(
+	focus_sessions,
+	lists,
+	tags,
+	task_estimates,
+	task_tags,
+	tasks,
+	templates,
+	time_entries,
+);
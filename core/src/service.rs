@@ -1,19 +1,34 @@
 use std::fmt::Display;
+use std::path::PathBuf;
 use std::sync::OnceLock;
 
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::{EnumIter, EnumString};
 
 use crate::{
+	models::account::Account,
+	models::profile::Profile,
 	services::{
-		local::service::ComputerStorage, microsoft::service::MicrosoftService,
+		accounts::Accounts,
+		local::{database::Database, service::ComputerStorage},
+		markdown::service::MarkdownService,
+		microsoft::service::MicrosoftService,
+		org::service::OrgService,
+		plugins::{
+			registry::{PluginId, PluginRegistry},
+			service::PluginService,
+		},
 		smart::Smart,
+		taskwarrior::service::TaskwarriorService,
+		todo_txt::service::TodoTxtService,
 	},
 	task_service::TodoProvider,
 };
 
 static APP_ID: OnceLock<&str> = OnceLock::new();
+static PROFILE: OnceLock<Profile> = OnceLock::new();
 
 pub struct Services;
 
@@ -21,6 +36,41 @@ impl Services {
 	pub fn init(app_id: &'static str) {
 		APP_ID.get_or_init(|| app_id);
 	}
+
+	/// Chooses the profile whose data is used, the default one unless this
+	/// is called before anything is read. It can't change afterwards.
+	pub fn select_profile(profile: Profile) {
+		PROFILE.get_or_init(|| profile);
+	}
+
+	pub fn profile() -> Profile {
+		PROFILE.get().cloned().unwrap_or_default()
+	}
+
+	/// Opens the local database and brings its schema up to date, done once
+	/// at startup so the first action doesn't wait for it.
+	pub fn open_database() -> Result<()> {
+		Database::new(Self::app_id()).migrate()
+	}
+
+	/// Closes the local database and releases its lock, so another machine
+	/// can write to it once this syncs.
+	pub fn close_database() -> Result<()> {
+		Database::new(Self::app_id()).close()
+	}
+
+	/// The id the data of the selected profile is stored under.
+	pub fn app_id() -> String {
+		Self::profile().app_id(&Self::base_app_id())
+	}
+
+	pub(crate) fn base_app_id() -> String {
+		if APP_ID.get().is_none() {
+			panic!("Must call Service::init before trying to get a service");
+		}
+
+		APP_ID.get().unwrap().to_string()
+	}
 }
 
 #[derive(
@@ -41,6 +91,13 @@ pub enum Service {
 	#[default]
 	Computer,
 	Microsoft,
+	TodoTxt,
+	Markdown,
+	Taskwarrior,
+	Org,
+	/// A provider registered at runtime, see [`PluginRegistry`].
+	#[strum(disabled)]
+	Plugin(PluginId),
 	Smart,
 }
 
@@ -49,22 +106,32 @@ impl Service {
 	/// After implementing the Service trait in your service
 	/// struct, register your service here.
 	pub fn get_service(&self) -> Box<dyn TodoProvider> {
-		if APP_ID.get().is_none() {
-			panic!("Must call Service::init before trying to get a service");
-		}
-
-		let app_id = APP_ID.get().unwrap().to_string();
+		let app_id = Services::app_id();
 
 		match self {
 			Service::Smart => Box::new(Smart::new()),
 			Service::Computer => Box::new(ComputerStorage::new(app_id)),
 			Service::Microsoft => Box::new(MicrosoftService::new()),
+			Service::TodoTxt => Box::new(TodoTxtService::new()),
+			Service::Markdown => Box::new(MarkdownService::new()),
+			Service::Taskwarrior => Box::new(TaskwarriorService::new()),
+			Service::Org => Box::new(OrgService::new()),
+			Service::Plugin(id) => Box::new(PluginService::new(*id)),
 		}
 	}
 
-	/// Convenience method to get the list of services.
+	/// Convenience method to get the list of services, registered plugins
+	/// included.
 	pub fn list() -> Vec<Self> {
-		Self::iter().collect()
+		let mut services: Vec<Self> = Self::iter()
+			.filter(|service| *service != Service::Smart)
+			.collect();
+		match PluginRegistry::services() {
+			Ok(plugins) => services.extend(plugins),
+			Err(err) => tracing::error!("Failed to list plugins: {err}"),
+		}
+		services.push(Service::Smart);
+		services
 	}
 
 	/// Returns the icon for the service.
@@ -77,6 +144,125 @@ impl Service {
 			Service::Microsoft => {
 				"/dev/edfloreshz/Done/icons/scalable/services/microsoft-todo.png"
 			},
+			Service::TodoTxt => {
+				"/dev/edfloreshz/Done/icons/scalable/services/todo-txt.svg"
+			},
+			Service::Markdown => {
+				"/dev/edfloreshz/Done/icons/scalable/services/markdown.svg"
+			},
+			Service::Taskwarrior => {
+				"/dev/edfloreshz/Done/icons/scalable/services/taskwarrior.svg"
+			},
+			Service::Org => "/dev/edfloreshz/Done/icons/scalable/services/org.svg",
+			Service::Plugin(_) => {
+				"/dev/edfloreshz/Done/icons/scalable/services/plugin.svg"
+			},
+		}
+	}
+
+	/// Whether the service stores its tasks in files chosen by the user.
+	pub fn file_backed(&self) -> bool {
+		matches!(
+			self,
+			Service::TodoTxt
+				| Service::Markdown
+				| Service::Taskwarrior
+				| Service::Org
+		)
+	}
+
+	/// Whether the service is pointed at a folder instead of a single file.
+	pub fn path_is_directory(&self) -> bool {
+		matches!(self, Service::Markdown | Service::Taskwarrior)
+	}
+
+	/// The file or folder a file backed service reads from, if one was chosen.
+	pub fn path(&self) -> Option<PathBuf> {
+		match self {
+			Service::TodoTxt => TodoTxtService::path(),
+			Service::Markdown => MarkdownService::path(),
+			Service::Taskwarrior => TaskwarriorService::path(),
+			Service::Org => OrgService::path(),
+			_ => None,
+		}
+	}
+
+	pub fn set_path(&self, path: PathBuf) -> Result<()> {
+		match self {
+			Service::TodoTxt => TodoTxtService::set_path(path),
+			Service::Markdown => MarkdownService::set_path(path),
+			Service::Taskwarrior => TaskwarriorService::set_path(path),
+			Service::Org => OrgService::set_path(path),
+			_ => Ok(()),
+		}
+	}
+
+	pub fn clear_path(&self) -> Result<()> {
+		match self {
+			Service::TodoTxt => TodoTxtService::clear_path(),
+			Service::Markdown => MarkdownService::clear_path(),
+			Service::Taskwarrior => TaskwarriorService::clear_path(),
+			Service::Org => OrgService::clear_path(),
+			_ => Ok(()),
+		}
+	}
+
+	/// Whether completed tasks are archived in a file of their own, which can
+	/// be chosen apart from [`Service::path`].
+	pub fn has_archive(&self) -> bool {
+		matches!(self, Service::TodoTxt)
+	}
+
+	/// The file completed tasks are archived in, if one was chosen instead of
+	/// the default.
+	pub fn archive_path(&self) -> Option<PathBuf> {
+		match self {
+			Service::TodoTxt => TodoTxtService::done_path(),
+			_ => None,
+		}
+	}
+
+	/// Chooses the file completed tasks are archived in, `None` goes back to
+	/// the default.
+	pub fn set_archive_path(&self, path: Option<PathBuf>) -> Result<()> {
+		match self {
+			Service::TodoTxt => TodoTxtService::set_done_path(path),
+			_ => Ok(()),
+		}
+	}
+
+	/// Whether several accounts can be signed in to the service at once.
+	pub fn has_accounts(&self) -> bool {
+		matches!(self, Service::Microsoft)
+	}
+
+	/// The accounts signed in to the service.
+	pub fn accounts(&self) -> Vec<Account> {
+		Accounts::list(*self)
+	}
+
+	/// Whether an account of the service must be signed in to again.
+	pub fn needs_sign_in(&self) -> bool {
+		self.accounts().iter().any(|account| account.needs_sign_in)
+	}
+
+	/// Signs an account out of the service.
+	pub fn remove_account(&self, id: &str) -> Result<()> {
+		match self {
+			Service::Microsoft => MicrosoftService::remove_account(id),
+			_ => Accounts::remove(*self, id),
+		}
+	}
+
+	/// Files checked by [`crate::services::files::watch_services`] for
+	/// changes made by other programs.
+	pub fn watched_paths(&self) -> Vec<PathBuf> {
+		match self {
+			Service::TodoTxt => TodoTxtService::watched_paths(),
+			Service::Markdown => MarkdownService::watched_paths(),
+			Service::Taskwarrior => TaskwarriorService::watched_paths(),
+			Service::Org => OrgService::watched_paths(),
+			_ => vec![],
 		}
 	}
 }
@@ -87,6 +273,15 @@ impl Display for Service {
 			Service::Smart => "Smart lists".to_string(),
 			Service::Computer => "Computer".to_string(),
 			Service::Microsoft => "Microsoft To Do".to_string(),
+			Service::TodoTxt => "todo.txt".to_string(),
+			Service::Markdown => "Markdown".to_string(),
+			Service::Taskwarrior => "Taskwarrior".to_string(),
+			Service::Org => "Org".to_string(),
+			Service::Plugin(id) => PluginRegistry::manifest(*id)
+				.ok()
+				.flatten()
+				.map(|manifest| manifest.name)
+				.unwrap_or_else(|| "Plugin".to_string()),
 		};
 		write!(f, "{}", str)
 	}
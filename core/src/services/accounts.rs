@@ -0,0 +1,97 @@
+use std::fmt::Display;
+
+use anyhow::{Context, Result};
+use libset::Config;
+
+use crate::{models::account::Account, service::Service};
+
+use super::files::{read_config, write_config};
+
+const CONFIG_KEY: &str = "accounts";
+
+/// The accounts signed in to every service, credentials are kept by each
+/// service in the keyring.
+pub struct Accounts;
+
+impl Accounts {
+	fn all() -> Vec<Account> {
+		read_config(CONFIG_KEY).unwrap_or_default()
+	}
+
+	/// The accounts of another profile, to clean up after it.
+	pub(crate) fn of_profile(application_id: &str) -> Vec<Account> {
+		Config::new(application_id, 1, Some("services"))
+			.ok()
+			.and_then(|config| config.get_json(CONFIG_KEY).ok())
+			.unwrap_or_default()
+	}
+
+	/// The accounts of a service, in the order they were added.
+	pub fn list(service: Service) -> Vec<Account> {
+		Self::all()
+			.into_iter()
+			.filter(|account| account.service == service)
+			.collect()
+	}
+
+	/// Adds an account, or updates it when it was already added, which means
+	/// the user signed in to it again.
+	pub fn add(account: Account) -> Result<()> {
+		let mut accounts = Self::all();
+		match accounts.iter_mut().find(|existing| {
+			existing.service == account.service && existing.id == account.id
+		}) {
+			Some(existing) => {
+				existing.display_name = account.display_name;
+				existing.needs_sign_in = false;
+			},
+			None => accounts.push(account),
+		}
+		write_config(CONFIG_KEY, accounts)
+	}
+
+	pub fn remove(service: Service, id: &str) -> Result<()> {
+		let mut accounts = Self::all();
+		accounts.retain(|account| account.service != service || account.id != id);
+		write_config(CONFIG_KEY, accounts)
+	}
+
+	/// Shows or hides the lists of an account.
+	pub fn set_enabled(service: Service, id: &str, enabled: bool) -> Result<()> {
+		let mut accounts = Self::all();
+		accounts
+			.iter_mut()
+			.find(|account| account.service == service && account.id == id)
+			.with_context(|| format!("Account {id} not found."))?
+			.enabled = enabled;
+		write_config(CONFIG_KEY, accounts)
+	}
+
+	/// Remembers that the credentials of an account stopped working.
+	pub fn set_needs_sign_in(
+		service: Service,
+		id: &str,
+		needs_sign_in: bool,
+	) -> Result<()> {
+		let mut accounts = Self::all();
+		accounts
+			.iter_mut()
+			.find(|account| account.service == service && account.id == id)
+			.with_context(|| format!("Account {id} not found."))?
+			.needs_sign_in = needs_sign_in;
+		write_config(CONFIG_KEY, accounts)
+	}
+}
+
+/// Returned by services when the credentials of an account were revoked or
+/// expired and can't be refreshed, only signing in again recovers.
+#[derive(Debug, Clone)]
+pub struct SignInRequired(pub Account);
+
+impl Display for SignInRequired {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Sign in to {} again.", self.0.display_name)
+	}
+}
+
+impl std::error::Error for SignInRequired {}
@@ -0,0 +1,173 @@
+use std::{
+	collections::HashMap,
+	path::{Path, PathBuf},
+	pin::Pin,
+	sync::{Mutex, OnceLock},
+	time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use futures::Stream;
+use libset::Config;
+use serde::{de::DeserializeOwned, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::{
+	service::{Service, Services},
+	services::local::database::Database,
+};
+
+/// How often watched files are checked for external changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Modification times of the files written by Done, so the watcher can
+/// tell our own writes apart from external edits.
+static KNOWN_MODIFICATIONS: OnceLock<Mutex<HashMap<PathBuf, SystemTime>>> =
+	OnceLock::new();
+
+fn known_modifications() -> &'static Mutex<HashMap<PathBuf, SystemTime>> {
+	KNOWN_MODIFICATIONS.get_or_init(Default::default)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+	path
+		.metadata()
+		.and_then(|metadata| metadata.modified())
+		.ok()
+}
+
+/// Reads the settings a file based provider stored under `key`.
+pub(crate) fn read_config<T: DeserializeOwned>(key: &str) -> Option<T> {
+	Config::new(&Services::app_id(), 1, Some("services"))
+		.ok()?
+		.get_json(key)
+		.ok()
+}
+
+pub(crate) fn write_config<T: Serialize>(key: &str, value: T) -> Result<()> {
+	Config::new(&Services::app_id(), 1, Some("services"))?
+		.set_json(key, value)?;
+	Ok(())
+}
+
+pub(crate) fn remove_config(key: &str) -> Result<()> {
+	let path = Config::new(&Services::app_id(), 1, Some("services"))?
+		.path(key, libset::FileType::Json)?;
+	if path.exists() {
+		std::fs::remove_file(path)?;
+	}
+	Ok(())
+}
+
+/// Reads a file, a missing file reads as empty.
+pub(crate) fn read_file(path: &Path) -> Result<String> {
+	match std::fs::read_to_string(path) {
+		Ok(contents) => Ok(contents),
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+		Err(err) => {
+			Err(err).with_context(|| format!("Failed to read {}", path.display()))
+		},
+	}
+}
+
+/// Replaces the contents of a file through a temporary file, so external
+/// readers never see it half written.
+pub(crate) fn write_file(path: &Path, contents: &str) -> Result<()> {
+	if let Some(parent) = path.parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+	let temporary = path.with_extension("done-tmp");
+	std::fs::write(&temporary, contents)?;
+	std::fs::rename(&temporary, path)
+		.with_context(|| format!("Failed to write {}", path.display()))?;
+	mark_written(path);
+	Ok(())
+}
+
+/// Records a file as written by Done, for files changed through another
+/// program on our behalf.
+pub(crate) fn mark_written(path: &Path) {
+	if let Some(modified) = modified(path) {
+		known_modifications()
+			.lock()
+			.unwrap()
+			.insert(path.to_path_buf(), modified);
+	}
+}
+
+pub(crate) fn remove_file(path: &Path) -> Result<()> {
+	std::fs::remove_file(path)
+		.with_context(|| format!("Failed to remove {}", path.display()))?;
+	known_modifications().lock().unwrap().remove(path);
+	Ok(())
+}
+
+/// Lists the files under a directory with the given extension, hidden files
+/// and directories are skipped.
+pub(crate) fn files_with_extension(
+	dir: &Path,
+	extension: &str,
+) -> Vec<PathBuf> {
+	let mut files = vec![];
+	let Ok(entries) = std::fs::read_dir(dir) else {
+		return files;
+	};
+	for entry in entries.flatten() {
+		let path = entry.path();
+		if entry.file_name().to_string_lossy().starts_with('.') {
+			continue;
+		}
+		if path.is_dir() {
+			files.extend(files_with_extension(&path, extension));
+		} else if path.extension().is_some_and(|ext| ext == extension) {
+			files.push(path);
+		}
+	}
+	files.sort();
+	files
+}
+
+/// Hash of a text that stays the same between runs and builds (FNV-1a).
+pub(crate) fn stable_hash(text: &str) -> u64 {
+	text.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+		(hash ^ byte as u64).wrapping_mul(0x100000001b3)
+	})
+}
+
+/// Id for an item that has none stored in its file, derived from its text
+/// so it stays the same between reads.
+pub(crate) fn stable_id(text: &str) -> String {
+	format!("{:016x}", stable_hash(text))
+}
+
+/// Polls the files of every file based service, and the local database when
+/// it's in a chosen folder, and yields a service each time one of its files
+/// is changed by another program.
+pub fn watch_services() -> Pin<Box<dyn Stream<Item = Service> + Send>> {
+	Box::pin(async_stream::stream! {
+		let mut seen: HashMap<PathBuf, Option<SystemTime>> = HashMap::new();
+		let mut database = Database::new(Services::app_id());
+		loop {
+			match database.poll() {
+				Ok(true) => yield Service::Computer,
+				Ok(false) => (),
+				Err(err) => tracing::error!("Failed to check the database: {err}"),
+			}
+			for service in Service::iter() {
+				let mut changed = false;
+				for path in service.watched_paths() {
+					let current = modified(&path);
+					let previous = seen.insert(path.clone(), current);
+					let known = known_modifications().lock().unwrap().get(&path).copied();
+					if previous.is_some() && previous != Some(current) && known != current {
+						changed = true;
+					}
+				}
+				if changed {
+					yield service;
+				}
+			}
+			tokio::time::sleep(WATCH_INTERVAL).await;
+		}
+	})
+}
@@ -0,0 +1,176 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::{
+	connection::SimpleConnection, Connection, QueryDsl, RunQueryDsl,
+	SqliteConnection,
+};
+
+use crate::{
+	models::backup::{BackupSettings, Snapshot},
+	schema::tasks,
+	service::Services,
+};
+
+use super::{
+	database::{backup::copy_database, Database},
+	encryption::{is_encrypted, key_pragma},
+};
+
+const SNAPSHOT_PREFIX: &str = "backup-";
+/// Milliseconds keep a snapshot taken right before a restore apart from one
+/// taken in the same second.
+const SNAPSHOT_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+/// Names of snapshots taken by older versions.
+const LEGACY_SNAPSHOT_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Rotating snapshots of the local database, stored in a `backups`
+/// directory next to the default location of the database, which isn't
+/// synced. Snapshots of an encrypted database are encrypted
+/// with the same key.
+#[derive(Debug, Clone)]
+pub struct Backups {
+	database: Database,
+	directory: PathBuf,
+}
+
+impl Backups {
+	pub fn new() -> Result<Self> {
+		let database = Database::new(Services::app_id());
+		let directory = database
+			.default_path()?
+			.parent()
+			.context("Failed to find the database directory.")?
+			.join("backups");
+		Ok(Self {
+			database,
+			directory,
+		})
+	}
+
+	/// Takes a snapshot of the database right now.
+	pub fn create(&mut self) -> Result<Snapshot> {
+		std::fs::create_dir_all(&self.directory)?;
+		let created_date_time = Utc::now();
+		let path = self.directory.join(format!(
+			"{SNAPSHOT_PREFIX}{}.db",
+			created_date_time.format(SNAPSHOT_FORMAT)
+		));
+		if path.exists() {
+			bail!("The backup {} already exists.", path.display());
+		}
+		let key = self.database.key()?;
+		copy_database(
+			Path::new(&self.database.database_url()?),
+			&path,
+			key.as_deref(),
+		)
+		.context("Failed to back up database.")?;
+		Ok(Snapshot {
+			task_count: task_count(&path, key.as_deref()),
+			path,
+			created_date_time,
+		})
+	}
+
+	/// Lists the available snapshots, newest first.
+	pub fn snapshots(&self) -> Result<Vec<Snapshot>> {
+		if !self.directory.exists() {
+			return Ok(vec![]);
+		}
+		let key = self.database.key().ok().flatten();
+		let mut snapshots: Vec<Snapshot> = std::fs::read_dir(&self.directory)?
+			.filter_map(|entry| entry.ok())
+			.filter_map(|entry| {
+				let path = entry.path();
+				let name = path.file_stem()?.to_str()?;
+				let timestamp = name.strip_prefix(SNAPSHOT_PREFIX)?;
+				let created_date_time =
+					NaiveDateTime::parse_from_str(timestamp, SNAPSHOT_FORMAT)
+						.or_else(|_| {
+							NaiveDateTime::parse_from_str(timestamp, LEGACY_SNAPSHOT_FORMAT)
+						})
+						.ok()?
+						.and_utc();
+				Some(Snapshot {
+					task_count: task_count(&path, key.as_deref()),
+					path,
+					created_date_time,
+				})
+			})
+			.collect();
+		snapshots
+			.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created_date_time));
+		Ok(snapshots)
+	}
+
+	/// Deletes every snapshot but the newest `keep`.
+	pub fn rotate(&self, keep: usize) -> Result<()> {
+		for snapshot in self.snapshots()?.iter().skip(keep) {
+			std::fs::remove_file(&snapshot.path)?;
+		}
+		Ok(())
+	}
+
+	/// Takes a snapshot if automatic backups are enabled and the newest one
+	/// is older than the configured interval.
+	pub fn run_scheduled(
+		&mut self,
+		settings: &BackupSettings,
+	) -> Result<Option<Snapshot>> {
+		if !settings.enabled {
+			return Ok(None);
+		}
+		let interval = Duration::hours(settings.interval_hours.max(1) as i64);
+		let due = self.snapshots()?.first().is_none_or(|snapshot| {
+			Utc::now() - snapshot.created_date_time >= interval
+		});
+		if !due {
+			return Ok(None);
+		}
+		let snapshot = self.create()?;
+		self.rotate(settings.keep.max(1) as usize)?;
+		Ok(Some(snapshot))
+	}
+
+	/// Replaces the database with a snapshot, the current database is
+	/// backed up first so a restore can be undone. The pool is closed while
+	/// the snapshot is copied in and opened again afterwards.
+	pub fn restore(&mut self, snapshot: &Snapshot) -> Result<()> {
+		if let Some(host) = self.database.locked_by() {
+			bail!("The database is in use on {host}.");
+		}
+		let database = self.database.database_url()?;
+		if is_encrypted(&snapshot.path)? != is_encrypted(Path::new(&database))? {
+			bail!(
+				"The backup and the database are not both encrypted, turn encryption \
+				 on or off to match the backup first."
+			);
+		}
+		self.create()?;
+		let key = self.database.key()?;
+		self.database.close()?;
+		// The WAL was written back on close, one left over would be applied to
+		// the snapshot.
+		for file in self.database.journal_files()? {
+			if file.exists() {
+				std::fs::remove_file(&file)?;
+			}
+		}
+		copy_database(&snapshot.path, Path::new(&database), key.as_deref())
+			.context("Failed to restore database.")?;
+		// Opens the pool again, snapshots taken by older versions need the
+		// newer migrations.
+		self.database.migrate()
+	}
+}
+
+fn task_count(path: &Path, key: Option<&str>) -> Option<i64> {
+	let mut connection =
+		SqliteConnection::establish(&path.to_string_lossy()).ok()?;
+	if is_encrypted(path).ok()? {
+		connection.batch_execute(&key_pragma(key?)).ok()?;
+	}
+	tasks::table.count().get_result(&mut connection).ok()
+}
@@ -0,0 +1,114 @@
+use std::{
+	ffi::{CStr, CString},
+	path::Path,
+	ptr,
+};
+
+use anyhow::{bail, Result};
+use libsqlite3_sys as ffi;
+
+use crate::services::local::encryption::key_pragma;
+
+/// Number of pages copied before giving writers a chance to run.
+const PAGES_PER_STEP: i32 = 256;
+
+struct RawConnection(*mut ffi::sqlite3);
+
+impl RawConnection {
+	fn open(path: &Path, flags: i32) -> Result<Self> {
+		let path = CString::new(path.to_string_lossy().as_bytes())?;
+		let mut handle = ptr::null_mut();
+		let code = unsafe {
+			ffi::sqlite3_open_v2(path.as_ptr(), &mut handle, flags, ptr::null())
+		};
+		let connection = Self(handle);
+		if code != ffi::SQLITE_OK {
+			bail!("Failed to open database: {}", connection.error_message());
+		}
+		Ok(connection)
+	}
+
+	fn execute(&self, sql: &str) -> Result<()> {
+		let sql = CString::new(sql)?;
+		let code = unsafe {
+			ffi::sqlite3_exec(
+				self.0,
+				sql.as_ptr(),
+				None,
+				ptr::null_mut(),
+				ptr::null_mut(),
+			)
+		};
+		if code != ffi::SQLITE_OK {
+			bail!("Failed to run query: {}", self.error_message());
+		}
+		Ok(())
+	}
+
+	fn error_message(&self) -> String {
+		if self.0.is_null() {
+			return "out of memory".into();
+		}
+		unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(self.0)) }
+			.to_string_lossy()
+			.into_owned()
+	}
+}
+
+impl Drop for RawConnection {
+	fn drop(&mut self) {
+		unsafe {
+			ffi::sqlite3_close(self.0);
+		}
+	}
+}
+
+/// Copies the `source` database into `destination` with SQLite's online
+/// backup API, which is safe while other connections are using `source`.
+/// Encrypted databases can only be copied to ones with the same `key`.
+pub(crate) fn copy_database(
+	source: &Path,
+	destination: &Path,
+	key: Option<&str>,
+) -> Result<()> {
+	let source = RawConnection::open(source, ffi::SQLITE_OPEN_READONLY)?;
+	let destination = RawConnection::open(
+		destination,
+		ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE,
+	)?;
+	if let Some(key) = key {
+		source.execute(&key_pragma(key))?;
+		destination.execute(&key_pragma(key))?;
+	}
+
+	let main = CString::new("main")?;
+	let backup = unsafe {
+		ffi::sqlite3_backup_init(
+			destination.0,
+			main.as_ptr(),
+			source.0,
+			main.as_ptr(),
+		)
+	};
+	if backup.is_null() {
+		bail!("Failed to start backup: {}", destination.error_message());
+	}
+
+	let code = loop {
+		match unsafe { ffi::sqlite3_backup_step(backup, PAGES_PER_STEP) } {
+			ffi::SQLITE_OK | ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED => unsafe {
+				ffi::sqlite3_sleep(25);
+			},
+			code => break code,
+		}
+	};
+	unsafe { ffi::sqlite3_backup_finish(backup) };
+
+	if code != ffi::SQLITE_DONE {
+		bail!(
+			"Failed to back up database: {}",
+			destination.error_message()
+		);
+	}
+	Ok(())
+}
@@ -0,0 +1,282 @@
+use std::path::{Path, PathBuf};
+#[cfg(not(unix))]
+use std::time::SystemTime;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use libset::{Config, FileType};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::services::files::write_file;
+
+const LOCATION_KEY: &str = "location";
+const MACHINE_KEY: &str = "machine";
+
+/// How long a lock is honored after it was last renewed, generous because
+/// the lock file has to sync to the other machines first.
+const LOCK_TIMEOUT_MINUTES: i64 = 10;
+
+/// How often the lock of a database in use is renewed.
+pub(crate) const LOCK_RENEWAL: std::time::Duration =
+	std::time::Duration::from_secs(2 * 60);
+
+/// The folder chosen for the database, stored per profile next to the
+/// default database so it never syncs itself.
+#[derive(Debug, Serialize, Deserialize)]
+struct LocationConfig {
+	folder: PathBuf,
+}
+
+fn config(application_id: &str) -> Result<Config> {
+	Ok(Config::new(application_id, 1, Some("database"))?)
+}
+
+pub(crate) fn location(application_id: &str) -> Option<PathBuf> {
+	config(application_id)
+		.ok()?
+		.get_json::<LocationConfig>(LOCATION_KEY)
+		.ok()
+		.map(|location| location.folder)
+}
+
+pub(crate) fn set_location(
+	application_id: &str,
+	folder: Option<PathBuf>,
+) -> Result<()> {
+	let config = config(application_id)?;
+	match folder {
+		Some(folder) => config.set_json(LOCATION_KEY, LocationConfig { folder })?,
+		None => {
+			let path = config.path(LOCATION_KEY, FileType::Json)?;
+			if path.exists() {
+				std::fs::remove_file(path)?;
+			}
+		},
+	}
+	Ok(())
+}
+
+/// A random id for this installation, host names aren't unique enough.
+fn machine(application_id: &str) -> Result<String> {
+	let config = config(application_id)?;
+	if let Ok(machine) = config.get_plain(MACHINE_KEY) {
+		if !machine.trim().is_empty() {
+			return Ok(machine.trim().to_string());
+		}
+	}
+	let machine = Uuid::new_v4().to_string();
+	config.set_plain(MACHINE_KEY, &machine)?;
+	Ok(machine)
+}
+
+fn host() -> String {
+	gethostname::gethostname()
+		.into_string()
+		.ok()
+		.map(|host| host.trim().to_string())
+		.filter(|host| !host.is_empty())
+		.unwrap_or_else(|| "another computer".to_string())
+}
+
+/// Marks a database as being written by one machine, so the others open
+/// it read only instead of creating conflicting copies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Lock {
+	machine: String,
+	pub host: String,
+	renewed: DateTime<Utc>,
+}
+
+impl Lock {
+	fn path(database: &Path) -> PathBuf {
+		PathBuf::from(format!("{}.lock", database.display()))
+	}
+
+	pub(crate) fn read(database: &Path) -> Option<Self> {
+		let lock = std::fs::read_to_string(Self::path(database)).ok()?;
+		serde_json::from_str(&lock).ok()
+	}
+
+	/// The lock of another machine that's still in use, if there's one.
+	pub(crate) fn held_elsewhere(
+		application_id: &str,
+		database: &Path,
+	) -> Result<Option<Self>> {
+		let machine = machine(application_id)?;
+		Ok(Self::read(database).filter(|lock| {
+			lock.machine != machine
+				&& Utc::now() - lock.renewed < Duration::minutes(LOCK_TIMEOUT_MINUTES)
+		}))
+	}
+
+	pub(crate) fn acquire(application_id: &str, database: &Path) -> Result<()> {
+		let lock = Self {
+			machine: machine(application_id)?,
+			host: host(),
+			renewed: Utc::now(),
+		};
+		write_file(&Self::path(database), &serde_json::to_string(&lock)?)
+	}
+
+	/// Removes the lock, unless another machine took it over.
+	pub(crate) fn release(application_id: &str, database: &Path) -> Result<()> {
+		let machine = machine(application_id)?;
+		if Self::read(database).is_some_and(|lock| lock.machine == machine) {
+			std::fs::remove_file(Self::path(database))?;
+		}
+		Ok(())
+	}
+}
+
+/// What tells a file apart from one put in its place, sync tools replace
+/// files instead of writing into them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FileIdentity {
+	#[cfg(unix)]
+	inode: (u64, u64),
+	#[cfg(not(unix))]
+	created: Option<SystemTime>,
+}
+
+impl FileIdentity {
+	pub(crate) fn of(path: &Path) -> Option<Self> {
+		let metadata = path.metadata().ok()?;
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::MetadataExt;
+			Some(Self {
+				inode: (metadata.dev(), metadata.ino()),
+			})
+		}
+		#[cfg(not(unix))]
+		Some(Self {
+			created: metadata.created().ok(),
+		})
+	}
+}
+
+/// The copies sync tools make when two machines changed the database before
+/// syncing, like `name.sync-conflict-….db` or `name (conflicted copy).db`.
+pub(crate) fn conflicts(database: &Path) -> Vec<PathBuf> {
+	let (Some(folder), Some(stem)) = (
+		database.parent(),
+		database.file_stem().and_then(|stem| stem.to_str()),
+	) else {
+		return vec![];
+	};
+	let Ok(entries) = std::fs::read_dir(folder) else {
+		return vec![];
+	};
+	let mut conflicts: Vec<PathBuf> = entries
+		.flatten()
+		.map(|entry| entry.path())
+		.filter(|path| {
+			path != database
+				&& path.extension().is_some_and(|extension| extension == "db")
+				&& path
+					.file_name()
+					.and_then(|name| name.to_str()?.strip_prefix(stem))
+					.is_some_and(|rest| {
+						rest.starts_with(".sync-conflict-")
+							|| (rest.starts_with(" (")
+								&& rest.to_lowercase().contains("conflict"))
+					})
+		})
+		.collect();
+	conflicts.sort();
+	conflicts
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const APP_ID: &str = "dev.edfloreshz.Done.Scratch";
+
+	fn database() -> PathBuf {
+		let folder =
+			std::env::temp_dir().join(format!("done-lock-{}", Uuid::new_v4()));
+		std::fs::create_dir_all(&folder).unwrap();
+		folder.join("done.db")
+	}
+
+	fn lock_of(database: &Path, machine: &str, renewed: DateTime<Utc>) {
+		let lock = Lock {
+			machine: machine.to_string(),
+			host: "laptop".to_string(),
+			renewed,
+		};
+		write_file(
+			&Lock::path(database),
+			&serde_json::to_string(&lock).unwrap(),
+		)
+		.unwrap();
+	}
+
+	#[test]
+	fn own_lock_is_not_held_elsewhere() {
+		let database = database();
+		Lock::acquire(APP_ID, &database).unwrap();
+		assert!(Lock::read(&database).is_some());
+		assert!(Lock::held_elsewhere(APP_ID, &database).unwrap().is_none());
+
+		Lock::release(APP_ID, &database).unwrap();
+		assert!(Lock::read(&database).is_none());
+		std::fs::remove_dir_all(database.parent().unwrap()).unwrap();
+	}
+
+	#[test]
+	fn recent_locks_of_other_machines_are_held() {
+		let database = database();
+		lock_of(&database, "other", Utc::now() - Duration::minutes(1));
+		let lock = Lock::held_elsewhere(APP_ID, &database).unwrap().unwrap();
+		assert_eq!(lock.host, "laptop");
+
+		// Another machine's lock is left alone.
+		Lock::release(APP_ID, &database).unwrap();
+		assert!(Lock::read(&database).is_some());
+		std::fs::remove_dir_all(database.parent().unwrap()).unwrap();
+	}
+
+	#[test]
+	fn stale_locks_are_not_held() {
+		let database = database();
+		lock_of(
+			&database,
+			"other",
+			Utc::now() - Duration::minutes(LOCK_TIMEOUT_MINUTES + 1),
+		);
+		assert!(Lock::held_elsewhere(APP_ID, &database).unwrap().is_none());
+		std::fs::remove_dir_all(database.parent().unwrap()).unwrap();
+	}
+
+	#[test]
+	fn finds_conflicting_copies() {
+		let database = database();
+		let folder = database.parent().unwrap();
+		for name in [
+			"done.db",
+			"done.sync-conflict-20240501-101010-ABCDEFG.db",
+			"done (conflicted copy 2024-05-01).db",
+			"done (copy).db",
+			"done.db.lock",
+			"other.sync-conflict-20240501-101010-ABCDEFG.db",
+		] {
+			std::fs::write(folder.join(name), "").unwrap();
+		}
+		assert_eq!(
+			conflicts(&database),
+			vec![
+				folder.join("done (conflicted copy 2024-05-01).db"),
+				folder.join("done.sync-conflict-20240501-101010-ABCDEFG.db"),
+			]
+		);
+		std::fs::remove_dir_all(folder).unwrap();
+	}
+
+	#[test]
+	fn names_this_host() {
+		assert!(!host().is_empty());
+	}
+}
@@ -0,0 +1,220 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use diesel::{connection::SimpleConnection, Connection, SqliteConnection};
+
+use crate::services::local::encryption::key_pragma;
+
+use super::run_migrations;
+
+/// Takes what a conflicting copy of the database has and the database
+/// lacks. Lists and other records missing from the database are added and
+/// tasks keep whichever version was changed last, tags included, so a tag
+/// removed on one machine stays removed when that machine changed the task
+/// last. Nothing written on either machine is lost, although lists deleted
+/// on one may come back.
+const MERGE: &str = "
+	CREATE TEMP TABLE newer_tasks AS
+		SELECT conflict_tasks.id_task FROM conflict.tasks AS conflict_tasks
+		LEFT JOIN main.tasks AS tasks ON tasks.id_task = conflict_tasks.id_task
+		WHERE tasks.id_task IS NULL
+			OR conflict_tasks.last_modified_date_time
+				> tasks.last_modified_date_time;
+	INSERT OR IGNORE INTO lists SELECT * FROM conflict.lists;
+	INSERT INTO tasks SELECT * FROM conflict.tasks WHERE true
+	ON CONFLICT (id_task) DO UPDATE SET
+		parent = excluded.parent,
+		parent_task = excluded.parent_task,
+		position = excluded.position,
+		title = excluded.title,
+		favorite = excluded.favorite,
+		today = excluded.today,
+		status = excluded.status,
+		priority = excluded.priority,
+		notes = excluded.notes,
+		completion_date = excluded.completion_date,
+		deletion_date = excluded.deletion_date,
+		due_date = excluded.due_date,
+		reminder_date = excluded.reminder_date,
+		recurrence = excluded.recurrence,
+		last_modified_date_time = excluded.last_modified_date_time
+	WHERE excluded.last_modified_date_time > tasks.last_modified_date_time;
+	INSERT OR IGNORE INTO tags (name) SELECT name FROM conflict.tags;
+	DELETE FROM main.task_tags
+		WHERE id_task IN (SELECT id_task FROM temp.newer_tasks);
+	INSERT OR IGNORE INTO task_tags (id_task, id_tag, position)
+		SELECT task_tags.id_task, tags.id_tag, task_tags.position
+		FROM conflict.task_tags AS task_tags
+		JOIN conflict.tags AS conflict_tags
+			ON conflict_tags.id_tag = task_tags.id_tag
+		JOIN main.tags AS tags ON tags.name = conflict_tags.name
+		WHERE task_tags.id_task IN (SELECT id_task FROM temp.newer_tasks);
+	INSERT OR IGNORE INTO task_estimates
+		SELECT * FROM conflict.task_estimates;
+	INSERT OR IGNORE INTO templates SELECT * FROM conflict.templates;
+	INSERT OR IGNORE INTO focus_sessions SELECT * FROM conflict.focus_sessions;
+	INSERT INTO time_entries SELECT * FROM conflict.time_entries WHERE true
+	ON CONFLICT (id_time_entry) DO UPDATE SET
+		end_date_time = excluded.end_date_time
+	WHERE time_entries.end_date_time IS NULL;
+	DROP TABLE temp.newer_tasks;
+";
+
+/// Merges a conflicting copy made by a sync tool into the database.
+pub(crate) fn merge(
+	connection: &mut SqliteConnection,
+	conflict: &Path,
+	key: Option<&str>,
+) -> Result<()> {
+	// Copies made by an older version have to match the schema first.
+	let mut copy = SqliteConnection::establish(&conflict.to_string_lossy())
+		.context("Failed to open the conflicting copy.")?;
+	if let Some(key) = key {
+		copy.batch_execute(&key_pragma(key))?;
+	}
+	run_migrations(&mut copy)?;
+	drop(copy);
+
+	let path = conflict.to_string_lossy().replace('\'', "''");
+	let key = key
+		.map(|key| format!(" KEY \"x'{key}'\""))
+		.unwrap_or_default();
+	connection
+		.batch_execute(&format!("ATTACH DATABASE '{path}' AS conflict{key};"))?;
+	let merged = connection
+		.transaction(|connection| connection.batch_execute(MERGE))
+		.context("Failed to merge the conflicting copy.");
+	connection.batch_execute("DETACH DATABASE conflict;")?;
+	merged?;
+	std::fs::remove_file(conflict)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+
+	use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+	use uuid::Uuid;
+
+	use crate::schema::{tags, task_tags, tasks};
+
+	use super::*;
+
+	fn database(folder: &Path, name: &str, sql: &str) -> SqliteConnection {
+		let path = folder.join(name);
+		let mut connection =
+			SqliteConnection::establish(&path.to_string_lossy()).unwrap();
+		run_migrations(&mut connection).unwrap();
+		connection
+			.batch_execute(&format!(
+				"INSERT INTO lists (id_list, name, description) VALUES ('list', 'List', '');
+				INSERT INTO tags (name) VALUES ('home'), ('work');
+				{sql}"
+			))
+			.unwrap();
+		connection
+	}
+
+	fn task(id: &str, title: &str, modified: &str) -> String {
+		format!(
+			"INSERT INTO tasks (id_task, parent, title, last_modified_date_time)
+			VALUES ('{id}', 'list', '{title}', '{modified}');"
+		)
+	}
+
+	fn tag(id: &str, name: &str) -> String {
+		format!(
+			"INSERT INTO task_tags (id_task, id_tag)
+			SELECT '{id}', id_tag FROM tags WHERE name = '{name}';"
+		)
+	}
+
+	fn tags_of(connection: &mut SqliteConnection, id: &str) -> Vec<String> {
+		task_tags::table
+			.inner_join(tags::table)
+			.filter(task_tags::id_task.eq(id))
+			.select(tags::name)
+			.order(tags::name)
+			.load(connection)
+			.unwrap()
+	}
+
+	fn title_of(connection: &mut SqliteConnection, id: &str) -> String {
+		tasks::table
+			.find(id)
+			.select(tasks::title)
+			.first(connection)
+			.unwrap()
+	}
+
+	fn folder() -> PathBuf {
+		let folder =
+			std::env::temp_dir().join(format!("done-merge-{}", Uuid::new_v4()));
+		std::fs::create_dir_all(&folder).unwrap();
+		folder
+	}
+
+	#[test]
+	fn tasks_keep_the_version_changed_last_with_its_tags() {
+		let folder = folder();
+		let mut main = database(
+			&folder,
+			"main.db",
+			&[
+				task("older", "Older here", "2024-05-01 10:00:00"),
+				tag("older", "home"),
+				tag("older", "work"),
+				task("newer", "Newer here", "2024-05-03 10:00:00"),
+				tag("newer", "home"),
+			]
+			.concat(),
+		);
+		drop(database(
+			&folder,
+			"conflict.db",
+			&[
+				task("older", "Changed there", "2024-05-02 10:00:00"),
+				tag("older", "work"),
+				task("newer", "Changed there", "2024-05-02 10:00:00"),
+				tag("newer", "work"),
+				task("added", "Added there", "2024-05-02 10:00:00"),
+				tag("added", "home"),
+			]
+			.concat(),
+		));
+		let conflict = folder.join("conflict.db");
+
+		merge(&mut main, &conflict, None).unwrap();
+
+		assert_eq!(title_of(&mut main, "older"), "Changed there");
+		assert_eq!(tags_of(&mut main, "older"), vec!["work"]);
+		assert_eq!(title_of(&mut main, "newer"), "Newer here");
+		assert_eq!(tags_of(&mut main, "newer"), vec!["home"]);
+		assert_eq!(title_of(&mut main, "added"), "Added there");
+		assert_eq!(tags_of(&mut main, "added"), vec!["home"]);
+		assert!(!conflict.exists());
+		std::fs::remove_dir_all(folder).unwrap();
+	}
+
+	#[test]
+	fn tags_only_known_to_the_copy_are_added() {
+		let folder = folder();
+		let mut main = database(&folder, "main.db", "");
+		drop(database(
+			&folder,
+			"conflict.db",
+			&[
+				"INSERT INTO tags (name) VALUES ('errands');",
+				&task("task", "Task", "2024-05-02 10:00:00"),
+				&tag("task", "errands"),
+			]
+			.concat(),
+		));
+
+		merge(&mut main, &folder.join("conflict.db"), None).unwrap();
+
+		assert_eq!(tags_of(&mut main, "task"), vec!["errands"]);
+		std::fs::remove_dir_all(folder).unwrap();
+	}
+}
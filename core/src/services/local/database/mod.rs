@@ -1,95 +1,445 @@
-use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use diesel::connection::SimpleConnection;
 use diesel::r2d2;
-use diesel::r2d2::{ConnectionManager, PooledConnection};
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, PooledConnection};
 use diesel::SqliteConnection;
 use diesel_migrations::{
 	embed_migrations, EmbeddedMigrations, MigrationHarness,
 };
 use libset::{Config, FileType};
 
+use super::{
+	backups::Backups,
+	encryption::{is_encrypted, key_pragma, stored_key},
+};
+
+use self::{
+	location::{FileIdentity, Lock, LOCK_RENEWAL},
+	merge::merge,
+};
+
+pub(crate) mod backup;
+mod location;
+mod merge;
 pub mod models;
 
 pub type Pool = r2d2::Pool<r2d2::ConnectionManager<SqliteConnection>>;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
 
+/// How long a connection waits for another one to finish writing.
+const BUSY_TIMEOUT_MS: u32 = 5000;
+/// How long closing waits for the connections in use to be given back.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The open database of each application id, shared by every handle in the
+/// process so services built for a single action don't open it again.
+static POOLS: OnceLock<Mutex<HashMap<String, OpenDatabase>>> = OnceLock::new();
+
+struct OpenDatabase {
+	pool: Pool,
+	/// The file the pool opened, to notice when it's replaced.
+	identity: Option<FileIdentity>,
+	/// Whether another machine holds the lock of the database.
+	read_only: bool,
+	renewed: Instant,
+}
+
+fn pools() -> Result<MutexGuard<'static, HashMap<String, OpenDatabase>>> {
+	POOLS
+		.get_or_init(Default::default)
+		.lock()
+		.map_err(|_| anyhow!("The database pools are poisoned."))
+}
+
+/// Settings SQLite keeps per connection. The key of an encrypted database
+/// has to come first, WAL lets reads go on while a write is in progress,
+/// and foreign keys are off unless turned on.
+#[derive(Debug)]
+struct ConnectionOptions {
+	key: Option<String>,
+	/// Whether the database is in a folder of the user's choosing.
+	chosen: bool,
+	read_only: bool,
+}
+
+impl CustomizeConnection<SqliteConnection, r2d2::Error> for ConnectionOptions {
+	fn on_acquire(
+		&self,
+		connection: &mut SqliteConnection,
+	) -> std::result::Result<(), r2d2::Error> {
+		if let Some(key) = &self.key {
+			connection
+				.batch_execute(&key_pragma(key))
+				.map_err(r2d2::Error::QueryError)?;
+		}
+		// Folders are chosen to sync them, which only works when every change
+		// is in the database file and not in a WAL next to it.
+		let journal_mode = if self.chosen { "DELETE" } else { "WAL" };
+		connection
+			.batch_execute(&format!(
+				"PRAGMA journal_mode = {journal_mode};
+				PRAGMA synchronous = NORMAL;
+				PRAGMA busy_timeout = {BUSY_TIMEOUT_MS};
+				PRAGMA foreign_keys = ON;
+				PRAGMA query_only = {};",
+				self.read_only
+			))
+			.map_err(r2d2::Error::QueryError)
+	}
+}
+
 #[derive(Debug, Clone)]
 pub struct Database {
 	application_id: String,
-	pool: Option<Pool>,
 }
 
 impl Database {
-	pub fn new(application_id: String) -> Result<Self> {
-		let database = Self {
-			application_id,
-			pool: None,
-		};
-
-		database
-			.ensure_database_exists()
-			.expect("Failed to ensure database exists.");
+	/// The database is opened on the first connection, so failures surface
+	/// where they can be handled.
+	pub fn new(application_id: String) -> Self {
+		Self { application_id }
+	}
 
-		Ok(database)
+	pub(crate) fn application_id(&self) -> &str {
+		&self.application_id
 	}
 
 	pub fn database_url(&self) -> Result<String> {
-		let app_id = self.application_id.clone();
-		let url = Config::new(&app_id, 1, Some("database"))?
-			.path(&format!("{app_id}.db"), libset::FileType::Plain)?
-			.display()
-			.to_string();
-		Ok(url)
+		Ok(self.path()?.display().to_string())
+	}
+
+	fn path(&self) -> Result<PathBuf> {
+		match self.location() {
+			Some(folder) => Ok(folder.join(format!("{}.db", self.application_id))),
+			None => self.default_path(),
+		}
+	}
+
+	/// Where the database is kept unless another folder is chosen.
+	pub fn default_path(&self) -> Result<PathBuf> {
+		let app_id = &self.application_id;
+		Ok(
+			Config::new(app_id, 1, Some("database"))?
+				.path(&format!("{app_id}.db"), FileType::Plain)?,
+		)
+	}
+
+	/// The folder chosen for the database, if it's not in the default one.
+	pub fn location(&self) -> Option<PathBuf> {
+		location::location(&self.application_id)
+	}
+
+	/// Moves the database to a folder, `None` being the default one. A
+	/// database already in a chosen folder, like one synced from another
+	/// machine, is used instead. The old file is left where it was.
+	pub fn set_location(&self, folder: Option<PathBuf>) -> Result<()> {
+		let from = self.path()?;
+		let to = match &folder {
+			Some(folder) => folder.join(format!("{}.db", self.application_id)),
+			None => self.default_path()?,
+		};
+		if from == to {
+			return Ok(());
+		}
+		self.close()?;
+		if folder.is_none()
+			|| !to.metadata().is_ok_and(|metadata| metadata.len() > 0)
+		{
+			std::fs::copy(&from, &to).with_context(|| {
+				format!("Failed to copy the database to {}", to.display())
+			})?;
+		}
+		location::set_location(&self.application_id, folder)
+	}
+
+	/// The machine writing to the database when it's open read only here.
+	pub fn locked_by(&self) -> Option<String> {
+		self.location()?;
+		let lock = Lock::held_elsewhere(&self.application_id, &self.path().ok()?);
+		lock.ok().flatten().map(|lock| lock.host)
+	}
+
+	/// Takes the lock from another machine, for when it was left behind.
+	pub fn take_over(&self) -> Result<()> {
+		self.close()?;
+		Lock::acquire(&self.application_id, &self.path()?)
 	}
 
 	pub fn establish_connection(
 		&mut self,
 	) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>> {
-		if self.pool.is_none() {
-			let manager =
-				ConnectionManager::<SqliteConnection>::new(self.database_url()?);
-			let pool = Pool::builder()
-				.build(manager)
-				.expect("Failed to create pool");
-			self.pool = Some(pool);
+		self
+			.shared_pool()?
+			.get()
+			.context("Failed to connect to the database.")
+	}
+
+	/// The key of the database, if it's encrypted.
+	pub(crate) fn key(&self) -> Result<Option<String>> {
+		if is_encrypted(&self.path()?)? {
+			Ok(Some(stored_key(&self.application_id)?))
+		} else {
+			Ok(None)
 		}
+	}
 
-		let Some(pool) = &self.pool else {
-			bail!("Failed to get pool");
-		};
+	/// Writes the WAL back to the database, releases the lock and closes the
+	/// pool of the process once its connections are given back, so the file
+	/// can be replaced. The next connection opens it again.
+	pub fn close(&self) -> Result<()> {
+		let open = pools()?.remove(&self.application_id);
+		if let Some(open) = open {
+			if !open.read_only {
+				open
+					.pool
+					.get()?
+					.batch_execute("PRAGMA wal_checkpoint(TRUNCATE);")?;
+				if self.location().is_some() {
+					Lock::release(&self.application_id, &self.path()?)?;
+				}
+			}
+			drain(&open.pool)?;
+		}
+		Ok(())
+	}
 
-		self
-			.ensure_migrations_up_to_date()
-			.expect("Failed to ensure migrations are up to date");
+	/// The files SQLite keeps next to the database while it's open.
+	pub(crate) fn journal_files(&self) -> Result<[PathBuf; 2]> {
+		let path = self.database_url()?;
+		Ok([
+			PathBuf::from(format!("{path}-wal")),
+			PathBuf::from(format!("{path}-shm")),
+		])
+	}
 
-		pool.get().map_err(|e| anyhow!(e))
+	/// Brings the schema up to date. Opening the database already does, this
+	/// is needed when the file was replaced, like when a backup is restored.
+	pub fn migrate(&mut self) -> Result<()> {
+		run_migrations(&mut *self.establish_connection()?)
 	}
 
-	pub fn ensure_migrations_up_to_date(&self) -> Result<()> {
-		let Some(pool) = &self.pool else {
-			bail!("Failed to get pool");
-		};
+	/// Checks a database in a chosen folder for changes from other machines
+	/// and renews its lock. Returns whether it changed, which means it was
+	/// opened again and what was read from it is out of date.
+	pub(crate) fn poll(&mut self) -> Result<bool> {
+		if self.location().is_none() {
+			return Ok(false);
+		}
+		let path = self.path()?;
+		let held_elsewhere =
+			Lock::held_elsewhere(&self.application_id, &path)?.is_some();
+		let conflicts = location::conflicts(&path);
+		{
+			let mut pools = pools()?;
+			let Some(open) = pools.get_mut(&self.application_id) else {
+				return Ok(false);
+			};
+			let replaced = FileIdentity::of(&path) != open.identity;
+			let mergeable = !open.read_only && !conflicts.is_empty();
+			if !replaced && held_elsewhere == open.read_only && !mergeable {
+				if !open.read_only && open.renewed.elapsed() >= LOCK_RENEWAL {
+					Lock::acquire(&self.application_id, &path)?;
+					open.renewed = Instant::now();
+				}
+				return Ok(false);
+			}
+			// The pool may hold the file that was replaced, so it's dropped
+			// without writing to it.
+			pools.remove(&self.application_id);
+		}
+		if !held_elsewhere && !conflicts.is_empty() {
+			Backups::new()?.create()?;
+			let key = self.key()?;
+			let mut connection = self.establish_connection()?;
+			for conflict in conflicts {
+				if let Err(err) = merge(&mut connection, &conflict, key.as_deref()) {
+					tracing::error!("Failed to merge {}: {err}", conflict.display());
+				}
+			}
+		}
+		Ok(true)
+	}
 
-		let mut connection = pool.get()?;
-		match connection.run_pending_migrations(MIGRATIONS) {
-			Ok(_) => Ok(()),
-			Err(err) => {
-				tracing::error!("{err}");
-				Err(anyhow!(err))
-			},
+	/// The pool of the process, opened and migrated the first time it's
+	/// needed. A database in a chosen folder is locked to this machine, or
+	/// opened read only when another machine holds the lock.
+	fn shared_pool(&self) -> Result<Pool> {
+		let mut pools = pools()?;
+		if let Some(open) = pools.get(&self.application_id) {
+			return Ok(open.pool.clone());
+		}
+		self.ensure_database_exists()?;
+		let path = self.path()?;
+		let chosen = self.location().is_some();
+		let read_only =
+			chosen && Lock::held_elsewhere(&self.application_id, &path)?.is_some();
+		if chosen && !read_only {
+			Lock::acquire(&self.application_id, &path)?;
 		}
+		let manager =
+			ConnectionManager::<SqliteConnection>::new(self.database_url()?);
+		let pool = Pool::builder()
+			.connection_customizer(Box::new(ConnectionOptions {
+				key: self.key()?,
+				chosen,
+				read_only,
+			}))
+			.build(manager)
+			.context("Failed to create the database pool.")?;
+		if !read_only {
+			run_migrations(&mut *pool.get()?)?;
+		}
+		pools.insert(
+			self.application_id.clone(),
+			OpenDatabase {
+				pool: pool.clone(),
+				identity: FileIdentity::of(&path),
+				read_only,
+				renewed: Instant::now(),
+			},
+		);
+		Ok(pool)
 	}
 
 	pub fn ensure_database_exists(&self) -> Result<()> {
-		let app_id = self.application_id.clone();
-		let database_config = Config::new(&app_id, 1, Some("database"))?;
+		let path = self.path()?;
+		if !path.exists() {
+			if let Some(folder) = path.parent() {
+				std::fs::create_dir_all(folder)?;
+			}
+			std::fs::write(&path, "")?;
+		}
+		Ok(())
+	}
+}
+
+/// Waits for the connections of a pool to be given back, so none is left
+/// open on the file once the pool is dropped.
+fn drain(pool: &Pool) -> Result<()> {
+	let start = Instant::now();
+	loop {
+		let state = pool.state();
+		if state.idle_connections == state.connections {
+			return Ok(());
+		}
+		if start.elapsed() >= DRAIN_TIMEOUT {
+			bail!("The database is still in use.");
+		}
+		std::thread::sleep(Duration::from_millis(25));
+	}
+}
+
+fn run_migrations(connection: &mut SqliteConnection) -> Result<()> {
+	connection
+		.run_pending_migrations(MIGRATIONS)
+		.map_err(|err| anyhow!(err))
+		.context("Failed to update the database.")?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use diesel::migration::{Migration, MigrationSource};
+	use diesel::sqlite::Sqlite;
+	use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl};
 
-		let database_path =
-			database_config.path(&format!("{app_id}.db"), FileType::Plain)?;
+	use crate::schema::{tags, task_tags, tasks};
 
-		if !database_path.exists() {
-			database_config.set_plain(&format!("{app_id}.db"), String::new())?;
+	use super::*;
+
+	/// The migration that moved sub-tasks and tags out of JSON columns.
+	const NORMALIZE_TASKS: &str = "2026-10-18-120000_normalize_tasks";
+
+	#[test]
+	fn migration_turns_json_sub_tasks_and_tags_into_rows() {
+		let mut connection = SqliteConnection::establish(":memory:").unwrap();
+		connection
+			.batch_execute("PRAGMA foreign_keys = ON;")
+			.unwrap();
+		let migrations: Vec<Box<dyn Migration<Sqlite>>> =
+			MIGRATIONS.migrations().unwrap();
+		let (before, after): (Vec<_>, Vec<_>) =
+			migrations.into_iter().partition(|migration| {
+				migration.name().to_string().as_str() < NORMALIZE_TASKS
+			});
+		for migration in &before {
+			migration.run(&mut connection).unwrap();
 		}
-		Ok(())
+
+		connection
+			.batch_execute(
+				r#"
+				INSERT INTO lists (id_list, name, description) VALUES ('list', 'List', '');
+				INSERT INTO tasks (id_task, parent, title, notes, tags, sub_tasks, recurrence)
+				VALUES ('task', 'list', 'Trip', '', '["travel","summer"]', '[
+					{"id": "pack", "title": "Pack", "status": "Completed",
+					 "priority": "High", "tags": ["home"],
+					 "created_date_time": "2024-05-01T10:00:00Z"},
+					{"id": "", "title": "Book", "tags": ["travel"]},
+					"not a task"
+				]', 'Mon, Fri');
+				INSERT INTO tasks (id_task, parent, title, notes, tags, sub_tasks)
+				VALUES ('orphan', 'gone', 'Orphan', '', 'not json', '[]');
+				"#,
+			)
+			.unwrap();
+		for migration in &after {
+			migration.run(&mut connection).unwrap();
+		}
+
+		let sub_tasks: Vec<(String, String, i32, i32, i32)> = tasks::table
+			.filter(tasks::parent_task.eq("task"))
+			.order(tasks::position.asc())
+			.select((
+				tasks::id_task,
+				tasks::title,
+				tasks::position,
+				tasks::status,
+				tasks::priority,
+			))
+			.load(&mut connection)
+			.unwrap();
+		assert_eq!(sub_tasks.len(), 2);
+		assert_eq!(
+			(sub_tasks[0].0.as_str(), sub_tasks[0].1.as_str()),
+			("pack", "Pack")
+		);
+		assert_eq!((sub_tasks[0].2, sub_tasks[0].3, sub_tasks[0].4), (0, 1, 2));
+		assert_eq!(sub_tasks[1].1, "Book");
+		assert!(!sub_tasks[1].0.is_empty());
+
+		let tag_names = |connection: &mut SqliteConnection, id: &str| {
+			task_tags::table
+				.inner_join(tags::table)
+				.filter(task_tags::id_task.eq(id.to_string()))
+				.order(task_tags::position.asc())
+				.select(tags::name)
+				.load::<String>(connection)
+				.unwrap()
+		};
+		assert_eq!(tag_names(&mut connection, "task"), vec!["travel", "summer"]);
+		assert_eq!(tag_names(&mut connection, "pack"), vec!["home"]);
+		assert_eq!(tag_names(&mut connection, &sub_tasks[1].0), vec!["travel"]);
+		assert!(tag_names(&mut connection, "orphan").is_empty());
+
+		let recurrence: i32 = tasks::table
+			.find("task")
+			.select(tasks::recurrence)
+			.first(&mut connection)
+			.unwrap();
+		assert_eq!(recurrence, 1 | 16);
+		// Tasks of a list that no longer exists get one back.
+		let parent: String = tasks::table
+			.find("orphan")
+			.select(tasks::parent)
+			.first(&mut connection)
+			.unwrap();
+		assert_eq!(parent, "gone");
 	}
 }
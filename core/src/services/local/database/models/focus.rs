@@ -0,0 +1,42 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+use crate::{models::focus::FocusSession, schema::focus_sessions};
+
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = focus_sessions)]
+pub struct QueryableFocusSession {
+	pub id_focus_session: String,
+	pub task_id: String,
+	pub list_id: String,
+	pub start_date_time: NaiveDateTime,
+	pub end_date_time: NaiveDateTime,
+	pub completed: bool,
+}
+
+impl From<FocusSession> for QueryableFocusSession {
+	fn from(value: FocusSession) -> Self {
+		Self {
+			id_focus_session: value.id,
+			task_id: value.task_id,
+			list_id: value.list_id,
+			start_date_time: value.start_date_time.naive_utc(),
+			end_date_time: value.end_date_time.naive_utc(),
+			completed: value.completed,
+		}
+	}
+}
+
+impl From<QueryableFocusSession> for FocusSession {
+	fn from(value: QueryableFocusSession) -> Self {
+		FocusSession {
+			id: value.id_focus_session,
+			task_id: value.task_id,
+			list_id: value.list_id,
+			start_date_time: value.start_date_time.and_utc(),
+			end_date_time: value.end_date_time.and_utc(),
+			completed: value.completed,
+		}
+	}
+}
@@ -2,7 +2,11 @@ use diesel::{Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{models::list::List, schema::lists, service::Service};
+use crate::{
+	models::list::{List, Sharing},
+	schema::lists,
+	service::Service,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Queryable, Insertable)]
 #[diesel(table_name = lists)]
@@ -34,6 +38,8 @@ impl From<QueryableList> for List {
 			id: value.id_list,
 			name: value.name,
 			service: Service::Computer,
+			sharing: Sharing::Private,
+			account: None,
 			icon: value.icon_name,
 			description: value.description,
 		}
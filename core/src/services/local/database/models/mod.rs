@@ -1,3 +1,11 @@
 pub mod list;
 
 pub mod task;
+
+pub mod tag;
+
+pub mod template;
+
+pub mod time_entry;
+
+pub mod focus;
@@ -0,0 +1,12 @@
+use diesel::{Insertable, Queryable};
+
+use crate::schema::task_tags;
+
+/// A tag of a task, `position` keeps the order the tags were added in.
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = task_tags)]
+pub struct QueryableTaskTag {
+	pub id_task: String,
+	pub id_tag: i32,
+	pub position: i32,
+}
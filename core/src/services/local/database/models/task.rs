@@ -1,5 +1,5 @@
 use chrono::{NaiveDateTime, Utc};
-use diesel::{Insertable, Queryable};
+use diesel::{AsChangeset, Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -8,99 +8,166 @@ use crate::{
 		priority::Priority, recurrence::Recurrence, status::Status, task::Task,
 	},
 	schema::tasks,
+	Error,
 };
 
-#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
-#[diesel(table_name = tasks)]
+/// A task or a sub-task, the sub-tasks of a task point to it through
+/// `parent_task` and are ordered by `position`. Tags are kept in `task_tags`.
+#[derive(
+	Debug, Clone, Insertable, Queryable, AsChangeset, Serialize, Deserialize,
+)]
+#[diesel(table_name = tasks, treat_none_as_null = true)]
 pub struct QueryableTask {
 	pub id_task: String,
 	pub parent: String,
+	pub parent_task: Option<String>,
+	pub position: i32,
 	pub title: String,
 	pub favorite: bool,
 	pub today: bool,
 	pub status: i32,
 	pub priority: i32,
-	pub sub_tasks: String,
-	pub tags: String,
 	pub notes: String,
 	pub completion_date: Option<NaiveDateTime>,
 	pub deletion_date: Option<NaiveDateTime>,
 	pub due_date: Option<NaiveDateTime>,
 	pub reminder_date: Option<NaiveDateTime>,
-	pub recurrence: String,
+	/// A bit per day, starting with Monday.
+	pub recurrence: i32,
 	pub created_date_time: NaiveDateTime,
 	pub last_modified_date_time: NaiveDateTime,
 }
 
 impl QueryableTask {
 	pub fn new(title: String, parent: String) -> Self {
-		let empty_vec: Vec<String> = vec![];
 		Self {
 			id_task: Uuid::new_v4().to_string(),
 			parent,
+			parent_task: None,
+			position: 0,
 			title,
 			favorite: false,
 			today: false,
 			notes: String::new(),
 			status: Status::NotStarted as i32,
 			priority: Priority::Low as i32,
-			sub_tasks: serde_json::to_string(&empty_vec).unwrap(),
-			tags: serde_json::to_string(&empty_vec).unwrap(),
 			completion_date: None,
 			deletion_date: None,
 			due_date: None,
 			reminder_date: None,
-			recurrence: String::new(),
+			recurrence: 0,
 			created_date_time: Utc::now().naive_utc(),
 			last_modified_date_time: Utc::now().naive_utc(),
 		}
 	}
+
+	/// The row of a sub-task, it stays in the list of its task. Sub-tasks
+	/// created before they had rows may not have an id yet.
+	pub fn sub_task(task: Task, parent_task: &Self, position: i32) -> Self {
+		let mut row: Self = task.into();
+		if row.id_task.is_empty() {
+			row.id_task = Uuid::new_v4().to_string();
+		}
+		row.parent = parent_task.parent.clone();
+		row.parent_task = Some(parent_task.id_task.clone());
+		row.position = position;
+		row
+	}
+}
+
+fn recurrence_bits(recurrence: &Recurrence) -> i32 {
+	[
+		recurrence.monday,
+		recurrence.tuesday,
+		recurrence.wednesday,
+		recurrence.thursday,
+		recurrence.friday,
+		recurrence.saturday,
+		recurrence.sunday,
+	]
+	.iter()
+	.enumerate()
+	.filter(|(_, repeats)| **repeats)
+	.fold(0, |bits, (bit, _)| bits | 1 << bit)
+}
+
+fn recurrence_from_bits(bits: i32) -> Recurrence {
+	let day = |bit: i32| bits & 1 << bit != 0;
+	Recurrence {
+		monday: day(0),
+		tuesday: day(1),
+		wednesday: day(2),
+		thursday: day(3),
+		friday: day(4),
+		saturday: day(5),
+		sunday: day(6),
+	}
 }
 
+/// Sub-tasks and tags are left out, they are written to rows of their own.
 impl From<Task> for QueryableTask {
 	fn from(value: Task) -> Self {
 		Self {
 			id_task: value.id,
 			parent: value.parent,
+			parent_task: None,
+			position: 0,
 			title: value.title,
 			favorite: value.favorite,
 			today: value.today,
 			notes: value.notes,
 			status: value.status.into(),
 			priority: value.priority.into(),
-			sub_tasks: serde_json::to_string(&value.sub_tasks).unwrap(),
-			tags: serde_json::to_string(&value.tags).unwrap(),
 			completion_date: value.completion_date.map(|dt| dt.naive_local()),
 			deletion_date: value.deletion_date.map(|dt| dt.naive_local()),
 			due_date: value.due_date.map(|dt| dt.naive_local()),
 			reminder_date: value.reminder_date.map(|dt| dt.naive_local()),
-			recurrence: value.recurrence.to_string(),
+			recurrence: recurrence_bits(&value.recurrence),
 			created_date_time: value.created_date_time.naive_local(),
 			last_modified_date_time: value.last_modified_date_time.naive_local(),
 		}
 	}
 }
 
-impl From<QueryableTask> for Task {
-	fn from(value: QueryableTask) -> Self {
-		Task {
+/// Rows written by older or broken versions can hold values a task can't,
+/// those are reported instead of read. Sub-tasks and tags are read
+/// separately.
+impl TryFrom<QueryableTask> for Task {
+	type Error = Error;
+
+	fn try_from(value: QueryableTask) -> Result<Self, Self::Error> {
+		let invalid = |message: String| {
+			Error::InvalidData(format!(
+				"Task {} is corrupt: {message}",
+				value.id_task
+			))
+		};
+		let status = value
+			.status
+			.try_into()
+			.map_err(|_| invalid(format!("{} is not a status", value.status)))?;
+		let priority = value
+			.priority
+			.try_into()
+			.map_err(|_| invalid(format!("{} is not a priority", value.priority)))?;
+		Ok(Task {
 			id: value.id_task,
 			parent: value.parent,
 			title: value.title,
 			favorite: value.favorite,
 			today: value.today,
 			notes: value.notes,
-			status: value.status.into(),
-			priority: value.priority.into(),
-			sub_tasks: serde_json::from_str(&value.sub_tasks).unwrap(),
-			tags: serde_json::from_str(&value.tags).unwrap(),
+			status,
+			priority,
+			sub_tasks: vec![],
+			tags: vec![],
 			completion_date: value.completion_date.map(|ndt| ndt.and_utc()),
 			deletion_date: value.deletion_date.map(|ndt| ndt.and_utc()),
 			due_date: value.due_date.map(|ndt| ndt.and_utc()),
 			reminder_date: value.reminder_date.map(|ndt| ndt.and_utc()),
-			recurrence: Recurrence::from_string(value.recurrence),
+			recurrence: recurrence_from_bits(value.recurrence),
 			created_date_time: value.created_date_time.and_utc(),
 			last_modified_date_time: value.last_modified_date_time.and_utc(),
-		}
+		})
 	}
 }
@@ -0,0 +1,52 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+use crate::{models::template::Template, schema::templates, Error};
+
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = templates)]
+pub struct QueryableTemplate {
+	pub id_template: String,
+	pub name: String,
+	pub content: String,
+	pub created_date_time: NaiveDateTime,
+}
+
+impl TryFrom<Template> for QueryableTemplate {
+	type Error = Error;
+
+	fn try_from(value: Template) -> Result<Self, Self::Error> {
+		let content = serde_json::to_string(&value.content).map_err(|err| {
+			Error::InvalidData(format!(
+				"Template {} can't be stored: {err}",
+				value.id
+			))
+		})?;
+		Ok(Self {
+			id_template: value.id,
+			name: value.name,
+			content,
+			created_date_time: value.created_date_time.naive_utc(),
+		})
+	}
+}
+
+impl TryFrom<QueryableTemplate> for Template {
+	type Error = Error;
+
+	fn try_from(value: QueryableTemplate) -> Result<Self, Self::Error> {
+		let content = serde_json::from_str(&value.content).map_err(|err| {
+			Error::InvalidData(format!(
+				"Template {} is corrupt: {err}",
+				value.id_template
+			))
+		})?;
+		Ok(Template {
+			id: value.id_template,
+			name: value.name,
+			content,
+			created_date_time: value.created_date_time.and_utc(),
+		})
+	}
+}
@@ -0,0 +1,49 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::time_entry::TimeEntry,
+	schema::{task_estimates, time_entries},
+};
+
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = time_entries)]
+pub struct QueryableTimeEntry {
+	pub id_time_entry: String,
+	pub task_id: String,
+	pub list_id: String,
+	pub start_date_time: NaiveDateTime,
+	pub end_date_time: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = task_estimates)]
+pub struct QueryableEstimate {
+	pub id_task: String,
+	pub estimate: i32,
+}
+
+impl From<TimeEntry> for QueryableTimeEntry {
+	fn from(value: TimeEntry) -> Self {
+		Self {
+			id_time_entry: value.id,
+			task_id: value.task_id,
+			list_id: value.list_id,
+			start_date_time: value.start_date_time.naive_utc(),
+			end_date_time: value.end_date_time.map(|dt| dt.naive_utc()),
+		}
+	}
+}
+
+impl From<QueryableTimeEntry> for TimeEntry {
+	fn from(value: QueryableTimeEntry) -> Self {
+		TimeEntry {
+			id: value.id_time_entry,
+			task_id: value.task_id,
+			list_id: value.list_id,
+			start_date_time: value.start_date_time.and_utc(),
+			end_date_time: value.end_date_time.map(|ndt| ndt.and_utc()),
+		}
+	}
+}
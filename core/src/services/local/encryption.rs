@@ -0,0 +1,193 @@
+use std::{
+	fs::File,
+	io::Read,
+	path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use diesel::{
+	connection::SimpleConnection, sql_query, sql_types::Text, Connection,
+	QueryableByName, RunQueryDsl, SqliteConnection,
+};
+use uuid::Uuid;
+
+use crate::service::Services;
+
+use super::database::Database;
+
+/// Name of the database key in the system keyring.
+const KEY_NAME: &str = "database-key";
+
+/// How every plain SQLite file starts, encrypted ones look random.
+const PLAIN_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+#[derive(QueryableByName)]
+struct CipherVersion {
+	#[diesel(sql_type = Text)]
+	#[allow(dead_code)]
+	cipher_version: String,
+}
+
+/// Encryption of the local database at rest with SQLCipher. The key is
+/// random and kept in the system keyring, next to the Microsoft tokens.
+#[derive(Debug, Clone)]
+pub struct Encryption {
+	database: Database,
+}
+
+impl Encryption {
+	pub fn new() -> Self {
+		Self {
+			database: Database::new(Services::app_id()),
+		}
+	}
+
+	/// Whether SQLite was built with SQLCipher, plain SQLite ignores the key
+	/// and would leave the database readable.
+	pub fn available() -> bool {
+		let Ok(mut connection) = SqliteConnection::establish(":memory:") else {
+			return false;
+		};
+		sql_query("PRAGMA cipher_version;")
+			.load::<CipherVersion>(&mut connection)
+			.is_ok_and(|version| !version.is_empty())
+	}
+
+	pub fn enabled(&self) -> Result<bool> {
+		is_encrypted(&self.path()?)
+	}
+
+	/// Encrypts the database with a new key.
+	pub fn enable(&self) -> Result<()> {
+		if !Self::available() {
+			bail!("This build of SQLite can't encrypt, it needs SQLCipher.");
+		}
+		if self.enabled()? {
+			return Ok(());
+		}
+		let key = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+		let application_id = self.database.application_id();
+		keytar::set_password(application_id, KEY_NAME, &key)?;
+		if let Err(err) = self.replace(None, Some(&key)) {
+			// The plain database is left as it was, so is the keyring.
+			if let Err(err) = keytar::delete_password(application_id, KEY_NAME) {
+				tracing::error!("Failed to delete the database key: {err}");
+			}
+			return Err(err);
+		}
+		Ok(())
+	}
+
+	/// Decrypts the database and forgets its key.
+	pub fn disable(&self) -> Result<()> {
+		if !self.enabled()? {
+			return Ok(());
+		}
+		let application_id = self.database.application_id();
+		let key = stored_key(application_id)?;
+		self.replace(Some(&key), None)?;
+		keytar::delete_password(application_id, KEY_NAME)?;
+		Ok(())
+	}
+
+	fn path(&self) -> Result<PathBuf> {
+		Ok(PathBuf::from(self.database.database_url()?))
+	}
+
+	/// Rewrites the database with another key, `None` being no encryption.
+	/// The new copy only replaces the database once it's complete.
+	fn replace(&self, from: Option<&str>, to: Option<&str>) -> Result<()> {
+		let path = self.path()?;
+		self.database.close()?;
+		let copy = path.with_extension("db.export");
+		if copy.exists() {
+			std::fs::remove_file(&copy)?;
+		}
+		if let Err(err) = export(&path, from, &copy, to) {
+			if let Err(err) = std::fs::remove_file(&copy) {
+				tracing::error!("Failed to remove {}: {err}", copy.display());
+			}
+			return Err(err);
+		}
+		std::fs::rename(&copy, &path)?;
+		// The WAL was written back on close, what's left belongs to the old
+		// file and would corrupt the new one.
+		for suffix in ["-wal", "-shm"] {
+			let file = PathBuf::from(format!("{}{suffix}", path.display()));
+			if file.exists() {
+				std::fs::remove_file(file)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl Default for Encryption {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// The pragma unlocking an encrypted database with a raw key, which skips
+/// deriving a key from a passphrase on every connection.
+pub(crate) fn key_pragma(key: &str) -> String {
+	format!("PRAGMA key = \"x'{key}'\";")
+}
+
+/// Whether a database file is encrypted, missing and empty files aren't.
+pub(crate) fn is_encrypted(path: &Path) -> Result<bool> {
+	if !path.exists() {
+		return Ok(false);
+	}
+	let mut header = [0; 16];
+	let mut file = File::open(path)?;
+	let mut read = 0;
+	while read < header.len() {
+		match file.read(&mut header[read..])? {
+			0 => break,
+			bytes => read += bytes,
+		}
+	}
+	Ok(read > 0 && &header != PLAIN_HEADER)
+}
+
+/// Deletes the key of a database that's being deleted.
+pub(crate) fn forget_key(application_id: &str) -> Result<()> {
+	keytar::delete_password(application_id, KEY_NAME)?;
+	Ok(())
+}
+
+pub(crate) fn stored_key(application_id: &str) -> Result<String> {
+	let password = keytar::get_password(application_id, KEY_NAME)?;
+	if !password.success || password.password.is_empty() {
+		bail!("The database is encrypted but its key is not in the keyring.");
+	}
+	Ok(password.password)
+}
+
+/// Copies a database into a new file with SQLCipher's export, which can
+/// add, change or remove the encryption on the way.
+fn export(
+	source: &Path,
+	source_key: Option<&str>,
+	destination: &Path,
+	destination_key: Option<&str>,
+) -> Result<()> {
+	let mut connection = SqliteConnection::establish(&source.to_string_lossy())
+		.context("Failed to open the database.")?;
+	if let Some(key) = source_key {
+		connection.batch_execute(&key_pragma(key))?;
+	}
+	let destination = destination.to_string_lossy().replace('\'', "''");
+	let destination_key = destination_key
+		.map(|key| format!("x'{key}'"))
+		.unwrap_or_default();
+	connection
+		.batch_execute(&format!(
+			"ATTACH DATABASE '{destination}' AS export KEY \"{destination_key}\";
+			SELECT sqlcipher_export('export');
+			DETACH DATABASE export;"
+		))
+		.context("Failed to export the database.")?;
+	Ok(())
+}
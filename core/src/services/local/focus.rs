@@ -0,0 +1,108 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::{
+	models::focus::{FocusSession, FocusStatistics},
+	schema::focus_sessions,
+	service::Services,
+};
+
+use super::database::{models::focus::QueryableFocusSession, Database};
+
+/// Logs Pomodoro focus sessions for tasks of any service in the local
+/// database.
+#[derive(Debug, Clone)]
+pub struct FocusLog {
+	database: Database,
+}
+
+impl FocusLog {
+	pub fn new() -> Result<Self> {
+		let database = Database::new(Services::app_id());
+		Ok(Self { database })
+	}
+
+	pub fn log_session(&mut self, session: FocusSession) -> Result<()> {
+		let queryable_session: QueryableFocusSession = session.into();
+		diesel::insert_into(focus_sessions::table)
+			.values(&queryable_session)
+			.execute(&mut self.database.establish_connection()?)
+			.context("Failed to log focus session.")?;
+		Ok(())
+	}
+
+	/// Reads the sessions started within the given range.
+	pub fn read_sessions(
+		&mut self,
+		from: Option<DateTime<Utc>>,
+		to: Option<DateTime<Utc>>,
+	) -> Result<Vec<FocusSession>> {
+		let mut query = focus_sessions::table.into_boxed();
+		if let Some(from) = from {
+			query =
+				query.filter(focus_sessions::start_date_time.ge(from.naive_utc()));
+		}
+		if let Some(to) = to {
+			query = query.filter(focus_sessions::start_date_time.lt(to.naive_utc()));
+		}
+		let sessions = query
+			.order(focus_sessions::start_date_time.asc())
+			.load::<QueryableFocusSession>(
+				&mut self.database.establish_connection()?,
+			)?
+			.into_iter()
+			.map(|session| session.into())
+			.collect();
+		Ok(sessions)
+	}
+
+	pub fn read_task_sessions(
+		&mut self,
+		task_id: String,
+	) -> Result<Vec<FocusSession>> {
+		let sessions = focus_sessions::table
+			.filter(focus_sessions::task_id.eq(task_id))
+			.order(focus_sessions::start_date_time.asc())
+			.load::<QueryableFocusSession>(
+				&mut self.database.establish_connection()?,
+			)?
+			.into_iter()
+			.map(|session| session.into())
+			.collect();
+		Ok(sessions)
+	}
+
+	/// Statistics for the sessions started today, in local time.
+	pub fn today_statistics(&mut self) -> Result<FocusStatistics> {
+		let today = Local::now().date_naive();
+		self.statistics(
+			Some(start_of_day(today)),
+			Some(start_of_day(today + Duration::days(1))),
+		)
+	}
+
+	pub fn statistics(
+		&mut self,
+		from: Option<DateTime<Utc>>,
+		to: Option<DateTime<Utc>>,
+	) -> Result<FocusStatistics> {
+		Ok(self.read_sessions(from, to)?.into_iter().collect())
+	}
+}
+
+/// When a local day starts, days whose midnight is skipped by a daylight
+/// saving change start an hour later.
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+	let midnight = date.and_time(NaiveTime::MIN);
+	midnight
+		.and_local_timezone(Local)
+		.earliest()
+		.or_else(|| {
+			(midnight + Duration::hours(1))
+				.and_local_timezone(Local)
+				.earliest()
+		})
+		.map(|start| start.with_timezone(&Utc))
+		.unwrap_or_else(|| midnight.and_utc())
+}
@@ -1,2 +1,7 @@
+pub mod backups;
 pub mod database;
+pub mod encryption;
+pub mod focus;
 pub(crate) mod service;
+pub mod templates;
+pub mod time_tracking;
@@ -1,25 +1,37 @@
-use std::pin::Pin;
+use std::{collections::HashMap, pin::Pin};
 
-use anyhow::{Context, Result};
+use anyhow::Context;
 use async_trait::async_trait;
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
-use futures::Stream;
+use chrono::{Duration, NaiveTime, Utc};
+use diesel::{
+	BoolExpressionMethods, Connection, ExpressionMethods, QueryDsl, RunQueryDsl,
+	SqliteConnection,
+};
+use futures::{Stream, StreamExt};
 use url::Url;
 
 use crate::{
-	models::{list::List, task::Task},
+	models::{list::List, smart_list::SmartList, status::Status, task::Task},
 	schema::lists::dsl::lists,
 	schema::lists::*,
 	schema::tasks::dsl::tasks,
 	schema::tasks::*,
+	schema::{tags, task_tags},
 	task_service::TodoProvider,
+	Result,
 };
 
 use super::database::{
-	models::{list::QueryableList, task::QueryableTask},
+	models::{list::QueryableList, tag::QueryableTaskTag, task::QueryableTask},
 	Database,
 };
 
+/// How many rows the streams read at a time.
+const PAGE_SIZE: i64 = 200;
+
+/// How many ids are looked up in one query.
+const ID_CHUNK_SIZE: usize = 500;
+
 #[derive(Debug, Clone)]
 pub struct ComputerStorage {
 	database: Database,
@@ -27,11 +39,193 @@ pub struct ComputerStorage {
 
 impl ComputerStorage {
 	pub(crate) fn new(application_id: String) -> Self {
-		let database =
-			Database::new(application_id).expect("Failed to create database");
+		Self {
+			database: Database::new(application_id),
+		}
+	}
+
+	/// Runs a query on the blocking thread pool, so reading a large list
+	/// doesn't stall the executor.
+	async fn blocking<T, F>(&self, query: F) -> Result<T>
+	where
+		T: Send + 'static,
+		F: FnOnce(&mut SqliteConnection) -> Result<T> + Send + 'static,
+	{
+		let mut database = self.database.clone();
+		tokio::task::spawn_blocking(move || {
+			let mut connection = database.establish_connection()?;
+			query(&mut connection)
+		})
+		.await
+		.context("Failed to run the query.")?
+	}
+
+	/// Reads a query a page at a time, ending the stream at the first page
+	/// that isn't full or with the error of the first that can't be read.
+	fn pages<T, F>(self, query: F) -> impl Stream<Item = Result<Vec<T>>> + Send
+	where
+		T: Send + 'static,
+		F: Fn(&mut SqliteConnection, i64) -> Result<Vec<T>>
+			+ Clone
+			+ Send
+			+ Sync
+			+ 'static,
+	{
+		futures::stream::unfold(Some(0), move |offset| {
+			let (storage, query) = (self.clone(), query.clone());
+			async move {
+				let offset = offset?;
+				let page = storage
+					.blocking(move |connection| query(connection, offset))
+					.await;
+				let next = match &page {
+					Ok(page) if page.len() as i64 == PAGE_SIZE => {
+						Some(offset + PAGE_SIZE)
+					},
+					_ => None,
+				};
+				Some((page, next))
+			}
+		})
+	}
+}
+
+/// Converts a row if it can be read, corrupt ones are logged and left out
+/// so one bad task doesn't hide the rest of the list.
+fn readable(row: QueryableTask) -> Option<Task> {
+	row
+		.try_into()
+		.map_err(|err| tracing::error!("Skipping task: {err}"))
+		.ok()
+}
+
+/// Reads tasks with their sub-tasks and tags.
+fn with_details(
+	connection: &mut SqliteConnection,
+	rows: Vec<QueryableTask>,
+) -> Result<Vec<Task>> {
+	let ids: Vec<String> = rows.iter().map(|row| row.id_task.clone()).collect();
+	let mut sub_task_rows: Vec<QueryableTask> = vec![];
+	// SQLite limits how many values a query can bind.
+	for chunk in ids.chunks(ID_CHUNK_SIZE) {
+		sub_task_rows.extend(
+			tasks
+				.filter(parent_task.eq_any(chunk))
+				.order(position.asc())
+				.load::<QueryableTask>(connection)?,
+		);
+	}
+	let tagged: Vec<&String> = ids
+		.iter()
+		.chain(sub_task_rows.iter().map(|row| &row.id_task))
+		.collect();
+	let mut task_tag_names: HashMap<String, Vec<String>> = HashMap::new();
+	for chunk in tagged.chunks(ID_CHUNK_SIZE) {
+		let names: Vec<(String, String)> = task_tags::table
+			.inner_join(tags::table)
+			.filter(task_tags::id_task.eq_any(chunk))
+			.order(task_tags::position.asc())
+			.select((task_tags::id_task, tags::name))
+			.load(connection)?;
+		for (task, tag) in names {
+			task_tag_names.entry(task).or_default().push(tag);
+		}
+	}
+	let mut sub_tasks: HashMap<String, Vec<Task>> = HashMap::new();
+	for row in sub_task_rows {
+		let parent_id = row.parent_task.clone().unwrap_or_default();
+		if let Some(mut task) = readable(row) {
+			task.tags = task_tag_names.remove(&task.id).unwrap_or_default();
+			sub_tasks.entry(parent_id).or_default().push(task);
+		}
+	}
+	Ok(
+		rows
+			.into_iter()
+			.filter_map(readable)
+			.map(|mut task| {
+				task.tags = task_tag_names.remove(&task.id).unwrap_or_default();
+				task.sub_tasks = sub_tasks.remove(&task.id).unwrap_or_default();
+				task
+			})
+			.collect(),
+	)
+}
 
-		Self { database }
+/// Brings the sub-tasks and tags of a task in line with the ones it has now.
+/// Rows that didn't change are left alone, removed sub-tasks are deleted
+/// along with their tags.
+fn save_details(
+	connection: &mut SqliteConnection,
+	row: &QueryableTask,
+	task: &Task,
+) -> diesel::QueryResult<()> {
+	save_tags(connection, &row.id_task, &task.tags)?;
+	let sub_task_rows: Vec<QueryableTask> = task
+		.sub_tasks
+		.iter()
+		.enumerate()
+		.map(|(index, sub_task)| {
+			QueryableTask::sub_task(sub_task.clone(), row, index as i32)
+		})
+		.collect();
+	let kept: Vec<&String> =
+		sub_task_rows.iter().map(|row| &row.id_task).collect();
+	diesel::delete(
+		tasks
+			.filter(parent_task.eq(&row.id_task))
+			.filter(id_task.ne_all(kept)),
+	)
+	.execute(connection)?;
+	for (sub_task_row, sub_task) in sub_task_rows.iter().zip(&task.sub_tasks) {
+		diesel::insert_into(tasks)
+			.values(sub_task_row)
+			.on_conflict(id_task)
+			.do_update()
+			.set(sub_task_row)
+			.execute(connection)?;
+		save_tags(connection, &sub_task_row.id_task, &sub_task.tags)?;
 	}
+	Ok(())
+}
+
+/// Brings the tags of a task in line with `names`, in that order.
+fn save_tags(
+	connection: &mut SqliteConnection,
+	task_id: &str,
+	names: &[String],
+) -> diesel::QueryResult<()> {
+	let mut tag_ids = vec![];
+	for tag in names {
+		diesel::insert_or_ignore_into(tags::table)
+			.values(tags::name.eq(tag))
+			.execute(connection)?;
+		tag_ids.push(
+			tags::table
+				.filter(tags::name.eq(tag))
+				.select(tags::id_tag)
+				.first::<i32>(connection)?,
+		);
+	}
+	diesel::delete(
+		task_tags::table
+			.filter(task_tags::id_task.eq(task_id))
+			.filter(task_tags::id_tag.ne_all(&tag_ids)),
+	)
+	.execute(connection)?;
+	for (index, tag_id) in tag_ids.into_iter().enumerate() {
+		diesel::insert_into(task_tags::table)
+			.values(QueryableTaskTag {
+				id_task: task_id.to_string(),
+				id_tag: tag_id,
+				position: index as i32,
+			})
+			.on_conflict((task_tags::id_task, task_tags::id_tag))
+			.do_update()
+			.set(task_tags::position.eq(index as i32))
+			.execute(connection)?;
+	}
+	Ok(())
 }
 
 #[async_trait]
@@ -40,11 +234,11 @@ impl TodoProvider for ComputerStorage {
 		Ok(())
 	}
 
-	fn login(&self) -> Result<()> {
+	async fn login(&self) -> Result<()> {
 		Ok(())
 	}
 
-	fn logout(&self) -> Result<()> {
+	async fn logout(&self) -> Result<()> {
 		Ok(())
 	}
 
@@ -53,38 +247,97 @@ impl TodoProvider for ComputerStorage {
 	}
 
 	fn stream_support(&self) -> bool {
-		false
+		true
 	}
 
 	async fn read_tasks(&mut self) -> Result<Vec<Task>> {
-		let task_list: Vec<Task> = tasks
-			.load::<QueryableTask>(&mut self.database.establish_connection()?)?
-			.iter()
-			.map(|t| t.clone().into())
-			.collect();
-
-		Ok(task_list)
+		self
+			.blocking(|connection| {
+				let rows = tasks
+					.filter(parent_task.is_null())
+					.load::<QueryableTask>(connection)?;
+				with_details(connection, rows)
+			})
+			.await
 	}
 
 	async fn get_tasks(
 		&mut self,
-		_parent_list: String,
-	) -> Result<Pin<Box<dyn Stream<Item = Task> + Send>>> {
-		todo!("This service does not implement streams")
+		parent_list: String,
+	) -> Result<Pin<Box<dyn Stream<Item = Result<Task>> + Send>>> {
+		let storage = self.clone();
+		let stream = self
+			.clone()
+			.pages(move |connection, offset| {
+				Ok(
+					tasks
+						.filter(parent.eq(&parent_list))
+						.filter(parent_task.is_null())
+						.order((created_date_time.asc(), id_task.asc()))
+						.limit(PAGE_SIZE)
+						.offset(offset)
+						.load::<QueryableTask>(connection)?,
+				)
+			})
+			.then(move |rows| {
+				let storage = storage.clone();
+				async move {
+					let rows = rows?;
+					storage
+						.blocking(move |connection| with_details(connection, rows))
+						.await
+				}
+			})
+			.flat_map(|page| {
+				futures::stream::iter(match page {
+					Ok(page) => page.into_iter().map(Ok).collect(),
+					Err(err) => vec![Err(err)],
+				})
+			})
+			.boxed();
+		Ok(stream)
 	}
 
 	async fn read_tasks_from_list(
 		&mut self,
 		parent_list: String,
 	) -> Result<Vec<Task>> {
-		let response: Vec<Task> = tasks
-			.filter(parent.eq(parent_list))
-			.load::<QueryableTask>(&mut self.database.establish_connection()?)?
-			.iter()
-			.map(|t| t.clone().into())
-			.collect();
+		self
+			.blocking(move |connection| {
+				let rows = tasks
+					.filter(parent.eq(parent_list))
+					.filter(parent_task.is_null())
+					.load::<QueryableTask>(connection)?;
+				with_details(connection, rows)
+			})
+			.await
+	}
 
-		Ok(response)
+	async fn read_smart_list(&mut self, list: SmartList) -> Result<Vec<Task>> {
+		let now = Utc::now().naive_utc();
+		let today_start = now.date().and_time(NaiveTime::MIN);
+		let query = tasks.filter(parent_task.is_null()).into_boxed();
+		let query = match list {
+			SmartList::All => query,
+			SmartList::Today => query.filter(
+				today.eq(true).or(
+					due_date
+						.ge(today_start)
+						.and(due_date.lt(today_start + Duration::days(1))),
+				),
+			),
+			SmartList::Starred => query.filter(favorite.eq(true)),
+			SmartList::Next7Days => {
+				query.filter(due_date.between(now, now + Duration::days(7)))
+			},
+			SmartList::Done => query.filter(status.eq(i32::from(Status::Completed))),
+		};
+		self
+			.blocking(move |connection| {
+				let rows = query.load::<QueryableTask>(connection)?;
+				with_details(connection, rows)
+			})
+			.await
 	}
 
 	async fn read_task(
@@ -92,63 +345,65 @@ impl TodoProvider for ComputerStorage {
 		_task_list_id: String,
 		task_id: String,
 	) -> Result<Task> {
-		let task: QueryableTask = tasks
-			.find(task_id)
-			.first(&mut self.database.establish_connection()?)
-			.context("Failed to fetch list of tasks.")?;
-
-		Ok(task.into())
+		self
+			.blocking(move |connection| {
+				let row: QueryableTask = tasks
+					.find(&task_id)
+					.first(connection)
+					.context("Failed to fetch list of tasks.")?;
+				match with_details(connection, vec![row.clone()])?.pop() {
+					Some(task) => Ok(task),
+					None => row.try_into(),
+				}
+			})
+			.await
 	}
 
 	async fn create_task(&mut self, task: Task) -> Result<()> {
-		let queryable_task: QueryableTask = task.into();
-
-		diesel::insert_into(tasks)
-			.values(&queryable_task)
-			.execute(&mut self.database.establish_connection()?)?;
-
-		Ok(())
+		self
+			.blocking(move |connection| {
+				connection.transaction(|connection| {
+					let row: QueryableTask = task.clone().into();
+					diesel::insert_into(tasks)
+						.values(&row)
+						.execute(connection)?;
+					save_details(connection, &row, &task)
+				})?;
+				Ok(())
+			})
+			.await
 	}
 
 	async fn update_task(&mut self, task: Task) -> Result<Task> {
-		let original_task = task.clone();
-		let queryable_task: QueryableTask = task.into();
-
-		diesel::update(tasks.filter(id_task.eq(queryable_task.id_task.clone())))
-			.set((
-				id_task.eq(queryable_task.id_task),
-				parent.eq(queryable_task.parent),
-				title.eq(queryable_task.title),
-				favorite.eq(queryable_task.favorite),
-				today.eq(queryable_task.today),
-				status.eq(queryable_task.status),
-				priority.eq(queryable_task.priority),
-				sub_tasks.eq(queryable_task.sub_tasks),
-				tags.eq(queryable_task.tags),
-				notes.eq(queryable_task.notes),
-				completion_date.eq(queryable_task.completion_date),
-				deletion_date.eq(queryable_task.deletion_date),
-				due_date.eq(queryable_task.due_date),
-				reminder_date.eq(queryable_task.reminder_date),
-				recurrence.eq(queryable_task.recurrence),
-				created_date_time.eq(queryable_task.created_date_time),
-				last_modified_date_time.eq(queryable_task.last_modified_date_time),
-			))
-			.execute(&mut self.database.establish_connection()?)
-			.context("Failed to update task.")?;
-
-		Ok(original_task)
+		self
+			.blocking(move |connection| {
+				connection
+					.transaction(|connection| {
+						let row: QueryableTask = task.clone().into();
+						diesel::update(tasks.find(&row.id_task))
+							.set(&row)
+							.execute(connection)?;
+						save_details(connection, &row, &task)
+					})
+					.context("Failed to update task.")?;
+				Ok(task)
+			})
+			.await
 	}
 
+	/// Sub-tasks and tags go with the task, the foreign keys cascade.
 	async fn delete_task(
 		&mut self,
 		_list_id: String,
 		task_id: String,
 	) -> Result<()> {
-		diesel::delete(tasks.filter(id_task.eq(task_id)))
-			.execute(&mut self.database.establish_connection()?)?;
-
-		Ok(())
+		self
+			.blocking(move |connection| {
+				diesel::delete(tasks.filter(id_task.eq(task_id)))
+					.execute(connection)?;
+				Ok(())
+			})
+			.await
 	}
 
 	async fn read_lists(&mut self) -> Result<Vec<List>> {
@@ -161,8 +416,26 @@ impl TodoProvider for ComputerStorage {
 
 	async fn get_lists(
 		&mut self,
-	) -> Result<Pin<Box<dyn Stream<Item = List> + Send>>> {
-		todo!("This service does not implement streams")
+	) -> Result<Pin<Box<dyn Stream<Item = Result<List>> + Send>>> {
+		let stream = self
+			.clone()
+			.pages(|connection, offset| {
+				Ok(
+					lists
+						.order(id_list.asc())
+						.limit(PAGE_SIZE)
+						.offset(offset)
+						.load::<QueryableList>(connection)?,
+				)
+			})
+			.flat_map(|rows| {
+				futures::stream::iter(match rows {
+					Ok(rows) => rows.into_iter().map(List::from).map(Ok).collect(),
+					Err(err) => vec![Err(err)],
+				})
+			})
+			.boxed();
+		Ok(stream)
 	}
 
 	async fn read_list(&mut self, id: String) -> Result<List> {
@@ -199,3 +472,67 @@ impl TodoProvider for ComputerStorage {
 		Ok(())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use uuid::Uuid;
+
+	use crate::{
+		service::{Service, Services},
+		services::local::time_tracking::TimeTracker,
+	};
+
+	use super::*;
+
+	#[tokio::test]
+	async fn updates_only_touch_changed_sub_tasks_and_tags() {
+		Services::init("dev.edfloreshz.Done.Scratch");
+		Database::new(Services::app_id()).migrate().unwrap();
+		let mut provider = Service::Computer.get_service();
+		let list = provider
+			.create_list(List::new("Details", Service::Computer))
+			.await
+			.unwrap();
+		let tag = |prefix: &str| format!("{prefix}-{}", Uuid::new_v4());
+		let (a, b, c) = (tag("a"), tag("b"), tag("c"));
+		let mut task = Task::new("Trip".into(), list.id.clone());
+		task.tags = vec![a.clone(), b.clone(), c.clone()];
+		let (kept, removed) = (
+			Task::new("Pack".into(), list.id.clone()),
+			Task::new("Book".into(), list.id.clone()),
+		);
+		task.sub_tasks = vec![removed.clone(), kept.clone()];
+		task.sub_tasks[1].tags = vec![a.clone()];
+		provider.create_task(task.clone()).await.unwrap();
+
+		// Estimates go when their task row is deleted, so one that survives
+		// the update shows the row was kept.
+		let mut tracker = TimeTracker::new().unwrap();
+		tracker.set_estimate(kept.id.clone(), Some(15)).unwrap();
+		tracker.set_estimate(removed.id.clone(), Some(15)).unwrap();
+
+		task.tags = vec![c.clone(), a.clone()];
+		task.sub_tasks.remove(0);
+		task.sub_tasks[0].title = "Pack bags".into();
+		task.sub_tasks[0].tags = vec![b.clone(), a.clone()];
+		provider.update_task(task.clone()).await.unwrap();
+
+		let read = provider
+			.read_task(list.id.clone(), task.id.clone())
+			.await
+			.unwrap();
+		assert_eq!(read.tags, vec![c, a.clone()]);
+		assert_eq!(read.sub_tasks.len(), 1);
+		assert_eq!(read.sub_tasks[0].id, kept.id);
+		assert_eq!(read.sub_tasks[0].title, "Pack bags");
+		assert_eq!(read.sub_tasks[0].tags, vec![b, a]);
+		assert_eq!(tracker.read_estimate(kept.id).unwrap(), Some(15));
+		assert_eq!(tracker.read_estimate(removed.id.clone()).unwrap(), None);
+		assert!(provider
+			.read_task(list.id.clone(), removed.id)
+			.await
+			.is_err());
+
+		provider.delete_list(list.id).await.unwrap();
+	}
+}
@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::{
+	models::{
+		list::List,
+		template::{ListTemplate, Template},
+	},
+	schema::templates::dsl::templates,
+	schema::templates::*,
+	service::{Service, Services},
+};
+
+use super::database::{models::template::QueryableTemplate, Database};
+
+/// Stores task and list templates in the local database, regardless of
+/// the service they were created from.
+#[derive(Debug, Clone)]
+pub struct TemplateStore {
+	database: Database,
+}
+
+impl TemplateStore {
+	pub fn new() -> Self {
+		let database = Database::new(Services::app_id());
+		Self { database }
+	}
+
+	pub fn read_templates(&mut self) -> Result<Vec<Template>> {
+		let results: Vec<Template> = templates
+			.order(created_date_time.asc())
+			.load::<QueryableTemplate>(&mut self.database.establish_connection()?)?
+			.into_iter()
+			.filter_map(|t| {
+				t.try_into()
+					.map_err(|err| tracing::error!("Skipping template: {err}"))
+					.ok()
+			})
+			.collect();
+		Ok(results)
+	}
+
+	pub fn create_template(&mut self, template: Template) -> Result<()> {
+		let template: QueryableTemplate = template.try_into()?;
+
+		diesel::insert_into(templates)
+			.values(&template)
+			.execute(&mut self.database.establish_connection()?)
+			.context("Failed to create template.")?;
+
+		Ok(())
+	}
+
+	pub fn delete_template(&mut self, id: String) -> Result<()> {
+		diesel::delete(templates.filter(id_template.eq(id)))
+			.execute(&mut self.database.establish_connection()?)?;
+		Ok(())
+	}
+
+	/// Creates a list and its tasks in the given service from a template,
+	/// with dates computed relative to today. The list is removed again if
+	/// any of its tasks can't be created.
+	pub async fn instantiate_list(
+		template: &ListTemplate,
+		list_name: &str,
+		service: Service,
+	) -> Result<List> {
+		let (list, tasks) = template.instantiate(list_name, service, Utc::now());
+		let mut provider = service.get_service();
+		let list = provider.create_list(list).await?;
+		for mut task in tasks {
+			task.parent = list.id.clone();
+			if let Err(err) = provider.create_task(task).await {
+				if let Err(err) = provider.delete_list(list.id.clone()).await {
+					tracing::error!("Failed to remove list {}: {err}", list.name);
+				}
+				return Err(err.into());
+			}
+		}
+		Ok(list)
+	}
+}
+
+impl Default for TemplateStore {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::models::{task::Task, template::TemplateContent};
+
+	use super::*;
+
+	#[tokio::test]
+	async fn instantiating_a_list_creates_its_tasks() {
+		Services::init("dev.edfloreshz.Done.Scratch");
+		Database::new(Services::app_id()).migrate().unwrap();
+
+		let list = List::new("Chores", Service::Computer);
+		let tasks = vec![
+			Task::new("Dishes".into(), list.id.clone()),
+			Task::new("Laundry".into(), list.id.clone()),
+		];
+		let template = Template::from_list(&list, &tasks);
+		let mut store = TemplateStore::new();
+		store.create_template(template.clone()).unwrap();
+		let stored = store
+			.read_templates()
+			.unwrap()
+			.into_iter()
+			.find(|stored| stored.id == template.id)
+			.unwrap();
+		let TemplateContent::List(list_template) = stored.content else {
+			panic!("Expected a list template");
+		};
+
+		let new = TemplateStore::instantiate_list(
+			&list_template,
+			"Weekend chores",
+			Service::Computer,
+		)
+		.await
+		.unwrap();
+		let mut provider = Service::Computer.get_service();
+		let mut titles: Vec<String> = provider
+			.read_tasks_from_list(new.id.clone())
+			.await
+			.unwrap()
+			.into_iter()
+			.map(|task| task.title)
+			.collect();
+		titles.sort();
+		assert_eq!(titles, vec!["Dishes", "Laundry"]);
+
+		provider.delete_list(new.id).await.unwrap();
+		store.delete_template(template.id).unwrap();
+	}
+}
@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use diesel::{
+	BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl,
+	RunQueryDsl,
+};
+
+use crate::{
+	models::{
+		task::Task,
+		time_entry::{TaskTime, TimeEntry, TimeReport},
+	},
+	schema::{task_estimates, time_entries},
+	service::Services,
+};
+
+use super::database::{
+	models::time_entry::{QueryableEstimate, QueryableTimeEntry},
+	Database,
+};
+
+/// Records time entries and estimates for tasks of any service in the
+/// local database.
+#[derive(Debug, Clone)]
+pub struct TimeTracker {
+	database: Database,
+}
+
+impl TimeTracker {
+	pub fn new() -> Result<Self> {
+		let database = Database::new(Services::app_id());
+		Ok(Self { database })
+	}
+
+	/// Starts a timer for the task, returns the running entry if there is one.
+	pub fn start(&mut self, task: &Task) -> Result<TimeEntry> {
+		if let Some(entry) = self.running_entry(task.id.clone())? {
+			return Ok(entry);
+		}
+
+		let entry = TimeEntry::new(task.id.clone(), task.parent.clone());
+		let queryable_entry: QueryableTimeEntry = entry.clone().into();
+
+		diesel::insert_into(time_entries::table)
+			.values(&queryable_entry)
+			.execute(&mut self.database.establish_connection()?)
+			.context("Failed to start timer.")?;
+
+		Ok(entry)
+	}
+
+	/// Stops the running timer of a task and returns the finished entry.
+	pub fn stop(&mut self, task_id: String) -> Result<Option<TimeEntry>> {
+		let Some(mut entry) = self.running_entry(task_id)? else {
+			return Ok(None);
+		};
+		entry.end_date_time = Some(Utc::now());
+
+		diesel::update(
+			time_entries::table
+				.filter(time_entries::id_time_entry.eq(entry.id.clone())),
+		)
+		.set(
+			time_entries::end_date_time
+				.eq(entry.end_date_time.map(|date| date.naive_utc())),
+		)
+		.execute(&mut self.database.establish_connection()?)
+		.context("Failed to stop timer.")?;
+
+		Ok(Some(entry))
+	}
+
+	pub fn running_entry(
+		&mut self,
+		task_id: String,
+	) -> Result<Option<TimeEntry>> {
+		let entry = time_entries::table
+			.filter(time_entries::task_id.eq(task_id))
+			.filter(time_entries::end_date_time.is_null())
+			.first::<QueryableTimeEntry>(&mut self.database.establish_connection()?)
+			.optional()?;
+		Ok(entry.map(|entry| entry.into()))
+	}
+
+	pub fn read_entries(&mut self, task_id: String) -> Result<Vec<TimeEntry>> {
+		let entries = time_entries::table
+			.filter(time_entries::task_id.eq(task_id))
+			.order(time_entries::start_date_time.asc())
+			.load::<QueryableTimeEntry>(&mut self.database.establish_connection()?)?
+			.into_iter()
+			.map(|entry| entry.into())
+			.collect();
+		Ok(entries)
+	}
+
+	pub fn delete_entry(&mut self, id: String) -> Result<()> {
+		diesel::delete(
+			time_entries::table.filter(time_entries::id_time_entry.eq(id)),
+		)
+		.execute(&mut self.database.establish_connection()?)?;
+		Ok(())
+	}
+
+	/// Total time tracked for a task, including a running timer.
+	pub fn tracked_time(&mut self, task_id: String) -> Result<Duration> {
+		let total = self
+			.read_entries(task_id)?
+			.iter()
+			.map(|entry| entry.duration())
+			.fold(Duration::zero(), |total, duration| total + duration);
+		Ok(total)
+	}
+
+	/// The tracked time, running timer and estimate of many tasks at once,
+	/// for the tasks of a list as they are loaded.
+	pub fn task_times(
+		&mut self,
+		task_ids: &[String],
+	) -> Result<HashMap<String, TaskTime>> {
+		let mut connection = self.database.establish_connection()?;
+		let mut times: HashMap<String, TaskTime> = HashMap::new();
+		// Older SQLite versions allow 999 variables in a query.
+		for task_ids in task_ids.chunks(500) {
+			let entries = time_entries::table
+				.filter(time_entries::task_id.eq_any(task_ids))
+				.load::<QueryableTimeEntry>(&mut connection)?;
+			for entry in entries {
+				let entry: TimeEntry = entry.into();
+				let time = times.entry(entry.task_id.clone()).or_default();
+				match entry.end_date_time {
+					Some(_) => time.tracked = time.tracked + entry.duration(),
+					None => time.running_since = Some(entry.start_date_time),
+				}
+			}
+			let estimates = task_estimates::table
+				.filter(task_estimates::id_task.eq_any(task_ids))
+				.load::<QueryableEstimate>(&mut connection)?;
+			for estimate in estimates {
+				times.entry(estimate.id_task).or_default().estimate =
+					Some(estimate.estimate);
+			}
+		}
+		Ok(times)
+	}
+
+	/// Reads the estimate of a task in minutes.
+	pub fn read_estimate(&mut self, task_id: String) -> Result<Option<i32>> {
+		let estimate = task_estimates::table
+			.find(task_id)
+			.first::<QueryableEstimate>(&mut self.database.establish_connection()?)
+			.optional()?;
+		Ok(estimate.map(|estimate| estimate.estimate))
+	}
+
+	/// Sets the estimate of a task in minutes, `None` removes it.
+	pub fn set_estimate(
+		&mut self,
+		task_id: String,
+		estimate: Option<i32>,
+	) -> Result<()> {
+		let mut connection = self.database.establish_connection()?;
+		match estimate {
+			Some(estimate) => {
+				diesel::replace_into(task_estimates::table)
+					.values(&QueryableEstimate {
+						id_task: task_id,
+						estimate,
+					})
+					.execute(&mut connection)
+					.context("Failed to set estimate.")?;
+			},
+			None => {
+				diesel::delete(
+					task_estimates::table.filter(task_estimates::id_task.eq(task_id)),
+				)
+				.execute(&mut connection)?;
+			},
+		}
+		Ok(())
+	}
+
+	/// Collects the entries overlapping the given range, for a single list
+	/// or for every list when `list_id` is `None`.
+	pub fn report(
+		&mut self,
+		list_id: Option<String>,
+		from: Option<DateTime<Utc>>,
+		to: Option<DateTime<Utc>>,
+	) -> Result<TimeReport> {
+		let mut query = time_entries::table.into_boxed();
+		if let Some(list_id) = &list_id {
+			query = query.filter(time_entries::list_id.eq(list_id.clone()));
+		}
+		if let Some(from) = from {
+			query = query.filter(
+				time_entries::end_date_time
+					.is_null()
+					.or(time_entries::end_date_time.gt(from.naive_utc())),
+			);
+		}
+		if let Some(to) = to {
+			query = query.filter(time_entries::start_date_time.lt(to.naive_utc()));
+		}
+
+		let entries = query
+			.order(time_entries::start_date_time.asc())
+			.load::<QueryableTimeEntry>(&mut self.database.establish_connection()?)?
+			.into_iter()
+			.map(|entry| entry.into())
+			.collect();
+
+		Ok(TimeReport {
+			list_id,
+			from,
+			to,
+			entries,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+	use uuid::Uuid;
+
+	use crate::{models::list::List, service::Service};
+
+	use super::*;
+
+	fn tracker() -> TimeTracker {
+		Services::init("dev.edfloreshz.Done.Scratch");
+		Database::new(Services::app_id()).migrate().unwrap();
+		TimeTracker::new().unwrap()
+	}
+
+	fn record(tracker: &mut TimeTracker, entry: TimeEntry) {
+		let entry: QueryableTimeEntry = entry.into();
+		diesel::insert_into(time_entries::table)
+			.values(&entry)
+			.execute(&mut tracker.database.establish_connection().unwrap())
+			.unwrap();
+	}
+
+	fn entry(task_id: &str, list_id: &str, start: u32, end: u32) -> TimeEntry {
+		let at = |hour| Utc.with_ymd_and_hms(2024, 5, 1, hour, 0, 0).unwrap();
+		TimeEntry {
+			start_date_time: at(start),
+			end_date_time: Some(at(end)),
+			..TimeEntry::new(task_id.into(), list_id.into())
+		}
+	}
+
+	#[test]
+	fn timers_start_once_and_stop() {
+		let mut tracker = tracker();
+		let task = Task::new("Timed".into(), Uuid::new_v4().to_string());
+		let started = tracker.start(&task).unwrap();
+		assert!(started.is_running());
+		assert_eq!(tracker.start(&task).unwrap().id, started.id);
+
+		let stopped = tracker.stop(task.id.clone()).unwrap().unwrap();
+		assert_eq!(stopped.id, started.id);
+		assert!(!stopped.is_running());
+		assert!(tracker.running_entry(task.id.clone()).unwrap().is_none());
+		assert!(tracker.stop(task.id.clone()).unwrap().is_none());
+		tracker.delete_entry(started.id).unwrap();
+	}
+
+	#[test]
+	fn times_are_aggregated_per_task() {
+		let mut tracker = tracker();
+		let (a, b) = (Uuid::new_v4().to_string(), Uuid::new_v4().to_string());
+		record(&mut tracker, entry(&a, "list", 9, 10));
+		record(&mut tracker, entry(&a, "list", 11, 13));
+		let running = tracker
+			.start(&Task::new("Running".into(), "list".into()))
+			.unwrap();
+		tracker.set_estimate(a.clone(), Some(90)).unwrap();
+		tracker.set_estimate(b.clone(), Some(15)).unwrap();
+
+		let times = tracker
+			.task_times(&[a.clone(), b.clone(), running.task_id.clone()])
+			.unwrap();
+		assert_eq!(times[&a].tracked, Duration::hours(3));
+		assert_eq!(times[&a].estimate, Some(90));
+		assert_eq!(times[&b].tracked, Duration::zero());
+		assert_eq!(times[&b].estimate, Some(15));
+		assert_eq!(
+			times[&running.task_id].running_since,
+			Some(running.start_date_time)
+		);
+		assert_eq!(tracker.tracked_time(a.clone()).unwrap(), Duration::hours(3));
+
+		tracker.set_estimate(b.clone(), None).unwrap();
+		assert_eq!(tracker.read_estimate(b).unwrap(), None);
+		for entry in tracker.read_entries(a.clone()).unwrap() {
+			tracker.delete_entry(entry.id).unwrap();
+		}
+		tracker.delete_entry(running.id).unwrap();
+		tracker.set_estimate(a, None).unwrap();
+	}
+
+	#[test]
+	fn reports_entries_of_a_list_within_a_range() {
+		let mut tracker = tracker();
+		let (list, other) =
+			(Uuid::new_v4().to_string(), Uuid::new_v4().to_string());
+		let inside = entry("a", &list, 9, 10);
+		let overlapping = entry("b", &list, 11, 13);
+		record(&mut tracker, inside.clone());
+		record(&mut tracker, overlapping.clone());
+		record(&mut tracker, entry("c", &list, 14, 15));
+		record(&mut tracker, entry("d", &other, 9, 10));
+
+		let at = |hour| Utc.with_ymd_and_hms(2024, 5, 1, hour, 0, 0).unwrap();
+		let report = tracker
+			.report(Some(list.clone()), Some(at(8)), Some(at(12)))
+			.unwrap();
+		let ids: Vec<&str> = report
+			.entries
+			.iter()
+			.map(|entry| entry.id.as_str())
+			.collect();
+		assert_eq!(ids, vec![inside.id.as_str(), overlapping.id.as_str()]);
+		assert_eq!(report.total(), Duration::hours(2));
+
+		for list_id in [list, other] {
+			for entry in tracker.report(Some(list_id), None, None).unwrap().entries {
+				tracker.delete_entry(entry.id).unwrap();
+			}
+		}
+	}
+
+	#[tokio::test]
+	async fn time_is_removed_with_its_task() {
+		let mut tracker = tracker();
+		let mut provider = Service::Computer.get_service();
+		let list = provider
+			.create_list(List::new("Timed", Service::Computer))
+			.await
+			.unwrap();
+		let (kept, deleted) = (
+			Task::new("Kept".into(), list.id.clone()),
+			Task::new("Deleted".into(), list.id.clone()),
+		);
+		for task in [&kept, &deleted] {
+			provider.create_task(task.clone()).await.unwrap();
+			record(&mut tracker, entry(&task.id, &list.id, 9, 10));
+			tracker.set_estimate(task.id.clone(), Some(30)).unwrap();
+		}
+
+		provider
+			.delete_task(list.id.clone(), deleted.id.clone())
+			.await
+			.unwrap();
+		assert!(tracker.read_entries(deleted.id.clone()).unwrap().is_empty());
+		assert_eq!(tracker.read_estimate(deleted.id).unwrap(), None);
+		assert_eq!(tracker.read_entries(kept.id.clone()).unwrap().len(), 1);
+
+		// Tasks deleted along with their list take their time with them too.
+		provider.delete_list(list.id).await.unwrap();
+		assert!(tracker.read_entries(kept.id.clone()).unwrap().is_empty());
+		assert_eq!(tracker.read_estimate(kept.id).unwrap(), None);
+	}
+}
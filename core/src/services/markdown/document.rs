@@ -0,0 +1,642 @@
+use std::{collections::HashMap, fmt::Display, sync::OnceLock};
+
+use chrono::{DateTime, NaiveDate, NaiveTime, SecondsFormat, Utc};
+use regex::Regex;
+
+use crate::{
+	models::{priority::Priority, status::Status, task::Task},
+	services::files::stable_id,
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+fn checkbox() -> &'static Regex {
+	static REGEX: OnceLock<Regex> = OnceLock::new();
+	REGEX.get_or_init(|| Regex::new(r"^(\s*)([-*+]) \[([ xX])\] ?(.*)$").unwrap())
+}
+
+fn note() -> &'static Regex {
+	static REGEX: OnceLock<Regex> = OnceLock::new();
+	REGEX.get_or_init(|| Regex::new(r"^(\s*)> ?(.*)$").unwrap())
+}
+
+fn id_comment() -> &'static Regex {
+	static REGEX: OnceLock<Regex> = OnceLock::new();
+	REGEX.get_or_init(|| {
+		Regex::new(r"\s*<!--\s*id:(\S+)(?:\s+created:(\S+))?\s*-->").unwrap()
+	})
+}
+
+/// List settings from the front matter of a file, `tags` and `priority`
+/// apply to every task of the file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct FrontMatter {
+	pub title: Option<String>,
+	pub icon: Option<String>,
+	pub tags: Vec<String>,
+	pub priority: Option<Priority>,
+}
+
+/// A checkbox item and the lines it spans: its sub-items and `>` notes.
+#[derive(Clone, Debug)]
+pub(crate) struct Item {
+	pub start: usize,
+	pub end: usize,
+	indent: String,
+	marker: String,
+	pub task: Task,
+	/// Where each of the sub-tasks was read from, in the same order.
+	sub_items: Vec<SubItem>,
+}
+
+/// The lines of a nested checkbox, written back as they were while its
+/// sub-task doesn't change.
+#[derive(Clone, Debug)]
+struct SubItem {
+	/// Index of its checkbox line.
+	start: usize,
+	indent: String,
+	marker: String,
+	lines: Vec<String>,
+}
+
+/// A Markdown file kept line by line, so anything that isn't a task is
+/// written back exactly as it was read.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Document {
+	lines: Vec<String>,
+}
+
+impl Document {
+	pub fn parse(text: &str) -> Self {
+		Self {
+			lines: text.lines().map(str::to_string).collect(),
+		}
+	}
+
+	/// A new file for a list, titled with a heading.
+	pub fn new(title: &str) -> Self {
+		Self {
+			lines: vec![format!("# {title}"), String::new()],
+		}
+	}
+
+	/// Index of the line closing the front matter.
+	fn front_matter_end(&self) -> Option<usize> {
+		if self.lines.first().map(|line| line.trim()) != Some("---") {
+			return None;
+		}
+		self
+			.lines
+			.iter()
+			.skip(1)
+			.position(|line| line.trim() == "---")
+			.map(|index| index + 1)
+	}
+
+	fn body_start(&self) -> usize {
+		self.front_matter_end().map_or(0, |end| end + 1)
+	}
+
+	pub fn front_matter(&self) -> FrontMatter {
+		let mut front_matter = FrontMatter::default();
+		let Some(end) = self.front_matter_end() else {
+			return front_matter;
+		};
+		let mut key = String::new();
+		for line in &self.lines[1..end] {
+			if let Some(value) = line.trim().strip_prefix("- ") {
+				if key == "tags" {
+					front_matter.tags.push(unquote(value));
+				}
+				continue;
+			}
+			let Some((line_key, value)) = line.split_once(':') else {
+				continue;
+			};
+			key = line_key.trim().to_string();
+			let value = value.trim();
+			match key.as_str() {
+				"title" => front_matter.title = Some(unquote(value)),
+				"icon" => front_matter.icon = Some(unquote(value)),
+				"priority" => front_matter.priority = parse_priority(&unquote(value)),
+				"tags" => front_matter.tags.extend(
+					value
+						.trim_start_matches('[')
+						.trim_end_matches(']')
+						.split(',')
+						.map(unquote)
+						.filter(|tag| !tag.is_empty()),
+				),
+				_ => (),
+			}
+		}
+		front_matter
+	}
+
+	/// The front matter title, or the first heading of the file.
+	pub fn title(&self) -> Option<String> {
+		self.front_matter().title.or_else(|| {
+			self.lines[self.body_start()..].iter().find_map(|line| {
+				line
+					.strip_prefix("# ")
+					.map(|title| title.trim().to_string())
+			})
+		})
+	}
+
+	/// Renames the list where its title is written, adding a heading when
+	/// the file has none.
+	pub fn set_title(&mut self, title: &str) {
+		if let Some(end) = self.front_matter_end() {
+			if let Some(line) = self.lines[1..end]
+				.iter_mut()
+				.find(|line| line.trim_start().starts_with("title:"))
+			{
+				*line = format!("title: {title}");
+				return;
+			}
+		}
+		let start = self.body_start();
+		match self.lines[start..]
+			.iter_mut()
+			.find(|line| line.starts_with("# "))
+		{
+			Some(line) => *line = format!("# {title}"),
+			None => self.lines.insert(start, format!("# {title}")),
+		}
+	}
+
+	/// Every top level checkbox of the file, with nested checkboxes as its
+	/// sub-tasks. Checkboxes in fenced code blocks are ignored.
+	pub fn items(&self, list_id: &str) -> Vec<Item> {
+		let front_matter = self.front_matter();
+		let mut occurrences: HashMap<&str, usize> = HashMap::new();
+		let mut items: Vec<Item> = vec![];
+		let mut open = false;
+		let mut fenced = false;
+		for (index, line) in self.lines.iter().enumerate().skip(self.body_start()) {
+			if line.trim_start().starts_with("```") {
+				fenced = !fenced;
+				open = false;
+				continue;
+			}
+			if fenced {
+				continue;
+			}
+			if let Some(captures) = checkbox().captures(line) {
+				let indent = &captures[1];
+				let text = &captures[4];
+				let mut task = parse_task(text, &front_matter);
+				task.status = if &captures[3] == " " {
+					Status::NotStarted
+				} else {
+					Status::Completed
+				};
+				match items.last_mut() {
+					Some(item) if open && indent.len() > item.indent.len() => {
+						if task.id.is_empty() {
+							let occurrence = item
+								.sub_items
+								.iter()
+								.filter(|sub_item| sub_item.lines[0].trim() == line.trim())
+								.count();
+							task.id = stable_id(&format!(
+								"{}\n{}\n{occurrence}",
+								item.task.id,
+								line.trim()
+							));
+						}
+						item.task.sub_tasks.push(task);
+						item.sub_items.push(SubItem {
+							start: index,
+							indent: indent.to_string(),
+							marker: captures[2].to_string(),
+							lines: vec![line.clone()],
+						});
+						item.end = index + 1;
+					},
+					_ => {
+						let occurrence = occurrences.entry(line.trim()).or_insert(0);
+						if task.id.is_empty() {
+							task.id =
+								stable_id(&format!("{list_id}\n{}\n{occurrence}", line.trim()));
+						}
+						*occurrence += 1;
+						task.parent = list_id.to_string();
+						items.push(Item {
+							start: index,
+							end: index + 1,
+							indent: indent.to_string(),
+							marker: captures[2].to_string(),
+							task,
+							sub_items: vec![],
+						});
+						open = true;
+					},
+				}
+				continue;
+			}
+			if let (Some(captures), Some(item)) =
+				(note().captures(line), items.last_mut())
+			{
+				if open && captures[1].len() > item.indent.len() {
+					let task = match item.task.sub_tasks.last_mut() {
+						Some(sub_task) => {
+							if let Some(sub_item) = item.sub_items.last_mut() {
+								sub_item.lines.push(line.clone());
+							}
+							sub_task
+						},
+						None => &mut item.task,
+					};
+					if !task.notes.is_empty() {
+						task.notes.push('\n');
+					}
+					task.notes.push_str(&captures[2]);
+					item.end = index + 1;
+					continue;
+				}
+			}
+			open = false;
+		}
+		items
+	}
+
+	/// Adds an id and creation comment to the checkboxes that have none, so
+	/// their ids no longer depend on their text. Returns whether a line
+	/// changed.
+	pub fn stamp(&mut self, list_id: &str) -> bool {
+		let now = Utc::now();
+		let mut changed = false;
+		for item in self.items(list_id) {
+			let checkboxes = std::iter::once((item.start, &item.task)).chain(
+				item
+					.sub_items
+					.iter()
+					.map(|sub_item| sub_item.start)
+					.zip(&item.task.sub_tasks),
+			);
+			for (index, task) in checkboxes {
+				let line = &mut self.lines[index];
+				if id_comment()
+					.captures(line)
+					.is_some_and(|captures| captures.get(2).is_some())
+				{
+					continue;
+				}
+				let created = created_or(task, now);
+				*line = format!(
+					"{} {}",
+					id_comment().replace_all(line, ""),
+					comment(&task.id, created)
+				);
+				changed = true;
+			}
+		}
+		changed
+	}
+
+	/// Rewrites the lines of an item from a task, sub-items that didn't change
+	/// are kept as they were.
+	pub fn replace(&mut self, item: &Item, task: &Task) {
+		let front_matter = self.front_matter();
+		let lines =
+			render(task, &item.indent, &item.marker, &front_matter, Some(item));
+		self.lines.splice(item.start..item.end, lines);
+	}
+
+	pub fn remove(&mut self, item: &Item) {
+		self.lines.drain(item.start..item.end);
+	}
+
+	/// Adds a task after the last item of the file, or at its end.
+	pub fn append(&mut self, task: &Task) {
+		let front_matter = self.front_matter();
+		let lines = render(task, "", "-", &front_matter, None);
+		match self.items("").last() {
+			Some(item) => {
+				let end = item.end;
+				self.lines.splice(end..end, lines);
+			},
+			None => {
+				if self
+					.lines
+					.last()
+					.is_some_and(|line| !line.trim().is_empty())
+				{
+					self.lines.push(String::new());
+				}
+				self.lines.extend(lines);
+			},
+		}
+	}
+}
+
+impl Display for Document {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for line in &self.lines {
+			writeln!(f, "{line}")?;
+		}
+		Ok(())
+	}
+}
+
+fn unquote(value: &str) -> String {
+	value
+		.trim()
+		.trim_matches(|c| c == '"' || c == '\'')
+		.to_string()
+}
+
+fn parse_priority(value: &str) -> Option<Priority> {
+	match value.to_lowercase().as_str() {
+		"high" => Some(Priority::High),
+		"normal" | "medium" => Some(Priority::Normal),
+		"low" => Some(Priority::Low),
+		_ => None,
+	}
+}
+
+/// The comment keeping the id and creation time of a task.
+fn comment(id: &str, created: DateTime<Utc>) -> String {
+	format!(
+		"<!-- id:{id} created:{} -->",
+		created.to_rfc3339_opts(SecondsFormat::Secs, true)
+	)
+}
+
+/// When a task was created, `now` for one that wasn't read with a creation
+/// time.
+fn created_or(task: &Task, now: DateTime<Utc>) -> DateTime<Utc> {
+	if task.created_date_time == DateTime::<Utc>::default() {
+		now
+	} else {
+		task.created_date_time
+	}
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+	NaiveDate::parse_from_str(value, DATE_FORMAT)
+		.ok()
+		.map(|date| date.and_time(NaiveTime::MIN).and_utc())
+}
+
+fn is_tag(word: &str) -> bool {
+	word.strip_prefix('#').is_some_and(|tag| {
+		!tag.is_empty()
+			&& tag
+				.chars()
+				.all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '/')
+	})
+}
+
+/// Reads a task from the text after its checkbox: `#tags`, `due:`,
+/// `priority:` and `done:` are metadata, the rest is the title.
+fn parse_task(text: &str, front_matter: &FrontMatter) -> Task {
+	let captures = id_comment().captures(text);
+	let id = captures
+		.as_ref()
+		.map(|captures| captures[1].to_string())
+		.unwrap_or_default();
+	let created = captures
+		.as_ref()
+		.and_then(|captures| captures.get(2))
+		.and_then(|created| DateTime::parse_from_rfc3339(created.as_str()).ok())
+		.map(|created| created.with_timezone(&Utc))
+		.unwrap_or_default();
+	let text = id_comment().replace_all(text, "");
+	let mut task = Task {
+		id,
+		created_date_time: created,
+		last_modified_date_time: created,
+		tags: front_matter.tags.clone(),
+		priority: front_matter.priority.unwrap_or_default(),
+		..Default::default()
+	};
+	let mut title = vec![];
+	for word in text.split_whitespace() {
+		if is_tag(word) {
+			let tag = word[1..].to_string();
+			if !task.tags.contains(&tag) {
+				task.tags.push(tag);
+			}
+		} else if let Some(due_date) =
+			word.strip_prefix("due:").and_then(parse_date)
+		{
+			task.due_date = Some(due_date);
+		} else if let Some(done_date) =
+			word.strip_prefix("done:").and_then(parse_date)
+		{
+			task.completion_date = Some(done_date);
+		} else if let Some(priority) =
+			word.strip_prefix("priority:").and_then(parse_priority)
+		{
+			task.priority = priority;
+		} else {
+			title.push(word);
+		}
+	}
+	task.title = title.join(" ");
+	task
+}
+
+/// The lines of a task: its checkbox, its notes and its sub-tasks. Tags and
+/// priority already set by the front matter are left out. Sub-tasks read
+/// from `original` keep their lines while they render the same.
+fn render(
+	task: &Task,
+	indent: &str,
+	marker: &str,
+	front_matter: &FrontMatter,
+	original: Option<&Item>,
+) -> Vec<String> {
+	let mut words = vec![task.title.clone()];
+	words.extend(
+		task
+			.tags
+			.iter()
+			.filter(|tag| !front_matter.tags.contains(tag))
+			.map(|tag| format!("#{tag}")),
+	);
+	if task.priority != front_matter.priority.unwrap_or_default() {
+		words.push(format!(
+			"priority:{}",
+			task.priority.as_str_name().to_lowercase()
+		));
+	}
+	if let Some(due_date) = task.due_date {
+		words.push(format!("due:{}", due_date.format(DATE_FORMAT)));
+	}
+	if task.status == Status::Completed {
+		let done_date = task.completion_date.unwrap_or_else(Utc::now);
+		words.push(format!("done:{}", done_date.format(DATE_FORMAT)));
+	}
+	words.push(comment(&task.id, created_or(task, Utc::now())));
+
+	let mut lines = vec![format!(
+		"{indent}{marker} {} {}",
+		checkbox_for(task),
+		words.join(" ")
+	)];
+	let nested = format!("{indent}  ");
+	lines.extend(note_lines(&task.notes, &nested));
+	for sub_task in &task.sub_tasks {
+		let sub_item = original.and_then(|item| {
+			item
+				.task
+				.sub_tasks
+				.iter()
+				.zip(&item.sub_items)
+				.find(|(read, _)| read.id == sub_task.id)
+		});
+		match sub_item {
+			Some((read, sub_item)) => {
+				let rendered = render(
+					sub_task,
+					&sub_item.indent,
+					&sub_item.marker,
+					front_matter,
+					None,
+				);
+				if render(read, &sub_item.indent, &sub_item.marker, front_matter, None)
+					== rendered
+				{
+					lines.extend(sub_item.lines.iter().cloned());
+				} else {
+					lines.extend(rendered);
+				}
+			},
+			// New sub-items line up with the first one already there.
+			None => {
+				let (indent, marker) = original
+					.and_then(|item| item.sub_items.first())
+					.map_or((nested.as_str(), "-"), |sub_item| {
+						(sub_item.indent.as_str(), sub_item.marker.as_str())
+					});
+				lines.extend(render(sub_task, indent, marker, front_matter, None))
+			},
+		}
+	}
+	lines
+}
+
+fn checkbox_for(task: &Task) -> &'static str {
+	if task.status == Status::Completed {
+		"[x]"
+	} else {
+		"[ ]"
+	}
+}
+
+fn note_lines(notes: &str, indent: &str) -> Vec<String> {
+	notes
+		.lines()
+		.filter(|line| !line.trim().is_empty())
+		.map(|line| format!("{indent}> {line}"))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const LIST: &str = "\
+---
+tags: [home]
+---
+# Chores
+
+Some text that isn't a task.
+
+- [ ] Water the plants #garden due:2024-05-01 priority:high
+  > Twice a week
+  - [x] Buy a watering can
+- [x] Take out the trash done:2024-04-20
+
+```
+- [ ] Not a task
+```
+";
+
+	#[test]
+	fn parses_tasks() {
+		let items = Document::parse(LIST).items("chores.md");
+		assert_eq!(items.len(), 2);
+
+		let plants = &items[0].task;
+		assert_eq!(plants.title, "Water the plants");
+		assert_eq!(plants.parent, "chores.md");
+		assert_eq!(plants.tags, vec!["home", "garden"]);
+		assert_eq!(plants.priority, Priority::High);
+		assert_eq!(plants.due_date, parse_date("2024-05-01"));
+		assert_eq!(plants.notes, "Twice a week");
+		assert_eq!(plants.status, Status::NotStarted);
+		assert_eq!(plants.sub_tasks.len(), 1);
+		assert_eq!(plants.sub_tasks[0].title, "Buy a watering can");
+		assert_eq!(plants.sub_tasks[0].status, Status::Completed);
+
+		let trash = &items[1].task;
+		assert_eq!(trash.status, Status::Completed);
+		assert_eq!(trash.completion_date, parse_date("2024-04-20"));
+	}
+
+	#[test]
+	fn writes_back_what_it_reads() {
+		let mut document = Document::parse(LIST);
+		document.stamp("chores.md");
+		let items = document.items("chores.md");
+		for item in items.iter().rev() {
+			document.replace(item, &item.task);
+		}
+		let written = document.to_string();
+		assert!(written.contains("Some text that isn't a task."));
+		assert!(written.contains("- [ ] Not a task"));
+
+		let read = Document::parse(&written).items("chores.md");
+		let tasks: Vec<Task> = items.into_iter().map(|item| item.task).collect();
+		let read: Vec<Task> = read.into_iter().map(|item| item.task).collect();
+		assert_eq!(read, tasks);
+	}
+
+	#[test]
+	fn keeps_ids_when_the_text_changes() {
+		let mut document = Document::parse(LIST);
+		assert!(document.stamp("chores.md"));
+		assert!(!document.stamp("chores.md"));
+		let item = document.items("chores.md").remove(0);
+		let created = item.task.created_date_time;
+		assert_ne!(created, DateTime::<Utc>::default());
+
+		let mut task = item.task.clone();
+		task.title = "Water the flowers".to_string();
+		task.status = Status::Completed;
+		document.replace(&item, &task);
+
+		let read = document.items("chores.md").remove(0).task;
+		assert_eq!(read.id, item.task.id);
+		assert_eq!(read.created_date_time, created);
+		assert_eq!(read.sub_tasks[0].id, item.task.sub_tasks[0].id);
+	}
+
+	#[test]
+	fn scopes_sub_task_ids_to_their_parent() {
+		let document = Document::parse(
+			"- [ ] First\n  - [ ] Same step\n- [ ] Second\n  - [ ] Same step\n",
+		);
+		let items = document.items("list.md");
+		assert_ne!(items[0].task.sub_tasks[0].id, items[1].task.sub_tasks[0].id);
+	}
+
+	#[test]
+	fn appends_new_tasks_after_the_last_one() {
+		let mut document = Document::parse(LIST);
+		let task = Task::new("Sweep".to_string(), "chores.md".to_string());
+		document.append(&task);
+		let items = document.items("chores.md");
+		assert_eq!(items.len(), 3);
+		assert_eq!(items[2].task.id, task.id);
+		assert_eq!(items[2].task.title, "Sweep");
+		assert_eq!(items[2].task.tags, vec!["home"]);
+	}
+}
@@ -0,0 +1,365 @@
+use std::{
+	path::{Component, Path, PathBuf},
+	pin::Pin,
+};
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+	models::{
+		list::{List, Sharing},
+		task::Task,
+	},
+	service::Service,
+	services::files::{
+		files_with_extension, read_config, read_file, remove_config, remove_file,
+		write_config, write_file,
+	},
+	task_service::TodoProvider,
+	Error, Result,
+};
+
+use super::document::Document;
+
+const CONFIG_KEY: &str = "markdown";
+
+/// Settings of the Markdown provider.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct MarkdownConfig {
+	pub path: PathBuf,
+}
+
+/// Tasks kept in a folder of Markdown files, each file is a list.
+///
+/// Lists are identified by their path relative to the folder.
+#[derive(Debug, Clone)]
+pub struct MarkdownService {
+	config: Option<MarkdownConfig>,
+}
+
+impl MarkdownService {
+	pub(crate) fn new() -> Self {
+		Self {
+			config: read_config(CONFIG_KEY),
+		}
+	}
+
+	/// The folder chosen by the user.
+	pub(crate) fn path() -> Option<PathBuf> {
+		read_config::<MarkdownConfig>(CONFIG_KEY).map(|config| config.path)
+	}
+
+	pub(crate) fn set_path(path: PathBuf) -> anyhow::Result<()> {
+		write_config(CONFIG_KEY, MarkdownConfig { path })
+	}
+
+	pub(crate) fn clear_path() -> anyhow::Result<()> {
+		remove_config(CONFIG_KEY)
+	}
+
+	/// The folder itself, so new and removed files are noticed, and every
+	/// Markdown file in it.
+	pub(crate) fn watched_paths() -> Vec<PathBuf> {
+		let Some(path) = Self::path() else {
+			return vec![];
+		};
+		let mut paths = files_with_extension(&path, "md");
+		paths.push(path);
+		paths
+	}
+
+	fn folder(&self) -> anyhow::Result<&Path> {
+		self
+			.config
+			.as_ref()
+			.map(|config| config.path.as_path())
+			.context("No Markdown folder has been chosen.")
+	}
+
+	/// The file of a list, ids are paths relative to the folder with `/`
+	/// between their parts.
+	fn file(&self, list_id: &str) -> anyhow::Result<PathBuf> {
+		let mut path = self.folder()?.to_path_buf();
+		for part in list_id.split('/') {
+			if !is_file_name(part) {
+				bail!("List {list_id} is outside of the Markdown folder.");
+			}
+			path.push(part);
+		}
+		Ok(path)
+	}
+
+	/// Reads the file of a list, writing an id comment for the tasks that
+	/// don't have one yet.
+	fn read(&self, list_id: &str) -> anyhow::Result<Document> {
+		let mut document = Document::parse(&read_file(&self.file(list_id)?)?);
+		if document.stamp(list_id) {
+			if let Err(err) = self.write(list_id, &document) {
+				tracing::error!("Failed to write the task ids of {list_id}: {err}");
+			}
+		}
+		Ok(document)
+	}
+
+	fn write(&self, list_id: &str, document: &Document) -> anyhow::Result<()> {
+		write_file(&self.file(list_id)?, &document.to_string())
+	}
+
+	fn list_ids(&self) -> anyhow::Result<Vec<String>> {
+		let folder = self.folder()?;
+		Ok(
+			files_with_extension(folder, "md")
+				.iter()
+				.filter_map(|path| path.strip_prefix(folder).ok())
+				.map(|path| {
+					path
+						.components()
+						.map(|component| component.as_os_str().to_string_lossy())
+						.collect::<Vec<_>>()
+						.join("/")
+				})
+				.collect(),
+		)
+	}
+
+	fn list(&self, list_id: &str, document: &Document) -> List {
+		let front_matter = document.front_matter();
+		List {
+			id: list_id.to_string(),
+			name: document.title().unwrap_or_else(|| {
+				Path::new(list_id)
+					.file_stem()
+					.map(|stem| stem.to_string_lossy().to_string())
+					.unwrap_or_default()
+			}),
+			description: String::new(),
+			icon: front_matter.icon,
+			service: Service::Markdown,
+			sharing: Sharing::Private,
+			account: None,
+		}
+	}
+}
+
+/// Whether a part of a list id names a single file or folder in its parent,
+/// separators of any platform are refused so ids mean the same everywhere.
+fn is_file_name(part: &str) -> bool {
+	!part.chars().any(|c| matches!(c, '/' | '\\' | ':'))
+		&& matches!(
+			Path::new(part).components().collect::<Vec<_>>()[..],
+			[Component::Normal(_)]
+		)
+}
+
+/// A file name for a new list, characters that don't belong in a file name
+/// become dashes.
+fn file_name(name: &str) -> Result<String> {
+	let stem: String = name
+		.trim()
+		.chars()
+		.map(|c| {
+			if c.is_alphanumeric() || c == ' ' || c == '_' || c == '-' {
+				c
+			} else {
+				'-'
+			}
+		})
+		.collect();
+	let stem = stem.trim();
+	if stem.is_empty() {
+		return Err(Error::InvalidData(format!(
+			"{name} can't be used as a file name."
+		)));
+	}
+	Ok(format!("{stem}.md"))
+}
+
+#[async_trait]
+impl TodoProvider for MarkdownService {
+	async fn handle_uri_params(&mut self, _uri: Url) -> Result<()> {
+		Ok(())
+	}
+
+	async fn login(&self) -> Result<()> {
+		Ok(())
+	}
+
+	async fn logout(&self) -> Result<()> {
+		Ok(Self::clear_path()?)
+	}
+
+	fn available(&self) -> bool {
+		self.config.is_some()
+	}
+
+	fn stream_support(&self) -> bool {
+		false
+	}
+
+	async fn read_tasks(&mut self) -> Result<Vec<Task>> {
+		let mut tasks = vec![];
+		for list_id in self.list_ids()? {
+			tasks.extend(self.read_tasks_from_list(list_id).await?);
+		}
+		Ok(tasks)
+	}
+
+	async fn get_tasks(
+		&mut self,
+		_parent_list: String,
+	) -> Result<Pin<Box<dyn Stream<Item = Result<Task>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
+	}
+
+	async fn read_tasks_from_list(
+		&mut self,
+		parent_list: String,
+	) -> Result<Vec<Task>> {
+		Ok(
+			self
+				.read(&parent_list)?
+				.items(&parent_list)
+				.into_iter()
+				.map(|item| item.task)
+				.collect(),
+		)
+	}
+
+	async fn read_task(
+		&mut self,
+		task_list_id: String,
+		task_id: String,
+	) -> Result<Task> {
+		self
+			.read_tasks_from_list(task_list_id)
+			.await?
+			.into_iter()
+			.find(|task| task.id == task_id)
+			.ok_or_else(|| Error::NotFound(format!("Task {task_id} not found.")))
+	}
+
+	async fn create_task(&mut self, task: Task) -> Result<()> {
+		let mut document = self.read(&task.parent)?;
+		document.append(&task);
+		Ok(self.write(&task.parent, &document)?)
+	}
+
+	async fn update_task(&mut self, task: Task) -> Result<Task> {
+		let mut document = self.read(&task.parent)?;
+		let item = document
+			.items(&task.parent)
+			.into_iter()
+			.find(|item| item.task.id == task.id)
+			.ok_or_else(|| Error::NotFound(format!("Task {} not found.", task.id)))?;
+		document.replace(&item, &task);
+		self.write(&task.parent, &document)?;
+		Ok(task)
+	}
+
+	async fn delete_task(
+		&mut self,
+		list_id: String,
+		task_id: String,
+	) -> Result<()> {
+		let mut document = self.read(&list_id)?;
+		let item = document
+			.items(&list_id)
+			.into_iter()
+			.find(|item| item.task.id == task_id)
+			.ok_or_else(|| Error::NotFound(format!("Task {task_id} not found.")))?;
+		document.remove(&item);
+		Ok(self.write(&list_id, &document)?)
+	}
+
+	async fn read_lists(&mut self) -> Result<Vec<List>> {
+		if self.config.is_none() {
+			return Ok(vec![]);
+		}
+		let mut lists = vec![];
+		for list_id in self.list_ids()? {
+			lists.push(self.list(&list_id, &self.read(&list_id)?));
+		}
+		Ok(lists)
+	}
+
+	async fn get_lists(
+		&mut self,
+	) -> Result<Pin<Box<dyn Stream<Item = Result<List>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
+	}
+
+	async fn read_list(&mut self, id: String) -> Result<List> {
+		if !self.file(&id)?.exists() {
+			return Err(Error::NotFound(format!("List {id} not found.")));
+		}
+		Ok(self.list(&id, &self.read(&id)?))
+	}
+
+	async fn create_list(&mut self, list: List) -> Result<List> {
+		let list_id = file_name(&list.name)?;
+		if self.file(&list_id)?.exists() {
+			return Err(Error::Conflict(format!(
+				"A list named {} already exists.",
+				list.name
+			)));
+		}
+		let document = Document::new(&list.name);
+		self.write(&list_id, &document)?;
+		Ok(self.list(&list_id, &document))
+	}
+
+	async fn update_list(&mut self, list: List) -> Result<()> {
+		let mut document = self.read(&list.id)?;
+		document.set_title(&list.name);
+		Ok(self.write(&list.id, &document)?)
+	}
+
+	async fn delete_list(&mut self, id: String) -> Result<()> {
+		Ok(remove_file(&self.file(&id)?)?)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn keeps_list_files_in_the_folder() {
+		let service = MarkdownService {
+			config: Some(MarkdownConfig {
+				path: PathBuf::from("notes"),
+			}),
+		};
+		assert_eq!(
+			service.file("work/today.md").unwrap(),
+			Path::new("notes").join("work").join("today.md")
+		);
+		for list_id in [
+			"../today.md",
+			"work/../../today.md",
+			"/etc/today.md",
+			"work//today.md",
+			"./today.md",
+			"work\\today.md",
+			"C:today.md",
+			"",
+		] {
+			assert!(service.file(list_id).is_err(), "{list_id} was accepted");
+		}
+	}
+
+	#[test]
+	fn names_files_after_lists() {
+		assert_eq!(file_name(" Groceries ").unwrap(), "Groceries.md");
+		assert_eq!(file_name("Home/Work").unwrap(), "Home-Work.md");
+		assert!(matches!(file_name("   "), Err(Error::InvalidData(_))));
+	}
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::services::files::{read_config, write_config};
+
+const CONFIG_KEY: &str = "microsoft";
+/// Where the identity platform is.
+const LOGIN_URL: &str = "https://login.microsoftonline.com";
+
+/// Which Microsoft accounts can sign in, the tenant segment of the identity
+/// platform endpoints.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Authority {
+	/// Personal Microsoft accounts.
+	#[default]
+	Consumers,
+	/// Work or school accounts of any organization.
+	Organizations,
+	/// Both personal and work or school accounts.
+	Common,
+	/// Work or school accounts of a single organization, by tenant id or
+	/// domain.
+	Tenant(String),
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MicrosoftConfig {
+	authority: Authority,
+	/// The authority each account signed in with, its tokens are refreshed
+	/// there.
+	accounts: HashMap<String, Authority>,
+}
+
+impl Authority {
+	/// The authority new accounts sign in with.
+	pub fn get() -> Self {
+		read_config::<MicrosoftConfig>(CONFIG_KEY)
+			.map(|config| config.authority)
+			.unwrap_or_default()
+	}
+
+	pub fn set(self) -> Result<()> {
+		if let Authority::Tenant(tenant) = &self {
+			if tenant.is_empty()
+				|| !tenant
+					.chars()
+					.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+			{
+				bail!("{tenant} is not a valid tenant id.")
+			}
+		}
+		let mut config: MicrosoftConfig =
+			read_config(CONFIG_KEY).unwrap_or_default();
+		config.authority = self;
+		write_config(CONFIG_KEY, config)
+	}
+
+	/// The authority an account signed in with.
+	pub(crate) fn of_account(account_id: &str) -> Self {
+		read_config::<MicrosoftConfig>(CONFIG_KEY)
+			.and_then(|mut config| config.accounts.remove(account_id))
+			.unwrap_or_default()
+	}
+
+	pub(crate) fn set_account(account_id: &str, authority: Self) -> Result<()> {
+		let mut config: MicrosoftConfig =
+			read_config(CONFIG_KEY).unwrap_or_default();
+		config.accounts.insert(account_id.to_string(), authority);
+		write_config(CONFIG_KEY, config)
+	}
+
+	pub(crate) fn remove_account(account_id: &str) -> Result<()> {
+		let mut config: MicrosoftConfig =
+			read_config(CONFIG_KEY).unwrap_or_default();
+		config.accounts.remove(account_id);
+		write_config(CONFIG_KEY, config)
+	}
+
+	/// The Microsoft identity platform endpoints accounts sign in at.
+	pub(crate) fn endpoint(&self) -> String {
+		format!("{LOGIN_URL}/{}/oauth2/v2.0", self.tenant())
+	}
+
+	/// Where tokens are requested and refreshed.
+	pub(crate) fn token_url(&self) -> String {
+		format!("{}/token", self.endpoint())
+	}
+
+	pub fn tenant(&self) -> &str {
+		match self {
+			Authority::Consumers => "consumers",
+			Authority::Organizations => "organizations",
+			Authority::Common => "common",
+			Authority::Tenant(tenant) => tenant,
+		}
+	}
+}
@@ -0,0 +1,320 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use graph_rs_sdk::oauth::AccessToken;
+use serde::Deserialize;
+use tokio::{
+	io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+	net::TcpListener,
+};
+use url::Url;
+
+use super::{
+	authority::Authority,
+	service::{authorization_code, MicrosoftService, CLIENT_ID, SCOPES},
+};
+
+/// How long a sign in in the browser is waited for.
+const LOOPBACK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Signs in through the browser, receiving the redirect on a local port
+/// instead of the `done://` URI scheme, which sandboxes and headless setups
+/// can't always register.
+pub async fn sign_in_with_loopback() -> Result<()> {
+	let listener = TcpListener::bind("127.0.0.1:0").await?;
+	let redirect_uri =
+		format!("http://localhost:{}", listener.local_addr()?.port());
+	MicrosoftService::oauth_client(&Authority::get(), &redirect_uri)
+		.build_async()
+		.authorization_code_grant()
+		.browser_authorization()
+		.open()?;
+
+	let code = tokio::time::timeout(LOOPBACK_TIMEOUT, async {
+		loop {
+			let (stream, _) = listener.accept().await?;
+			let mut stream = BufReader::new(stream);
+			let mut request = String::new();
+			stream.read_line(&mut request).await?;
+			// Browsers also ask for things like the favicon, only the redirect
+			// carries a code or an error.
+			let path = request.split_whitespace().nth(1).unwrap_or_default();
+			let uri = Url::parse(&format!("http://localhost{path}"))?;
+			if !uri
+				.query_pairs()
+				.any(|(key, _)| key == "code" || key == "error")
+			{
+				stream
+					.write_all(b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\n")
+					.await?;
+				continue;
+			}
+			let code = authorization_code(&uri);
+			let message = match &code {
+				Ok(_) => "You are signed in, you can close this page and return to Done.",
+				Err(_) => "The sign in failed, return to Done for details.",
+			};
+			stream
+				.write_all(
+					format!(
+						"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nConnection: close\r\n\r\n{message}"
+					)
+					.as_bytes(),
+				)
+				.await?;
+			return code;
+		}
+	})
+	.await
+	.context("The sign in wasn't completed in time.")??;
+
+	MicrosoftService::new()
+		.request_token(code, &redirect_uri)
+		.await
+}
+
+/// Trades a refresh token for a new token at `token_url`, `None` when the
+/// refresh token was revoked or expired and only signing in again helps.
+pub(crate) async fn refresh_token(
+	token_url: &str,
+	refresh_token: &str,
+) -> Result<Option<AccessToken>> {
+	let scope = SCOPES.join(" ");
+	let response = reqwest::Client::new()
+		.post(token_url)
+		.form(&[
+			("grant_type", "refresh_token"),
+			("client_id", CLIENT_ID),
+			("refresh_token", refresh_token),
+			("scope", &scope),
+		])
+		.send()
+		.await?;
+	if response.status().is_success() {
+		let mut token: AccessToken = response.json().await?;
+		token.gen_timestamp();
+		if token.refresh_token().is_none() {
+			token.set_refresh_token(refresh_token);
+		}
+		return Ok(Some(token));
+	}
+	let status = response.status();
+	let error: ErrorResponse = response.json().await?;
+	match error.error.as_str() {
+		"invalid_grant" | "interaction_required" => Ok(None),
+		_ => bail!(
+			"Failed to refresh the token ({status}): {}",
+			error.error_description.unwrap_or(error.error)
+		),
+	}
+}
+
+/// A code to enter on another device to sign in, for setups without a
+/// browser.
+#[derive(Debug, Clone)]
+pub struct DeviceCode {
+	pub user_code: String,
+	pub verification_uri: String,
+	/// Instructions for the user, in their language.
+	pub message: String,
+	device_code: String,
+	interval: u64,
+	expires_at: Instant,
+	authority: Authority,
+}
+
+#[derive(Deserialize)]
+struct DeviceCodeResponse {
+	user_code: String,
+	device_code: String,
+	verification_uri: String,
+	message: String,
+	expires_in: u64,
+	interval: u64,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+	error: String,
+	error_description: Option<String>,
+}
+
+impl DeviceCode {
+	pub async fn request() -> Result<Self> {
+		let authority = Authority::get();
+		let response = reqwest::Client::new()
+			.post(format!("{}/devicecode", authority.endpoint()))
+			.form(&[("client_id", CLIENT_ID), ("scope", &SCOPES.join(" "))])
+			.send()
+			.await?;
+		if !response.status().is_success() {
+			let error: ErrorResponse = response.json().await?;
+			bail!(
+				"Failed to request a code: {}",
+				error.error_description.unwrap_or(error.error)
+			)
+		}
+		let response: DeviceCodeResponse = response.json().await?;
+		Ok(Self {
+			user_code: response.user_code,
+			verification_uri: response.verification_uri,
+			message: response.message,
+			device_code: response.device_code,
+			interval: response.interval,
+			expires_at: Instant::now() + Duration::from_secs(response.expires_in),
+			authority,
+		})
+	}
+
+	/// Waits until the code is entered, then adds the account it was entered
+	/// for.
+	pub async fn sign_in(&self) -> Result<()> {
+		let client = reqwest::Client::new();
+		let mut interval = self.interval.max(1);
+		loop {
+			tokio::time::sleep(Duration::from_secs(interval)).await;
+			if Instant::now() > self.expires_at {
+				bail!("The code expired before it was entered.")
+			}
+			let response = client
+				.post(self.authority.token_url())
+				.form(&[
+					("grant_type", DEVICE_CODE_GRANT),
+					("client_id", CLIENT_ID),
+					("device_code", &self.device_code),
+				])
+				.send()
+				.await?;
+			if response.status().is_success() {
+				let token: AccessToken = response.json().await?;
+				return MicrosoftService::new()
+					.add_account(token, self.authority.clone())
+					.await;
+			}
+			let error: ErrorResponse = response.json().await?;
+			match error.error.as_str() {
+				"authorization_pending" => {},
+				"slow_down" => interval += 5,
+				_ => bail!(
+					"The sign in failed: {}",
+					error.error_description.unwrap_or(error.error)
+				),
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use tokio::io::AsyncReadExt;
+
+	use super::*;
+
+	/// Answers one request with a status and JSON body, like the token
+	/// endpoint would, and returns the form that was posted.
+	async fn mock_token_endpoint(
+		status: &'static str,
+		body: &'static str,
+	) -> (String, tokio::task::JoinHandle<String>) {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url =
+			format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port());
+		let server = tokio::spawn(async move {
+			let (stream, _) = listener.accept().await.unwrap();
+			let mut stream = BufReader::new(stream);
+			let mut length = 0;
+			loop {
+				let mut line = String::new();
+				stream.read_line(&mut line).await.unwrap();
+				if line.trim().is_empty() {
+					break;
+				}
+				if let Some((name, value)) = line.split_once(':') {
+					if name.eq_ignore_ascii_case("content-length") {
+						length = value.trim().parse().unwrap();
+					}
+				}
+			}
+			let mut form = vec![0; length];
+			stream.read_exact(&mut form).await.unwrap();
+			stream
+				.write_all(
+					format!(
+						"HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+						body.len()
+					)
+					.as_bytes(),
+				)
+				.await
+				.unwrap();
+			String::from_utf8(form).unwrap()
+		});
+		(url, server)
+	}
+
+	async fn refresh(
+		status: &'static str,
+		body: &'static str,
+	) -> (Result<Option<AccessToken>>, String) {
+		let (url, server) = mock_token_endpoint(status, body).await;
+		let token = refresh_token(&format!("{url}/token"), "old-refresh").await;
+		(token, server.await.unwrap())
+	}
+
+	#[tokio::test]
+	async fn posts_the_refresh_token() {
+		let (_, form) =
+			refresh("500 Internal Server Error", r#"{"error":"x"}"#).await;
+		assert!(form.contains("grant_type=refresh_token"));
+		assert!(form.contains("client_id="));
+		assert!(form.contains("refresh_token=old-refresh"));
+	}
+
+	#[tokio::test]
+	async fn expired_becomes_valid_with_a_rotated_refresh_token() {
+		let (token, _) = refresh(
+			"200 OK",
+			r#"{"access_token":"new","token_type":"Bearer","expires_in":3600,"refresh_token":"new-refresh"}"#,
+		)
+		.await;
+		let token = token.unwrap().unwrap();
+		assert_eq!(token.bearer_token(), "new");
+		assert_eq!(token.refresh_token().as_deref(), Some("new-refresh"));
+		assert!(!token.is_expired());
+	}
+
+	#[tokio::test]
+	async fn expired_becomes_valid_keeping_the_refresh_token() {
+		// Microsoft may not rotate the refresh token, the old one still works.
+		let (token, _) = refresh(
+			"200 OK",
+			r#"{"access_token":"newer","token_type":"Bearer","expires_in":3600}"#,
+		)
+		.await;
+		let token = token.unwrap().unwrap();
+		assert_eq!(token.bearer_token(), "newer");
+		assert_eq!(token.refresh_token().as_deref(), Some("old-refresh"));
+	}
+
+	#[tokio::test]
+	async fn expired_becomes_revoked_when_the_grant_is_refused() {
+		let (token, _) = refresh(
+			"400 Bad Request",
+			r#"{"error":"invalid_grant","error_description":"Revoked."}"#,
+		)
+		.await;
+		assert!(token.unwrap().is_none());
+
+		let (token, _) =
+			refresh("400 Bad Request", r#"{"error":"interaction_required"}"#).await;
+		assert!(token.unwrap().is_none());
+	}
+
+	#[tokio::test]
+	async fn expired_stays_expired_when_the_endpoint_fails() {
+		let (token, _) =
+			refresh("500 Internal Server Error", r#"{"error":"server_error"}"#).await;
+		assert!(token.is_err());
+	}
+}
@@ -1,2 +1,4 @@
+pub mod authority;
+pub mod login;
 pub(crate) mod models;
 pub(crate) mod service;
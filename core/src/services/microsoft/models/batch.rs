@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Microsoft Graph accepts up to this many requests in a batch.
+pub const MAX_BATCH_SIZE: usize = 20;
+
+/// Requests sent to Microsoft Graph together, in a single round trip.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct BatchRequest {
+	pub requests: Vec<BatchItem>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BatchItem {
+	pub id: String,
+	pub method: String,
+	/// Relative to the version of the API, like `/me/todo/lists`.
+	pub url: String,
+	pub headers: HashMap<String, String>,
+	pub body: Value,
+}
+
+impl BatchItem {
+	pub fn patch(id: String, url: String, body: Value) -> Self {
+		Self {
+			id,
+			method: "PATCH".to_string(),
+			url,
+			headers: HashMap::from([(
+				"Content-Type".to_string(),
+				"application/json".to_string(),
+			)]),
+			body,
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct BatchResponse {
+	pub responses: Vec<BatchItemResponse>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BatchItemResponse {
+	pub id: String,
+	pub status: u16,
+	#[serde(default)]
+	pub body: Value,
+}
@@ -1,6 +1,8 @@
 use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::Error;
+
 #[derive(
 	Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord,
 )]
@@ -10,16 +12,20 @@ pub struct DateTimeTimeZone {
 	pub time_zone: String,
 }
 
-impl From<DateTimeTimeZone> for DateTime<Utc> {
-	fn from(date: DateTimeTimeZone) -> Self {
+impl TryFrom<DateTimeTimeZone> for DateTime<Utc> {
+	type Error = Error;
+
+	fn try_from(date: DateTimeTimeZone) -> Result<Self, Self::Error> {
 		let datetime =
 			NaiveDateTime::parse_from_str(&date.date_time, "%Y-%m-%dT%H:%M:%S%.f")
 				.or_else(|_| {
 					NaiveDateTime::parse_from_str(&date.date_time, "%Y-%m-%dT%H:%M:%S")
 				})
-				.expect("Failed to parse date string");
+				.map_err(|err| {
+					Error::InvalidData(format!("Invalid date {}: {err}", date.date_time))
+				})?;
 
-		DateTime::<Utc>::from_naive_utc_and_offset(datetime, Utc)
+		Ok(DateTime::<Utc>::from_naive_utc_and_offset(datetime, Utc))
 	}
 }
 
@@ -1,3 +1,4 @@
+pub mod batch;
 pub mod body;
 pub mod checklist_item;
 pub mod collection;
@@ -7,4 +8,3 @@ pub mod list;
 pub mod recurrence;
 pub mod status;
 pub mod task;
-pub mod token;
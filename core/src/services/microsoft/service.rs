@@ -1,104 +1,434 @@
+use std::future::Future;
 use std::pin::Pin;
 
+use crate::models::account::Account;
 use crate::models::list::List;
 use crate::models::task::Task;
+use crate::service::{Service, Services};
+use crate::services::accounts::{Accounts, SignInRequired};
 use crate::services::microsoft::models::{
-	checklist_item::ChecklistItem, collection::Collection, list::TodoTaskList,
+	batch::{BatchItem, BatchRequest, BatchResponse, MAX_BATCH_SIZE},
+	checklist_item::ChecklistItem,
+	collection::Collection,
+	list::TodoTaskList,
 	task::TodoTask,
 };
+use crate::services::microsoft::{authority::Authority, login};
 use crate::task_service::TodoProvider;
-use anyhow::{bail, Result};
+use crate::{Error, Result};
+use anyhow::{bail, Context};
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
 use graph_rs_sdk::{
 	oauth::{AccessToken, OAuth},
-	Graph,
+	Graph, GraphResult,
 };
 use reqwest::StatusCode;
 use url::Url;
 
-pub const APP_ID: &str = "dev.edfloreshz.Done";
-const CLIENT_ID: &str = "75d8509b-cf9b-4245-9550-1e5f1d7c66e4";
-const REDIRECT_URI: &str = "done://msft";
+pub(crate) const CLIENT_ID: &str = "75d8509b-cf9b-4245-9550-1e5f1d7c66e4";
+pub(crate) const REDIRECT_URI: &str = "done://msft";
+pub(crate) const SCOPES: [&str; 6] = [
+	"offline_access",
+	"user.read",
+	"tasks.read",
+	"tasks.read.shared",
+	"tasks.readwrite",
+	"tasks.readwrite.shared",
+];
+
+/// Separates the account from the Microsoft Graph id in the ids of lists, so
+/// requests for a list are made on behalf of the account that owns it.
+const ACCOUNT_SEPARATOR: char = ':';
+/// The key tokens were stored under before several accounts were supported.
+const LEGACY_TOKEN_KEY: &str = "access_token";
+
+/// Where the token of an account is in its life.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenState {
+	/// Can be sent with requests.
+	Valid,
+	/// Past its expiry or rejected by Microsoft Graph, refreshing it may
+	/// recover.
+	Expired,
+	/// Missing, unreadable or impossible to refresh, only signing in again
+	/// recovers.
+	Revoked,
+}
 
+/// A signed in account and the client making requests on its behalf.
 #[derive(Debug, Clone)]
-
-pub struct MicrosoftService {
+struct Session {
+	account: Account,
 	client: Graph,
 	token: AccessToken,
+	state: TokenState,
 }
 
-#[allow(unused)]
-impl MicrosoftService {
-	pub fn new() -> Self {
-		let mut token = AccessToken::default();
-
-		if let Ok(access_token) = keytar::get_password(APP_ID, "access_token") {
-			if (!access_token.password.is_empty()) {
-				token = serde_json::from_str(access_token.password.as_str()).unwrap();
-			}
-		}
+impl Session {
+	fn new(account: Account, token: Option<AccessToken>) -> Self {
+		let state = match token {
+			Some(_) if !account.needs_sign_in => TokenState::Valid,
+			_ => TokenState::Revoked,
+		};
+		let token = token.unwrap_or_default();
 		Self {
 			client: Graph::new(token.bearer_token()),
+			account,
 			token,
+			state,
 		}
 	}
 
-	fn oauth_client() -> OAuth {
-		let mut oauth = OAuth::new();
-		oauth
-			.client_id(CLIENT_ID)
-			.redirect_uri(REDIRECT_URI)
-			.add_scope("offline_access")
-			.add_scope("user.read")
-			.add_scope("tasks.read")
-			.add_scope("tasks.read.shared")
-			.add_scope("tasks.readwrite")
-			.add_scope("tasks.readwrite.shared")
-			.authorize_url(
-				"https://login.microsoftonline.com/consumers/oauth2/v2.0/authorize",
-			)
-			.access_token_url(
-				"https://login.microsoftonline.com/consumers/oauth2/v2.0/token",
-			)
-			.refresh_token_url(
-				"https://login.microsoftonline.com/consumers/oauth2/v2.0/token",
-			)
-			.response_type("code");
-		oauth
+	/// The id Done uses for a list of this account.
+	fn list_id(&self, id: &str) -> String {
+		format!("{}{ACCOUNT_SEPARATOR}{id}", self.account.id)
 	}
 
-	async fn refresh_token(&mut self) -> Result<()> {
-		if self.token.is_expired() {
-			if let Some(refresh_token) = self.token.refresh_token() {
-				let mut oauth = Self::oauth_client();
-				oauth.access_token(self.token.clone());
-				let token: AccessToken = oauth
-					.build_async()
-					.authorization_code_grant()
-					.refresh_token()
-					.send()
-					.await?
-					.json()
-					.await?;
-				self.store_token(token)?;
+	/// Refreshes the token when it expired, fails with [`SignInRequired`] when
+	/// it can't be.
+	async fn authorize(&mut self) -> anyhow::Result<()> {
+		if self.state == TokenState::Valid && self.token.is_expired() {
+			self.state = TokenState::Expired;
+		}
+		match self.state {
+			TokenState::Valid => Ok(()),
+			TokenState::Expired => {
+				let refreshed = match self.token.refresh_token() {
+					Some(refresh_token) => {
+						login::refresh_token(
+							&Authority::of_account(&self.account.id).token_url(),
+							&refresh_token,
+						)
+						.await?
+					},
+					None => None,
+				};
+				let Some(token) = refreshed else {
+					return Err(self.revoke());
+				};
+				MicrosoftService::store_token(&self.account.id, &token)?;
+				self.client = Graph::new(token.bearer_token());
+				self.token = token;
+				self.state = TokenState::Valid;
+				Ok(())
+			},
+			TokenState::Revoked => Err(SignInRequired(self.account.clone()).into()),
+		}
+	}
+
+	/// Remembers the account must be signed in to again, so it is shown until
+	/// the user does.
+	fn revoke(&mut self) -> anyhow::Error {
+		self.state = TokenState::Revoked;
+		if !self.account.needs_sign_in {
+			self.account.needs_sign_in = true;
+			if let Err(err) =
+				Accounts::set_needs_sign_in(Service::Microsoft, &self.account.id, true)
+			{
+				tracing::error!("Failed to update the account: {err}");
 			}
 		}
-		Ok(())
+		SignInRequired(self.account.clone()).into()
 	}
 
-	fn store_token(&mut self, token: AccessToken) -> Result<()> {
-		keytar::set_password(
-			APP_ID,
-			"access_token",
-			&serde_json::to_string(&token)?,
+	/// Sends a request, refreshing the token and sending it once more when
+	/// Microsoft Graph rejects it.
+	async fn send<F, Fut>(
+		&mut self,
+		request: F,
+	) -> anyhow::Result<reqwest::Response>
+	where
+		F: Fn(Graph) -> Fut,
+		Fut: Future<Output = GraphResult<reqwest::Response>>,
+	{
+		self.authorize().await?;
+		let response = request(self.client.clone()).await?;
+		if response.status() != StatusCode::UNAUTHORIZED {
+			return Ok(response);
+		}
+		self.state = TokenState::Expired;
+		self.authorize().await?;
+		let response = request(self.client.clone()).await?;
+		if response.status() == StatusCode::UNAUTHORIZED {
+			return Err(self.revoke());
+		}
+		Ok(response)
+	}
+
+	/// Streams the pages of a collection. The first page is sent with
+	/// [`Session::send`], so a rejected token is refreshed or the account is
+	/// asked to sign in again, the next pages follow with the same token.
+	async fn pages<F, Fut>(
+		&mut self,
+		request: F,
+	) -> anyhow::Result<
+		Pin<Box<dyn Stream<Item = anyhow::Result<Vec<serde_json::Value>>> + Send>>,
+	>
+	where
+		F: Fn(Graph) -> Fut,
+		Fut: Future<Output = GraphResult<reqwest::Response>>,
+	{
+		enum Page {
+			Read(serde_json::Value),
+			Next(String),
+		}
+
+		let first: serde_json::Value =
+			self.send(request).await?.error_for_status()?.json().await?;
+		let token = self.token.bearer_token().to_string();
+		let http = reqwest::Client::new();
+		Ok(
+			futures::stream::unfold(Some(Page::Read(first)), move |page| {
+				let (http, token) = (http.clone(), token.clone());
+				async move {
+					let page = match page? {
+						Page::Read(page) => page,
+						Page::Next(url) => {
+							let response = http
+								.get(&url)
+								.bearer_auth(&token)
+								.send()
+								.await
+								.and_then(|response| response.error_for_status());
+							let page = match response {
+								Ok(response) => response.json().await,
+								Err(err) => Err(err),
+							};
+							match page {
+								Ok(page) => page,
+								Err(err) => return Some((Err(err.into()), None)),
+							}
+						},
+					};
+					let next = page["@odata.nextLink"]
+						.as_str()
+						.map(|url| Page::Next(url.to_string()));
+					let values = page["value"].as_array().cloned().unwrap_or_default();
+					Some((Ok(values), next))
+				}
+			})
+			.boxed(),
+		)
+	}
+
+	async fn update_check_list_items(
+		&mut self,
+		todo_task_list_id: &str,
+		todo_task_id: &str,
+		checklist_items: &Option<Vec<ChecklistItem>>,
+	) -> anyhow::Result<()> {
+		let Some(checklist_items) = checklist_items else {
+			return Ok(());
+		};
+		// One request per item would be a round trip each, they are sent in
+		// batches instead.
+		let mut failed = vec![];
+		for items in checklist_items.chunks(MAX_BATCH_SIZE) {
+			let batch = &BatchRequest {
+				requests: items
+					.iter()
+					.enumerate()
+					.map(|(index, item)| {
+						BatchItem::patch(
+							index.to_string(),
+							format!(
+								"/me/todo/lists/{todo_task_list_id}/tasks/{todo_task_id}/checklistItems/{}",
+								item.id
+							),
+							serde_json::json!(item),
+						)
+					})
+					.collect(),
+			};
+			let response = self
+				.send(|client| async move { client.batch(batch).send().await })
+				.await?;
+			let batch: BatchResponse = response.error_for_status()?.json().await?;
+			for response in batch.responses.iter().filter(|r| r.status >= 400) {
+				let item = response
+					.id
+					.parse::<usize>()
+					.ok()
+					.and_then(|index| items.get(index));
+				tracing::error!(
+					"There was an error updating check list item {} ({}): {}",
+					item
+						.map(|item| item.display_name.as_str())
+						.unwrap_or_default(),
+					response.status,
+					response.body
+				);
+				failed.push(response.status);
+			}
+		}
+		let Some(status) = failed.first() else {
+			return Ok(());
+		};
+		let message = format!(
+			"{} of {} check list items weren't updated.",
+			failed.len(),
+			checklist_items.len()
 		);
-		self.token = token;
+		let error = match StatusCode::from_u16(*status) {
+			Ok(StatusCode::UNAUTHORIZED) => Error::Unauthorized(message),
+			Ok(StatusCode::NOT_FOUND) => Error::NotFound(message),
+			Ok(StatusCode::CONFLICT | StatusCode::PRECONDITION_FAILED) => {
+				Error::Conflict(message)
+			},
+			_ => Error::Network(message),
+		};
+		Err(error.into())
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct MicrosoftService {
+	sessions: Vec<Session>,
+}
+
+impl MicrosoftService {
+	pub fn new() -> Self {
+		Self::migrate_legacy_token();
+		let sessions = Accounts::list(Service::Microsoft)
+			.into_iter()
+			.map(|account| {
+				let token = Self::stored_token(&account.id);
+				Session::new(account, token)
+			})
+			.collect();
+		Self { sessions }
+	}
+
+	fn token_key(account_id: &str) -> String {
+		format!("{LEGACY_TOKEN_KEY}:{account_id}")
+	}
+
+	fn stored_token(account_id: &str) -> Option<AccessToken> {
+		let password =
+			keytar::get_password(&Services::app_id(), &Self::token_key(account_id))
+				.ok()?;
+		if password.password.is_empty() {
+			return None;
+		}
+		match serde_json::from_str(&password.password) {
+			Ok(token) => Some(token),
+			Err(err) => {
+				tracing::error!("The token of account {account_id} is invalid: {err}");
+				None
+			},
+		}
+	}
+
+	fn store_token(account_id: &str, token: &AccessToken) -> anyhow::Result<()> {
+		keytar::set_password(
+			&Services::app_id(),
+			&Self::token_key(account_id),
+			&serde_json::to_string(token)?,
+		)?;
+		Ok(())
+	}
+
+	/// Turns the token stored before accounts existed into an account.
+	fn migrate_legacy_token() {
+		let application_id = Services::app_id();
+		let Ok(password) = keytar::get_password(&application_id, LEGACY_TOKEN_KEY)
+		else {
+			return;
+		};
+		if password.password.is_empty()
+			|| !Accounts::list(Service::Microsoft).is_empty()
+		{
+			return;
+		}
+		let account =
+			Account::new("default", "Microsoft account", Service::Microsoft);
+		let migrated = keytar::set_password(
+			&application_id,
+			&Self::token_key(&account.id),
+			&password.password,
+		)
+		.map_err(anyhow::Error::from)
+		.and_then(|_| Accounts::add(account));
+		match migrated {
+			Ok(()) => {
+				if let Err(err) =
+					keytar::delete_password(&application_id, LEGACY_TOKEN_KEY)
+				{
+					tracing::error!("Failed to delete the previous token: {err}");
+				}
+			},
+			Err(err) => {
+				tracing::error!("Failed to migrate the previous token: {err}")
+			},
+		}
+	}
+
+	/// Signs an account out, forgetting its token.
+	pub fn remove_account(account_id: &str) -> anyhow::Result<()> {
+		Self::forget_token(&Services::app_id(), account_id)?;
+		Authority::remove_account(account_id)?;
+		Accounts::remove(Service::Microsoft, account_id)
+	}
+
+	/// Deletes the token of an account of a profile, tokens are kept per
+	/// profile so signing out in one leaves the others signed in.
+	pub(crate) fn forget_token(
+		application_id: &str,
+		account_id: &str,
+	) -> anyhow::Result<()> {
+		keytar::delete_password(application_id, &Self::token_key(account_id))?;
 		Ok(())
 	}
 
-	pub async fn request_token(&mut self, access_code: String) -> Result<()> {
-		let mut oauth = Self::oauth_client();
+	/// Finds the session owning a list and the Microsoft Graph id of the list.
+	/// Lists without an account belong to the first account.
+	async fn session(
+		&mut self,
+		list_id: &str,
+	) -> anyhow::Result<(&mut Session, String)> {
+		let (account_id, id) = match list_id.split_once(ACCOUNT_SEPARATOR) {
+			Some((account_id, id)) => (Some(account_id), id.to_string()),
+			None => (None, list_id.to_string()),
+		};
+		let session = match account_id {
+			Some(account_id) => self
+				.sessions
+				.iter_mut()
+				.find(|session| session.account.id == account_id),
+			None => self.sessions.first_mut(),
+		}
+		.with_context(|| format!("No account signed in for list {list_id}."))?;
+		Ok((session, id))
+	}
+
+	pub(crate) fn oauth_client(
+		authority: &Authority,
+		redirect_uri: &str,
+	) -> OAuth {
+		let endpoint = authority.endpoint();
+		let mut oauth = OAuth::new();
+		oauth.client_id(CLIENT_ID).redirect_uri(redirect_uri);
+		for scope in SCOPES {
+			oauth.add_scope(scope);
+		}
+		oauth
+			.authorize_url(&format!("{endpoint}/authorize"))
+			.access_token_url(&format!("{endpoint}/token"))
+			.refresh_token_url(&format!("{endpoint}/token"))
+			.response_type("code");
+		oauth
+	}
+
+	/// Exchanges the code a sign in redirected with for a token, the redirect
+	/// URI must be the one the sign in was started with.
+	pub async fn request_token(
+		&mut self,
+		access_code: String,
+		redirect_uri: &str,
+	) -> anyhow::Result<()> {
+		let authority = Authority::get();
+		let mut oauth = Self::oauth_client(&authority, redirect_uri);
 		oauth.access_code(access_code.as_str());
 		let mut request = oauth.build_async().authorization_code_grant();
 
@@ -107,71 +437,115 @@ impl MicrosoftService {
 		match response.error_for_status() {
 			Ok(response) => {
 				let access_token: AccessToken = response.json().await?;
-				oauth.access_token(access_token.clone());
-				self.store_token(access_token);
-				Ok(())
+				self.add_account(access_token, authority).await
 			},
 			Err(error) => Err(error.into()),
 		}
 	}
 
-	pub async fn update_check_list_items(
-		&self,
-		todo_task_list_id: &str,
-		todo_task_id: &str,
-		checklist_items: &Option<Vec<ChecklistItem>>,
-	) -> Result<()> {
-		if let Some(checklist_items) = checklist_items {
-			for item in checklist_items {
-				let response = self
-					.client
-					.me()
-					.todo()
-					.list(todo_task_list_id)
-					.task(todo_task_id)
-					.update_checklist_items(&item.id, &serde_json::json!(item))
-					.send()
-					.await?;
-
-				if let Err(err) = response.error_for_status() {
-					tracing::error!(
-						"There was an error updating a check list item: {}",
-						err.to_string()
-					)
-				}
-			}
-		}
+	/// Adds the account a token was issued for, updating it when it was
+	/// already signed in.
+	pub(crate) async fn add_account(
+		&mut self,
+		mut access_token: AccessToken,
+		authority: Authority,
+	) -> anyhow::Result<()> {
+		access_token.gen_timestamp();
+		let client = Graph::new(access_token.bearer_token());
+		let user: serde_json::Value = client
+			.me()
+			.get_user()
+			.send()
+			.await?
+			.error_for_status()
+			.context("Failed to read the signed in account.")?
+			.json()
+			.await?;
+		let id = user["id"]
+			.as_str()
+			.context("The signed in account has no id.")?;
+		let display_name = user["displayName"]
+			.as_str()
+			.or(user["userPrincipalName"].as_str())
+			.unwrap_or(id);
+		let account = Account::new(id, display_name, Service::Microsoft);
+		Self::store_token(&account.id, &access_token)?;
+		Authority::set_account(&account.id, authority)?;
+		Accounts::add(account.clone())?;
+		self
+			.sessions
+			.retain(|session| session.account.id != account.id);
+		self
+			.sessions
+			.push(Session::new(account, Some(access_token)));
 		Ok(())
 	}
 }
 
+/// The code a sign in redirected with, or the reason it failed.
+pub(crate) fn authorization_code(uri: &Url) -> anyhow::Result<String> {
+	let param = |name: &str| {
+		uri
+			.query_pairs()
+			.find(|(key, _)| key == name)
+			.map(|(_, value)| value.to_string())
+	};
+	if let Some(error) = param("error_description").or(param("error")) {
+		bail!("Microsoft declined the sign in: {error}")
+	}
+	param("code").context("The sign in returned no code.")
+}
+
+/// Converts the tasks of a list, tasks with data that can't be read are
+/// logged and left out instead of failing the whole list.
+fn readable(tasks: Vec<TodoTask>, parent_list: &str) -> Vec<Task> {
+	tasks
+		.into_iter()
+		.filter_map(|task| {
+			let id = task.id.clone();
+			match Task::try_from(task) {
+				Ok(mut task) => {
+					task.parent = parent_list.to_string();
+					Some(task)
+				},
+				Err(err) => {
+					tracing::error!("Skipping task {id}: {err}");
+					None
+				},
+			}
+		})
+		.collect()
+}
+
 #[async_trait]
-#[allow(unused)]
 impl TodoProvider for MicrosoftService {
 	async fn handle_uri_params(&mut self, uri: Url) -> Result<()> {
-		let mut pairs = uri.query_pairs();
 		if uri.as_str().contains("msft") {
-			let code = pairs.next().unwrap().1.to_string();
-			self.request_token(code).await?;
+			let code = authorization_code(&uri)?;
+			self.request_token(code, REDIRECT_URI).await?;
 		}
 		Ok(())
 	}
 
-	fn login(&self) -> anyhow::Result<()> {
-		let mut oauth = MicrosoftService::oauth_client();
+	async fn login(&self) -> Result<()> {
+		let mut oauth =
+			MicrosoftService::oauth_client(&Authority::get(), REDIRECT_URI);
 		let mut request = oauth.build_async().authorization_code_grant();
 		request.browser_authorization().open()?;
 		Ok(())
 	}
 
-	fn logout(&self) -> anyhow::Result<()> {
-		keytar::delete_password(APP_ID, "access_token")?;
+	async fn logout(&self) -> Result<()> {
+		for account in Accounts::list(Service::Microsoft) {
+			Self::remove_account(&account.id)?;
+		}
 		Ok(())
 	}
 
+	/// Stays available while accounts need signing in to again, so the user
+	/// is asked to.
 	fn available(&self) -> bool {
-		let password = keytar::get_password(APP_ID, "access_token");
-		password.is_ok() && !password.unwrap().password.is_empty()
+		!self.sessions.is_empty()
 	}
 
 	fn stream_support(&self) -> bool {
@@ -186,66 +560,55 @@ impl TodoProvider for MicrosoftService {
 		&mut self,
 		parent_list: String,
 	) -> Result<Vec<Task>> {
-		self.refresh_token().await?;
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(parent_list.clone())
-			.tasks()
-			.list_tasks()
-			.send()
+		let (session, list_id) = self.session(&parent_list).await?;
+		let list_id = &list_id;
+		let response = session
+			.send(|client| async move {
+				client
+					.me()
+					.todo()
+					.list(list_id)
+					.tasks()
+					.list_tasks()
+					.send()
+					.await
+			})
 			.await?;
-		let collection: Collection<TodoTask> = response.json().await?;
-		Ok(
-			collection
-				.value
-				.iter()
-				.map(|task| {
-					let mut task: Task = task.clone().into();
-					task.parent = parent_list.clone();
-					task
-				})
-				.collect(),
-		)
+		let collection: Collection<TodoTask> =
+			response.error_for_status()?.json().await?;
+		Ok(readable(collection.value, &parent_list))
 	}
 
 	async fn get_tasks(
 		&mut self,
 		parent_list: String,
-	) -> Result<Pin<Box<dyn Stream<Item = Task> + Send>>> {
-		self.refresh_token().await?;
-		let mut stream = self
-			.client
-			.me()
-			.todo()
-			.list(parent_list.clone())
-			.tasks()
-			.list_tasks()
-			.paging()
-			.stream::<serde_json::Value>()?
-			.filter_map(|response| async move {
-				match response {
-					Ok(response) => {
-						let value = response.into_body().ok()?;
-						let tasks: Vec<serde_json::Value> =
-							value["value"].as_array().cloned()?;
-
-						let task_list = tasks
-							.iter()
-							.flat_map(|t| serde_json::from_value(t.clone()).ok())
-							.flat_map(|t: TodoTask| Some(t.into()))
-							.collect::<Vec<Task>>();
-
-						Some(task_list)
-					},
-					Err(err) => {
-						tracing::error!("There was an error getting the tasks: {}", err);
-						None
+	) -> Result<Pin<Box<dyn Stream<Item = Result<Task>> + Send>>> {
+		let (session, list_id) = self.session(&parent_list).await?;
+		let list_id = &list_id;
+		let stream = session
+			.pages(|client| async move {
+				client
+					.me()
+					.todo()
+					.list(list_id)
+					.tasks()
+					.list_tasks()
+					.send()
+					.await
+			})
+			.await?
+			.flat_map(move |values| {
+				futures::stream::iter(match values {
+					Ok(values) => {
+						let tasks = values
+							.into_iter()
+							.flat_map(serde_json::from_value)
+							.collect();
+						readable(tasks, &parent_list).into_iter().map(Ok).collect()
 					},
-				}
+					Err(err) => vec![Err(err.into())],
+				})
 			})
-			.flat_map(futures::stream::iter)
 			.boxed();
 
 		Ok(stream)
@@ -256,74 +619,73 @@ impl TodoProvider for MicrosoftService {
 		task_list_id: String,
 		task_id: String,
 	) -> Result<Task> {
-		self.refresh_token().await?;
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(task_list_id.clone())
-			.task(task_id)
-			.get_tasks()
-			.send()
+		let (session, list_id) = self.session(&task_list_id).await?;
+		let (list_id, task_id) = (&list_id, &task_id);
+		let response = session
+			.send(|client| async move {
+				client
+					.me()
+					.todo()
+					.list(list_id)
+					.task(task_id)
+					.get_tasks()
+					.send()
+					.await
+			})
 			.await?;
-		let task: TodoTask = response.json().await?;
-		let mut task: Task = task.clone().into();
+		let task: TodoTask = response.error_for_status()?.json().await?;
+		let mut task: Task = task.try_into()?;
 		task.parent = task_list_id;
 		Ok(task)
 	}
 
 	async fn create_task(&mut self, task: Task) -> Result<()> {
-		self.refresh_token().await?;
+		let (session, list_id) = self.session(&task.parent).await?;
 		let todo_task: TodoTask = task.clone().into();
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(task.parent)
-			.tasks()
-			.create_tasks(&serde_json::json!(todo_task))
-			.send()
+		let (list_id, body) = (&list_id, &serde_json::json!(todo_task));
+		let response = session
+			.send(|client| async move {
+				client
+					.me()
+					.todo()
+					.list(list_id)
+					.tasks()
+					.create_tasks(body)
+					.send()
+					.await
+			})
 			.await?;
 
-		if response.status() == StatusCode::CREATED {
-			Ok(())
-		} else {
-			bail!("An error ocurred while creating the task.")
-		}
+		response.error_for_status()?;
+		Ok(())
 	}
 
 	async fn update_task(&mut self, task: Task) -> Result<Task> {
-		self.refresh_token().await?;
+		let (session, list_id) = self.session(&task.parent).await?;
 		let mut todo_task: TodoTask = task.clone().into();
-		self
-			.update_check_list_items(
-				&task.parent,
-				&task.id,
-				&todo_task.checklist_items,
-			)
+		session
+			.update_check_list_items(&list_id, &task.id, &todo_task.checklist_items)
 			.await?;
 		todo_task.checklist_items = None;
-		println!("{}", serde_json::json!(todo_task));
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(task.parent)
-			.task(todo_task.id.clone())
-			.update_tasks(&serde_json::json!(todo_task))
-			.send()
+		let body = &serde_json::json!(todo_task);
+		let (list_id, task_id) = (&list_id, &todo_task.id);
+		let response = session
+			.send(|client| async move {
+				client
+					.me()
+					.todo()
+					.list(list_id)
+					.task(task_id)
+					.update_tasks(body)
+					.send()
+					.await
+			})
 			.await?;
 
-		let status = response.status();
-		match response.error_for_status() {
-			Ok(response) => {
-				let task: TodoTask = response.json().await?;
-				Ok(task.into())
-			},
-			Err(err) => {
-				bail!("An error ocurred while updating the list: {err}")
-			},
-		}
+		let todo_task: TodoTask = response.error_for_status()?.json().await?;
+		let mut updated: Task = todo_task.try_into()?;
+		updated.parent = task.parent;
+		Ok(updated)
 	}
 
 	async fn delete_task(
@@ -331,131 +693,187 @@ impl TodoProvider for MicrosoftService {
 		list_id: String,
 		task_id: String,
 	) -> Result<()> {
-		self.refresh_token().await?;
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(list_id)
-			.task(task_id)
-			.delete_tasks()
-			.send()
+		let (session, list_id) = self.session(&list_id).await?;
+		let (list_id, task_id) = (&list_id, &task_id);
+		let response = session
+			.send(|client| async move {
+				client
+					.me()
+					.todo()
+					.list(list_id)
+					.task(task_id)
+					.delete_tasks()
+					.send()
+					.await
+			})
 			.await?;
-		if response.status() == StatusCode::NO_CONTENT {
-			Ok(())
-		} else {
-			bail!("An error ocurred while deleting the task.")
-		}
+		response.error_for_status()?;
+		Ok(())
 	}
 
+	/// Reads the lists of every shown account, skipping the accounts that
+	/// fail so the others are still shown.
 	async fn read_lists(&mut self) -> Result<Vec<List>> {
-		self.refresh_token().await?;
-		let response = self.client.me().todo().lists().list_lists().send().await?;
-
-		let lists: Collection<TodoTaskList> = response.json().await?;
-		Ok(lists.value.iter().map(|t| t.clone().into()).collect())
+		let mut lists = vec![];
+		for session in self
+			.sessions
+			.iter_mut()
+			.filter(|session| session.account.enabled)
+		{
+			let collection = async {
+				let response = session
+					.send(|client| async move {
+						client.me().todo().lists().list_lists().send().await
+					})
+					.await?;
+				Ok::<_, anyhow::Error>(
+					response
+						.error_for_status()?
+						.json::<Collection<TodoTaskList>>()
+						.await?,
+				)
+			}
+			.await;
+			let collection = match collection {
+				Ok(collection) => collection,
+				Err(err) => {
+					tracing::error!(
+						"Failed to read the lists of {}: {err}",
+						session.account.display_name
+					);
+					continue;
+				},
+			};
+			lists.extend(collection.value.into_iter().map(|list| {
+				let mut list: List = list.into();
+				list.id = session.list_id(&list.id);
+				list.account = Some(session.account.display_name.clone());
+				list
+			}));
+		}
+		Ok(lists)
 	}
 
 	async fn get_lists(
 		&mut self,
-	) -> Result<Pin<Box<dyn Stream<Item = List> + Send>>> {
-		self.refresh_token().await?;
-		let mut stream = self
-			.client
-			.me()
-			.todo()
-			.lists()
-			.list_lists()
-			.paging()
-			.stream::<serde_json::Value>()?
-			.filter_map(|response| async move {
-				match response {
-					Ok(response) => {
-						let value = response.into_body().ok()?;
-						let lists: Vec<serde_json::Value> =
-							value["value"].as_array().cloned()?;
-
-						let list = lists
-							.iter()
-							.flat_map(|t| serde_json::from_value(t.clone()).ok())
-							.flat_map(|t: TodoTaskList| Some(t.into()))
-							.collect::<Vec<List>>();
-
-						Some(list)
-					},
-					Err(err) => {
-						tracing::error!("There was an error getting the tasks: {}", err);
-						None
-					},
-				}
-			})
-			.flat_map(futures::stream::iter)
-			.boxed();
+	) -> Result<Pin<Box<dyn Stream<Item = Result<List>> + Send>>> {
+		let mut streams = vec![];
+		for session in self
+			.sessions
+			.iter_mut()
+			.filter(|session| session.account.enabled)
+		{
+			let pages = session
+				.pages(|client| async move {
+					client.me().todo().lists().list_lists().send().await
+				})
+				.await;
+			// The lists of the other accounts are still read, the error is
+			// sent in place of the lists of this one.
+			let pages = match pages {
+				Ok(pages) => pages,
+				Err(err) => {
+					let error: Error = err.into();
+					streams.push(futures::stream::once(async { Err(error) }).boxed());
+					continue;
+				},
+			};
+			let account = session.account.clone();
+			let stream = pages
+				.map(move |values| match values {
+					Ok(values) => values
+						.into_iter()
+						.flat_map(serde_json::from_value)
+						.map(|list: TodoTaskList| {
+							let mut list: List = list.into();
+							list.id = format!("{}{ACCOUNT_SEPARATOR}{}", account.id, list.id);
+							list.account = Some(account.display_name.clone());
+							Ok(list)
+						})
+						.collect::<Vec<Result<List>>>(),
+					Err(err) => vec![Err(err.into())],
+				})
+				.flat_map(futures::stream::iter);
+			streams.push(stream.boxed());
+		}
 
-		Ok(stream)
+		Ok(futures::stream::iter(streams).flatten().boxed())
 	}
 
 	async fn read_list(&mut self, id: String) -> Result<List> {
-		self.refresh_token().await?;
-		let response = self.client.me().todo().list(id).get_lists().send().await?;
-		let list: TodoTaskList = response.json().await?;
-		Ok(list.into())
+		let (session, list_id) = self.session(&id).await?;
+		let list_id = &list_id;
+		let response = session
+			.send(|client| async move {
+				client.me().todo().list(list_id).get_lists().send().await
+			})
+			.await?;
+		let list: TodoTaskList = response.error_for_status()?.json().await?;
+		let mut list: List = list.into();
+		list.id = id;
+		list.account = Some(session.account.display_name.clone());
+		Ok(list)
 	}
 
+	/// Creates the list in the account its id names, or in the first account
+	/// that is signed in.
 	async fn create_list(&mut self, list: List) -> Result<List> {
-		self.refresh_token().await?;
+		let session = match list.id.split_once(ACCOUNT_SEPARATOR) {
+			Some((account_id, _)) => self
+				.sessions
+				.iter_mut()
+				.find(|session| session.account.id == account_id),
+			None => self
+				.sessions
+				.iter_mut()
+				.find(|session| session.state != TokenState::Revoked),
+		}
+		.ok_or_else(|| Error::Unauthorized("No account signed in.".to_string()))?;
 		let list: TodoTaskList = list.into();
-		println!("{}", serde_json::json!(list));
-		let response = self
-			.client
-			.me()
-			.todo()
-			.lists()
-			.create_lists(&serde_json::json!(list))
-			.send()
+		let body = &serde_json::json!(list);
+		let response = session
+			.send(|client| async move {
+				client.me().todo().lists().create_lists(body).send().await
+			})
 			.await?;
-		match response.error_for_status() {
-			Ok(response) => {
-				let list: TodoTaskList = response.json().await?;
-				Ok(list.into())
-			},
-			Err(err) => bail!("An error ocurred while creating the list: {err}"),
-		}
+		let list: TodoTaskList = response.error_for_status()?.json().await?;
+		let mut list: List = list.into();
+		list.id = session.list_id(&list.id);
+		list.account = Some(session.account.display_name.clone());
+		Ok(list)
 	}
 
 	async fn update_list(&mut self, list: List) -> Result<()> {
-		self.refresh_token().await?;
-		let list: TodoTaskList = list.into();
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(list.id.clone())
-			.update_lists(&serde_json::json!(list))
-			.send()
+		let (session, list_id) = self.session(&list.id).await?;
+		let mut list: TodoTaskList = list.into();
+		list.id = list_id;
+		let (list_id, body) = (&list.id, &serde_json::json!(list));
+		let response = session
+			.send(|client| async move {
+				client
+					.me()
+					.todo()
+					.list(list_id)
+					.update_lists(body)
+					.send()
+					.await
+			})
 			.await?;
 
-		if response.status() == StatusCode::OK {
-			Ok(())
-		} else {
-			bail!("An error ocurred while updating the list.")
-		}
+		response.error_for_status()?;
+		Ok(())
 	}
 
 	async fn delete_list(&mut self, id: String) -> Result<()> {
-		self.refresh_token().await?;
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(id)
-			.delete_lists()
-			.send()
+		let (session, list_id) = self.session(&id).await?;
+		let list_id = &list_id;
+		let response = session
+			.send(|client| async move {
+				client.me().todo().list(list_id).delete_lists().send().await
+			})
 			.await?;
 
-		match response.error_for_status() {
-			Ok(_) => Ok(()),
-			Err(err) => bail!("An error ocurred while deleting the list: {err}"),
-		}
+		response.error_for_status()?;
+		Ok(())
 	}
 }
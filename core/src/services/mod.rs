@@ -1,3 +1,11 @@
+pub mod accounts;
+pub mod files;
 pub mod local;
-pub(crate) mod microsoft;
+pub(crate) mod markdown;
+pub mod microsoft;
+pub(crate) mod org;
+pub mod plugins;
+pub mod profiles;
 pub(crate) mod smart;
+pub(crate) mod taskwarrior;
+pub(crate) mod todo_txt;
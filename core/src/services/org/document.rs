@@ -0,0 +1,746 @@
+use std::{collections::HashMap, fmt::Display, sync::OnceLock};
+
+use chrono::{
+	DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc,
+};
+use regex::{Captures, Regex};
+
+use crate::{
+	models::{priority::Priority, status::Status, task::Task},
+	services::files::stable_id,
+};
+
+/// Id of the list holding the tasks that are top level headings.
+pub(crate) const INBOX_ID: &str = "no heading";
+
+fn heading() -> &'static Regex {
+	static REGEX: OnceLock<Regex> = OnceLock::new();
+	REGEX.get_or_init(|| {
+		Regex::new(
+			r"^(\*+) +(?:(TODO|DONE) +)?(?:\[#([A-Z])\] +)?(.*?)(?:\s+(:[\w@#%:]+:))?\s*$",
+		)
+		.unwrap()
+	})
+}
+
+fn timestamp() -> &'static Regex {
+	static REGEX: OnceLock<Regex> = OnceLock::new();
+	REGEX.get_or_init(|| {
+		Regex::new(r"(SCHEDULED|DEADLINE|CLOSED): *([<\[])(\d{4}-\d{2}-\d{2})(?: [^\s\d>\]+.-]+)?(?: (\d{1,2}:\d{2}))?([^>\]]*)([>\]])")
+			.unwrap()
+	})
+}
+
+fn time_range_end() -> &'static Regex {
+	static REGEX: OnceLock<Regex> = OnceLock::new();
+	REGEX.get_or_init(|| Regex::new(r"^-(\d{1,2}:\d{2})").unwrap())
+}
+
+fn checkbox() -> &'static Regex {
+	static REGEX: OnceLock<Regex> = OnceLock::new();
+	REGEX.get_or_init(|| Regex::new(r"^\s*[-+] \[([ Xx-])\] (.*)$").unwrap())
+}
+
+/// A parsed heading line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Heading {
+	level: usize,
+	keyword: Option<String>,
+	priority: Option<char>,
+	title: String,
+	tags: Vec<String>,
+}
+
+impl Heading {
+	fn parse(line: &str) -> Option<Self> {
+		let captures = heading().captures(line)?;
+		Some(Self {
+			level: captures[1].len(),
+			keyword: captures.get(2).map(|keyword| keyword.as_str().to_string()),
+			priority: captures
+				.get(3)
+				.and_then(|priority| priority.as_str().chars().next()),
+			title: captures[4].to_string(),
+			tags: captures
+				.get(5)
+				.map(|tags| {
+					tags
+						.as_str()
+						.split(':')
+						.filter(|tag| !tag.is_empty())
+						.map(str::to_string)
+						.collect()
+				})
+				.unwrap_or_default(),
+		})
+	}
+
+	fn is_task(&self) -> bool {
+		self.keyword.is_some()
+	}
+}
+
+impl Display for Heading {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", "*".repeat(self.level))?;
+		if let Some(keyword) = &self.keyword {
+			write!(f, " {keyword}")?;
+		}
+		if let Some(priority) = self.priority {
+			write!(f, " [#{priority}]")?;
+		}
+		write!(f, " {}", self.title)?;
+		if !self.tags.is_empty() {
+			write!(f, " :{}:", self.tags.join(":"))?;
+		}
+		Ok(())
+	}
+}
+
+/// A TODO or DONE heading and the lines up to the next heading.
+#[derive(Clone, Debug)]
+pub(crate) struct Item {
+	pub start: usize,
+	pub end: usize,
+	heading: Heading,
+	pub task: Task,
+}
+
+/// The lines of an entry after its heading.
+#[derive(Clone, Debug, Default)]
+struct Entry {
+	planning: Option<String>,
+	properties: Vec<String>,
+	/// Drawers other than the properties, like `:LOGBOOK:`, kept verbatim.
+	drawers: Vec<String>,
+	notes: Vec<String>,
+	checkboxes: Vec<String>,
+	trailing_blank: bool,
+}
+
+impl Entry {
+	fn parse(lines: &[String]) -> Self {
+		let mut entry = Self::default();
+		let mut lines = lines.iter().peekable();
+		if let Some(line) = lines.peek() {
+			if timestamp().is_match(line)
+				&& line.trim_start().starts_with(['S', 'D', 'C'])
+			{
+				entry.planning = lines.next().cloned();
+			}
+		}
+		let mut drawer: Option<bool> = None;
+		for line in lines {
+			let trimmed = line.trim();
+			match drawer {
+				Some(properties) => {
+					if properties {
+						entry.properties.push(line.clone());
+					} else {
+						entry.drawers.push(line.clone());
+					}
+					if trimmed.eq_ignore_ascii_case(":END:") {
+						drawer = None;
+					}
+				},
+				None if trimmed.eq_ignore_ascii_case(":PROPERTIES:") => {
+					drawer = Some(true);
+					entry.properties.push(line.clone());
+				},
+				None
+					if trimmed.len() > 2
+						&& trimmed.starts_with(':')
+						&& trimmed.ends_with(':')
+						&& !trimmed.contains(' ') =>
+				{
+					drawer = Some(false);
+					entry.drawers.push(line.clone());
+				},
+				None if checkbox().is_match(line) => {
+					entry.checkboxes.push(line.clone())
+				},
+				None => entry.notes.push(line.clone()),
+			}
+		}
+		while entry
+			.notes
+			.last()
+			.is_some_and(|line| line.trim().is_empty())
+		{
+			entry.notes.pop();
+			entry.trailing_blank = true;
+		}
+		entry
+	}
+
+	fn property(&self, key: &str) -> Option<String> {
+		self.properties.iter().find_map(|line| {
+			let (name, value) = line.trim().strip_prefix(':')?.split_once(':')?;
+			(name.eq_ignore_ascii_case(key) && !value.trim().is_empty())
+				.then(|| value.trim().to_string())
+		})
+	}
+
+	fn set_property(&mut self, key: &str, value: &str) {
+		let line = format!(":{key}: {value}");
+		if self.properties.is_empty() {
+			self.properties = vec![":PROPERTIES:".into(), line, ":END:".into()];
+			return;
+		}
+		let position = self.properties.iter().position(|existing| {
+			existing
+				.trim()
+				.to_uppercase()
+				.starts_with(&format!(":{}:", key.to_uppercase()))
+		});
+		match position {
+			Some(position) => self.properties[position] = line,
+			None => {
+				let end = self.properties.len() - 1;
+				self.properties.insert(end, line);
+			},
+		}
+	}
+
+	fn date(&self, keyword: &str) -> Option<DateTime<Utc>> {
+		let planning = self.planning.as_ref()?;
+		timestamp()
+			.captures_iter(planning)
+			.find(|captures| &captures[1] == keyword)
+			.and_then(|captures| timestamp_date(&captures))
+	}
+
+	/// The checkboxes of the entry, their ids are derived from the id of the
+	/// task they belong to.
+	fn sub_tasks(&self, parent: &str) -> Vec<Task> {
+		let mut occurrences: HashMap<&str, usize> = HashMap::new();
+		self
+			.checkboxes
+			.iter()
+			.filter_map(|line| checkbox().captures(line))
+			.map(|captures| {
+				let title = captures.get(2).map_or("", |title| title.as_str());
+				let occurrence = occurrences.entry(title).or_insert(0);
+				let id = stable_id(&format!("{parent}\n{title}\n{occurrence}"));
+				*occurrence += 1;
+				(id, captures)
+			})
+			.map(|(id, captures)| Task {
+				id,
+				title: captures[2].to_string(),
+				status: if &captures[1] == " " || &captures[1] == "-" {
+					Status::NotStarted
+				} else {
+					Status::Completed
+				},
+				..Default::default()
+			})
+			.collect()
+	}
+
+	fn lines(&self, heading: &Heading) -> Vec<String> {
+		let mut lines = vec![heading.to_string()];
+		lines.extend(self.planning.clone());
+		lines.extend(self.properties.iter().cloned());
+		lines.extend(self.drawers.iter().cloned());
+		lines.extend(self.notes.iter().cloned());
+		lines.extend(self.checkboxes.iter().cloned());
+		if self.trailing_blank {
+			lines.push(String::new());
+		}
+		lines
+	}
+}
+
+/// An Org file kept line by line, so anything that isn't a task is written
+/// back exactly as it was read.
+///
+/// Top level headings without a keyword are lists, TODO and DONE headings
+/// under them are their tasks.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Document {
+	lines: Vec<String>,
+}
+
+impl Document {
+	pub fn parse(text: &str) -> Self {
+		Self {
+			lines: text.lines().map(str::to_string).collect(),
+		}
+	}
+
+	/// The `#+TITLE:` of the file.
+	pub fn title(&self) -> Option<String> {
+		self.lines.iter().find_map(|line| {
+			let (key, value) = line.strip_prefix("#+")?.split_once(':')?;
+			key
+				.eq_ignore_ascii_case("title")
+				.then(|| value.trim().to_string())
+		})
+	}
+
+	fn headings(&self) -> Vec<(usize, Heading)> {
+		self
+			.lines
+			.iter()
+			.enumerate()
+			.filter_map(|(index, line)| {
+				Heading::parse(line).map(|heading| (index, heading))
+			})
+			.collect()
+	}
+
+	/// Titles of the top level headings without a keyword.
+	pub fn lists(&self) -> Vec<String> {
+		self
+			.headings()
+			.into_iter()
+			.filter(|(_, heading)| heading.level == 1 && !heading.is_task())
+			.map(|(_, heading)| heading.title)
+			.collect()
+	}
+
+	/// The lines of the subtree of a list, its heading included.
+	fn list_range(&self, list_id: &str) -> Option<(usize, usize)> {
+		let headings = self.headings();
+		let start = headings
+			.iter()
+			.find(|(_, heading)| {
+				heading.level == 1 && !heading.is_task() && heading.title == list_id
+			})
+			.map(|(index, _)| *index)?;
+		let end = headings
+			.iter()
+			.find(|(index, heading)| *index > start && heading.level == 1)
+			.map_or(self.lines.len(), |(index, _)| *index);
+		Some((start, end))
+	}
+
+	pub fn items(&self) -> Vec<Item> {
+		let headings = self.headings();
+		let mut occurrences: HashMap<String, usize> = HashMap::new();
+		let mut list = INBOX_ID.to_string();
+		let mut items = vec![];
+		for (position, (index, heading)) in headings.iter().enumerate() {
+			if heading.level == 1 {
+				list = if heading.is_task() {
+					INBOX_ID.to_string()
+				} else {
+					heading.title.clone()
+				};
+			}
+			if !heading.is_task() {
+				continue;
+			}
+			let end = headings
+				.get(position + 1)
+				.map_or(self.lines.len(), |(next, _)| *next);
+			let entry = Entry::parse(&self.lines[index + 1..end]);
+			// Without an :ID: the id comes from the heading, leaving out the
+			// keyword so marking the task done doesn't change it.
+			let key = Heading {
+				keyword: None,
+				..heading.clone()
+			}
+			.to_string();
+			let occurrence = occurrences.entry(key.clone()).or_insert(0);
+			let id = entry
+				.property("ID")
+				.unwrap_or_else(|| stable_id(&format!("{list}\n{key}\n{occurrence}")));
+			*occurrence += 1;
+			let completed = heading.keyword.as_deref() == Some("DONE");
+			let completion_date = entry.date("CLOSED").filter(|_| completed);
+			let created_date_time = entry
+				.property("CREATED")
+				.and_then(|created| parse_timestamp(&created))
+				.unwrap_or_default();
+			let task = Task {
+				id: id.clone(),
+				parent: list.clone(),
+				title: heading.title.clone(),
+				status: if completed {
+					Status::Completed
+				} else {
+					Status::NotStarted
+				},
+				priority: match heading.priority {
+					Some('A') => Priority::High,
+					Some('B') => Priority::Normal,
+					_ => Priority::Low,
+				},
+				sub_tasks: entry.sub_tasks(&id),
+				tags: heading.tags.clone(),
+				notes: entry.notes.join("\n"),
+				completion_date,
+				due_date: entry.date("DEADLINE"),
+				reminder_date: entry.date("SCHEDULED"),
+				created_date_time,
+				last_modified_date_time: created_date_time,
+				..Default::default()
+			};
+			items.push(Item {
+				start: *index,
+				end,
+				heading: heading.clone(),
+				task,
+			});
+		}
+		items
+	}
+
+	/// Rewrites the heading, planning and id of an item from a task, the
+	/// body is only rewritten when its notes or sub-tasks changed. An entry
+	/// without a creation time gets one.
+	pub fn replace(&mut self, item: &Item, task: &Task) {
+		let mut entry =
+			Entry::parse(self.lines.get(item.start + 1..item.end).unwrap_or(&[]));
+		let mut heading = item.heading.clone();
+		heading.keyword = Some(
+			if task.status == Status::Completed {
+				"DONE"
+			} else {
+				"TODO"
+			}
+			.into(),
+		);
+		heading.priority = match task.priority {
+			Priority::High => Some('A'),
+			Priority::Normal => Some('B'),
+			Priority::Low => heading.priority.filter(|priority| *priority > 'B'),
+		};
+		heading.title = task.title.clone();
+		heading.tags = task.tags.clone();
+
+		entry.planning = planning(task, entry.planning.as_deref());
+		entry.set_property("ID", &task.id);
+		if entry.property("CREATED").is_none() {
+			let created = if task.created_date_time == DateTime::<Utc>::default() {
+				Utc::now()
+			} else {
+				task.created_date_time
+			};
+			entry.set_property("CREATED", &org_timestamp(created, false));
+		}
+		if task.notes != item.task.notes {
+			entry.notes = task.notes.lines().map(str::to_string).collect();
+		}
+		if task.sub_tasks != item.task.sub_tasks {
+			entry.checkboxes = task
+				.sub_tasks
+				.iter()
+				.map(|sub_task| {
+					let mark = if sub_task.status == Status::Completed {
+						"X"
+					} else {
+						" "
+					};
+					format!("- [{mark}] {}", sub_task.title)
+				})
+				.collect();
+		}
+		self
+			.lines
+			.splice(item.start..item.end, entry.lines(&heading));
+	}
+
+	pub fn remove(&mut self, item: &Item) {
+		self.lines.drain(item.start..item.end);
+	}
+
+	/// Adds a task at the end of a list, tasks of the inbox go to the end of
+	/// the file as top level headings.
+	pub fn append(&mut self, task: &Task) {
+		let (level, index) = match self.list_range(&task.parent) {
+			Some((_, end)) => (2, end),
+			None => (1, self.lines.len()),
+		};
+		let item = Item {
+			start: index,
+			end: index,
+			heading: Heading {
+				level,
+				..Default::default()
+			},
+			task: Task::default(),
+		};
+		self.replace(&item, task);
+	}
+
+	pub fn add_list(&mut self, name: &str) {
+		if self
+			.lines
+			.last()
+			.is_some_and(|line| !line.trim().is_empty())
+		{
+			self.lines.push(String::new());
+		}
+		self.lines.push(format!("* {name}"));
+	}
+
+	/// Renames a list, its tags are kept.
+	pub fn rename_list(&mut self, list_id: &str, name: &str) -> bool {
+		let Some((start, _)) = self.list_range(list_id) else {
+			return false;
+		};
+		if let Some(mut heading) = Heading::parse(&self.lines[start]) {
+			heading.title = name.to_string();
+			self.lines[start] = heading.to_string();
+		}
+		true
+	}
+
+	/// Removes a list together with everything under it.
+	pub fn remove_list(&mut self, list_id: &str) -> bool {
+		let Some((start, end)) = self.list_range(list_id) else {
+			return false;
+		};
+		self.lines.drain(start..end);
+		true
+	}
+}
+
+impl Display for Document {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for line in &self.lines {
+			writeln!(f, "{line}")?;
+		}
+		Ok(())
+	}
+}
+
+fn org_timestamp(date: DateTime<Utc>, active: bool) -> String {
+	let date = date.with_timezone(&Local);
+	let format = if date.hour() == 0 && date.minute() == 0 {
+		"%Y-%m-%d %a"
+	} else {
+		"%Y-%m-%d %a %H:%M"
+	};
+	let (open, close) = if active { ('<', '>') } else { ('[', ']') };
+	format!("{open}{}{close}", date.format(format))
+}
+
+/// The date and time of a timestamp on its own, like the `:CREATED:`
+/// property.
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+	let mut words = value
+		.trim()
+		.trim_start_matches(['[', '<'])
+		.trim_end_matches([']', '>'])
+		.split_whitespace();
+	let date = NaiveDate::parse_from_str(words.next()?, "%Y-%m-%d").ok()?;
+	let time = words
+		.find_map(|word| NaiveTime::parse_from_str(word, "%H:%M").ok())
+		.unwrap_or(NaiveTime::MIN);
+	Local
+		.from_local_datetime(&NaiveDateTime::new(date, time))
+		.earliest()
+		.map(|date| date.with_timezone(&Utc))
+}
+
+/// The date and time of a timestamp matched by [`timestamp`].
+fn timestamp_date(captures: &Captures) -> Option<DateTime<Utc>> {
+	let date = NaiveDate::parse_from_str(&captures[3], "%Y-%m-%d").ok()?;
+	let time = captures
+		.get(4)
+		.and_then(|time| NaiveTime::parse_from_str(time.as_str(), "%H:%M").ok())
+		.unwrap_or(NaiveTime::MIN);
+	Local
+		.from_local_datetime(&NaiveDateTime::new(date, time))
+		.earliest()
+		.map(|date| date.with_timezone(&Utc))
+}
+
+/// Moves a timestamp to another date, its brackets and whatever follows the
+/// time, like repeaters and warning periods, are kept. The end of a time
+/// range moves along with its start.
+fn move_timestamp(captures: &Captures, date: DateTime<Utc>) -> String {
+	let date = date.with_timezone(&Local);
+	let start = captures
+		.get(4)
+		.and_then(|time| NaiveTime::parse_from_str(time.as_str(), "%H:%M").ok());
+	let mut rest = captures[5].to_string();
+	let end = time_range_end().captures(&rest).and_then(|end| {
+		let time = NaiveTime::parse_from_str(&end[1], "%H:%M").ok()?;
+		Some((end[0].len(), time))
+	});
+	let time = if date.hour() == 0 && date.minute() == 0 {
+		// The end of a time range makes no sense without its start.
+		if let (Some(_), Some((length, _))) = (start, end) {
+			rest.replace_range(..length, "");
+		}
+		String::new()
+	} else {
+		if let (Some(start), Some((length, end))) = (start, end) {
+			let end = date.time() + (end - start);
+			rest.replace_range(..length, &end.format("-%H:%M").to_string());
+		}
+		date.format(" %H:%M").to_string()
+	};
+	format!(
+		"{}: {}{}{time}{rest}{}",
+		&captures[1],
+		&captures[2],
+		date.format("%Y-%m-%d %a"),
+		&captures[6]
+	)
+}
+
+/// The planning line of a task. Timestamps are updated in the line that was
+/// read, those whose date didn't change are kept exactly as written.
+fn planning(task: &Task, original: Option<&str>) -> Option<String> {
+	let completion_date = (task.status == Status::Completed)
+		.then(|| task.completion_date.unwrap_or_else(Utc::now));
+	let dates = [
+		("CLOSED", completion_date, false),
+		("DEADLINE", task.due_date, true),
+		("SCHEDULED", task.reminder_date, true),
+	];
+	let mut planning = original.unwrap_or_default().to_string();
+	for (keyword, date, active) in dates {
+		let existing = timestamp()
+			.captures_iter(&planning)
+			.find(|captures| &captures[1] == keyword)
+			.map(|captures| {
+				let range = captures.get(0).map_or(0..0, |all| all.range());
+				let text = match date {
+					Some(date) if timestamp_date(&captures) != Some(date) => {
+						Some(move_timestamp(&captures, date))
+					},
+					Some(_) => Some(captures[0].to_string()),
+					None => None,
+				};
+				(range, text)
+			});
+		match (existing, date) {
+			(Some((range, Some(text))), _) => planning.replace_range(range, &text),
+			(Some((range, None)), _) => {
+				let spaces = planning[range.end..].len()
+					- planning[range.end..].trim_start().len();
+				planning.replace_range(range.start..range.end + spaces, "");
+			},
+			(None, Some(date)) => {
+				if !planning.trim().is_empty() {
+					planning.push(' ');
+				}
+				planning
+					.push_str(&format!("{keyword}: {}", org_timestamp(date, active)));
+			},
+			(None, None) => {},
+		}
+	}
+	let planning = planning.trim_end().to_string();
+	(!planning.trim().is_empty()).then_some(planning)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const FILE: &str = "\
+#+TITLE: Plans
+Some words before the first heading.
+
+* Home :house:
+** TODO [#A] Paint the fence :outside:
+DEADLINE: <2024-05-01 Wed>
+:PROPERTIES:
+:ID: fence
+:CREATED: [2024-04-01 Mon 09:30]
+:EFFORT: 2:00
+:END:
+:LOGBOOK:
+CLOCK: [2024-04-02 Tue 10:00]--[2024-04-02 Tue 11:00] =>  1:00
+:END:
+Buy white paint.
+- [ ] Sand it
+- [X] Wash it
+
+** DONE Fix the door
+CLOSED: [2024-04-20 Sat 18:00]
+** Notes without a keyword
+* TODO Call the bank
+";
+
+	#[test]
+	fn parses_tasks() {
+		let document = Document::parse(FILE);
+		assert_eq!(document.title().as_deref(), Some("Plans"));
+		assert_eq!(document.lists(), vec!["Home"]);
+
+		let items = document.items();
+		assert_eq!(items.len(), 3);
+
+		let fence = &items[0].task;
+		assert_eq!(fence.id, "fence");
+		assert_eq!(fence.parent, "Home");
+		assert_eq!(fence.title, "Paint the fence");
+		assert_eq!(fence.priority, Priority::High);
+		assert_eq!(fence.tags, vec!["outside"]);
+		assert_eq!(fence.notes, "Buy white paint.");
+		assert_eq!(fence.due_date, parse_timestamp("<2024-05-01 Wed>"));
+		assert_eq!(
+			fence.created_date_time,
+			parse_timestamp("[2024-04-01 Mon 09:30]").unwrap()
+		);
+		assert_eq!(fence.sub_tasks.len(), 2);
+		assert_eq!(fence.sub_tasks[0].status, Status::NotStarted);
+		assert_eq!(fence.sub_tasks[1].status, Status::Completed);
+
+		let door = &items[1].task;
+		assert_eq!(door.status, Status::Completed);
+		assert_eq!(
+			door.completion_date,
+			parse_timestamp("[2024-04-20 Sat 18:00]")
+		);
+		assert_eq!(door.created_date_time, DateTime::<Utc>::default());
+
+		assert_eq!(items[2].task.parent, INBOX_ID);
+		assert_eq!(items[2].task.title, "Call the bank");
+	}
+
+	#[test]
+	fn keeps_unknown_content_on_write() {
+		let mut document = Document::parse(FILE);
+		let item = document.items().remove(0);
+		let mut task = item.task.clone();
+		task.title = "Paint the gate".to_string();
+		document.replace(&item, &task);
+
+		let written = document.to_string();
+		for line in [
+			"Some words before the first heading.",
+			":EFFORT: 2:00",
+			"CLOCK: [2024-04-02 Tue 10:00]--[2024-04-02 Tue 11:00] =>  1:00",
+			"DEADLINE: <2024-05-01 Wed>",
+			":CREATED: [2024-04-01 Mon 09:30]",
+			"- [X] Wash it",
+			"** Notes without a keyword",
+		] {
+			assert!(written.contains(line), "{line} was lost");
+		}
+		assert!(written.contains("** TODO [#A] Paint the gate :outside:"));
+	}
+
+	#[test]
+	fn keeps_ids_when_a_task_is_done() {
+		let mut document = Document::parse(FILE);
+		let item = document.items().remove(2);
+		let mut task = item.task.clone();
+		task.status = Status::Completed;
+		document.replace(&item, &task);
+
+		let read = document.items().remove(2).task;
+		assert_eq!(read.id, item.task.id);
+		assert_eq!(read.status, Status::Completed);
+		assert_ne!(read.created_date_time, DateTime::<Utc>::default());
+	}
+
+	#[test]
+	fn scopes_sub_task_ids_to_their_parent() {
+		let document = Document::parse(
+			"* TODO First\n- [ ] Same step\n* TODO Second\n- [ ] Same step\n",
+		);
+		let items = document.items();
+		assert_ne!(items[0].task.sub_tasks[0].id, items[1].task.sub_tasks[0].id);
+	}
+}
@@ -0,0 +1,274 @@
+use std::{
+	path::{Path, PathBuf},
+	pin::Pin,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+	models::{
+		list::{List, Sharing},
+		task::Task,
+	},
+	service::Service,
+	services::files::{
+		read_config, read_file, remove_config, write_config, write_file,
+	},
+	task_service::TodoProvider,
+	Error, Result,
+};
+
+use super::document::{Document, INBOX_ID};
+
+const CONFIG_KEY: &str = "org";
+
+/// Settings of the Org provider.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct OrgConfig {
+	pub path: PathBuf,
+}
+
+/// Tasks of an Org file, its top level headings are the lists.
+#[derive(Debug, Clone)]
+pub struct OrgService {
+	config: Option<OrgConfig>,
+}
+
+impl OrgService {
+	pub(crate) fn new() -> Self {
+		Self {
+			config: read_config(CONFIG_KEY),
+		}
+	}
+
+	/// The Org file chosen by the user.
+	pub(crate) fn path() -> Option<PathBuf> {
+		read_config::<OrgConfig>(CONFIG_KEY).map(|config| config.path)
+	}
+
+	pub(crate) fn set_path(path: PathBuf) -> anyhow::Result<()> {
+		write_config(CONFIG_KEY, OrgConfig { path })
+	}
+
+	pub(crate) fn clear_path() -> anyhow::Result<()> {
+		remove_config(CONFIG_KEY)
+	}
+
+	pub(crate) fn watched_paths() -> Vec<PathBuf> {
+		Self::path().into_iter().collect()
+	}
+
+	fn file(&self) -> anyhow::Result<&Path> {
+		self
+			.config
+			.as_ref()
+			.map(|config| config.path.as_path())
+			.context("No Org file has been chosen.")
+	}
+
+	fn read(&self) -> anyhow::Result<Document> {
+		Ok(Document::parse(&read_file(self.file()?)?))
+	}
+
+	fn write(&self, document: &Document) -> anyhow::Result<()> {
+		write_file(self.file()?, &document.to_string())
+	}
+
+	fn list(&self, list_id: &str, document: &Document) -> List {
+		let name = if list_id == INBOX_ID {
+			document.title().unwrap_or_else(|| {
+				self
+					.file()
+					.ok()
+					.and_then(Path::file_stem)
+					.map(|stem| stem.to_string_lossy().to_string())
+					.unwrap_or_default()
+			})
+		} else {
+			list_id.to_string()
+		};
+		List {
+			id: list_id.to_string(),
+			name,
+			description: String::new(),
+			icon: None,
+			service: Service::Org,
+			sharing: Sharing::Private,
+			account: None,
+		}
+	}
+}
+
+#[async_trait]
+impl TodoProvider for OrgService {
+	async fn handle_uri_params(&mut self, _uri: Url) -> Result<()> {
+		Ok(())
+	}
+
+	async fn login(&self) -> Result<()> {
+		Ok(())
+	}
+
+	async fn logout(&self) -> Result<()> {
+		Ok(Self::clear_path()?)
+	}
+
+	fn available(&self) -> bool {
+		self.config.is_some()
+	}
+
+	fn stream_support(&self) -> bool {
+		false
+	}
+
+	async fn read_tasks(&mut self) -> Result<Vec<Task>> {
+		Ok(
+			self
+				.read()?
+				.items()
+				.into_iter()
+				.map(|item| item.task)
+				.collect(),
+		)
+	}
+
+	async fn get_tasks(
+		&mut self,
+		_parent_list: String,
+	) -> Result<Pin<Box<dyn Stream<Item = Result<Task>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
+	}
+
+	async fn read_tasks_from_list(
+		&mut self,
+		parent_list: String,
+	) -> Result<Vec<Task>> {
+		Ok(
+			self
+				.read_tasks()
+				.await?
+				.into_iter()
+				.filter(|task| task.parent == parent_list)
+				.collect(),
+		)
+	}
+
+	async fn read_task(
+		&mut self,
+		_task_list_id: String,
+		task_id: String,
+	) -> Result<Task> {
+		self
+			.read_tasks()
+			.await?
+			.into_iter()
+			.find(|task| task.id == task_id)
+			.ok_or_else(|| Error::NotFound(format!("Task {task_id} not found.")))
+	}
+
+	async fn create_task(&mut self, task: Task) -> Result<()> {
+		let mut document = self.read()?;
+		document.append(&task);
+		Ok(self.write(&document)?)
+	}
+
+	async fn update_task(&mut self, task: Task) -> Result<Task> {
+		let mut document = self.read()?;
+		let item = document
+			.items()
+			.into_iter()
+			.find(|item| item.task.id == task.id)
+			.ok_or_else(|| Error::NotFound(format!("Task {} not found.", task.id)))?;
+		if item.task.parent != task.parent {
+			document.remove(&item);
+			document.append(&task);
+		} else {
+			document.replace(&item, &task);
+		}
+		self.write(&document)?;
+		Ok(task)
+	}
+
+	async fn delete_task(
+		&mut self,
+		_list_id: String,
+		task_id: String,
+	) -> Result<()> {
+		let mut document = self.read()?;
+		let item = document
+			.items()
+			.into_iter()
+			.find(|item| item.task.id == task_id)
+			.ok_or_else(|| Error::NotFound(format!("Task {task_id} not found.")))?;
+		document.remove(&item);
+		Ok(self.write(&document)?)
+	}
+
+	async fn read_lists(&mut self) -> Result<Vec<List>> {
+		if self.config.is_none() {
+			return Ok(vec![]);
+		}
+		let document = self.read()?;
+		Ok(
+			std::iter::once(INBOX_ID.to_string())
+				.chain(document.lists())
+				.map(|list_id| self.list(&list_id, &document))
+				.collect(),
+		)
+	}
+
+	async fn get_lists(
+		&mut self,
+	) -> Result<Pin<Box<dyn Stream<Item = Result<List>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
+	}
+
+	async fn read_list(&mut self, id: String) -> Result<List> {
+		self
+			.read_lists()
+			.await?
+			.into_iter()
+			.find(|list| list.id == id)
+			.ok_or_else(|| Error::NotFound(format!("List {id} not found.")))
+	}
+
+	async fn create_list(&mut self, list: List) -> Result<List> {
+		let name = list.name.trim();
+		if name.is_empty() {
+			return Err(Error::InvalidData("A list needs a name.".to_string()));
+		}
+		let mut document = self.read()?;
+		if !document.lists().iter().any(|existing| existing == name) {
+			document.add_list(name);
+			self.write(&document)?;
+		}
+		Ok(self.list(name, &document))
+	}
+
+	async fn update_list(&mut self, list: List) -> Result<()> {
+		let mut document = self.read()?;
+		if !document.rename_list(&list.id, list.name.trim()) {
+			return Err(Error::Unsupported(format!(
+				"List {} can't be renamed.",
+				list.id
+			)));
+		}
+		Ok(self.write(&document)?)
+	}
+
+	async fn delete_list(&mut self, id: String) -> Result<()> {
+		let mut document = self.read()?;
+		if !document.remove_list(&id) {
+			return Err(Error::Unsupported(format!("List {id} can't be deleted.")));
+		}
+		Ok(self.write(&document)?)
+	}
+}
@@ -0,0 +1,182 @@
+use std::{process::Stdio, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::{
+	io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+	process::{Child, ChildStdin, ChildStdout, Command},
+};
+
+use super::registry::PluginManifest;
+
+/// How long a plugin may take to answer a request.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Deserialize)]
+struct Response {
+	id: Option<u64>,
+	#[serde(default)]
+	result: Value,
+	error: Option<ResponseError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseError {
+	code: i64,
+	message: String,
+}
+
+/// A running plugin process.
+#[derive(Debug)]
+pub(crate) struct Client {
+	_child: Child,
+	stdin: ChildStdin,
+	stdout: Lines<BufReader<ChildStdout>>,
+	next_id: u64,
+}
+
+impl Client {
+	pub fn spawn(manifest: &PluginManifest) -> Result<Self> {
+		let mut child = Command::new(&manifest.command)
+			.args(&manifest.args)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.kill_on_drop(true)
+			.spawn()
+			.with_context(|| format!("Failed to start plugin {}.", manifest.id))?;
+		let stdin = child.stdin.take().context("Failed to open plugin stdin.")?;
+		let stdout = child
+			.stdout
+			.take()
+			.context("Failed to open plugin stdout.")?;
+		Ok(Self {
+			_child: child,
+			stdin,
+			stdout: BufReader::new(stdout).lines(),
+			next_id: 1,
+		})
+	}
+
+	/// Sends a request and waits for its response, notifications sent by the
+	/// plugin in between are ignored.
+	pub async fn call(&mut self, method: &str, params: Value) -> Result<Value> {
+		tokio::time::timeout(TIMEOUT, self.request(method, params))
+			.await
+			.with_context(|| format!("Plugin took too long to answer {method}."))?
+	}
+
+	async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+		let id = self.next_id;
+		self.next_id += 1;
+		let mut message = serde_json::to_string(&json!({
+			"jsonrpc": "2.0",
+			"id": id,
+			"method": method,
+			"params": params,
+		}))?;
+		message.push('\n');
+		self.stdin.write_all(message.as_bytes()).await?;
+		self.stdin.flush().await?;
+
+		while let Some(line) = self.stdout.next_line().await? {
+			if line.trim().is_empty() {
+				continue;
+			}
+			let response: Response = serde_json::from_str(&line)
+				.with_context(|| format!("Invalid message from plugin: {line}"))?;
+			if response.id != Some(id) {
+				continue;
+			}
+			if let Some(error) = response.error {
+				bail!("{method} failed ({}): {}", error.code, error.message);
+			}
+			return Ok(response.result);
+		}
+		bail!("Plugin exited while answering {method}.")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A plugin running `script` with `sh`.
+	fn plugin(script: &str) -> Client {
+		Client::spawn(&PluginManifest {
+			id: "test".into(),
+			name: "Test".into(),
+			command: "sh".into(),
+			args: vec!["-c".into(), script.into()],
+		})
+		.unwrap()
+	}
+
+	#[tokio::test]
+	async fn sends_one_request_per_line() {
+		let mut client = plugin(
+			r#"read line; printf '{"jsonrpc":"2.0","id":1,"result":%s}\n' "$line""#,
+		);
+		let request = client
+			.call("read_list", json!({ "id": "a" }))
+			.await
+			.unwrap();
+		assert_eq!(
+			request,
+			json!({
+				"jsonrpc": "2.0",
+				"id": 1,
+				"method": "read_list",
+				"params": { "id": "a" },
+			})
+		);
+	}
+
+	#[tokio::test]
+	async fn skips_notifications_and_other_responses() {
+		let mut client = plugin(
+			r#"read line
+echo '{"jsonrpc":"2.0","method":"progress","params":{}}'
+echo
+echo '{"jsonrpc":"2.0","id":7,"result":"stale"}'
+echo '{"jsonrpc":"2.0","id":1,"result":"answer"}'"#,
+		);
+		let result = client.call("read_lists", json!({})).await.unwrap();
+		assert_eq!(result, json!("answer"));
+	}
+
+	#[tokio::test]
+	async fn numbers_requests() {
+		let mut client = plugin(
+			r#"read line; echo '{"jsonrpc":"2.0","id":1,"result":1}'
+read line; echo '{"jsonrpc":"2.0","id":2,"result":2}'"#,
+		);
+		assert_eq!(client.call("a", json!({})).await.unwrap(), json!(1));
+		assert_eq!(client.call("b", json!({})).await.unwrap(), json!(2));
+	}
+
+	#[tokio::test]
+	async fn returns_errors_of_the_plugin() {
+		let mut client = plugin(
+			r#"read line; echo '{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"Unknown method"}}'"#,
+		);
+		let error = client.call("sync", json!({})).await.unwrap_err();
+		assert_eq!(error.to_string(), "sync failed (-32601): Unknown method");
+	}
+
+	#[tokio::test]
+	async fn fails_when_the_plugin_exits() {
+		let mut client = plugin("read line");
+		let error = client.call("read_lists", json!({})).await.unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"Plugin exited while answering read_lists."
+		);
+	}
+
+	#[tokio::test]
+	async fn refuses_invalid_messages() {
+		let mut client = plugin("read line; echo 'not json'");
+		assert!(client.call("read_lists", json!({})).await.is_err());
+	}
+}
@@ -0,0 +1,20 @@
+//! Providers implemented by other programs.
+//!
+//! A plugin is described by a JSON manifest in the `plugins` configuration
+//! directory, or registered at runtime with [`registry::PluginRegistry`].
+//! Done starts the plugin's command when it's first used and talks to it with
+//! JSON-RPC 2.0 over stdio, one message per line.
+//!
+//! Every [`crate::task_service::TodoProvider`] operation is a method of the
+//! same name, with named parameters: `read_tasks`, `read_tasks_from_list`
+//! (`list_id`), `read_task` (`list_id`, `task_id`), `create_task` (`task`),
+//! `update_task` (`task`), `delete_task` (`list_id`, `task_id`),
+//! `read_lists`, `read_list` (`id`), `create_list` (`list`), `update_list`
+//! (`list`), `delete_list` (`id`), `handle_uri_params` (`uri`), `login` and
+//! `logout`. Tasks and lists use the serde representation of
+//! [`crate::models::task::Task`] and [`crate::models::list::List`], the
+//! `service` of a list can be left out.
+
+pub(crate) mod client;
+pub mod registry;
+pub(crate) mod service;
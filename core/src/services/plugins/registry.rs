@@ -0,0 +1,240 @@
+use std::{
+	collections::BTreeMap,
+	path::PathBuf,
+	sync::{Arc, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use anyhow::{anyhow, Context, Result};
+use libset::Config;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::{
+	service::{Service, Services},
+	services::files::{files_with_extension, stable_hash},
+};
+
+use super::client::Client;
+
+/// Identifies a plugin inside [`Service::Plugin`], derived from the id in
+/// its manifest so it stays the same between runs.
+#[derive(
+	Debug,
+	Default,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Hash,
+	Serialize,
+	Deserialize,
+)]
+pub struct PluginId(u64);
+
+impl PluginId {
+	pub fn new(id: &str) -> Self {
+		Self(stable_hash(id))
+	}
+}
+
+/// Describes how to start a plugin.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginManifest {
+	/// Unique and stable, e.g. `com.example.tracker`.
+	pub id: String,
+	/// Shown as the name of the service.
+	pub name: String,
+	pub command: PathBuf,
+	#[serde(default)]
+	pub args: Vec<String>,
+}
+
+#[derive(Debug)]
+struct Plugin {
+	manifest: PluginManifest,
+	client: Arc<Mutex<Option<Client>>>,
+}
+
+static PLUGINS: OnceLock<RwLock<BTreeMap<PluginId, Plugin>>> = OnceLock::new();
+
+type Plugins = BTreeMap<PluginId, Plugin>;
+
+fn plugins() -> &'static RwLock<Plugins> {
+	PLUGINS.get_or_init(Default::default)
+}
+
+/// The registered plugins, an error if a thread panicked while changing them.
+fn read_plugins() -> Result<RwLockReadGuard<'static, Plugins>> {
+	plugins()
+		.read()
+		.map_err(|_| anyhow!("The plugin registry is unusable."))
+}
+
+fn write_plugins() -> Result<RwLockWriteGuard<'static, Plugins>> {
+	plugins()
+		.write()
+		.map_err(|_| anyhow!("The plugin registry is unusable."))
+}
+
+/// The providers registered at runtime.
+pub struct PluginRegistry;
+
+impl PluginRegistry {
+	/// The directory plugin manifests are read from.
+	pub fn directory() -> Result<PathBuf> {
+		let path = Config::new(&Services::app_id(), 1, Some("plugins"))?
+			.path("manifest", libset::FileType::Json)?;
+		Ok(
+			path
+				.parent()
+				.context("Invalid plugins directory.")?
+				.to_path_buf(),
+		)
+	}
+
+	/// Registers every manifest in [`PluginRegistry::directory`], invalid
+	/// manifests are skipped.
+	pub fn load() -> Result<()> {
+		for path in files_with_extension(&Self::directory()?, "json") {
+			let manifest = std::fs::read_to_string(&path)
+				.map_err(anyhow::Error::from)
+				.and_then(|json| Ok(serde_json::from_str::<PluginManifest>(&json)?));
+			match manifest {
+				Ok(manifest) => {
+					Self::register(manifest)?;
+				},
+				Err(err) => {
+					tracing::error!("Failed to load plugin {}: {err}", path.display())
+				},
+			}
+		}
+		Ok(())
+	}
+
+	/// Adds a provider, replacing any plugin with the same id, and returns the
+	/// service it's reachable through.
+	pub fn register(manifest: PluginManifest) -> Result<Service> {
+		let id = PluginId::new(&manifest.id);
+		write_plugins()?.insert(
+			id,
+			Plugin {
+				manifest,
+				client: Default::default(),
+			},
+		);
+		Ok(Service::Plugin(id))
+	}
+
+	/// Removes a provider, its process is stopped.
+	pub fn unregister(id: &str) -> Result<()> {
+		write_plugins()?.remove(&PluginId::new(id));
+		Ok(())
+	}
+
+	pub fn services() -> Result<Vec<Service>> {
+		Ok(
+			read_plugins()?
+				.keys()
+				.map(|id| Service::Plugin(*id))
+				.collect(),
+		)
+	}
+
+	pub fn manifest(id: PluginId) -> Result<Option<PluginManifest>> {
+		Ok(
+			read_plugins()?
+				.get(&id)
+				.map(|plugin| plugin.manifest.clone()),
+		)
+	}
+
+	pub(crate) fn client(
+		id: PluginId,
+	) -> Result<Option<Arc<Mutex<Option<Client>>>>> {
+		Ok(read_plugins()?.get(&id).map(|plugin| plugin.client.clone()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn manifest(id: &str, name: &str) -> PluginManifest {
+		PluginManifest {
+			id: id.into(),
+			name: name.into(),
+			command: "true".into(),
+			args: vec![],
+		}
+	}
+
+	#[test]
+	fn ids_are_stable() {
+		assert_eq!(
+			PluginId::new("com.example.tracker"),
+			PluginId::new("com.example.tracker")
+		);
+		assert_ne!(
+			PluginId::new("com.example.tracker"),
+			PluginId::new("com.example.other")
+		);
+	}
+
+	#[test]
+	fn registers_and_unregisters_plugins() {
+		let service =
+			PluginRegistry::register(manifest("test.registers", "Tracker")).unwrap();
+		let Service::Plugin(id) = service else {
+			panic!("Expected a plugin service");
+		};
+		assert_eq!(id, PluginId::new("test.registers"));
+		assert!(PluginRegistry::services().unwrap().contains(&service));
+		assert_eq!(
+			PluginRegistry::manifest(id)
+				.unwrap()
+				.map(|manifest| manifest.name),
+			Some("Tracker".to_string())
+		);
+		assert!(PluginRegistry::client(id).unwrap().is_some());
+
+		PluginRegistry::unregister("test.registers").unwrap();
+		assert!(!PluginRegistry::services().unwrap().contains(&service));
+		assert!(PluginRegistry::manifest(id).unwrap().is_none());
+	}
+
+	#[test]
+	fn registering_again_replaces_the_plugin() {
+		PluginRegistry::register(manifest("test.replaces", "Old")).unwrap();
+		let service =
+			PluginRegistry::register(manifest("test.replaces", "New")).unwrap();
+		let Service::Plugin(id) = service else {
+			panic!("Expected a plugin service");
+		};
+		assert_eq!(
+			PluginRegistry::manifest(id)
+				.unwrap()
+				.map(|manifest| manifest.name),
+			Some("New".to_string())
+		);
+		assert_eq!(
+			PluginRegistry::services()
+				.unwrap()
+				.iter()
+				.filter(|registered| **registered == service)
+				.count(),
+			1
+		);
+		PluginRegistry::unregister("test.replaces").unwrap();
+	}
+
+	#[test]
+	fn manifests_default_to_no_arguments() {
+		let manifest: PluginManifest = serde_json::from_str(
+			r#"{"id":"com.example.tracker","name":"Tracker","command":"/usr/bin/tracker"}"#,
+		)
+		.unwrap();
+		assert!(manifest.args.is_empty());
+	}
+}
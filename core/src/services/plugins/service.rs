@@ -0,0 +1,220 @@
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use futures::Stream;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::{
+	models::{list::List, task::Task},
+	service::Service,
+	task_service::TodoProvider,
+	Error, Result,
+};
+
+use super::{
+	client::Client,
+	registry::{PluginId, PluginRegistry},
+};
+
+/// A provider implemented by a plugin process.
+#[derive(Debug, Clone)]
+pub struct PluginService {
+	id: PluginId,
+}
+
+impl PluginService {
+	pub(crate) fn new(id: PluginId) -> Self {
+		Self { id }
+	}
+
+	/// Calls a method of the plugin, starting it first if needed. A plugin
+	/// that fails to answer is restarted on the next call.
+	async fn call<T: DeserializeOwned>(
+		&self,
+		method: &str,
+		params: Value,
+	) -> Result<T> {
+		let (Some(manifest), Some(client)) = (
+			PluginRegistry::manifest(self.id)?,
+			PluginRegistry::client(self.id)?,
+		) else {
+			return Err(Error::Unsupported("Plugin is not registered.".to_string()));
+		};
+		let mut client = client.lock().await;
+		if client.is_none() {
+			*client = Some(Client::spawn(&manifest)?);
+		}
+		let response = client.as_mut().unwrap().call(method, params).await;
+		if response.is_err() {
+			*client = None;
+		}
+		serde_json::from_value(response?).map_err(|err| {
+			Error::InvalidData(format!("Invalid result for {method}: {err}"))
+		})
+	}
+
+	/// Fills in the service of lists sent by the plugin.
+	fn list(&self, mut value: Value) -> Result<List> {
+		if let Some(object) = value.as_object_mut() {
+			object.insert("service".into(), json!(Service::Plugin(self.id)));
+		}
+		Ok(serde_json::from_value(value)?)
+	}
+}
+
+#[async_trait]
+impl TodoProvider for PluginService {
+	async fn handle_uri_params(&mut self, uri: Url) -> Result<()> {
+		self
+			.call::<Value>("handle_uri_params", json!({ "uri": uri }))
+			.await?;
+		Ok(())
+	}
+
+	async fn login(&self) -> Result<()> {
+		self.call::<Value>("login", json!({})).await?;
+		Ok(())
+	}
+
+	async fn logout(&self) -> Result<()> {
+		self.call::<Value>("logout", json!({})).await?;
+		Ok(())
+	}
+
+	fn available(&self) -> bool {
+		PluginRegistry::manifest(self.id).is_ok_and(|manifest| manifest.is_some())
+	}
+
+	fn stream_support(&self) -> bool {
+		false
+	}
+
+	async fn read_tasks(&mut self) -> Result<Vec<Task>> {
+		self.call("read_tasks", json!({})).await
+	}
+
+	async fn get_tasks(
+		&mut self,
+		_parent_list: String,
+	) -> Result<Pin<Box<dyn Stream<Item = Result<Task>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
+	}
+
+	async fn read_tasks_from_list(
+		&mut self,
+		parent_list: String,
+	) -> Result<Vec<Task>> {
+		self
+			.call("read_tasks_from_list", json!({ "list_id": parent_list }))
+			.await
+	}
+
+	async fn read_task(
+		&mut self,
+		task_list_id: String,
+		task_id: String,
+	) -> Result<Task> {
+		self
+			.call(
+				"read_task",
+				json!({ "list_id": task_list_id, "task_id": task_id }),
+			)
+			.await
+	}
+
+	async fn create_task(&mut self, task: Task) -> Result<()> {
+		self
+			.call::<Value>("create_task", json!({ "task": task }))
+			.await?;
+		Ok(())
+	}
+
+	async fn update_task(&mut self, task: Task) -> Result<Task> {
+		self.call("update_task", json!({ "task": task })).await
+	}
+
+	async fn delete_task(
+		&mut self,
+		list_id: String,
+		task_id: String,
+	) -> Result<()> {
+		self
+			.call::<Value>(
+				"delete_task",
+				json!({ "list_id": list_id, "task_id": task_id }),
+			)
+			.await?;
+		Ok(())
+	}
+
+	async fn read_lists(&mut self) -> Result<Vec<List>> {
+		let lists: Vec<Value> = self.call("read_lists", json!({})).await?;
+		lists.into_iter().map(|list| self.list(list)).collect()
+	}
+
+	async fn get_lists(
+		&mut self,
+	) -> Result<Pin<Box<dyn Stream<Item = Result<List>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
+	}
+
+	async fn read_list(&mut self, id: String) -> Result<List> {
+		let list = self.call("read_list", json!({ "id": id })).await?;
+		self.list(list)
+	}
+
+	async fn create_list(&mut self, list: List) -> Result<List> {
+		let list = self.call("create_list", json!({ "list": list })).await?;
+		self.list(list)
+	}
+
+	async fn update_list(&mut self, list: List) -> Result<()> {
+		self
+			.call::<Value>("update_list", json!({ "list": list }))
+			.await?;
+		Ok(())
+	}
+
+	async fn delete_list(&mut self, id: String) -> Result<()> {
+		self
+			.call::<Value>("delete_list", json!({ "id": id }))
+			.await?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::registry::PluginManifest;
+
+	use super::*;
+
+	#[tokio::test]
+	async fn login_returns_the_error_of_the_plugin() {
+		let service = PluginRegistry::register(PluginManifest {
+			id: "test.login".into(),
+			name: "Login".into(),
+			command: "sh".into(),
+			args: vec![
+				"-c".into(),
+				r#"read line; echo '{"jsonrpc":"2.0","id":1,"error":{"code":1,"message":"No browser"}}'"#.into(),
+			],
+		})
+		.unwrap();
+		let Service::Plugin(id) = service else {
+			panic!("Expected a plugin service");
+		};
+		let provider = PluginService::new(id);
+		assert!(provider.available());
+		let error = provider.login().await.unwrap_err();
+		assert!(error.to_string().contains("No browser"));
+		PluginRegistry::unregister("test.login").unwrap();
+		assert!(!provider.available());
+	}
+}
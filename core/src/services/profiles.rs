@@ -0,0 +1,122 @@
+use anyhow::{bail, Result};
+use libset::Config;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::profile::Profile,
+	service::{Service, Services},
+	services::{
+		accounts::Accounts, local::encryption::forget_key,
+		microsoft::service::MicrosoftService,
+	},
+};
+
+const CONFIG_KEY: &str = "profiles";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProfilesConfig {
+	profiles: Vec<Profile>,
+	last: Option<Profile>,
+}
+
+/// The profiles that were created, stored with the settings of the default
+/// profile. Each profile keeps its data under [`Profile::app_id`].
+pub struct Profiles;
+
+impl Profiles {
+	fn config() -> Result<Config> {
+		Ok(Config::new(&Services::base_app_id(), 1, None)?)
+	}
+
+	fn read() -> ProfilesConfig {
+		Self::config()
+			.and_then(|config| Ok(config.get_json(CONFIG_KEY)?))
+			.unwrap_or_default()
+	}
+
+	fn write(profiles: ProfilesConfig) -> Result<()> {
+		Self::config()?.set_json(CONFIG_KEY, profiles)?;
+		Ok(())
+	}
+
+	/// Every profile, the default one first.
+	pub fn list() -> Vec<Profile> {
+		let mut profiles = vec![Profile::default()];
+		profiles.extend(Self::read().profiles);
+		profiles
+	}
+
+	/// Finds a profile by name, letter case and punctuation aside.
+	pub fn find(name: &str) -> Option<Profile> {
+		let slug = Profile::new(name).slug();
+		Self::list()
+			.into_iter()
+			.find(|profile| profile.slug() == slug)
+	}
+
+	pub fn add(name: &str) -> Result<Profile> {
+		let profile = Profile::new(name);
+		if profile.name.is_empty() {
+			bail!("Profiles need a name.");
+		}
+		if Self::find(name).is_some() {
+			bail!("A profile named {name} already exists.");
+		}
+		let mut config = Self::read();
+		config.profiles.push(profile.clone());
+		Self::write(config)?;
+		Ok(profile)
+	}
+
+	/// Finds a profile, or creates it when there's none with that name.
+	pub fn open(name: &str) -> Result<Profile> {
+		match Self::find(name) {
+			Some(profile) => Ok(profile),
+			None => Self::add(name),
+		}
+	}
+
+	/// Removes a profile and deletes its database, services and preferences.
+	pub fn remove(profile: &Profile) -> Result<()> {
+		if profile.is_default() {
+			bail!("The default profile can't be removed.");
+		}
+		if profile.slug() == Services::profile().slug() {
+			bail!("The profile in use can't be removed.");
+		}
+		let mut config = Self::read();
+		config.profiles.retain(|existing| existing != profile);
+		if config.last.as_ref() == Some(profile) {
+			config.last = None;
+		}
+		Self::write(config)?;
+		let app_id = profile.app_id(&Services::base_app_id());
+		if let Err(err) = forget_key(&app_id) {
+			tracing::error!("Failed to delete the database key of {profile}: {err}");
+		}
+		for account in Accounts::of_profile(&app_id)
+			.into_iter()
+			.filter(|account| account.service == Service::Microsoft)
+		{
+			if let Err(err) = MicrosoftService::forget_token(&app_id, &account.id) {
+				tracing::error!("Failed to delete the token of {}: {err}", account.id);
+			}
+		}
+		Config::new(&app_id, 1, None)?.clean()?;
+		Ok(())
+	}
+
+	/// The profile opened last, used when none is asked for.
+	pub fn last() -> Profile {
+		Self::read()
+			.last
+			.and_then(|last| Self::find(&last.name))
+			.unwrap_or_default()
+	}
+
+	pub fn set_last(profile: &Profile) -> Result<()> {
+		let mut config = Self::read();
+		config.last = Some(profile.clone());
+		Self::write(config)
+	}
+}
@@ -3,8 +3,8 @@ use std::pin::Pin;
 use crate::{
 	models::{list::List, task::Task},
 	task_service::TodoProvider,
+	Error, Result,
 };
-use anyhow::Result;
 use async_trait::async_trait;
 use futures::Stream;
 use url::Url;
@@ -25,11 +25,11 @@ impl TodoProvider for Smart {
 		Ok(())
 	}
 
-	fn login(&self) -> anyhow::Result<()> {
+	async fn login(&self) -> Result<()> {
 		Ok(())
 	}
 
-	fn logout(&self) -> anyhow::Result<()> {
+	async fn logout(&self) -> Result<()> {
 		Ok(())
 	}
 
@@ -55,8 +55,10 @@ impl TodoProvider for Smart {
 	async fn get_tasks(
 		&mut self,
 		_parent_list: String,
-	) -> Result<Pin<Box<dyn Stream<Item = Task> + Send>>> {
-		todo!("This service does not implement streams")
+	) -> Result<Pin<Box<dyn Stream<Item = Result<Task>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
 	}
 
 	async fn read_task(
@@ -89,8 +91,10 @@ impl TodoProvider for Smart {
 
 	async fn get_lists(
 		&mut self,
-	) -> Result<Pin<Box<dyn Stream<Item = List> + Send>>> {
-		todo!("This service does not implement streams")
+	) -> Result<Pin<Box<dyn Stream<Item = Result<List>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
 	}
 
 	async fn read_list(&mut self, id: String) -> Result<List> {
@@ -0,0 +1,463 @@
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::models::{
+	priority::Priority, recurrence::Recurrence, status::Status, task::Task,
+};
+
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Annotations starting with these are sub-tasks, Taskwarrior has no
+/// checklists of its own.
+const OPEN_SUB_TASK: &str = "[ ] ";
+const DONE_SUB_TASK: &str = "[x] ";
+
+/// A task as exported by `task export`.
+///
+/// The whole JSON object is kept so attributes Done doesn't know about, like
+/// user defined attributes, survive an update.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct TaskwarriorItem(pub Map<String, Value>);
+
+impl TaskwarriorItem {
+	fn string(&self, key: &str) -> Option<&str> {
+		self.0.get(key).and_then(Value::as_str)
+	}
+
+	fn date(&self, key: &str) -> Option<DateTime<Utc>> {
+		self.string(key).and_then(parse_date)
+	}
+
+	fn set(&mut self, key: &str, value: Option<String>) {
+		match value {
+			Some(value) => self.0.insert(key.to_string(), Value::String(value)),
+			None => self.0.remove(key),
+		};
+	}
+
+	pub fn uuid(&self) -> Option<&str> {
+		self.string("uuid")
+	}
+
+	pub fn status(&self) -> &str {
+		self.string("status").unwrap_or("pending")
+	}
+
+	pub fn project(&self) -> Option<&str> {
+		self.string("project").filter(|project| !project.is_empty())
+	}
+
+	pub fn set_project(&mut self, project: Option<&str>) {
+		self.set("project", project.map(str::to_string));
+	}
+
+	/// Deleted tasks and the templates recurring tasks are created from are
+	/// not shown.
+	pub fn visible(&self) -> bool {
+		!matches!(self.status(), "deleted" | "recurring")
+	}
+
+	pub fn delete(&mut self) {
+		self.set("status", Some("deleted".into()));
+		self.set("end", Some(format_date(Utc::now())));
+	}
+
+	fn annotations(&self) -> Vec<(String, String)> {
+		self
+			.0
+			.get("annotations")
+			.and_then(Value::as_array)
+			.map(|annotations| {
+				annotations
+					.iter()
+					.filter_map(|annotation| {
+						Some((
+							annotation.get("entry")?.as_str()?.to_string(),
+							annotation.get("description")?.as_str()?.to_string(),
+						))
+					})
+					.collect()
+			})
+			.unwrap_or_default()
+	}
+
+	pub fn to_task(&self, parent: String) -> Task {
+		let mut notes = vec![];
+		let mut sub_tasks = vec![];
+		for (entry, description) in self.annotations() {
+			let sub_task = description
+				.strip_prefix(OPEN_SUB_TASK)
+				.map(|title| (title, Status::NotStarted))
+				.or_else(|| {
+					description
+						.strip_prefix(DONE_SUB_TASK)
+						.map(|title| (title, Status::Completed))
+				});
+			match sub_task {
+				Some((title, status)) => sub_tasks.push(Task {
+					id: format!("{}-{}", self.uuid().unwrap_or_default(), entry),
+					title: title.to_string(),
+					status,
+					created_date_time: parse_date(&entry).unwrap_or_default(),
+					..Default::default()
+				}),
+				None => notes.push(description),
+			}
+		}
+		let created_date_time = self.date("entry").unwrap_or_default();
+		Task {
+			id: self.uuid().unwrap_or_default().to_string(),
+			parent,
+			title: self.string("description").unwrap_or_default().to_string(),
+			status: if self.status() == "completed" {
+				Status::Completed
+			} else {
+				Status::NotStarted
+			},
+			priority: match self.string("priority") {
+				Some("H") => Priority::High,
+				Some("M") => Priority::Normal,
+				_ => Priority::Low,
+			},
+			sub_tasks,
+			tags: self
+				.0
+				.get("tags")
+				.and_then(Value::as_array)
+				.map(|tags| {
+					tags
+						.iter()
+						.filter_map(|tag| tag.as_str().map(str::to_string))
+						.collect()
+				})
+				.unwrap_or_default(),
+			notes: notes.join("\n"),
+			completion_date: self
+				.date("end")
+				.filter(|_| self.status() == "completed"),
+			due_date: self.date("due"),
+			reminder_date: self.date("wait"),
+			recurrence: self
+				.string("recur")
+				.map(|recur| recurrence(recur, self.date("due")))
+				.unwrap_or_default(),
+			created_date_time,
+			last_modified_date_time: self
+				.date("modified")
+				.unwrap_or(created_date_time),
+			..Default::default()
+		}
+	}
+
+	/// Rewrites this item from a task, `project` is the project of the list
+	/// the task belongs to.
+	pub fn apply(&mut self, task: &Task, project: Option<&str>) {
+		let now = Utc::now();
+		let previous = self.to_task(task.parent.clone());
+		self.set("uuid", Some(task.id.clone()));
+		self.set("description", Some(task.title.clone()));
+		self.set_project(project);
+		if self.0.get("entry").is_none() {
+			let created = if task.created_date_time == DateTime::<Utc>::default() {
+				now
+			} else {
+				task.created_date_time
+			};
+			self.set("entry", Some(format_date(created)));
+		}
+		self.set("modified", Some(format_date(now)));
+
+		match task.status {
+			Status::Completed => {
+				self.set("status", Some("completed".into()));
+				self.set(
+					"end",
+					Some(format_date(task.completion_date.unwrap_or(now))),
+				);
+			},
+			Status::NotStarted => {
+				self.set("status", Some("pending".into()));
+				self.set("end", None);
+			},
+		}
+
+		let priority = match task.priority {
+			Priority::High => Some("H"),
+			Priority::Normal => Some("M"),
+			Priority::Low => {
+				self.string("priority").filter(|priority| *priority == "L")
+			},
+		};
+		self.set("priority", priority.map(str::to_string));
+
+		if task.tags.is_empty() {
+			self.0.remove("tags");
+		} else {
+			self.0.insert("tags".into(), json!(task.tags));
+		}
+
+		self.set("due", task.due_date.map(format_date));
+		self.set("wait", task.reminder_date.map(format_date));
+		if task.recurrence != previous.recurrence {
+			let recur = recur(task.recurrence);
+			if recur.is_some() && task.due_date.is_none() {
+				self.set("due", Some(format_date(now)));
+			}
+			self.set("recur", recur);
+		}
+
+		if task.notes != previous.notes || task.sub_tasks != previous.sub_tasks {
+			let mut existing = self.annotations();
+			let sub_task_prefix = format!("{}-", task.id);
+			// Notes keep the entry of the annotation with the same text, sub-tasks
+			// the one their id was made from.
+			let mut annotations: Vec<(Option<String>, String)> = task
+				.notes
+				.lines()
+				.filter(|line| !line.trim().is_empty())
+				.map(|line| {
+					let entry = existing
+						.iter()
+						.position(|(_, description)| description == line)
+						.map(|position| existing.remove(position).0);
+					(entry, line.to_string())
+				})
+				.collect();
+			annotations.extend(task.sub_tasks.iter().map(|sub_task| {
+				let prefix = if sub_task.status == Status::Completed {
+					DONE_SUB_TASK
+				} else {
+					OPEN_SUB_TASK
+				};
+				let entry = sub_task
+					.id
+					.strip_prefix(&sub_task_prefix)
+					.filter(|entry| parse_date(entry).is_some())
+					.map(str::to_string);
+				(entry, format!("{prefix}{}", sub_task.title))
+			}));
+			// Annotations are told apart by their entry date, new ones get the
+			// first free second from now.
+			let mut used: Vec<String> = annotations
+				.iter()
+				.filter_map(|(entry, _)| entry.clone())
+				.collect();
+			let mut next = now;
+			let annotations: Vec<Value> = annotations
+				.into_iter()
+				.map(|(entry, description)| {
+					let entry = entry.unwrap_or_else(|| {
+						while used.contains(&format_date(next)) {
+							next += chrono::Duration::seconds(1);
+						}
+						used.push(format_date(next));
+						format_date(next)
+					});
+					json!({ "entry": entry, "description": description })
+				})
+				.collect();
+			if annotations.is_empty() {
+				self.0.remove("annotations");
+			} else {
+				self
+					.0
+					.insert("annotations".into(), Value::Array(annotations));
+			}
+		}
+	}
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+	NaiveDateTime::parse_from_str(value, DATE_FORMAT)
+		.ok()
+		.map(|date| date.and_utc())
+}
+
+fn format_date(date: DateTime<Utc>) -> String {
+	date.format(DATE_FORMAT).to_string()
+}
+
+/// The days a Taskwarrior recurrence falls on, a weekly recurrence falls on
+/// the day of its due date. Periods that aren't a set of weekdays, like
+/// `monthly`, have no equivalent.
+fn recurrence(recur: &str, due_date: Option<DateTime<Utc>>) -> Recurrence {
+	let every_day = Recurrence {
+		monday: true,
+		tuesday: true,
+		wednesday: true,
+		thursday: true,
+		friday: true,
+		saturday: true,
+		sunday: true,
+	};
+	match recur {
+		"daily" | "day" | "1d" => every_day,
+		"weekdays" => Recurrence {
+			saturday: false,
+			sunday: false,
+			..every_day
+		},
+		"weekly" | "week" | "1w" => {
+			let mut recurrence = Recurrence::default();
+			match due_date.map(|due_date| due_date.weekday()) {
+				Some(Weekday::Mon) => recurrence.monday = true,
+				Some(Weekday::Tue) => recurrence.tuesday = true,
+				Some(Weekday::Wed) => recurrence.wednesday = true,
+				Some(Weekday::Thu) => recurrence.thursday = true,
+				Some(Weekday::Fri) => recurrence.friday = true,
+				Some(Weekday::Sat) => recurrence.saturday = true,
+				Some(Weekday::Sun) => recurrence.sunday = true,
+				None => (),
+			}
+			recurrence
+		},
+		_ => Recurrence::default(),
+	}
+}
+
+/// The Taskwarrior recurrence for a set of days. A single day recurs weekly
+/// from the due date, other sets fall back to daily.
+fn recur(recurrence: Recurrence) -> Option<String> {
+	let days = [
+		(Weekday::Mon, recurrence.monday),
+		(Weekday::Tue, recurrence.tuesday),
+		(Weekday::Wed, recurrence.wednesday),
+		(Weekday::Thu, recurrence.thursday),
+		(Weekday::Fri, recurrence.friday),
+		(Weekday::Sat, recurrence.saturday),
+		(Weekday::Sun, recurrence.sunday),
+	];
+	let selected: Vec<Weekday> = days
+		.iter()
+		.filter(|(_, selected)| *selected)
+		.map(|(day, _)| *day)
+		.collect();
+	match selected.len() {
+		0 => None,
+		1 => Some("weekly".into()),
+		_ if selected.len() == 5
+			&& selected.iter().all(|day| day.number_from_monday() <= 5) =>
+		{
+			Some("weekdays".into())
+		},
+		_ => Some("daily".into()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn item(value: Value) -> TaskwarriorItem {
+		serde_json::from_value(value).unwrap()
+	}
+
+	#[test]
+	fn maps_priorities() {
+		for (priority, expected) in [
+			(Some("H"), Priority::High),
+			(Some("M"), Priority::Normal),
+			(Some("L"), Priority::Low),
+			(None, Priority::Low),
+		] {
+			let mut value = json!({ "uuid": "a", "description": "Task" });
+			if let Some(priority) = priority {
+				value["priority"] = json!(priority);
+			}
+			let mut item = item(value);
+			let task = item.to_task("inbox".to_string());
+			assert_eq!(task.priority, expected);
+
+			item.apply(&task, None);
+			assert_eq!(item.string("priority"), priority);
+		}
+	}
+
+	#[test]
+	fn maps_dates_and_recurrence() {
+		let mut item = item(json!({
+			"uuid": "a",
+			"description": "Pay rent",
+			"status": "recurring",
+			"entry": "20240401T090000Z",
+			"due": "20240405T000000Z",
+			"wait": "20240404T080000Z",
+			"recur": "weekly",
+		}));
+		let task = item.to_task("home".to_string());
+		assert_eq!(
+			task.created_date_time,
+			parse_date("20240401T090000Z").unwrap()
+		);
+		assert_eq!(task.due_date, parse_date("20240405T000000Z"));
+		assert_eq!(task.reminder_date, parse_date("20240404T080000Z"));
+		assert!(task.recurrence.friday);
+		assert!(!task.recurrence.monday);
+
+		item.apply(&task, Some("home"));
+		assert_eq!(item.string("entry"), Some("20240401T090000Z"));
+		assert_eq!(item.string("due"), Some("20240405T000000Z"));
+		assert_eq!(item.string("wait"), Some("20240404T080000Z"));
+		assert_eq!(item.string("recur"), Some("weekly"));
+
+		let mut task = task;
+		task.recurrence = Recurrence {
+			monday: true,
+			tuesday: true,
+			wednesday: true,
+			thursday: true,
+			friday: true,
+			..Default::default()
+		};
+		task.reminder_date = None;
+		item.apply(&task, Some("home"));
+		assert_eq!(item.string("recur"), Some("weekdays"));
+		assert_eq!(item.string("wait"), None);
+	}
+
+	#[test]
+	fn keeps_no_entry_when_none_is_written() {
+		let task = item(json!({ "uuid": "a", "description": "Task" }))
+			.to_task("inbox".to_string());
+		assert_eq!(task.created_date_time, DateTime::<Utc>::default());
+	}
+
+	#[test]
+	fn keeps_annotation_entries() {
+		let mut item = item(json!({
+			"uuid": "a",
+			"description": "Paint the fence",
+			"annotations": [
+				{ "entry": "20240401T090000Z", "description": "Buy white paint." },
+				{ "entry": "20240401T090001Z", "description": "[ ] Sand it" },
+				{ "entry": "20240401T090002Z", "description": "[x] Wash it" },
+			],
+		}));
+		let mut task = item.to_task("home".to_string());
+		assert_eq!(task.notes, "Buy white paint.");
+		task.sub_tasks[0].title = "Sand it twice".to_string();
+		task.sub_tasks[0].status = Status::Completed;
+		task.sub_tasks.push(Task {
+			title: "Paint it".to_string(),
+			..Default::default()
+		});
+		item.apply(&task, Some("home"));
+
+		let annotations = item.annotations();
+		assert_eq!(annotations[0].0, "20240401T090000Z");
+		assert_eq!(
+			annotations[1],
+			("20240401T090001Z".into(), "[x] Sand it twice".into())
+		);
+		assert_eq!(annotations[2].0, "20240401T090002Z");
+		assert!(
+			!["20240401T090000Z", "20240401T090001Z", "20240401T090002Z"]
+				.contains(&annotations[3].0.as_str())
+		);
+
+		let read = item.to_task("home".to_string());
+		assert_eq!(read.sub_tasks[0].id, task.sub_tasks[0].id);
+	}
+}
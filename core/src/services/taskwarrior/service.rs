@@ -0,0 +1,388 @@
+use std::{collections::BTreeSet, path::PathBuf, pin::Pin, process::Stdio};
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::{io::AsyncWriteExt, process::Command};
+use url::Url;
+
+use crate::{
+	models::{
+		list::{List, Sharing},
+		task::Task,
+	},
+	service::Service,
+	services::files::{mark_written, read_config, remove_config, write_config},
+	task_service::TodoProvider,
+	Error, Result,
+};
+
+use super::item::TaskwarriorItem;
+
+const CONFIG_KEY: &str = "taskwarrior";
+
+/// Id of the list holding the tasks without a project.
+const INBOX_ID: &str = "no project";
+
+/// Files Taskwarrior 2 and 3 keep their tasks in.
+const DATA_FILES: [&str; 3] =
+	["pending.data", "completed.data", "taskchampion.sqlite3"];
+
+/// Settings of the Taskwarrior provider.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct TaskwarriorConfig {
+	/// The data directory, `~/.task` unless the user moved it.
+	pub path: PathBuf,
+	/// Projects created from Done that have no tasks yet.
+	#[serde(default)]
+	pub projects: Vec<String>,
+}
+
+/// Tasks of a Taskwarrior data directory, read and written through the
+/// `task` command so the command line and Done share the same tasks.
+#[derive(Debug, Clone)]
+pub struct TaskwarriorService {
+	config: Option<TaskwarriorConfig>,
+}
+
+impl TaskwarriorService {
+	pub(crate) fn new() -> Self {
+		Self {
+			config: read_config(CONFIG_KEY),
+		}
+	}
+
+	/// The data directory chosen by the user.
+	pub(crate) fn path() -> Option<PathBuf> {
+		read_config::<TaskwarriorConfig>(CONFIG_KEY).map(|config| config.path)
+	}
+
+	pub(crate) fn set_path(path: PathBuf) -> anyhow::Result<()> {
+		write_config(
+			CONFIG_KEY,
+			TaskwarriorConfig {
+				path,
+				..Default::default()
+			},
+		)
+	}
+
+	pub(crate) fn clear_path() -> anyhow::Result<()> {
+		remove_config(CONFIG_KEY)
+	}
+
+	pub(crate) fn watched_paths() -> Vec<PathBuf> {
+		Self::path()
+			.map(|path| DATA_FILES.iter().map(|file| path.join(file)).collect())
+			.unwrap_or_default()
+	}
+
+	fn config(&self) -> anyhow::Result<&TaskwarriorConfig> {
+		self
+			.config
+			.as_ref()
+			.context("No Taskwarrior data directory has been chosen.")
+	}
+
+	fn save_config(&self) -> anyhow::Result<()> {
+		write_config(CONFIG_KEY, self.config()?)
+	}
+
+	/// Runs `task` against the chosen data directory, without prompts.
+	async fn run(
+		&self,
+		args: &[&str],
+		input: Option<String>,
+	) -> anyhow::Result<String> {
+		let data_location =
+			format!("rc.data.location={}", self.config()?.path.display());
+		let mut child = Command::new("task")
+			.arg(data_location)
+			.args([
+				"rc.confirmation=off",
+				"rc.verbose=nothing",
+				"rc.json.array=on",
+			])
+			.args(args)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()
+			.context("Failed to run task, is Taskwarrior installed?")?;
+		let mut stdin = child.stdin.take().context("Failed to open stdin.")?;
+		if let Some(input) = input {
+			stdin.write_all(input.as_bytes()).await?;
+		}
+		drop(stdin);
+		let output = child.wait_with_output().await?;
+		if !output.status.success() {
+			bail!(
+				"task {} failed: {}",
+				args.join(" "),
+				String::from_utf8_lossy(&output.stderr).trim()
+			);
+		}
+		Ok(String::from_utf8_lossy(&output.stdout).to_string())
+	}
+
+	async fn export(&self) -> anyhow::Result<Vec<TaskwarriorItem>> {
+		let json = self.run(&["export"], None).await?;
+		let items: Vec<TaskwarriorItem> = serde_json::from_str(&json)
+			.context("Failed to parse Taskwarrior export.")?;
+		Ok(items.into_iter().filter(TaskwarriorItem::visible).collect())
+	}
+
+	async fn find(&self, id: &str) -> Result<TaskwarriorItem> {
+		self
+			.export()
+			.await?
+			.into_iter()
+			.find(|item| item.uuid() == Some(id))
+			.ok_or_else(|| Error::NotFound(format!("Task {id} not found.")))
+	}
+
+	/// Creates or updates tasks, Taskwarrior matches them by uuid.
+	async fn import(&self, items: Vec<TaskwarriorItem>) -> anyhow::Result<()> {
+		if items.is_empty() {
+			return Ok(());
+		}
+		self
+			.run(&["import", "-"], Some(serde_json::to_string(&items)?))
+			.await?;
+		for path in Self::watched_paths() {
+			mark_written(&path);
+		}
+		Ok(())
+	}
+
+	fn list(&self, project: &str) -> List {
+		List {
+			id: project.to_string(),
+			name: if project == INBOX_ID {
+				"Inbox".to_string()
+			} else {
+				project.to_string()
+			},
+			description: String::new(),
+			icon: None,
+			service: Service::Taskwarrior,
+			sharing: Sharing::Private,
+			account: None,
+		}
+	}
+}
+
+fn list_id(item: &TaskwarriorItem) -> String {
+	item.project().unwrap_or(INBOX_ID).to_string()
+}
+
+fn project(list_id: &str) -> Option<&str> {
+	(list_id != INBOX_ID).then_some(list_id)
+}
+
+/// Whether a project is `parent` or one of its sub-projects.
+fn in_project(project: Option<&str>, parent: &str) -> bool {
+	project.is_some_and(|project| {
+		project == parent || project.starts_with(&format!("{parent}."))
+	})
+}
+
+#[async_trait]
+impl TodoProvider for TaskwarriorService {
+	async fn handle_uri_params(&mut self, _uri: Url) -> Result<()> {
+		Ok(())
+	}
+
+	async fn login(&self) -> Result<()> {
+		Ok(())
+	}
+
+	async fn logout(&self) -> Result<()> {
+		Ok(Self::clear_path()?)
+	}
+
+	fn available(&self) -> bool {
+		self.config.is_some()
+	}
+
+	fn stream_support(&self) -> bool {
+		false
+	}
+
+	async fn read_tasks(&mut self) -> Result<Vec<Task>> {
+		Ok(
+			self
+				.export()
+				.await?
+				.iter()
+				.map(|item| item.to_task(list_id(item)))
+				.collect(),
+		)
+	}
+
+	async fn get_tasks(
+		&mut self,
+		_parent_list: String,
+	) -> Result<Pin<Box<dyn Stream<Item = Result<Task>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
+	}
+
+	async fn read_tasks_from_list(
+		&mut self,
+		parent_list: String,
+	) -> Result<Vec<Task>> {
+		Ok(
+			self
+				.read_tasks()
+				.await?
+				.into_iter()
+				.filter(|task| task.parent == parent_list)
+				.collect(),
+		)
+	}
+
+	async fn read_task(
+		&mut self,
+		_task_list_id: String,
+		task_id: String,
+	) -> Result<Task> {
+		let item = self.find(&task_id).await?;
+		Ok(item.to_task(list_id(&item)))
+	}
+
+	async fn create_task(&mut self, task: Task) -> Result<()> {
+		let mut item = TaskwarriorItem::default();
+		item.apply(&task, project(&task.parent));
+		Ok(self.import(vec![item]).await?)
+	}
+
+	async fn update_task(&mut self, task: Task) -> Result<Task> {
+		let mut item = self.find(&task.id).await?;
+		item.apply(&task, project(&task.parent));
+		self.import(vec![item]).await?;
+		Ok(task)
+	}
+
+	async fn delete_task(
+		&mut self,
+		_list_id: String,
+		task_id: String,
+	) -> Result<()> {
+		let mut item = self.find(&task_id).await?;
+		item.delete();
+		Ok(self.import(vec![item]).await?)
+	}
+
+	async fn read_lists(&mut self) -> Result<Vec<List>> {
+		if self.config.is_none() {
+			return Ok(vec![]);
+		}
+		let mut projects: BTreeSet<String> =
+			self.config()?.projects.iter().cloned().collect();
+		for item in self.export().await? {
+			projects.extend(item.project().map(str::to_string));
+		}
+		Ok(
+			std::iter::once(INBOX_ID.to_string())
+				.chain(projects)
+				.map(|project| self.list(&project))
+				.collect(),
+		)
+	}
+
+	async fn get_lists(
+		&mut self,
+	) -> Result<Pin<Box<dyn Stream<Item = Result<List>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
+	}
+
+	async fn read_list(&mut self, id: String) -> Result<List> {
+		self
+			.read_lists()
+			.await?
+			.into_iter()
+			.find(|list| list.id == id)
+			.ok_or_else(|| Error::NotFound(format!("List {id} not found.")))
+	}
+
+	async fn create_list(&mut self, list: List) -> Result<List> {
+		let project = list.name.trim().to_string();
+		if project.is_empty() {
+			return Err(Error::InvalidData("A project needs a name.".to_string()));
+		}
+		let config = self
+			.config
+			.as_mut()
+			.context("No Taskwarrior data directory has been chosen.")?;
+		if !config.projects.contains(&project) {
+			config.projects.push(project.clone());
+		}
+		self.save_config()?;
+		Ok(self.list(&project))
+	}
+
+	/// Renames the project, sub-projects keep their place under it.
+	async fn update_list(&mut self, list: List) -> Result<()> {
+		let Some(old) = project(&list.id) else {
+			return Err(Error::Unsupported(
+				"The inbox can't be renamed.".to_string(),
+			));
+		};
+		let new = list.name.trim();
+		if new.is_empty() || new == old {
+			return Ok(());
+		}
+		let items: Vec<TaskwarriorItem> = self
+			.export()
+			.await?
+			.into_iter()
+			.filter(|item| in_project(item.project(), old))
+			.map(|mut item| {
+				let project = item.project().unwrap_or_default().replacen(old, new, 1);
+				item.set_project(Some(&project));
+				item
+			})
+			.collect();
+		self.import(items).await?;
+		let config = self
+			.config
+			.as_mut()
+			.context("No Taskwarrior data directory has been chosen.")?;
+		config.projects.retain(|project| project != old);
+		config.projects.push(new.to_string());
+		Ok(self.save_config()?)
+	}
+
+	/// Deletes the tasks of the project and of its sub-projects.
+	async fn delete_list(&mut self, id: String) -> Result<()> {
+		let Some(old) = project(&id) else {
+			return Err(Error::Unsupported(
+				"The inbox can't be deleted.".to_string(),
+			));
+		};
+		let items: Vec<TaskwarriorItem> = self
+			.export()
+			.await?
+			.into_iter()
+			.filter(|item| in_project(item.project(), old))
+			.map(|mut item| {
+				item.delete();
+				item
+			})
+			.collect();
+		self.import(items).await?;
+		let config = self
+			.config
+			.as_mut()
+			.context("No Taskwarrior data directory has been chosen.")?;
+		config
+			.projects
+			.retain(|project| !in_project(Some(project), old));
+		Ok(self.save_config()?)
+	}
+}
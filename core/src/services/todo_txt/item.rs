@@ -0,0 +1,318 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+
+use crate::models::{priority::Priority, status::Status, task::Task};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A single line of a todo.txt file.
+///
+/// `words` keeps the description as written, including the `+project`,
+/// `@context` and `key:value` tokens, so a line Done rewrites keeps
+/// anything it doesn't understand.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TodoTxtItem {
+	pub completed: bool,
+	pub priority: Option<char>,
+	pub completion_date: Option<NaiveDate>,
+	pub creation_date: Option<NaiveDate>,
+	pub words: Vec<String>,
+}
+
+impl TodoTxtItem {
+	/// Parses a line, blank lines have no item.
+	pub fn parse(line: &str) -> Option<Self> {
+		let mut words = line.split_whitespace().peekable();
+		words.peek()?;
+		let mut item = Self::default();
+		if words.peek() == Some(&"x") {
+			item.completed = true;
+			words.next();
+		}
+		if let Some(priority) = words.peek().and_then(|word| parse_priority(word)) {
+			item.priority = Some(priority);
+			words.next();
+		}
+		if let Some(date) = words.peek().and_then(|word| parse_date(word)) {
+			words.next();
+			match words.peek().and_then(|word| parse_date(word)) {
+				Some(creation_date) if item.completed => {
+					words.next();
+					item.completion_date = Some(date);
+					item.creation_date = Some(creation_date);
+				},
+				_ if item.completed => item.completion_date = Some(date),
+				_ => item.creation_date = Some(date),
+			}
+		}
+		item.words = words.map(str::to_string).collect();
+		if item.priority.is_none() {
+			item.priority = item.value("pri").and_then(|pri| pri.chars().next());
+		}
+		Some(item)
+	}
+
+	pub fn projects(&self) -> impl Iterator<Item = &str> {
+		self.words.iter().filter_map(|word| word.strip_prefix('+'))
+	}
+
+	pub fn contexts(&self) -> impl Iterator<Item = &str> {
+		self.words.iter().filter_map(|word| word.strip_prefix('@'))
+	}
+
+	/// The value of the first `key:value` token with the given key.
+	pub fn value(&self, key: &str) -> Option<&str> {
+		self.words.iter().find_map(|word| {
+			word
+				.split_once(':')
+				.filter(|(word_key, value)| *word_key == key && !value.is_empty())
+				.map(|(_, value)| value)
+		})
+	}
+
+	/// The project of the list this item belongs to.
+	pub fn project(&self) -> Option<&str> {
+		self.projects().next()
+	}
+
+	/// Builds a task from this item, tokens are stripped from the title.
+	pub fn to_task(&self, id: String, parent: String) -> Task {
+		let title = self
+			.words
+			.iter()
+			.filter(|word| !is_token(word))
+			.cloned()
+			.collect::<Vec<String>>()
+			.join(" ");
+		let completion_date = self.completion_date.map(to_date_time);
+		let created_date_time =
+			self.creation_date.map(to_date_time).unwrap_or_default();
+		Task {
+			id,
+			parent,
+			title,
+			status: if self.completed {
+				Status::Completed
+			} else {
+				Status::NotStarted
+			},
+			priority: match self.priority {
+				Some('A') => Priority::High,
+				Some('B') => Priority::Normal,
+				_ => Priority::Low,
+			},
+			tags: self.contexts().map(str::to_string).collect(),
+			due_date: self.value("due").and_then(parse_date).map(to_date_time),
+			completion_date,
+			created_date_time,
+			last_modified_date_time: created_date_time,
+			..Default::default()
+		}
+	}
+
+	/// Rewrites this item from a task, `project` is the project of the list
+	/// the task belongs to. Unknown tokens are kept as they were.
+	pub fn apply(&mut self, task: &Task, project: Option<&str>) {
+		let previous_project = self.project().map(str::to_string);
+		self.completed = task.status == Status::Completed;
+		self.priority = match task.priority {
+			Priority::High => Some('A'),
+			Priority::Normal => Some('B'),
+			Priority::Low => self.priority.filter(|priority| *priority > 'B'),
+		};
+		self.completion_date = self
+			.completed
+			.then(|| task.completion_date.unwrap_or_else(Utc::now).date_naive());
+		if self.creation_date.is_none() {
+			let created = if task.created_date_time == DateTime::<Utc>::default() {
+				Utc::now()
+			} else {
+				task.created_date_time
+			};
+			self.creation_date = Some(created.date_naive());
+		}
+
+		let mut words: Vec<String> =
+			task.title.split_whitespace().map(str::to_string).collect();
+		if let Some(project) = project {
+			words.push(format!("+{project}"));
+		}
+		words.extend(
+			self
+				.words
+				.iter()
+				.filter(|word| {
+					word.starts_with('+')
+						&& word.strip_prefix('+') != previous_project.as_deref()
+						&& word.strip_prefix('+') != project
+				})
+				.cloned(),
+		);
+		words.extend(task.tags.iter().map(|tag| format!("@{tag}")));
+		words.extend(
+			self
+				.words
+				.iter()
+				.filter(|word| {
+					is_key_value(word)
+						&& !["due", "id", "pri"]
+							.iter()
+							.any(|key| word.starts_with(&format!("{key}:")))
+				})
+				.cloned(),
+		);
+		if let Some(due_date) = task.due_date {
+			words.push(format!("due:{}", due_date.format(DATE_FORMAT)));
+		}
+		words.push(format!("id:{}", task.id));
+		self.words = words;
+	}
+}
+
+impl Display for TodoTxtItem {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let mut parts = vec![];
+		if self.completed {
+			parts.push("x".to_string());
+		} else if let Some(priority) = self.priority {
+			parts.push(format!("({priority})"));
+		}
+		if let Some(completion_date) = self.completion_date {
+			parts.push(completion_date.format(DATE_FORMAT).to_string());
+		}
+		if let Some(creation_date) = self.creation_date {
+			parts.push(creation_date.format(DATE_FORMAT).to_string());
+		}
+		parts.extend(
+			self
+				.words
+				.iter()
+				.filter(|word| !word.starts_with("pri:"))
+				.cloned(),
+		);
+		if self.completed {
+			if let Some(priority) = self.priority {
+				parts.push(format!("pri:{priority}"));
+			}
+		}
+		write!(f, "{}", parts.join(" "))
+	}
+}
+
+fn parse_priority(word: &str) -> Option<char> {
+	let mut chars = word.chars();
+	match (chars.next(), chars.next(), chars.next(), chars.next()) {
+		(Some('('), Some(priority), Some(')'), None)
+			if priority.is_ascii_uppercase() =>
+		{
+			Some(priority)
+		},
+		_ => None,
+	}
+}
+
+fn parse_date(word: &str) -> Option<NaiveDate> {
+	NaiveDate::parse_from_str(word, DATE_FORMAT).ok()
+}
+
+fn to_date_time(date: NaiveDate) -> DateTime<Utc> {
+	date.and_time(NaiveTime::MIN).and_utc()
+}
+
+fn is_key_value(word: &str) -> bool {
+	word.split_once(':').is_some_and(|(key, value)| {
+		!key.is_empty()
+			&& !value.is_empty()
+			&& !value.starts_with('/')
+			&& !key.contains(|c: char| !c.is_alphanumeric() && c != '_' && c != '-')
+	})
+}
+
+fn is_token(word: &str) -> bool {
+	(word.len() > 1 && (word.starts_with('+') || word.starts_with('@')))
+		|| is_key_value(word)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_a_line() {
+		let item = TodoTxtItem::parse(
+			"(A) 2024-04-01 Call mom +family @phone due:2024-04-05 id:call",
+		)
+		.unwrap();
+		assert!(!item.completed);
+		assert_eq!(item.priority, Some('A'));
+		assert_eq!(item.creation_date, parse_date("2024-04-01"));
+		assert_eq!(item.project(), Some("family"));
+		assert_eq!(item.contexts().collect::<Vec<_>>(), vec!["phone"]);
+
+		let task = item.to_task("call".to_string(), "family".to_string());
+		assert_eq!(task.title, "Call mom");
+		assert_eq!(task.priority, Priority::High);
+		assert_eq!(task.tags, vec!["phone"]);
+		assert_eq!(task.due_date, parse_date("2024-04-05").map(to_date_time));
+		assert_eq!(
+			task.created_date_time,
+			to_date_time(parse_date("2024-04-01").unwrap())
+		);
+	}
+
+	#[test]
+	fn parses_a_completed_line() {
+		let item =
+			TodoTxtItem::parse("x 2024-04-03 2024-04-01 Pay rent pri:B").unwrap();
+		assert!(item.completed);
+		assert_eq!(item.priority, Some('B'));
+		assert_eq!(item.completion_date, parse_date("2024-04-03"));
+		assert_eq!(item.creation_date, parse_date("2024-04-01"));
+
+		let task = item.to_task("rent".to_string(), "no project".to_string());
+		assert_eq!(task.status, Status::Completed);
+		assert_eq!(task.priority, Priority::Normal);
+		assert_eq!(
+			task.completion_date,
+			parse_date("2024-04-03").map(to_date_time)
+		);
+	}
+
+	#[test]
+	fn writes_back_what_it_reads() {
+		for line in [
+			"(A) 2024-04-01 Call mom +family @phone due:2024-04-05 id:call",
+			"x 2024-04-03 2024-04-01 Pay rent +home pri:B",
+			"Water plants @home rec:1w",
+		] {
+			assert_eq!(TodoTxtItem::parse(line).unwrap().to_string(), line);
+		}
+	}
+
+	#[test]
+	fn keeps_the_line_when_applying_its_own_task() {
+		let line = "(B) 2024-04-01 Call mom +family @phone t:2024-04-04 due:2024-04-05 id:call";
+		let mut item = TodoTxtItem::parse(line).unwrap();
+		let task = item.to_task("call".to_string(), "family".to_string());
+		item.apply(&task, Some("family"));
+		assert_eq!(item.to_string(), line);
+
+		let mut task = task;
+		task.status = Status::Completed;
+		task.completion_date = parse_date("2024-04-06").map(to_date_time);
+		item.apply(&task, Some("family"));
+		assert_eq!(
+			item.to_string(),
+			"x 2024-04-06 2024-04-01 Call mom +family @phone t:2024-04-04 due:2024-04-05 id:call pri:B"
+		);
+	}
+
+	#[test]
+	fn keeps_no_creation_date_when_none_is_written() {
+		let task = TodoTxtItem::parse("Call mom")
+			.unwrap()
+			.to_task("call".to_string(), "no project".to_string());
+		assert_eq!(task.created_date_time, DateTime::<Utc>::default());
+	}
+}
@@ -0,0 +1,472 @@
+use std::{
+	collections::{BTreeSet, HashMap},
+	path::PathBuf,
+	pin::Pin,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+	models::{
+		list::{List, Sharing},
+		task::Task,
+	},
+	service::Service,
+	services::files::{
+		read_config, read_file, remove_config, stable_id, write_config, write_file,
+	},
+	task_service::TodoProvider,
+	Error, Result,
+};
+
+use super::item::TodoTxtItem;
+
+const CONFIG_KEY: &str = "todo-txt";
+
+/// Id of the list holding the tasks without a `+project`, projects can't
+/// contain spaces so it never clashes with one.
+const INBOX_ID: &str = "no project";
+
+/// Settings of the todo.txt provider.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct TodoTxtConfig {
+	pub path: PathBuf,
+	/// Where completed tasks are archived, when it isn't the `done.txt` next
+	/// to `todo.txt`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub done_path: Option<PathBuf>,
+	/// Projects created from Done that have no tasks yet.
+	#[serde(default)]
+	pub projects: Vec<String>,
+}
+
+impl TodoTxtConfig {
+	/// The file completed tasks are archived in, the `done.txt` next to
+	/// `todo.txt` unless another one was chosen.
+	fn done_file(&self) -> PathBuf {
+		self
+			.done_path
+			.clone()
+			.unwrap_or_else(|| self.path.with_file_name("done.txt"))
+	}
+}
+
+/// Tasks of a todo.txt file and its done.txt archive.
+#[derive(Debug, Clone)]
+pub struct TodoTxtService {
+	config: Option<TodoTxtConfig>,
+}
+
+/// A task and where it is written.
+struct Entry {
+	file: usize,
+	line: usize,
+	id: String,
+	item: TodoTxtItem,
+}
+
+/// The lines of `todo.txt` and `done.txt`, in that order.
+struct Files {
+	paths: [PathBuf; 2],
+	lines: [Vec<String>; 2],
+}
+
+impl Files {
+	fn entries(&self) -> Vec<Entry> {
+		let mut occurrences: HashMap<&str, usize> = HashMap::new();
+		let mut entries = vec![];
+		for (file, lines) in self.lines.iter().enumerate() {
+			for (line, text) in lines.iter().enumerate() {
+				let Some(item) = TodoTxtItem::parse(text) else {
+					continue;
+				};
+				let occurrence = occurrences.entry(text.as_str()).or_insert(0);
+				let id = match item.value("id") {
+					Some(id) => id.to_string(),
+					None => stable_id(&format!("{text}\n{occurrence}")),
+				};
+				*occurrence += 1;
+				entries.push(Entry {
+					file,
+					line,
+					id,
+					item,
+				});
+			}
+		}
+		entries
+	}
+
+	fn find(&self, id: &str) -> Result<Entry> {
+		self
+			.entries()
+			.into_iter()
+			.find(|entry| entry.id == id)
+			.ok_or_else(|| Error::NotFound(format!("Task {id} not found.")))
+	}
+
+	/// Adds an `id:` to the lines that have none, so their ids no longer
+	/// depend on their text. Returns the files that changed.
+	fn stamp(&mut self) -> Vec<usize> {
+		let mut changed = vec![];
+		for entry in self.entries() {
+			if entry.item.value("id").is_none() {
+				let line = &mut self.lines[entry.file][entry.line];
+				*line = format!("{} id:{}", line.trim_end(), entry.id);
+				if !changed.contains(&entry.file) {
+					changed.push(entry.file);
+				}
+			}
+		}
+		changed
+	}
+
+	fn save(&self, file: usize) -> anyhow::Result<()> {
+		let mut contents = self.lines[file].join("\n");
+		if !contents.is_empty() {
+			contents.push('\n');
+		}
+		write_file(&self.paths[file], &contents)
+	}
+}
+
+impl TodoTxtService {
+	pub(crate) fn new() -> Self {
+		Self {
+			config: read_config(CONFIG_KEY),
+		}
+	}
+
+	/// The `todo.txt` file chosen by the user.
+	pub(crate) fn path() -> Option<PathBuf> {
+		read_config::<TodoTxtConfig>(CONFIG_KEY).map(|config| config.path)
+	}
+
+	pub(crate) fn set_path(path: PathBuf) -> anyhow::Result<()> {
+		write_config(
+			CONFIG_KEY,
+			TodoTxtConfig {
+				path,
+				..Default::default()
+			},
+		)
+	}
+
+	/// The `done.txt` file chosen by the user, if any.
+	pub(crate) fn done_path() -> Option<PathBuf> {
+		read_config::<TodoTxtConfig>(CONFIG_KEY).and_then(|config| config.done_path)
+	}
+
+	pub(crate) fn set_done_path(
+		done_path: Option<PathBuf>,
+	) -> anyhow::Result<()> {
+		let config = read_config::<TodoTxtConfig>(CONFIG_KEY)
+			.context("No todo.txt file has been chosen.")?;
+		write_config(
+			CONFIG_KEY,
+			TodoTxtConfig {
+				done_path,
+				..config
+			},
+		)
+	}
+
+	pub(crate) fn clear_path() -> anyhow::Result<()> {
+		remove_config(CONFIG_KEY)
+	}
+
+	pub(crate) fn watched_paths() -> Vec<PathBuf> {
+		read_config::<TodoTxtConfig>(CONFIG_KEY)
+			.map(|config| vec![config.done_file(), config.path])
+			.unwrap_or_default()
+	}
+
+	fn config(&self) -> anyhow::Result<&TodoTxtConfig> {
+		self
+			.config
+			.as_ref()
+			.context("No todo.txt file has been chosen.")
+	}
+
+	fn files(&self) -> anyhow::Result<Files> {
+		let config = self.config()?;
+		let paths = [config.path.clone(), config.done_file()];
+		let lines = [read_lines(&paths[0])?, read_lines(&paths[1])?];
+		let mut files = Files { paths, lines };
+		for file in files.stamp() {
+			if let Err(err) = files.save(file) {
+				tracing::error!(
+					"Failed to write the task ids of {}: {err}",
+					files.paths[file].display()
+				);
+			}
+		}
+		Ok(files)
+	}
+
+	fn save_config(&self) -> anyhow::Result<()> {
+		write_config(CONFIG_KEY, self.config()?)
+	}
+
+	fn list(&self, project: &str) -> List {
+		List {
+			id: project.to_string(),
+			name: if project == INBOX_ID {
+				"Inbox".to_string()
+			} else {
+				project.to_string()
+			},
+			description: String::new(),
+			icon: None,
+			service: Service::TodoTxt,
+			sharing: Sharing::Private,
+			account: None,
+		}
+	}
+}
+
+fn read_lines(path: &std::path::Path) -> anyhow::Result<Vec<String>> {
+	Ok(read_file(path)?.lines().map(str::to_string).collect())
+}
+
+fn list_id(item: &TodoTxtItem) -> String {
+	item.project().unwrap_or(INBOX_ID).to_string()
+}
+
+/// Notes, sub-tasks and reminders have no place in a todo.txt line, a task
+/// with them is refused rather than losing them.
+fn check_supported(task: &Task) -> Result<()> {
+	if !task.notes.is_empty()
+		|| !task.sub_tasks.is_empty()
+		|| task.reminder_date.is_some()
+	{
+		return Err(Error::Unsupported(
+			"todo.txt tasks can't have notes, sub-tasks or reminders.".to_string(),
+		));
+	}
+	Ok(())
+}
+
+fn project(list_id: &str) -> Option<&str> {
+	(list_id != INBOX_ID).then_some(list_id)
+}
+
+/// Projects are single words, spaces in a list name become dashes.
+fn project_name(name: &str) -> String {
+	name.split_whitespace().collect::<Vec<&str>>().join("-")
+}
+
+#[async_trait]
+impl TodoProvider for TodoTxtService {
+	async fn handle_uri_params(&mut self, _uri: Url) -> Result<()> {
+		Ok(())
+	}
+
+	async fn login(&self) -> Result<()> {
+		Ok(())
+	}
+
+	async fn logout(&self) -> Result<()> {
+		Ok(Self::clear_path()?)
+	}
+
+	fn available(&self) -> bool {
+		self.config.is_some()
+	}
+
+	fn stream_support(&self) -> bool {
+		false
+	}
+
+	async fn read_tasks(&mut self) -> Result<Vec<Task>> {
+		Ok(
+			self
+				.files()?
+				.entries()
+				.into_iter()
+				.map(|entry| entry.item.to_task(entry.id, list_id(&entry.item)))
+				.collect(),
+		)
+	}
+
+	async fn get_tasks(
+		&mut self,
+		_parent_list: String,
+	) -> Result<Pin<Box<dyn Stream<Item = Result<Task>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
+	}
+
+	async fn read_tasks_from_list(
+		&mut self,
+		parent_list: String,
+	) -> Result<Vec<Task>> {
+		Ok(
+			self
+				.read_tasks()
+				.await?
+				.into_iter()
+				.filter(|task| task.parent == parent_list)
+				.collect(),
+		)
+	}
+
+	async fn read_task(
+		&mut self,
+		_task_list_id: String,
+		task_id: String,
+	) -> Result<Task> {
+		let entry = self.files()?.find(&task_id)?;
+		Ok(entry.item.to_task(entry.id, list_id(&entry.item)))
+	}
+
+	async fn create_task(&mut self, task: Task) -> Result<()> {
+		check_supported(&task)?;
+		let mut files = self.files()?;
+		let mut item = TodoTxtItem::default();
+		item.apply(&task, project(&task.parent));
+		files.lines[0].push(item.to_string());
+		Ok(files.save(0)?)
+	}
+
+	async fn update_task(&mut self, task: Task) -> Result<Task> {
+		check_supported(&task)?;
+		let mut files = self.files()?;
+		let mut entry = files.find(&task.id)?;
+		entry.item.apply(&task, project(&task.parent));
+		files.lines[entry.file][entry.line] = entry.item.to_string();
+		files.save(entry.file)?;
+		Ok(task)
+	}
+
+	async fn delete_task(
+		&mut self,
+		_list_id: String,
+		task_id: String,
+	) -> Result<()> {
+		let mut files = self.files()?;
+		let entry = files.find(&task_id)?;
+		files.lines[entry.file].remove(entry.line);
+		Ok(files.save(entry.file)?)
+	}
+
+	async fn read_lists(&mut self) -> Result<Vec<List>> {
+		if self.config.is_none() {
+			return Ok(vec![]);
+		}
+		let mut projects: BTreeSet<String> =
+			self.config()?.projects.iter().cloned().collect();
+		for entry in self.files()?.entries() {
+			projects.extend(entry.item.project().map(str::to_string));
+		}
+		Ok(
+			std::iter::once(INBOX_ID.to_string())
+				.chain(projects)
+				.map(|project| self.list(&project))
+				.collect(),
+		)
+	}
+
+	async fn get_lists(
+		&mut self,
+	) -> Result<Pin<Box<dyn Stream<Item = Result<List>> + Send>>> {
+		Err(Error::Unsupported(
+			"This service does not implement streams".to_string(),
+		))
+	}
+
+	async fn read_list(&mut self, id: String) -> Result<List> {
+		self
+			.read_lists()
+			.await?
+			.into_iter()
+			.find(|list| list.id == id)
+			.ok_or_else(|| Error::NotFound(format!("List {id} not found.")))
+	}
+
+	async fn create_list(&mut self, list: List) -> Result<List> {
+		let project = project_name(&list.name);
+		if project.is_empty() {
+			return Err(Error::InvalidData("A project needs a name.".to_string()));
+		}
+		let config = self
+			.config
+			.as_mut()
+			.context("No todo.txt file has been chosen.")?;
+		if !config.projects.contains(&project) {
+			config.projects.push(project.clone());
+		}
+		self.save_config()?;
+		Ok(self.list(&project))
+	}
+
+	async fn update_list(&mut self, list: List) -> Result<()> {
+		let Some(old) = project(&list.id) else {
+			return Err(Error::Unsupported(
+				"The inbox can't be renamed.".to_string(),
+			));
+		};
+		let new = project_name(&list.name);
+		if new.is_empty() || new == old {
+			return Ok(());
+		}
+		let mut files = self.files()?;
+		for file in 0..files.lines.len() {
+			let mut changed = false;
+			for line in files.lines[file].iter_mut() {
+				let Some(mut item) = TodoTxtItem::parse(line) else {
+					continue;
+				};
+				if item.project() != Some(old) {
+					continue;
+				}
+				for word in item.words.iter_mut() {
+					if word.strip_prefix('+') == Some(old) {
+						*word = format!("+{new}");
+					}
+				}
+				*line = item.to_string();
+				changed = true;
+			}
+			if changed {
+				files.save(file)?;
+			}
+		}
+		let config = self
+			.config
+			.as_mut()
+			.context("No todo.txt file has been chosen.")?;
+		config.projects.retain(|project| project != old);
+		config.projects.push(new);
+		Ok(self.save_config()?)
+	}
+
+	async fn delete_list(&mut self, id: String) -> Result<()> {
+		let Some(old) = project(&id) else {
+			return Err(Error::Unsupported(
+				"The inbox can't be deleted.".to_string(),
+			));
+		};
+		let mut files = self.files()?;
+		for file in 0..files.lines.len() {
+			let before = files.lines[file].len();
+			files.lines[file].retain(|line| {
+				TodoTxtItem::parse(line).is_none_or(|item| item.project() != Some(old))
+			});
+			if files.lines[file].len() != before {
+				files.save(file)?;
+			}
+		}
+		let config = self
+			.config
+			.as_mut()
+			.context("No todo.txt file has been chosen.")?;
+		config.projects.retain(|project| project != old);
+		Ok(self.save_config()?)
+	}
+}
@@ -1,11 +1,11 @@
 use std::pin::Pin;
 
-use anyhow::Result;
+use crate::Result;
 use async_trait::async_trait;
 use futures::Stream;
 use url::Url;
 
-use crate::models::{list::List, task::Task};
+use crate::models::{list::List, smart_list::SmartList, task::Task};
 
 #[async_trait]
 pub trait TodoProvider: Sync + Send {
@@ -13,10 +13,10 @@ pub trait TodoProvider: Sync + Send {
 	async fn handle_uri_params(&mut self, uri: Url) -> Result<()>;
 
 	/// Handles the login action.
-	fn login(&self) -> Result<()>;
+	async fn login(&self) -> Result<()>;
 
 	/// Handles the logout action.
-	fn logout(&self) -> Result<()>;
+	async fn logout(&self) -> Result<()>;
 
 	/// Checks to see if the service is available.
 	fn available(&self) -> bool;
@@ -28,10 +28,11 @@ pub trait TodoProvider: Sync + Send {
 	async fn read_tasks(&mut self) -> Result<Vec<Task>>;
 
 	/// Returns a stream of tasks from a list and sends it through a channel.
+	/// The stream ends with an error when a page of the list can't be read.
 	async fn get_tasks(
 		&mut self,
 		parent_list: String,
-	) -> Result<Pin<Box<dyn Stream<Item = Task> + Send>>>;
+	) -> Result<Pin<Box<dyn Stream<Item = Result<Task>> + Send>>>;
 
 	/// Read all the tasks from a list.
 	async fn read_tasks_from_list(
@@ -39,6 +40,19 @@ pub trait TodoProvider: Sync + Send {
 		parent_list: String,
 	) -> Result<Vec<Task>>;
 
+	/// Reads the tasks of a smart list, services that can filter tasks where
+	/// they are stored should do it there instead.
+	async fn read_smart_list(&mut self, list: SmartList) -> Result<Vec<Task>> {
+		Ok(
+			self
+				.read_tasks()
+				.await?
+				.into_iter()
+				.filter(|task| list.contains(task))
+				.collect(),
+		)
+	}
+
 	/// Reads a single task by its id.
 	async fn read_task(
 		&mut self,
@@ -65,7 +79,7 @@ pub trait TodoProvider: Sync + Send {
 	/// Returns a stream of lists and sends it through a channel.
 	async fn get_lists(
 		&mut self,
-	) -> Result<Pin<Box<dyn Stream<Item = List> + Send>>>;
+	) -> Result<Pin<Box<dyn Stream<Item = Result<List>> + Send>>>;
 
 	/// Read a single list from a service.
 	async fn read_list(&mut self, id: String) -> Result<List>;
@@ -29,6 +29,7 @@ menu = Menu
 preferences = Preferences
 keyboard-shortcuts = Keyboard shortcuts
 about-done = About Done
+profiles = Profiles
 quit = Quit
 close = Close
 
@@ -79,6 +80,23 @@ search = Search
 # Services
 msft-todo = Microsoft To Do
 msft-todo-description = To Do gives you focus, from work to play
+msft-account-type = Account type
+msft-account-type-description = The Microsoft accounts that can sign in
+msft-personal = Personal
+msft-work-or-school = Work or school
+msft-any-account = Personal, work or school
+msft-single-organization = A single organization
+msft-tenant = Tenant ID or domain
+msft-sign-in = Sign in without redirect
+msft-sign-in-description = For sandboxed or headless setups, through the browser or with a code
+msft-browser = Browser
+msft-code = Code
+msft-waiting = Waiting for the sign in to finish…
+msft-enter-code = Enter the code { $code } at { $uri }
+msft-signed-in = Signed in
+msft-sign-in-failed = The sign in failed: { $error }
+shared-by-me = Shared by you
+shared-with-me = Shared with you
 
 # Details
 details = Details
@@ -138,5 +156,175 @@ fri = Fri
 sat = Sat
 sun = Sun
 
+# Templates
+templates = Templates
+no-templates = No templates saved yet
+no-template = No template
+save-as-template = Save as template
+save-as-template-desc = Reuse this task and its sub tasks later
+template-saved = Template saved
+
+# Time tracking
+track-time = Track time
+estimate = Estimate
+estimate-desc = Expected minutes of work
+tracked = tracked
+time-report = Time report
+export-csv = Export as CSV
+total-tracked = Total tracked
+this-week = This week
+this-month = This month
+all-time = All time
+
+# Focus
+focus = Focus
+focus-desc = Run Pomodoro work and break cycles on this task
+start-focus = Start focus
+pause-focus = Pause or resume
+skip-phase = Skip phase
+stop-focus = Stop focus
+focus-work = Work
+focus-short-break = Short break
+focus-long-break = Long break
+focus-long-break-interval = Long break interval
+focus-long-break-interval-desc = Work sessions before a long break
+focus-completed = completed
+focus-interrupted = interrupted
+focus-work-over = Work session finished, time for a break
+focus-break-over = Break finished, back to work
+minutes = Minutes
+
+# Statistics
+statistics = Statistics
+refresh = Refresh
+summary = Summary
+total-tasks = Total tasks
+completed-tasks = Completed tasks
+overdue-tasks = Overdue tasks
+overdue = Overdue
+average-lead-time = Average time to complete
+completed-per-day = Completed per day
+week-of = Week of
+by-list = By list
+by-priority = By priority
+by-tag = By tag
+statistics-skipped = Not included, these couldn't be read: { $services }
+
+# Backups
+backups = Backups
+automatic-backups = Automatic backups
+automatic-backups-desc = Periodically save a copy of the local database
+backup-interval = Backup interval
+hours = Hours
+backups-kept = Backups kept
+backups-kept-desc = Older backups are deleted
+back-up-now = Back up now
+back-up = Back up
+backup-created = Backup created
+restore = Restore
+restore-backup = Restore a backup
+restore-backup-desc = The current database is backed up before restoring
+backup-restored = Backup restored
+no-backups = There are no backups yet
+tasks = tasks
+
+# Encryption
+local-database = Local database
+database-location = Location
+default-location = Default folder
+database-location-desc = A synced folder lets other computers use the same database
+reset = Reset
+database-moved = Database moved
+database-read-only = Read only, the database is in use on {$host}
+take-over = Take over
+encrypt-database = Encrypt local database
+encrypt-database-desc = The key is kept in the system keyring
+encrypt-database-unavailable = This build of Done can't encrypt the database
+database-encrypted = Database encrypted
+database-decrypted = Database decrypted
+
+# Profiles
+profiles-desc = Each profile has its own lists, services and preferences, opening one starts it in a new window
+new-profile = New profile
+default-profile = Default
+current-profile = In use
+open = Open
+remove-profile = Remove {$name}?
+remove-profile-desc = Its lists, services and preferences will be deleted
+
+# Workspace
+workspace = Workspace
+export-workspace = Export workspace
+export-workspace-desc = Save every list and task of every service to a JSON file
+export = Export
+workspace-exported = Workspace exported
+workspace-exported-without = Workspace exported without { $services }, they couldn't be read
+import-workspace = Import workspace
+import-workspace-desc = Recreate the lists and tasks of an exported workspace
+import-list = Import list
+import-list-desc = Add a list someone exported, importing a newer export again updates it
+list-already-imported = { $name } was imported before, update it with the changes in this file or import it as a separate copy?
+import-as-copy = Import as copy
+list-import-done = { $name }: { $created } tasks created, { $updated } updated and { $unchanged } unchanged
+import = Import
+import-into = Import into { $service }
+workspace-imported = Workspace imported
+workspace-import-preview = { $lists } lists, { $tasks } tasks and { $sub_tasks } sub tasks will be created
+workspace-import-done = { $lists } lists, { $tasks } tasks and { $sub_tasks } sub tasks were created
+workspace-import-list = { $name }: { $tasks } tasks
+workspace-import-skipped = The export is missing { $services }, they couldn't be read
+
+# File services
+choose = Choose
+disconnect = Disconnect
+no-file-chosen = No file chosen
+no-folder-chosen = No folder chosen
+archive = Completed tasks
+default-archive = done.txt next to todo.txt
+
+# Accounts
+add-account = Add account
+sign-out = Sign out
+sign-in-again = Sign in again
+sign-in-again-description = Your { $service } account needs you to sign in again
+
+# Errors
+retry = Retry
+task-not-added = The task couldn't be added
+task-not-saved = The task couldn't be saved
+task-not-removed = The task couldn't be removed
+tasks-not-loaded = The tasks couldn't be loaded
+list-not-created = The list couldn't be created
+lists-not-loaded = The lists couldn't be loaded
+list-not-exported = The list couldn't be exported
+list-not-read = The list couldn't be read
+list-not-imported = The list couldn't be imported
+template-not-saved = The template couldn't be saved
+timer-not-changed = The timer couldn't be changed
+estimate-not-saved = The estimate couldn't be saved
+backup-not-created = The backup couldn't be created
+backup-not-restored = The backup couldn't be restored
+backups-not-loaded = The backups couldn't be loaded
+encryption-not-changed = The database encryption couldn't be changed
+database-not-moved = The database couldn't be moved
+database-not-taken-over = The database couldn't be taken over
+workspace-not-exported = The workspace couldn't be exported
+workspace-not-read = The workspace couldn't be read
+workspace-not-imported = The workspace couldn't be imported
+service-not-connected = { $service } couldn't be connected
+service-not-disconnected = { $service } couldn't be disconnected
+archive-not-changed = The { $service } archive couldn't be changed
+time-report-not-loaded = The time report couldn't be loaded
+time-report-not-exported = The time report couldn't be exported
+profile-not-added = The profile couldn't be added
+profile-not-opened = { $profile } couldn't be opened
+profile-not-removed = { $profile } couldn't be removed
+troubleshooting = Troubleshooting
+error-log = Error log
+error-log-desc = Errors that happened since Done was opened
+view = View
+clear = Clear
+no-errors = No errors happened
+
 # Translator credits
 translator-credits = Eduardo Flores <edfloreshz@gmail.com> \n Sergio Varela <sergiovg01@outlook.com> \n Jürgen Benvenuti <gastornis@posteo.org> \n Sabri Ünal<libreajans@gmail.com> \n Isabella Breder \n albanobattistella
\ No newline at end of file
@@ -1,6 +1,7 @@
 use std::str::FromStr;
 
 use adw::glib::Propagation;
+use futures::StreamExt;
 use relm4::{
 	actions::{ActionGroupName, RelmAction, RelmActionGroup},
 	adw,
@@ -23,13 +24,18 @@ use relm4::{
 };
 use relm4_icons::icon_name;
 
-use done_core::service::Service;
+use done_core::{
+	service::{Service, Services},
+	services::{files::watch_services, local::database::Database},
+};
 
 use crate::{
 	app::{
 		components::{
-			content::ContentOutput, list_sidebar::ListSidebarOutput,
-			preferences::PreferencesComponentOutput,
+			content::ContentOutput,
+			list_sidebar::ListSidebarOutput,
+			preferences::{PreferencesComponentInput, PreferencesComponentOutput},
+			profiles::ProfilesOutput,
 		},
 		config::{info::PROFILE, setup},
 	},
@@ -42,8 +48,10 @@ use self::{
 		content::{ContentInput, ContentModel},
 		list_sidebar::{ListSidebarInput, ListSidebarModel},
 		preferences::PreferencesComponentModel,
+		profiles::{ProfilesInput, ProfilesModel},
+		statistics::{StatisticsInput, StatisticsModel},
 	},
-	models::sidebar_list::SidebarList,
+	models::{failure::Failure, sidebar_list::SidebarList},
 };
 
 pub mod components;
@@ -55,6 +63,8 @@ new_action_group!(pub(super) WindowActionGroup, "win");
 new_stateless_action!(pub(super) ShortcutsAction, WindowActionGroup, "show-help-overlay");
 new_stateless_action!(AboutAction, WindowActionGroup, "about");
 new_stateless_action!(PreferencesAction, WindowActionGroup, "preferences");
+new_stateless_action!(StatisticsAction, WindowActionGroup, "statistics");
+new_stateless_action!(ProfilesAction, WindowActionGroup, "profiles");
 new_stateless_action!(QuitAction, WindowActionGroup, "quit");
 
 pub struct Done {
@@ -62,7 +72,11 @@ pub struct Done {
 	content_controller: AsyncController<ContentModel>,
 	about_dialog: Controller<AboutDialog>,
 	preferences: AsyncController<PreferencesComponentModel>,
+	statistics: AsyncController<StatisticsModel>,
+	profiles: AsyncController<ProfilesModel>,
 	startup_failed: bool,
+	/// The machine writing to the local database, when it's read only here.
+	database_locked_by: Option<String>,
 }
 
 #[derive(Debug)]
@@ -70,11 +84,16 @@ pub enum AppInput {
 	ServiceDisabled(Service),
 	ListSelected(SidebarList, Service),
 	ReloadSidebar(Service),
+	ServiceChanged(Service),
+	ShowError(Failure),
+	ShowNotice(String),
 	ExpandSubTasks(bool),
 	CollapseSidebar,
 	CleanContent,
 	Refresh,
+	FlushEdits,
 	Quit,
+	Closed,
 }
 
 #[relm4::component(pub async)]
@@ -93,6 +112,11 @@ impl AsyncComponent for Done {
 				sender.input(AppInput::Quit);
 				Propagation::Stop
 			},
+			connect_is_active_notify[sender] => move |window| {
+				if !window.is_active() {
+					sender.input(AppInput::FlushEdits);
+				}
+			},
 
 			#[wrap(Some)]
 			set_help_overlay: shortcuts = &gtk::Builder::from_resource(
@@ -259,11 +283,14 @@ impl AsyncComponent for Done {
 						AppInput::ListSelected(list, service)
 					},
 					ListSidebarOutput::CleanContent => AppInput::CleanContent,
+					ListSidebarOutput::Error(failure) => AppInput::ShowError(failure),
+					ListSidebarOutput::Notice(message) => AppInput::ShowNotice(message),
 				}),
 			content_controller: ContentModel::builder().launch(None).forward(
 				sender.input_sender(),
 				|output| match output {
 					ContentOutput::CollapseSidebar => AppInput::CollapseSidebar,
+					ContentOutput::Closed => AppInput::Closed,
 				},
 			),
 			about_dialog,
@@ -276,15 +303,47 @@ impl AsyncComponent for Done {
 					PreferencesComponentOutput::ExpandSubTasks(expand) => {
 						AppInput::ExpandSubTasks(expand)
 					},
+					PreferencesComponentOutput::DatabaseRestored => {
+						AppInput::ReloadSidebar(Service::Computer)
+					},
+					PreferencesComponentOutput::DatabaseMoved => {
+						AppInput::ServiceChanged(Service::Computer)
+					},
+					PreferencesComponentOutput::WorkspaceImported(service) => {
+						AppInput::ReloadSidebar(service)
+					},
+					PreferencesComponentOutput::ListImported(service) => {
+						AppInput::ReloadSidebar(service)
+					},
+					PreferencesComponentOutput::ServiceConnected(service) => {
+						AppInput::ReloadSidebar(service)
+					},
+				},
+			),
+			statistics: StatisticsModel::builder().launch(()).detach(),
+			profiles: ProfilesModel::builder().launch(()).forward(
+				sender.input_sender(),
+				|message| match message {
+					ProfilesOutput::Switched => AppInput::Quit,
 				},
 			),
 			startup_failed: false,
+			database_locked_by: None,
 		};
 
 		match setup::init_services() {
 			Ok(_) => (),
 			Err(_) => model.startup_failed = true,
 		};
+		model.check_database_lock(&sender);
+
+		let watcher_sender = sender.clone();
+		relm4::tokio::spawn(async move {
+			let mut changes = watch_services();
+			while let Some(service) = changes.next().await {
+				watcher_sender.input(AppInput::ServiceChanged(service));
+			}
+		});
 
 		let widgets = view_output!();
 
@@ -311,6 +370,20 @@ impl AsyncComponent for Done {
 			})
 		};
 
+		let statistics_action = {
+			let sender = model.statistics.sender().clone();
+			RelmAction::<StatisticsAction>::new_stateless(move |_| {
+				sender.send(StatisticsInput::Open).unwrap_or_default();
+			})
+		};
+
+		let profiles_action = {
+			let sender = model.profiles.sender().clone();
+			RelmAction::<ProfilesAction>::new_stateless(move |_| {
+				sender.send(ProfilesInput::Open).unwrap_or_default();
+			})
+		};
+
 		let quit_action = {
 			let sender = sender.clone();
 			RelmAction::<QuitAction>::new_stateless(move |_| {
@@ -324,6 +397,8 @@ impl AsyncComponent for Done {
 		actions.add_action(shortcuts_action);
 		actions.add_action(about_action);
 		actions.add_action(preferences_action);
+		actions.add_action(statistics_action);
+		actions.add_action(profiles_action);
 		actions.add_action(quit_action);
 
 		root.insert_action_group(
@@ -342,7 +417,22 @@ impl AsyncComponent for Done {
 		_root: &Self::Root,
 	) {
 		match message {
-			AppInput::Quit => main_adw_application().quit(),
+			AppInput::Quit => self
+				.content_controller
+				.sender()
+				.send(ContentInput::Close)
+				.unwrap_or_default(),
+			AppInput::Closed => {
+				if let Err(err) = Services::close_database() {
+					tracing::error!("Failed to close the database: {err}");
+				}
+				main_adw_application().quit()
+			},
+			AppInput::FlushEdits => self
+				.content_controller
+				.sender()
+				.send(ContentInput::FlushAll)
+				.unwrap_or_default(),
 			AppInput::Refresh => {
 				match setup::refresh() {
 					Ok(_) => main_adw_application().quit(),
@@ -365,6 +455,16 @@ impl AsyncComponent for Done {
 					.send(ContentInput::SelectList(list, service))
 					.unwrap_or_default();
 			},
+			AppInput::ShowError(failure) => self
+				.content_controller
+				.sender()
+				.send(ContentInput::ShowError(failure))
+				.unwrap_or_default(),
+			AppInput::ShowNotice(message) => self
+				.content_controller
+				.sender()
+				.send(ContentInput::ShowNotice(message))
+				.unwrap_or_default(),
 			AppInput::CleanContent => self
 				.content_controller
 				.sender()
@@ -382,7 +482,50 @@ impl AsyncComponent for Done {
 				.sender()
 				.send(ListSidebarInput::ReloadSidebar(service))
 				.unwrap_or_default(),
+			AppInput::ServiceChanged(service) => {
+				self
+					.task_list_sidebar_controller
+					.sender()
+					.send(ListSidebarInput::ServiceChanged(service))
+					.unwrap_or_default();
+				self
+					.content_controller
+					.sender()
+					.send(ContentInput::ServiceChanged(service))
+					.unwrap_or_default();
+				if service == Service::Computer {
+					self.check_database_lock(&sender);
+					// Smart lists are read from the local database.
+					self
+						.content_controller
+						.sender()
+						.send(ContentInput::ServiceChanged(Service::Smart))
+						.unwrap_or_default();
+					self
+						.preferences
+						.sender()
+						.send(PreferencesComponentInput::DatabaseChanged)
+						.unwrap_or_default();
+				}
+			},
 		}
 		self.update_view(widgets, sender)
 	}
 }
+
+impl Done {
+	/// Tells the user when another machine starts writing to the local
+	/// database, which is read only here until it stops.
+	fn check_database_lock(&mut self, sender: &AsyncComponentSender<Self>) {
+		let locked_by = Database::new(Services::app_id()).locked_by();
+		if locked_by != self.database_locked_by {
+			if let Some(host) = &locked_by {
+				sender.input(AppInput::ShowError(Failure::new(
+					fl!("database-read-only", host = host.as_str()),
+					"the lock is held by another machine",
+				)));
+			}
+			self.database_locked_by = locked_by;
+		}
+	}
+}
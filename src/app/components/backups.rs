@@ -0,0 +1,181 @@
+use chrono::Local;
+use relm4::{
+	adw,
+	adw::prelude::{ActionRowExt, PreferencesRowExt},
+	component::{AsyncComponent, AsyncComponentParts},
+	gtk::{
+		self,
+		prelude::{BoxExt, ButtonExt, GtkWindowExt, WidgetExt},
+		traits::OrientableExt,
+	},
+	AsyncComponentSender, RelmWidgetExt,
+};
+
+use done_core::{models::backup::Snapshot, services::local::backups::Backups};
+
+use crate::app::models::failure::Failure;
+use crate::fl;
+
+pub struct BackupsModel {
+	snapshots: Vec<Snapshot>,
+	rows: gtk::ListBox,
+	overlay: adw::ToastOverlay,
+}
+
+#[derive(Debug)]
+pub enum BackupsInput {
+	Open,
+	Refresh,
+	Restore(usize),
+}
+
+#[derive(Debug)]
+pub enum BackupsOutput {
+	Restored,
+}
+
+#[relm4::component(pub async)]
+impl AsyncComponent for BackupsModel {
+	type CommandOutput = ();
+	type Input = BackupsInput;
+	type Output = BackupsOutput;
+	type Init = ();
+
+	view! {
+		#[root]
+		adw::Window {
+			set_hide_on_close: true,
+			set_default_width: 420,
+			set_default_height: 500,
+			set_modal: true,
+
+			#[local_ref]
+			overlay -> adw::ToastOverlay {
+				#[wrap(Some)]
+				set_child = &gtk::Box {
+					set_orientation: gtk::Orientation::Vertical,
+					adw::HeaderBar {
+						set_show_end_title_buttons: true,
+						set_css_classes: &["flat"],
+						set_title_widget: Some(&gtk::Label::new(Some(fl!("restore-backup")))),
+					},
+					gtk::Box {
+						set_orientation: gtk::Orientation::Vertical,
+						set_margin_all: 20,
+						set_spacing: 10,
+						gtk::Label {
+							set_css_classes: &["dim-label"],
+							set_wrap: true,
+							set_label: fl!("restore-backup-desc"),
+						},
+						gtk::Label {
+							#[watch]
+							set_visible: model.snapshots.is_empty(),
+							set_vexpand: true,
+							set_label: fl!("no-backups"),
+						},
+						gtk::ScrolledWindow {
+							#[watch]
+							set_visible: !model.snapshots.is_empty(),
+							set_vexpand: true,
+							#[local_ref]
+							rows -> gtk::ListBox {
+								set_css_classes: &["boxed-list"],
+								set_selection_mode: gtk::SelectionMode::None,
+								set_valign: gtk::Align::Start,
+							},
+						},
+					}
+				}
+			}
+		}
+	}
+
+	async fn init(
+		_init: Self::Init,
+		root: Self::Root,
+		sender: AsyncComponentSender<Self>,
+	) -> AsyncComponentParts<Self> {
+		let model = Self {
+			snapshots: vec![],
+			rows: gtk::ListBox::default(),
+			overlay: adw::ToastOverlay::default(),
+		};
+
+		let rows = &model.rows;
+		let overlay = &model.overlay;
+
+		let widgets = view_output!();
+
+		AsyncComponentParts { model, widgets }
+	}
+
+	async fn update(
+		&mut self,
+		message: Self::Input,
+		sender: AsyncComponentSender<Self>,
+		root: &Self::Root,
+	) {
+		match message {
+			BackupsInput::Open => {
+				sender.input(BackupsInput::Refresh);
+				root.present();
+			},
+			BackupsInput::Refresh => {
+				match Backups::new().and_then(|backups| backups.snapshots()) {
+					Ok(snapshots) => self.snapshots = snapshots,
+					Err(err) => {
+						let retry = sender.input_sender().clone();
+						self.overlay.add_toast(
+							Failure::new(fl!("backups-not-loaded"), err)
+								.with_retry(move || retry.emit(BackupsInput::Refresh))
+								.toast(),
+						);
+					},
+				}
+				self.rows.remove_all();
+				for (index, snapshot) in self.snapshots.iter().enumerate() {
+					let row = adw::ActionRow::new();
+					row.set_title(
+						&snapshot
+							.created_date_time
+							.with_timezone(&Local)
+							.format("%m/%d/%Y %H:%M")
+							.to_string(),
+					);
+					if let Some(task_count) = snapshot.task_count {
+						row.set_subtitle(&format!("{task_count} {}", fl!("tasks")));
+					}
+					let button = gtk::Button::with_label(fl!("restore"));
+					button.set_valign(gtk::Align::Center);
+					let sender = sender.clone();
+					button.connect_clicked(move |_| {
+						sender.input(BackupsInput::Restore(index));
+					});
+					row.add_suffix(&button);
+					self.rows.append(&row);
+				}
+			},
+			BackupsInput::Restore(index) => {
+				let Some(snapshot) = self.snapshots.get(index) else {
+					return;
+				};
+				match Backups::new().and_then(|mut backups| backups.restore(snapshot)) {
+					Ok(_) => {
+						sender.output(BackupsOutput::Restored).unwrap_or_default();
+						root.close();
+					},
+					Err(err) => {
+						let retry = sender.input_sender().clone();
+						self.overlay.add_toast(
+							Failure::new(fl!("backup-not-restored"), err)
+								.with_retry(move || retry.emit(BackupsInput::Restore(index)))
+								.toast(),
+						);
+					},
+				}
+				sender.input(BackupsInput::Refresh);
+			},
+		}
+	}
+}
@@ -1,42 +1,77 @@
-use chrono::{DateTime, Utc};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
 use futures::StreamExt;
 use relm4::component::{
-	AsyncComponent, AsyncComponentParts, AsyncComponentSender,
+	AsyncComponent, AsyncComponentController, AsyncComponentParts,
+	AsyncComponentSender, AsyncController,
 };
-use relm4::factory::AsyncFactoryVecDeque;
 use relm4::gtk::traits::ButtonExt;
-use relm4::prelude::DynamicIndex;
 use relm4::{
 	adw,
 	adw::prelude::NavigationPageExt,
 	gtk,
-	gtk::prelude::{BoxExt, OrientableExt, WidgetExt},
+	gtk::prelude::{
+		BoxExt, Cast, CastNone, ListItemExt, ListModelExt, OrientableExt, WidgetExt,
+	},
+	gtk::{gio, glib},
 };
 use relm4::{
 	tokio, Component, ComponentController, Controller, JoinHandle, RelmWidgetExt,
 };
 use relm4_icons::icon_name;
 
-use done_core::models::status::Status;
+use done_core::models::list::List;
 use done_core::models::task::Task;
+use done_core::models::time_entry::TaskTime;
 use done_core::service::Service;
+use done_core::services::local::time_tracking::TimeTracker;
 
 use crate::app::components::task_input::TaskInputOutput;
-use crate::app::factories::task::{TaskInit, TaskInput, TaskModel, TaskOutput};
+use crate::app::factories::task::{TaskInput, TaskItem, TaskModel, TaskOutput};
+use crate::app::models::failure::Failure;
 use crate::app::models::sidebar_list::SidebarList;
 use crate::fl;
 
+use super::focus::{FocusInput, FocusModel};
 use super::task_input::{TaskInputInput, TaskInputModel};
 use super::welcome::WelcomeComponent;
 
+/// How long typing has to pause before an edit is saved.
+const SAVE_DELAY: Duration = Duration::from_millis(800);
+/// How long closing waits for pending edits to be saved.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How many tasks of a stream are added to the list at a time.
+const PAGE_SIZE: usize = 100;
+
 pub struct ContentModel {
-	task_factory: AsyncFactoryVecDeque<TaskModel>,
+	/// The tasks of the list as [`TaskItem`]s, the list view only has rows
+	/// for the ones scrolled into view.
+	tasks: gio::ListStore,
+	/// The rows created by the list view, reused for other tasks as the list
+	/// scrolls.
+	rows: Rc<RefCell<Vec<AsyncController<TaskModel>>>>,
 	task_entry: Controller<TaskInputModel>,
+	focus: Controller<FocusModel>,
 	welcome: Controller<WelcomeComponent>,
 	state: ContentState,
 	service: Service,
 	parent_list: Option<SidebarList>,
 	handle: Option<JoinHandle<()>>,
+	update_toast: Option<adw::Toast>,
+	/// Edits waiting to be saved, by task id. Each edit holds the whole task,
+	/// so later edits replace earlier ones.
+	pending: HashMap<String, PendingEdit>,
+	generation: u64,
+}
+
+struct PendingEdit {
+	task: Task,
+	service: Service,
+	/// Tells the timer of the latest edit apart from older ones.
+	generation: u64,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -50,11 +85,25 @@ pub enum ContentState {
 #[derive(Debug)]
 pub enum ContentInput {
 	AddTask(Task),
-	RemoveTask(DynamicIndex),
+	RemoveTask(Task),
 	UpdateTask(Task),
-	LoadTask(Task),
+	SetTime(String, TaskTime),
+	/// Sends a change to a service, marking the row when it fails.
+	Write(TaskWrite, Service),
+	ShowError(Failure),
+	ShowNotice(String),
+	/// Saves an edit if no other edit of the task came after it.
+	SaveTask(String, u64),
+	/// Saves the pending edit of a task right away.
+	FlushTask(String),
+	FlushAll,
+	/// Saves pending edits before the app quits.
+	Close,
+	LoadPage(Vec<Task>, HashMap<String, TaskTime>),
+	Focus(Task),
 	SelectList(SidebarList, Service),
 	ServiceDisabled(Service),
+	ServiceChanged(Service),
 	LoadTasks(SidebarList, Service),
 	SetState(ContentState),
 	ExpandSubTasks(bool),
@@ -65,6 +114,7 @@ pub enum ContentInput {
 #[derive(Debug)]
 pub enum ContentOutput {
 	CollapseSidebar,
+	Closed,
 }
 
 #[relm4::component(pub async)]
@@ -96,6 +146,7 @@ impl AsyncComponent for ContentModel {
 					set_tooltip: fl!("search"),
 					set_icon_name: icon_name::LOUPE,
 				},
+				pack_end: model.focus.widget(),
 			},
 			#[name(overlay)]
 			#[wrap(Some)]
@@ -206,7 +257,7 @@ impl AsyncComponent for ContentModel {
 												gtk::Stack {
 													set_transition_duration: 250,
 													set_transition_type: gtk::StackTransitionType::Crossfade,
-													if model.task_factory.is_empty() {
+													if model.tasks.n_items() == 0 {
 														gtk::Box {
 															set_vexpand: true,
 															set_hexpand: true,
@@ -238,14 +289,13 @@ impl AsyncComponent for ContentModel {
 														}
 													} else {
 														gtk::ScrolledWindow {
-															set_visible: !model.task_factory.is_empty(),
 															#[watch]
 															set_visible: model.state == ContentState::TasksLoaded,
 															set_vexpand: true,
 															set_hexpand: true,
 															#[local_ref]
-															list_box -> adw::PreferencesGroup {
-																set_css_classes: &["boxed-list"],
+															list_view -> gtk::ListView {
+																set_css_classes: &["background"],
 																set_valign: gtk::Align::Fill,
 																set_margin_all: 5,
 															},
@@ -273,27 +323,93 @@ impl AsyncComponent for ContentModel {
 		root: Self::Root,
 		sender: AsyncComponentSender<Self>,
 	) -> AsyncComponentParts<Self> {
+		let tasks = gio::ListStore::new::<glib::BoxedAnyObject>();
+		let rows: Rc<RefCell<Vec<AsyncController<TaskModel>>>> = Rc::default();
+		let factory = gtk::SignalListItemFactory::new();
+		{
+			let (rows, sender) = (rows.clone(), sender.clone());
+			factory.connect_setup(move |_, item| {
+				let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+					return;
+				};
+				let row = TaskModel::builder().launch(()).forward(
+					sender.input_sender(),
+					|output| match output {
+						TaskOutput::Remove(task) => ContentInput::RemoveTask(task),
+						TaskOutput::UpdateTask(task) => ContentInput::UpdateTask(task),
+						TaskOutput::Time(id, time) => ContentInput::SetTime(id, time),
+						TaskOutput::Focus(task) => ContentInput::Focus(task),
+						TaskOutput::Flush(task_id) => ContentInput::FlushTask(task_id),
+						TaskOutput::Error(failure) => ContentInput::ShowError(failure),
+						TaskOutput::Notice(message) => ContentInput::ShowNotice(message),
+					},
+				);
+				item.set_activatable(false);
+				item.set_selectable(false);
+				item.set_child(Some(row.widget()));
+				rows.borrow_mut().push(row);
+			});
+		}
+		{
+			let rows = rows.clone();
+			factory.connect_bind(move |_, item| {
+				let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+					return;
+				};
+				let (Some(child), Some(task)) = (
+					item.child(),
+					item.item().and_downcast::<glib::BoxedAnyObject>(),
+				) else {
+					return;
+				};
+				let rows = rows.borrow();
+				if let Some(row) = rows
+					.iter()
+					.find(|row| row.widget().upcast_ref::<gtk::Widget>() == &child)
+				{
+					row
+						.emit(TaskInput::Bind(Box::new(task.borrow::<TaskItem>().clone())));
+				}
+			});
+		}
+		{
+			let rows = rows.clone();
+			factory.connect_teardown(move |_, item| {
+				let Some(child) = item
+					.downcast_ref::<gtk::ListItem>()
+					.and_then(|item| item.child())
+				else {
+					return;
+				};
+				rows
+					.borrow_mut()
+					.retain(|row| row.widget().upcast_ref::<gtk::Widget>() != &child);
+			});
+		}
+		let list_view = gtk::ListView::new(
+			Some(gtk::NoSelection::new(Some(tasks.clone()))),
+			Some(factory),
+		);
+
 		let model = ContentModel {
-			task_factory: AsyncFactoryVecDeque::builder()
-				.launch(adw::PreferencesGroup::default())
-				.forward(sender.input_sender(), |output| match output {
-					TaskOutput::Remove(index) => ContentInput::RemoveTask(index),
-					TaskOutput::UpdateTask(task) => ContentInput::UpdateTask(task),
-				}),
+			tasks,
+			rows,
 			task_entry: TaskInputModel::builder()
 				.launch(SidebarList::default())
 				.forward(sender.input_sender(), |message| match message {
 					TaskInputOutput::AddTask(task) => ContentInput::AddTask(task),
 				}),
+			focus: FocusModel::builder().launch(()).detach(),
 			welcome: WelcomeComponent::builder().launch(()).detach(),
 			state: ContentState::Unselected,
 			service: Service::Smart,
 			parent_list: None,
 			handle: None,
+			update_toast: None,
+			pending: HashMap::new(),
+			generation: 0,
 		};
 
-		let list_box = model.task_factory.widget();
-
 		let widgets = view_output!();
 
 		AsyncComponentParts { model, widgets }
@@ -310,64 +426,110 @@ impl AsyncComponent for ContentModel {
 			ContentInput::Clean => self.state = ContentState::Unselected,
 			ContentInput::SetState(state) => self.state = state,
 			ContentInput::ExpandSubTasks(expand) => {
-				println!("{}", self.task_factory.len());
-				for (i, _) in self.task_factory.iter().enumerate() {
-					self.task_factory.send(i, TaskInput::ExpandSubTask(expand))
+				for row in self.rows.borrow().iter() {
+					row.emit(TaskInput::ExpandSubTask(expand));
 				}
 			},
 			ContentInput::CollapseSidebar => sender
 				.output(ContentOutput::CollapseSidebar)
 				.unwrap_or_default(),
-			ContentInput::LoadTask(task) => {
-				if let SidebarList::Custom(parent) = &self.parent_list.as_ref().unwrap()
-				{
-					let mut guard = self.task_factory.guard();
-					guard.push_back(TaskInit::new(task, parent.clone()));
-					self.state = ContentState::TasksLoaded;
-				}
-			},
+			ContentInput::LoadPage(tasks, times) => self.append(tasks, times),
+			ContentInput::Focus(task) => self.focus.emit(FocusInput::Start(task)),
 			ContentInput::AddTask(mut task) => {
 				if let SidebarList::Custom(parent) = &self.parent_list.as_ref().unwrap()
 				{
 					task.parent = parent.id.clone();
-					let mut service = self.service.get_service();
-					match service.create_task(task.clone()).await {
-						Ok(_) => {
-							self
-								.task_factory
-								.guard()
-								.push_back(TaskInit::new(task.clone(), parent.clone()));
-							self.state = ContentState::TasksLoaded;
-						},
-						Err(err) => {
-							tracing::error!("An error ocurred: {err}");
-						},
-					}
+					sender
+						.input(ContentInput::Write(TaskWrite::Create(task), self.service));
 				}
 			},
-			ContentInput::RemoveTask(index) => {
-				let mut guard = self.task_factory.guard();
-				if let Some(task) = guard.get(index.current_index()) {
-					let mut service = self.service.get_service();
-					match service
-						.delete_task(task.task.clone().parent, task.task.clone().id)
-						.await
-					{
-						Ok(_) => {
-							guard.remove(index.current_index());
-						},
-						Err(err) => tracing::error!("An error ocurred: {err}"),
-					}
+			ContentInput::RemoveTask(task) => {
+				self.pending.remove(&task.id);
+				sender
+					.input(ContentInput::Write(TaskWrite::Delete(task), self.service));
+			},
+			ContentInput::SetTime(id, time) => {
+				if let Some(item) = self.item(&id) {
+					item.borrow_mut::<TaskItem>().time = time;
 				}
 			},
 			ContentInput::UpdateTask(task) => {
-				let mut service = self.service.get_service();
-				match service.update_task(task).await {
-					Ok(task) => tracing::info!("Task {} successfully saved.", task.id),
-					Err(err) => tracing::error!("An error ocurred: {err}"),
+				// The row may be reused for another task before the edit is saved.
+				if let Some(item) = self.item(&task.id) {
+					item.borrow_mut::<TaskItem>().task = task.clone();
+				}
+				self.generation += 1;
+				let (id, generation) = (task.id.clone(), self.generation);
+				self.pending.insert(
+					id.clone(),
+					PendingEdit {
+						task,
+						service: self.service,
+						generation,
+					},
+				);
+				let sender = sender.clone();
+				tokio::spawn(async move {
+					tokio::time::sleep(SAVE_DELAY).await;
+					sender.input(ContentInput::SaveTask(id, generation));
+				});
+			},
+			ContentInput::Write(write, service) => {
+				self.write(&widgets.overlay, &sender, write, service).await;
+			},
+			ContentInput::SaveTask(id, generation) => {
+				if self
+					.pending
+					.get(&id)
+					.is_some_and(|edit| edit.generation == generation)
+				{
+					sender.input(ContentInput::FlushTask(id));
+				}
+			},
+			ContentInput::FlushTask(id) => {
+				if let Some(edit) = self.pending.remove(&id) {
+					self
+						.write(
+							&widgets.overlay,
+							&sender,
+							TaskWrite::Update(edit.task),
+							edit.service,
+						)
+						.await;
 				}
 			},
+			ContentInput::FlushAll => {
+				for id in self.pending.keys().cloned().collect::<Vec<String>>() {
+					sender.input(ContentInput::FlushTask(id));
+				}
+			},
+			ContentInput::Close => {
+				let pending = std::mem::take(&mut self.pending);
+				let flush = async {
+					for edit in pending.into_values() {
+						self
+							.write(
+								&widgets.overlay,
+								&sender,
+								TaskWrite::Update(edit.task),
+								edit.service,
+							)
+							.await;
+					}
+				};
+				if tokio::time::timeout(CLOSE_TIMEOUT, flush).await.is_err() {
+					tracing::error!("Not every edit was saved before closing.");
+				}
+				sender.output(ContentOutput::Closed).unwrap_or_default();
+			},
+			ContentInput::ShowError(failure) => {
+				widgets.overlay.add_toast(failure.toast());
+			},
+			ContentInput::ShowNotice(message) => {
+				widgets.overlay.add_toast(adw::Toast::new(&message));
+			},
 			ContentInput::SelectList(list, service) => {
+				sender.input(ContentInput::FlushAll);
 				self.state = ContentState::Loading;
 				if let Some(handle) = &self.handle {
 					handle.abort()
@@ -375,120 +537,112 @@ impl AsyncComponent for ContentModel {
 				sender.input(ContentInput::LoadTasks(list, service));
 			},
 			ContentInput::LoadTasks(list, service) => {
-				let mut guard = self.task_factory.guard();
-				guard.clear();
+				self.tasks.remove_all();
 				self.service = service;
+				self.parent_list = Some(list.clone());
 
-				let mut service = service.get_service();
-				if let Ok(tasks) = service.read_tasks().await {
-					match &list {
-						SidebarList::All => {
-							self.parent_list = Some(SidebarList::All);
-							for task in tasks {
-								guard.push_back(TaskInit::new(
-									task.clone(),
-									service.read_list(task.parent).await.unwrap(),
-								));
-							}
-							self.state = ContentState::TasksLoaded;
-						},
-						SidebarList::Today => {
-							self.parent_list = Some(SidebarList::Today);
-							for task in tasks.iter().filter(|task| {
-								task.today
-									|| task.due_date.is_some()
-										&& task.due_date.unwrap().date_naive()
-											== Utc::now().date_naive()
-							}) {
-								guard.push_back(TaskInit::new(
-									task.clone(),
-									service.read_list(task.parent.clone()).await.unwrap(),
-								));
-							}
-							self.state = ContentState::TasksLoaded;
-						},
-						SidebarList::Starred => {
-							self.parent_list = Some(SidebarList::Starred);
-							for task in tasks.iter().filter(|task| task.favorite) {
-								guard.push_back(TaskInit::new(
-									task.clone(),
-									service.read_list(task.parent.clone()).await.unwrap(),
-								));
-							}
-							self.state = ContentState::TasksLoaded;
-						},
-						SidebarList::Next7Days => {
-							self.parent_list = Some(SidebarList::Next7Days);
-							for task in tasks.iter().filter(|task: &&Task| {
-								task.due_date.is_some()
-									&& is_within_next_7_days(task.due_date.unwrap())
-							}) {
-								guard.push_back(TaskInit::new(
-									task.clone(),
-									service.read_list(task.parent.clone()).await.unwrap(),
-								));
-							}
-							self.state = ContentState::TasksLoaded;
-						},
-						SidebarList::Done => {
-							self.parent_list = Some(SidebarList::Done);
-							for task in tasks
-								.iter()
-								.filter(|task: &&Task| task.status == Status::Completed)
-							{
-								guard.push_back(TaskInit::new(
-									task.clone(),
-									service.read_list(task.parent.clone()).await.unwrap(),
-								));
-							}
-							self.state = ContentState::TasksLoaded;
-						},
-						SidebarList::Custom(list) => {
-							self.parent_list = Some(SidebarList::Custom(list.clone()));
-							let sender_clone = sender.clone();
-							let list_clone = list.clone();
-							let mut service = self.service.get_service();
-							self.state = ContentState::Loading;
-							if service.stream_support() {
-								self.handle = Some(tokio::spawn(async move {
-									match service.get_tasks(list_clone.id.clone()).await {
-										Ok(mut stream) => {
-											let first = stream.next().await;
-											if let Some(task) = first {
-												sender_clone.input(ContentInput::LoadTask(task));
-												while let Some(task) = stream.next().await {
-													sender_clone.input(ContentInput::LoadTask(task));
+				let mut provider = service.get_service();
+				match &list {
+					SidebarList::Custom(parent) => {
+						let sender_clone = sender.clone();
+						let list_clone = list.clone();
+						let parent = parent.clone();
+						self.state = ContentState::Loading;
+						if provider.stream_support() {
+							self.handle =
+								Some(tokio::spawn(async move {
+									match provider.get_tasks(parent.id.clone()).await {
+										Ok(stream) => {
+											let mut pages = stream.ready_chunks(PAGE_SIZE);
+											let mut loaded = false;
+											while let Some(page) = pages.next().await {
+												let mut tasks = vec![];
+												let mut error = None;
+												for task in page {
+													match task {
+														Ok(task) => tasks.push(task),
+														Err(err) => {
+															error = Some(err);
+															break;
+														},
+													}
+												}
+												if !tasks.is_empty() {
+													loaded = true;
+													let times = task_times(&tasks).await;
+													sender_clone
+														.input(ContentInput::LoadPage(tasks, times));
 												}
-											} else {
+												// The stream ends at the first page it can't read.
+												if let Some(err) = error {
+													sender_clone.input(ContentInput::ShowError(
+														load_failure(
+															&sender_clone,
+															err,
+															list_clone.clone(),
+															service,
+														),
+													));
+													break;
+												}
+											}
+											if !loaded {
 												sender_clone
 													.input(ContentInput::SetState(ContentState::Empty));
 											}
 										},
-										Err(err) => tracing::error!("{err}"),
+										Err(err) => {
+											sender_clone
+												.input(ContentInput::SetState(ContentState::Empty));
+											sender_clone.input(ContentInput::ShowError(
+												load_failure(&sender_clone, err, list_clone, service),
+											));
+										},
 									}
 								}));
-							} else if let Ok(tasks) =
-								service.read_tasks_from_list(list_clone.id.clone()).await
-							{
-								if tasks.is_empty() {
+						} else {
+							match provider.read_tasks_from_list(parent.id.clone()).await {
+								Ok(tasks) if tasks.is_empty() => {
 									self.state = ContentState::Empty;
-								} else {
-									for task in &tasks {
-										guard.push_back(TaskInit::new(
-											task.clone(),
-											service.read_list(task.parent.clone()).await.unwrap(),
-										));
+								},
+								Ok(tasks) => {
+									let times = task_times(&tasks).await;
+									self.append(tasks, times);
+								},
+								Err(err) => {
+									self.state = ContentState::Empty;
+									widgets.overlay.add_toast(
+										load_failure(&sender, err, list_clone, service).toast(),
+									);
+								},
+							}
+						}
+					},
+					smart_list => {
+						if let Some(smart_list) = smart_list.smart_list() {
+							match provider.read_smart_list(smart_list).await {
+								Ok(tasks) => {
+									let times = task_times(&tasks).await;
+									let (items, error) = with_lists(service, tasks, times).await;
+									self.push(items);
+									if let Some(err) = error {
+										widgets.overlay.add_toast(
+											Failure::new(fl!("tasks-not-loaded"), err).toast(),
+										);
 									}
 									self.state = ContentState::TasksLoaded;
-								}
-							} else {
-								self.state = ContentState::Empty;
+								},
+								Err(err) => {
+									widgets.overlay.add_toast(
+										load_failure(&sender, err, list.clone(), service).toast(),
+									);
+								},
 							}
-						},
-					}
+						}
+					},
 				}
 
-				if guard.is_empty() && self.state != ContentState::Loading {
+				if self.tasks.n_items() == 0 && self.state != ContentState::Loading {
 					self.state = ContentState::Empty;
 				}
 
@@ -509,13 +663,217 @@ impl AsyncComponent for ContentModel {
 					self.state = ContentState::Unselected;
 				}
 			},
+			ContentInput::ServiceChanged(service) => {
+				if self.service == service && self.state != ContentState::Unselected {
+					if let Some(list) = self.parent_list.clone() {
+						sender.input(ContentInput::SelectList(list, service));
+					}
+				}
+			},
 		}
 		self.update_view(widgets, sender)
 	}
 }
 
-fn is_within_next_7_days(date: DateTime<Utc>) -> bool {
-	let now = Utc::now();
-	let next_7_days = now + chrono::Duration::days(7);
-	date >= now && date <= next_7_days
+impl ContentModel {
+	/// Adds tasks of the list shown.
+	fn append(&mut self, tasks: Vec<Task>, mut times: HashMap<String, TaskTime>) {
+		let Some(SidebarList::Custom(parent)) = &self.parent_list else {
+			return;
+		};
+		let items = tasks
+			.into_iter()
+			.map(|task| {
+				let time = times.remove(&task.id).unwrap_or_default();
+				TaskItem::new(task, parent.clone(), time)
+			})
+			.collect();
+		self.push(items);
+	}
+
+	fn push(&mut self, items: Vec<TaskItem>) {
+		let items: Vec<glib::BoxedAnyObject> =
+			items.into_iter().map(glib::BoxedAnyObject::new).collect();
+		self.tasks.extend_from_slice(&items);
+		if self.tasks.n_items() > 0 {
+			self.state = ContentState::TasksLoaded;
+		}
+	}
+
+	/// The item holding a task.
+	fn item(&self, task_id: &str) -> Option<glib::BoxedAnyObject> {
+		self
+			.position(task_id)
+			.and_then(|position| self.tasks.item(position))
+			.and_downcast()
+	}
+
+	/// The position of a task in the list.
+	fn position(&self, task_id: &str) -> Option<u32> {
+		(0..self.tasks.n_items()).find(|position| {
+			self
+				.tasks
+				.item(*position)
+				.and_downcast::<glib::BoxedAnyObject>()
+				.is_some_and(|item| item.borrow::<TaskItem>().task.id == task_id)
+		})
+	}
+
+	/// Marks whether the last change of a task was saved, on its item and on
+	/// the row showing it.
+	fn set_saved(&self, task_id: &str, saved: bool) {
+		if let Some(item) = self.item(task_id) {
+			item.borrow_mut::<TaskItem>().saved = saved;
+		}
+		for row in self.rows.borrow().iter() {
+			row.emit(TaskInput::SetSaved(task_id.to_string(), saved));
+		}
+	}
+
+	/// Sends a change to its service, updating the rows when it succeeds and
+	/// offering to try again when it fails.
+	async fn write(
+		&mut self,
+		overlay: &adw::ToastOverlay,
+		sender: &AsyncComponentSender<Self>,
+		mut write: TaskWrite,
+		service: Service,
+	) {
+		let shown = self.service == service;
+		// Retries send the task as it is now, not as it was when it failed.
+		if let (TaskWrite::Update(task), true) = (&mut write, shown) {
+			if let Some(item) = self.item(&task.id) {
+				*task = item.borrow::<TaskItem>().task.clone();
+			}
+		}
+		let mut provider = service.get_service();
+		let result = match &write {
+			TaskWrite::Create(task) => provider.create_task(task.clone()).await,
+			TaskWrite::Update(task) => {
+				provider.update_task(task.clone()).await.map(|_| ())
+			},
+			TaskWrite::Delete(task) => {
+				provider
+					.delete_task(task.parent.clone(), task.id.clone())
+					.await
+			},
+		};
+		match result {
+			Ok(_) if shown => match write {
+				TaskWrite::Create(task) => {
+					if let Some(SidebarList::Custom(parent)) = &self.parent_list {
+						if parent.id == task.parent {
+							let item =
+								TaskItem::new(task, parent.clone(), TaskTime::default());
+							self.push(vec![item]);
+						}
+					}
+				},
+				TaskWrite::Update(task) => self.set_saved(&task.id, true),
+				TaskWrite::Delete(task) => {
+					if let Some(position) = self.position(&task.id) {
+						self.tasks.remove(position);
+					}
+				},
+			},
+			Ok(_) => (),
+			Err(err) => {
+				let update = matches!(write, TaskWrite::Update(_));
+				let message = match &write {
+					TaskWrite::Create(_) => fl!("task-not-added"),
+					TaskWrite::Update(task) => {
+						if shown {
+							self.set_saved(&task.id, false);
+						}
+						fl!("task-not-saved")
+					},
+					TaskWrite::Delete(_) => fl!("task-not-removed"),
+				};
+				let retry = sender.input_sender().clone();
+				let toast = Failure::new(message, err)
+					.with_retry(move || {
+						retry.emit(ContentInput::Write(write.clone(), service))
+					})
+					.toast();
+				// Edits are saved as typing pauses, only the latest failure to
+				// save one is kept on screen.
+				if update {
+					if let Some(toast) = self.update_toast.replace(toast.clone()) {
+						toast.dismiss();
+					}
+				}
+				overlay.add_toast(toast);
+			},
+		}
+	}
+}
+
+/// A change sent to a service, kept so it can be sent again when it fails.
+#[derive(Debug, Clone)]
+pub enum TaskWrite {
+	Create(Task),
+	Update(Task),
+	Delete(Task),
+}
+
+fn load_failure(
+	sender: &AsyncComponentSender<ContentModel>,
+	error: done_core::Error,
+	list: SidebarList,
+	service: Service,
+) -> Failure {
+	let retry = sender.input_sender().clone();
+	Failure::new(fl!("tasks-not-loaded"), error).with_retry(move || {
+		retry.emit(ContentInput::SelectList(list.clone(), service))
+	})
+}
+
+/// Reads the time tracked for the tasks in one go, away from the main thread.
+async fn task_times(tasks: &[Task]) -> HashMap<String, TaskTime> {
+	let ids: Vec<String> = tasks.iter().map(|task| task.id.clone()).collect();
+	let times =
+		tokio::task::spawn_blocking(move || TimeTracker::new()?.task_times(&ids))
+			.await;
+	match times {
+		Ok(Ok(times)) => times,
+		Ok(Err(err)) => {
+			tracing::error!("Failed to load tracked time: {err}");
+			HashMap::new()
+		},
+		Err(err) => {
+			tracing::error!("Failed to load tracked time: {err}");
+			HashMap::new()
+		},
+	}
+}
+
+/// Pairs tasks with the lists they belong to, reading each list once. Tasks
+/// whose list can't be read are left out, the last error is returned.
+async fn with_lists(
+	service: Service,
+	tasks: Vec<Task>,
+	mut times: HashMap<String, TaskTime>,
+) -> (Vec<TaskItem>, Option<done_core::Error>) {
+	let mut provider = service.get_service();
+	let mut lists: HashMap<String, List> = HashMap::new();
+	let mut error = None;
+	let mut inits = vec![];
+	for task in tasks {
+		let list = match lists.get(&task.parent) {
+			Some(list) => list.clone(),
+			None => match provider.read_list(task.parent.clone()).await {
+				Ok(list) => {
+					lists.insert(task.parent.clone(), list.clone());
+					list
+				},
+				Err(err) => {
+					error = Some(err);
+					continue;
+				},
+			},
+		};
+		let time = times.remove(&task.id).unwrap_or_default();
+		inits.push(TaskItem::new(task, list, time));
+	}
+	(inits, error)
 }
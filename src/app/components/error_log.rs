@@ -0,0 +1,131 @@
+use relm4::{
+	adw,
+	adw::prelude::{ActionRowExt, PreferencesRowExt},
+	component::{AsyncComponent, AsyncComponentParts},
+	gtk::{
+		self,
+		prelude::{BoxExt, ButtonExt, GtkWindowExt, WidgetExt},
+		traits::OrientableExt,
+	},
+	AsyncComponentSender, RelmWidgetExt,
+};
+
+use crate::{
+	app::models::error_log::{ErrorLog, LoggedError},
+	fl,
+};
+
+pub struct ErrorLogModel {
+	entries: Vec<LoggedError>,
+	rows: gtk::ListBox,
+}
+
+#[derive(Debug)]
+pub enum ErrorLogInput {
+	Open,
+	Refresh,
+	Clear,
+}
+
+#[relm4::component(pub async)]
+impl AsyncComponent for ErrorLogModel {
+	type CommandOutput = ();
+	type Input = ErrorLogInput;
+	type Output = ();
+	type Init = ();
+
+	view! {
+		#[root]
+		adw::Window {
+			set_hide_on_close: true,
+			set_default_width: 480,
+			set_default_height: 500,
+			set_modal: true,
+
+			gtk::Box {
+				set_orientation: gtk::Orientation::Vertical,
+				adw::HeaderBar {
+					set_show_end_title_buttons: true,
+					set_css_classes: &["flat"],
+					set_title_widget: Some(&gtk::Label::new(Some(fl!("error-log")))),
+					pack_start = &gtk::Button {
+						set_label: fl!("clear"),
+						#[watch]
+						set_sensitive: !model.entries.is_empty(),
+						connect_clicked => ErrorLogInput::Clear,
+					},
+				},
+				gtk::Box {
+					set_orientation: gtk::Orientation::Vertical,
+					set_margin_all: 20,
+					set_spacing: 10,
+					gtk::Label {
+						#[watch]
+						set_visible: model.entries.is_empty(),
+						set_vexpand: true,
+						set_label: fl!("no-errors"),
+					},
+					gtk::ScrolledWindow {
+						#[watch]
+						set_visible: !model.entries.is_empty(),
+						set_vexpand: true,
+						#[local_ref]
+						rows -> gtk::ListBox {
+							set_css_classes: &["boxed-list"],
+							set_selection_mode: gtk::SelectionMode::None,
+							set_valign: gtk::Align::Start,
+						},
+					},
+				}
+			}
+		}
+	}
+
+	async fn init(
+		_init: Self::Init,
+		root: Self::Root,
+		sender: AsyncComponentSender<Self>,
+	) -> AsyncComponentParts<Self> {
+		let model = Self {
+			entries: vec![],
+			rows: gtk::ListBox::default(),
+		};
+
+		let rows = &model.rows;
+
+		let widgets = view_output!();
+
+		AsyncComponentParts { model, widgets }
+	}
+
+	async fn update(
+		&mut self,
+		message: Self::Input,
+		sender: AsyncComponentSender<Self>,
+		root: &Self::Root,
+	) {
+		match message {
+			ErrorLogInput::Open => {
+				sender.input(ErrorLogInput::Refresh);
+				root.present();
+			},
+			ErrorLogInput::Refresh => {
+				self.entries = ErrorLog::entries();
+				self.rows.remove_all();
+				for entry in &self.entries {
+					let row = adw::ActionRow::new();
+					row.set_title(&entry.message);
+					row.set_title_selectable(true);
+					row.set_subtitle(
+						&entry.date_time.format("%m/%d/%Y %H:%M:%S").to_string(),
+					);
+					self.rows.append(&row);
+				}
+			},
+			ErrorLogInput::Clear => {
+				ErrorLog::clear();
+				sender.input(ErrorLogInput::Refresh);
+			},
+		}
+	}
+}
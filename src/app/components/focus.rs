@@ -0,0 +1,302 @@
+use chrono::{DateTime, Duration, Utc};
+use libset::Config;
+use relm4::{
+	gtk,
+	gtk::{
+		gio::{self, prelude::ApplicationExt},
+		prelude::{BoxExt, ButtonExt, OrientableExt, WidgetExt},
+	},
+	main_adw_application, tokio, ComponentParts, ComponentSender, JoinHandle,
+	RelmWidgetExt, SimpleComponent,
+};
+use relm4_icons::icon_name;
+
+use done_core::{
+	models::{
+		focus::{
+			FocusPhase, FocusSession, FocusStatistics, FocusTimer, PomodoroSettings,
+		},
+		task::Task,
+	},
+	service::Services,
+	services::local::focus::FocusLog,
+};
+
+use crate::app::{
+	components::time_report::format_duration, config::preferences::Preferences,
+};
+use crate::fl;
+
+pub struct FocusModel {
+	task: Option<Task>,
+	timer: Option<FocusTimer>,
+	statistics: FocusStatistics,
+	handle: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug)]
+pub enum FocusInput {
+	Start(Task),
+	Tick,
+	TogglePause,
+	Skip,
+	Stop,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for FocusModel {
+	type Input = FocusInput;
+	type Output = ();
+	type Init = ();
+
+	view! {
+		#[root]
+		gtk::MenuButton {
+			#[watch]
+			set_visible: model.timer.is_some(),
+			set_tooltip: fl!("focus"),
+			#[watch]
+			set_class_active: ("accent", model.phase() == Some(FocusPhase::Work)),
+			#[wrap(Some)]
+			set_child = &gtk::Box {
+				set_spacing: 5,
+				gtk::Image {
+					#[watch]
+					set_icon_name: Some(if model.phase() == Some(FocusPhase::Work) {
+						icon_name::TARGET_REGULAR
+					} else {
+						icon_name::PAUSE
+					}),
+				},
+				gtk::Label {
+					add_css_class: "numeric",
+					#[watch]
+					set_label: &model.remaining(),
+				},
+			},
+			#[wrap(Some)]
+			set_popover = &gtk::Popover {
+				gtk::Box {
+					set_orientation: gtk::Orientation::Vertical,
+					set_margin_all: 10,
+					set_spacing: 10,
+					gtk::Label {
+						add_css_class: "heading",
+						set_wrap: true,
+						set_max_width_chars: 30,
+						#[watch]
+						set_label: model.task.as_ref().map(|task| task.title.as_str()).unwrap_or_default(),
+					},
+					gtk::Label {
+						add_css_class: "dim-label",
+						#[watch]
+						set_label: &model.phase_label(),
+					},
+					gtk::Label {
+						add_css_class: "title-1",
+						add_css_class: "numeric",
+						#[watch]
+						set_label: &model.remaining(),
+					},
+					gtk::Box {
+						set_halign: gtk::Align::Center,
+						set_spacing: 10,
+						gtk::Button {
+							add_css_class: "circular",
+							#[watch]
+							set_icon_name: if model.paused() { icon_name::PLAY } else { icon_name::PAUSE },
+							set_tooltip: fl!("pause-focus"),
+							connect_clicked => FocusInput::TogglePause,
+						},
+						gtk::Button {
+							add_css_class: "circular",
+							set_icon_name: icon_name::NEXT_REGULAR,
+							set_tooltip: fl!("skip-phase"),
+							connect_clicked => FocusInput::Skip,
+						},
+						gtk::Button {
+							add_css_class: "circular",
+							add_css_class: "destructive-action",
+							set_icon_name: icon_name::STOP,
+							set_tooltip: fl!("stop-focus"),
+							connect_clicked => FocusInput::Stop,
+						},
+					},
+					gtk::Separator,
+					gtk::Label {
+						add_css_class: "caption",
+						set_halign: gtk::Align::Start,
+						#[watch]
+						set_label: &format!(
+							"{}: {} · {} {} · {} {}",
+							fl!("today"),
+							format_duration(model.statistics.focused_time),
+							model.statistics.completed_sessions,
+							fl!("focus-completed"),
+							model.statistics.interrupted_sessions,
+							fl!("focus-interrupted"),
+						),
+					},
+				}
+			}
+		}
+	}
+
+	fn init(
+		_init: Self::Init,
+		root: &Self::Root,
+		sender: ComponentSender<Self>,
+	) -> ComponentParts<Self> {
+		let model = Self {
+			task: None,
+			timer: None,
+			statistics: FocusStatistics::default(),
+			handle: None,
+		};
+
+		let widgets = view_output!();
+
+		ComponentParts { model, widgets }
+	}
+
+	fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+		match message {
+			FocusInput::Start(task) => {
+				self.interrupt_work();
+				let settings =
+					if let Ok(config) = Config::new(&Services::app_id(), 1, None) {
+						config
+							.get_json::<Preferences>("preferences")
+							.map(|preferences| preferences.pomodoro)
+							.unwrap_or_default()
+					} else {
+						PomodoroSettings::default()
+					};
+				self.task = Some(task);
+				self.timer = Some(FocusTimer::new(settings));
+				self.load_statistics();
+				if self.handle.is_none() {
+					self.handle = Some(tokio::spawn(async move {
+						let mut interval =
+							tokio::time::interval(std::time::Duration::from_secs(1));
+						interval.tick().await;
+						loop {
+							interval.tick().await;
+							sender.input(FocusInput::Tick);
+						}
+					}));
+				}
+			},
+			FocusInput::Tick => {
+				let Some(timer) = self.timer.as_mut() else {
+					return;
+				};
+				let start_date_time = timer.phase_start_date_time;
+				if let Some(finished) = timer.tick(Duration::seconds(1)) {
+					if finished == FocusPhase::Work {
+						self.log_session(start_date_time, true);
+					}
+					self.notify(finished);
+				}
+			},
+			FocusInput::TogglePause => {
+				if let Some(timer) = self.timer.as_mut() {
+					timer.toggle_pause();
+				}
+			},
+			FocusInput::Skip => {
+				self.interrupt_work();
+				if let Some(timer) = self.timer.as_mut() {
+					timer.skip();
+				}
+			},
+			FocusInput::Stop => {
+				self.interrupt_work();
+				self.timer = None;
+				self.task = None;
+				if let Some(handle) = self.handle.take() {
+					handle.abort();
+				}
+			},
+		}
+	}
+}
+
+impl FocusModel {
+	fn phase(&self) -> Option<FocusPhase> {
+		self.timer.as_ref().map(|timer| timer.phase)
+	}
+
+	fn paused(&self) -> bool {
+		self.timer.as_ref().is_some_and(|timer| timer.paused)
+	}
+
+	fn remaining(&self) -> String {
+		let seconds = self
+			.timer
+			.as_ref()
+			.map(|timer| timer.remaining.num_seconds().max(0))
+			.unwrap_or_default();
+		format!("{:02}:{:02}", seconds / 60, seconds % 60)
+	}
+
+	fn phase_label(&self) -> String {
+		match self.phase() {
+			Some(FocusPhase::Work) => fl!("focus-work").clone(),
+			Some(FocusPhase::ShortBreak) => fl!("focus-short-break").clone(),
+			Some(FocusPhase::LongBreak) => fl!("focus-long-break").clone(),
+			None => String::new(),
+		}
+	}
+
+	fn load_statistics(&mut self) {
+		match FocusLog::new().and_then(|mut log| log.today_statistics()) {
+			Ok(statistics) => self.statistics = statistics,
+			Err(err) => tracing::error!("Failed to load focus statistics: {err}"),
+		}
+	}
+
+	/// Logs the running work phase, if any, as an interrupted session.
+	fn interrupt_work(&mut self) {
+		if let Some(start_date_time) = self
+			.timer
+			.as_ref()
+			.filter(|timer| timer.phase == FocusPhase::Work)
+			.map(|timer| timer.phase_start_date_time)
+		{
+			self.log_session(start_date_time, false);
+		}
+	}
+
+	fn log_session(&mut self, start_date_time: DateTime<Utc>, completed: bool) {
+		let Some(task) = &self.task else {
+			return;
+		};
+		let session = FocusSession::new(
+			task.id.clone(),
+			task.parent.clone(),
+			start_date_time,
+			completed,
+		);
+		if let Err(err) =
+			FocusLog::new().and_then(|mut log| log.log_session(session))
+		{
+			tracing::error!("Failed to log focus session: {err}");
+		}
+		self.load_statistics();
+	}
+
+	fn notify(&self, finished: FocusPhase) {
+		let title = match finished {
+			FocusPhase::Work => fl!("focus-work-over").clone(),
+			FocusPhase::ShortBreak | FocusPhase::LongBreak => {
+				fl!("focus-break-over").clone()
+			},
+		};
+		let notification = gio::Notification::new(&title);
+		if let Some(task) = &self.task {
+			notification.set_body(Some(&task.title));
+		}
+		main_adw_application().send_notification(Some("focus"), &notification);
+	}
+}
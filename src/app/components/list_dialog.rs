@@ -1,5 +1,5 @@
 use gtk::prelude::{
-	BoxExt, ButtonExt, EntryBufferExtManual, EntryExt, WidgetExt,
+	BoxExt, ButtonExt, EntryBufferExtManual, EntryExt, ListModelExt, WidgetExt,
 };
 use relm4::{
 	adw,
@@ -11,6 +11,9 @@ use relm4::{
 };
 use relm4_icons::icon_name;
 
+use done_core::models::template::{ListTemplate, Template, TemplateContent};
+use done_core::services::local::templates::TemplateStore;
+
 use crate::fl;
 
 #[derive(Debug)]
@@ -18,6 +21,9 @@ pub struct ListDialogComponent {
 	pub name: gtk::EntryBuffer,
 	pub mode: ListDialogMode,
 	pub label: String,
+	templates: Vec<Template>,
+	templates_model: gtk::StringList,
+	selected_template: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -29,11 +35,14 @@ pub enum ListDialogMode {
 #[derive(Debug)]
 pub enum ListDialogInput {
 	HandleEntry,
+	LoadTemplates,
+	SelectTemplate(u32),
 }
 
 #[derive(Debug)]
 pub enum ListDialogOutput {
 	AddTaskListToSidebar(String),
+	AddTaskListFromTemplate(String, ListTemplate),
 	RenameList(String),
 }
 
@@ -87,6 +96,14 @@ impl Component for ListDialogComponent {
 						set_buffer: &model.name,
 						connect_activate => ListDialogInput::HandleEntry,
 					},
+					gtk::DropDown {
+						set_visible: matches!(model.mode, ListDialogMode::New),
+						set_tooltip: fl!("templates"),
+						set_model: Some(&model.templates_model),
+						connect_selected_notify[sender] => move |dropdown| {
+							sender.input(ListDialogInput::SelectTemplate(dropdown.selected()));
+						}
+					},
 					gtk::Button {
 						set_css_classes: &["suggested-action"],
 						set_label: model.label.as_str(),
@@ -102,17 +119,25 @@ impl Component for ListDialogComponent {
 		root: &Self::Root,
 		sender: ComponentSender<Self>,
 	) -> ComponentParts<Self> {
+		let templates_model = gtk::StringList::new(&[fl!("no-template").as_str()]);
 		let model = if let Some(name) = init {
 			ListDialogComponent {
 				name: gtk::EntryBuffer::new(Some(name)),
 				mode: ListDialogMode::Edit,
 				label: fl!("rename").clone(),
+				templates: vec![],
+				templates_model,
+				selected_template: 0,
 			}
 		} else {
+			sender.input(ListDialogInput::LoadTemplates);
 			ListDialogComponent {
 				name: gtk::EntryBuffer::new(Some("")),
 				mode: ListDialogMode::New,
 				label: fl!("add-list").clone(),
+				templates: vec![],
+				templates_model,
+				selected_template: 0,
 			}
 		};
 
@@ -132,9 +157,25 @@ impl Component for ListDialogComponent {
 
 				match self.mode {
 					ListDialogMode::New => {
-						sender
-							.output(ListDialogOutput::AddTaskListToSidebar(name.to_string()))
-							.unwrap_or_default();
+						let template = self
+							.selected_template
+							.checked_sub(1)
+							.and_then(|index| self.templates.get(index as usize));
+						let output = match template.map(|t| (&t.name, &t.content)) {
+							Some((template_name, TemplateContent::List(template))) => {
+								let name = if name.is_empty() {
+									template_name.clone()
+								} else {
+									name.to_string()
+								};
+								ListDialogOutput::AddTaskListFromTemplate(
+									name,
+									template.clone(),
+								)
+							},
+							_ => ListDialogOutput::AddTaskListToSidebar(name.to_string()),
+						};
+						sender.output(output).unwrap_or_default();
 					},
 					ListDialogMode::Edit => {
 						sender
@@ -144,6 +185,28 @@ impl Component for ListDialogComponent {
 				}
 				root.close();
 			},
+			ListDialogInput::LoadTemplates => {
+				self.templates = match TemplateStore::new().read_templates() {
+					Ok(templates) => templates
+						.into_iter()
+						.filter(|template| template.is_list())
+						.collect(),
+					Err(err) => {
+						tracing::error!("Failed to load templates: {err}");
+						vec![]
+					},
+				};
+				let names: Vec<&str> =
+					self.templates.iter().map(|t| t.name.as_str()).collect();
+				self.templates_model.splice(
+					1,
+					self.templates_model.n_items().saturating_sub(1),
+					&names,
+				);
+			},
+			ListDialogInput::SelectTemplate(index) => {
+				self.selected_template = index;
+			},
 		}
 	}
 }
@@ -17,22 +17,32 @@ use relm4::{
 };
 use relm4_icons::icon_name;
 
-use done_core::{models::list::List, service::Service};
+use done_core::{
+	models::{list::List, template::ListTemplate},
+	service::{Service, Services},
+	services::local::templates::TemplateStore,
+};
 
 use crate::{
 	app::{
-		components::{list_dialog::ListDialogOutput, services::ServicesOutput},
+		components::{
+			list_dialog::{ListDialogInput, ListDialogOutput},
+			services::ServicesOutput,
+			time_report::{TimeReportInput, TimeReportModel},
+		},
 		factories::task_list::{
 			TaskListFactoryInit, TaskListFactoryModel, TaskListFactoryOutput,
 		},
-		models::sidebar_list::SidebarList,
-		AboutAction, PreferencesAction, QuitAction, ShortcutsAction,
+		models::{failure::Failure, sidebar_list::SidebarList},
+		AboutAction, PreferencesAction, ProfilesAction, QuitAction,
+		ShortcutsAction, StatisticsAction,
 	},
 	fl,
 };
 
 use super::{
 	list_dialog::ListDialogComponent,
+	profiles::profile_name,
 	services::{ServicesInput, ServicesModel},
 };
 
@@ -42,7 +52,10 @@ pub struct ListSidebarModel {
 	task_list_factory: AsyncFactoryVecDeque<TaskListFactoryModel>,
 	list_entry: Controller<ListDialogComponent>,
 	services_sidebar_controller: AsyncController<ServicesModel>,
+	time_report: AsyncController<TimeReportModel>,
 	handle: Option<JoinHandle<()>>,
+	/// Whether an account of the service must be signed in to again.
+	needs_sign_in: bool,
 }
 
 #[derive(Debug)]
@@ -51,12 +64,19 @@ pub enum ListSidebarInput {
 	OpenNewTaskListDialog,
 	LoadTaskList(List),
 	AddTaskListToSidebar(String),
+	AddTaskListFromTemplate(String, ListTemplate),
 	ServiceSelected(Service),
 	ServiceDisabled(Service),
 	SelectList(SidebarList),
 	DeleteTaskList(DynamicIndex),
+	OpenTimeReport(List),
 	SetStatus(ListSidebarStatus),
 	ReloadSidebar(Service),
+	ServiceChanged(Service),
+	CheckSignIn,
+	SignInAgain,
+	ShowError(Failure),
+	ShowNotice(String),
 }
 
 #[derive(Debug)]
@@ -64,6 +84,9 @@ pub enum ListSidebarOutput {
 	SelectList(SidebarList, Service),
 	ServiceDisabled(Service),
 	CleanContent,
+	/// Shown on the toast overlay of the content.
+	Error(Failure),
+	Notice(String),
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -85,6 +108,8 @@ impl SimpleAsyncComponent for ListSidebarModel {
 				keyboard_shortcuts => ShortcutsAction,
 				about_done => AboutAction,
 				preferences => PreferencesAction,
+				statistics => StatisticsAction,
+				profiles => ProfilesAction,
 				quit => QuitAction,
 			}
 		}
@@ -97,7 +122,14 @@ impl SimpleAsyncComponent for ListSidebarModel {
 				set_css_classes: &["flat"],
 				set_show_start_title_buttons: false,
 				set_show_back_button: true,
-				set_title_widget: Some(&gtk::Label::new(Some("Lists"))),
+				set_title_widget: Some(&adw::WindowTitle::new(
+					"Lists",
+					&if profile.is_default() {
+						String::new()
+					} else {
+						profile_name(&profile)
+					},
+				)),
 				pack_start = &gtk::Button {
 					set_tooltip: fl!("add-new-task-list"),
 					set_icon_name: icon_name::PLUS,
@@ -117,6 +149,16 @@ impl SimpleAsyncComponent for ListSidebarModel {
 			set_content = &gtk::Box {
 				set_orientation: gtk::Orientation::Vertical,
 				append = model.services_sidebar_controller.widget(),
+				append = &adw::Banner {
+					#[watch]
+					set_revealed: model.needs_sign_in,
+					#[watch]
+					set_title: fl!("sign-in-again-description", service = model.service.to_string()),
+					set_button_label: Some(fl!("sign-in-again")),
+					connect_button_clicked[sender] => move |_| {
+						sender.input(ListSidebarInput::SignInAgain)
+					},
+				},
 				append = &gtk::Box {
 					set_margin_start: 15,
 					set_margin_end: 15,
@@ -193,7 +235,10 @@ impl SimpleAsyncComponent for ListSidebarModel {
 		let keyboard_shortcuts: &str = fl!("keyboard-shortcuts");
 		let about_done: &str = fl!("about-done");
 		let preferences: &str = fl!("preferences");
+		let statistics: &str = fl!("statistics");
+		let profiles: &str = fl!("profiles");
 		let quit: &str = fl!("quit");
+		let profile = Services::profile();
 
 		let model = ListSidebarModel {
 			service: init,
@@ -207,6 +252,15 @@ impl SimpleAsyncComponent for ListSidebarModel {
 					TaskListFactoryOutput::DeleteTaskList(index) => {
 						ListSidebarInput::DeleteTaskList(index)
 					},
+					TaskListFactoryOutput::TimeReport(list) => {
+						ListSidebarInput::OpenTimeReport(list)
+					},
+					TaskListFactoryOutput::Error(failure) => {
+						ListSidebarInput::ShowError(failure)
+					},
+					TaskListFactoryOutput::Notice(message) => {
+						ListSidebarInput::ShowNotice(message)
+					},
 				}),
 			list_entry: ListDialogComponent::builder().launch(None).forward(
 				sender.input_sender(),
@@ -214,6 +268,9 @@ impl SimpleAsyncComponent for ListSidebarModel {
 					ListDialogOutput::AddTaskListToSidebar(name) => {
 						ListSidebarInput::AddTaskListToSidebar(name)
 					},
+					ListDialogOutput::AddTaskListFromTemplate(name, template) => {
+						ListSidebarInput::AddTaskListFromTemplate(name, template)
+					},
 					ListDialogOutput::RenameList(_) => todo!(),
 				},
 			),
@@ -226,9 +283,14 @@ impl SimpleAsyncComponent for ListSidebarModel {
 					ServicesOutput::ServiceDisabled(service) => {
 						ListSidebarInput::ServiceDisabled(service)
 					},
+					ServicesOutput::AccountsChanged(service) => {
+						ListSidebarInput::ServiceChanged(service)
+					},
 				},
 			),
+			time_report: TimeReportModel::builder().launch(()).detach(),
 			handle: None,
+			needs_sign_in: init.needs_sign_in(),
 		};
 		sender.input(ListSidebarInput::LoadTaskLists);
 		let task_list_widget = model.task_list_factory.widget();
@@ -257,8 +319,42 @@ impl SimpleAsyncComponent for ListSidebarModel {
 						));
 						self.state = ListSidebarStatus::Loaded;
 					},
-					Err(e) => {
-						tracing::error!("Error while creating task list: {}", e);
+					Err(err) => {
+						let retry = sender.input_sender().clone();
+						let failure = Failure::new(fl!("list-not-created"), err)
+							.with_retry(move || {
+								retry.emit(ListSidebarInput::AddTaskListToSidebar(name.clone()))
+							});
+						sender
+							.output(ListSidebarOutput::Error(failure))
+							.unwrap_or_default();
+					},
+				}
+			},
+			ListSidebarInput::AddTaskListFromTemplate(name, template) => {
+				match TemplateStore::instantiate_list(&template, &name, self.service)
+					.await
+				{
+					Ok(list) => {
+						let mut guard = self.task_list_factory.guard();
+						guard.push_back(TaskListFactoryInit::new(
+							self.service,
+							SidebarList::Custom(list),
+						));
+						self.state = ListSidebarStatus::Loaded;
+					},
+					Err(err) => {
+						let retry = sender.input_sender().clone();
+						let failure = Failure::new(fl!("list-not-created"), err)
+							.with_retry(move || {
+								retry.emit(ListSidebarInput::AddTaskListFromTemplate(
+									name.clone(),
+									template.clone(),
+								))
+							});
+						sender
+							.output(ListSidebarOutput::Error(failure))
+							.unwrap_or_default();
 					},
 				}
 			},
@@ -267,7 +363,17 @@ impl SimpleAsyncComponent for ListSidebarModel {
 				.sender()
 				.send(ServicesInput::ReloadServices(service))
 				.unwrap_or_default(),
+			ListSidebarInput::ServiceChanged(service) => {
+				if self.service == service {
+					sender.input(ListSidebarInput::LoadTaskLists);
+				}
+			},
 			ListSidebarInput::OpenNewTaskListDialog => {
+				self
+					.list_entry
+					.sender()
+					.send(ListDialogInput::LoadTemplates)
+					.unwrap_or_default();
 				let list_entry = self.list_entry.widget();
 				list_entry.present();
 			},
@@ -297,9 +403,22 @@ impl SimpleAsyncComponent for ListSidebarModel {
 				));
 				self.state = ListSidebarStatus::Loaded;
 			},
+			ListSidebarInput::OpenTimeReport(list) => self
+				.time_report
+				.sender()
+				.send(TimeReportInput::Open(self.service, list))
+				.unwrap_or_default(),
 			ListSidebarInput::SetStatus(status) => {
 				self.state = status;
 			},
+			ListSidebarInput::CheckSignIn => {
+				self.needs_sign_in = self.service.needs_sign_in();
+			},
+			ListSidebarInput::SignInAgain => {
+				if let Err(err) = self.service.get_service().login().await {
+					tracing::error!("Failed to start signing in: {err}");
+				}
+			},
 			ListSidebarInput::LoadTaskLists => {
 				let mut guard = self.task_list_factory.guard();
 				guard.clear();
@@ -310,20 +429,36 @@ impl SimpleAsyncComponent for ListSidebarModel {
 					self.handle = Some(tokio::spawn(async move {
 						match service.get_lists().await {
 							Ok(mut stream) => {
-								let first = stream.next().await;
-								if let Some(list) = first {
-									sender_clone.input(ListSidebarInput::LoadTaskList(list));
-									while let Some(list) = stream.next().await {
-										sender_clone.input(ListSidebarInput::LoadTaskList(list));
+								let mut empty = true;
+								let mut failed = None;
+								// An account that fails doesn't keep the lists of the others
+								// from loading, only the first failure is shown.
+								while let Some(list) = stream.next().await {
+									match list {
+										Ok(list) => {
+											empty = false;
+											sender_clone.input(ListSidebarInput::LoadTaskList(list));
+										},
+										Err(err) if failed.is_none() => failed = Some(err),
+										Err(err) => tracing::error!("Failed to read lists: {err}"),
 									}
-								} else {
+								}
+								if let Some(err) = failed {
+									sender_clone.input(ListSidebarInput::ShowError(
+										lists_failure(&sender_clone, err),
+									));
+								}
+								if empty {
 									sender_clone.input(ListSidebarInput::SetStatus(
 										ListSidebarStatus::Empty,
 									));
 								}
 							},
-							Err(err) => tracing::error!("{err}"),
+							Err(err) => sender_clone.input(ListSidebarInput::ShowError(
+								lists_failure(&sender_clone, err),
+							)),
 						}
+						sender_clone.input(ListSidebarInput::CheckSignIn);
 					}));
 				} else {
 					if matches!(self.service, Service::Smart) {
@@ -334,11 +469,18 @@ impl SimpleAsyncComponent for ListSidebarModel {
 							));
 						}
 					} else {
-						for list in service.read_lists().await.unwrap() {
-							guard.push_back(TaskListFactoryInit::new(
-								self.service,
-								SidebarList::Custom(list),
-							));
+						match service.read_lists().await {
+							Ok(lists) => {
+								for list in lists {
+									guard.push_back(TaskListFactoryInit::new(
+										self.service,
+										SidebarList::Custom(list),
+									));
+								}
+							},
+							Err(err) => sender.input(ListSidebarInput::ShowError(
+								lists_failure(&sender, err),
+							)),
 						}
 					}
 					if guard.is_empty() {
@@ -346,8 +488,15 @@ impl SimpleAsyncComponent for ListSidebarModel {
 					} else {
 						self.state = ListSidebarStatus::Loaded;
 					}
+					self.needs_sign_in = self.service.needs_sign_in();
 				}
 			},
+			ListSidebarInput::ShowError(failure) => sender
+				.output(ListSidebarOutput::Error(failure))
+				.unwrap_or_default(),
+			ListSidebarInput::ShowNotice(message) => sender
+				.output(ListSidebarOutput::Notice(message))
+				.unwrap_or_default(),
 			ListSidebarInput::SelectList(list) => sender
 				.output(ListSidebarOutput::SelectList(list, self.service))
 				.unwrap(),
@@ -363,3 +512,13 @@ impl SimpleAsyncComponent for ListSidebarModel {
 		}
 	}
 }
+
+/// The lists of the service couldn't be read, retrying reads them again.
+fn lists_failure(
+	sender: &AsyncComponentSender<ListSidebarModel>,
+	error: done_core::Error,
+) -> Failure {
+	let retry = sender.input_sender().clone();
+	Failure::new(fl!("lists-not-loaded"), error)
+		.with_retry(move || retry.emit(ListSidebarInput::LoadTaskLists))
+}
@@ -1,9 +1,15 @@
 pub mod about_dialog;
+pub mod backups;
 pub mod content;
 pub mod delete;
+pub mod error_log;
+pub mod focus;
 pub mod list_dialog;
 pub mod list_sidebar;
 pub mod preferences;
+pub mod profiles;
 pub mod services;
+pub mod statistics;
 pub mod task_input;
+pub mod time_report;
 pub mod welcome;
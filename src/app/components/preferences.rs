@@ -1,3 +1,5 @@
+use std::{fmt::Display, path::PathBuf};
+
 use anyhow::Result;
 use libset::Config;
 use relm4::{
@@ -6,35 +8,121 @@ use relm4::{
 		ActionRowExt, AdwWindowExt, BoxExt, GtkWindowExt, OrientableExt,
 		PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt, WidgetExt,
 	},
-	adw::traits::ComboRowExt,
-	component::{AsyncComponent, AsyncComponentParts},
-	gtk, AsyncComponentSender,
+	adw::traits::{ComboRowExt, EntryRowExt, MessageDialogExt},
+	component::{
+		AsyncComponent, AsyncComponentController, AsyncComponentParts,
+		AsyncController,
+	},
+	gtk,
+	gtk::prelude::{ButtonExt, EditableExt, FileExt},
+	AsyncComponentSender,
 };
 use relm4_icons::icon_name;
 
-use done_core::service::Service;
+use done_core::{
+	models::{
+		bundle::{BundleImport, BundleReport, ListBundle},
+		focus::FocusPhase,
+		workspace::{ImportReport, Workspace},
+	},
+	service::{Service, Services},
+	services::{
+		local::{backups::Backups, database::Database, encryption::Encryption},
+		microsoft::{
+			authority::Authority,
+			login::{sign_in_with_loopback, DeviceCode},
+		},
+	},
+};
 
+use crate::app::config::appearance::ColorScheme;
 use crate::app::config::preferences::Preferences;
-use crate::app::config::{appearance::ColorScheme, info::APP_ID};
+use crate::app::models::failure::Failure;
 use crate::fl;
 
-#[derive(Debug)]
+use super::backups::{BackupsInput, BackupsModel, BackupsOutput};
+use super::error_log::{ErrorLogInput, ErrorLogModel};
+
 pub struct PreferencesComponentModel {
 	pub preferences: Preferences,
+	backups: AsyncController<BackupsModel>,
+	encrypted: bool,
+	database_location: Option<PathBuf>,
+	database_locked_by: Option<String>,
+	error_log: AsyncController<ErrorLogModel>,
+	workspace: Option<Workspace>,
+	bundle: Option<ListBundle>,
+	service_rows: Vec<(Service, adw::ActionRow, gtk::Button)>,
+	/// Rows to choose where completed tasks are archived, with the button
+	/// going back to the default file.
+	archive_rows: Vec<(Service, adw::ActionRow, gtk::Button)>,
+	authority: Authority,
+	sign_in: MicrosoftSignIn,
+}
+
+/// Progress of signing in to Microsoft without the `done://` redirect.
+#[derive(Debug, Default)]
+pub enum MicrosoftSignIn {
+	#[default]
+	Idle,
+	WaitingForBrowser,
+	/// Waiting for the code to be entered at the address.
+	WaitingForCode(String, String),
+	SignedIn,
+	Failed(String),
 }
 
 #[derive(Debug)]
 pub enum PreferencesComponentInput {
 	SetColorScheme(ColorScheme),
 	ExpandSubTasks,
+	SetFocusDuration(FocusPhase, u32),
+	SetSessionsBeforeLongBreak(u32),
+	ToggleBackups,
+	SetBackupInterval(u32),
+	SetBackupsKept(u32),
+	BackUpNow,
+	OpenBackups,
+	SetEncryption(bool),
+	ChooseDatabaseLocation,
+	SetDatabaseLocation(Option<PathBuf>),
+	TakeOverDatabase,
+	/// The database was changed by another program or machine.
+	DatabaseChanged,
+	OpenErrorLog,
+	DatabaseRestored,
+	ExportWorkspace,
+	ExportWorkspaceTo(PathBuf),
+	ImportWorkspace,
+	ImportWorkspaceFrom(PathBuf),
+	ImportWorkspaceInto(Service),
+	ImportList,
+	ImportListFrom(PathBuf),
+	ImportListInto(Service),
+	ImportListAs(Service, BundleImport),
+	ChooseServicePath(Service),
+	SetServicePath(Service, PathBuf),
+	ClearServicePath(Service),
+	ChooseArchivePath(Service),
+	SetArchivePath(Service, Option<PathBuf>),
 	MicrosoftLogin,
 	MicrosoftLogout,
+	SelectMicrosoftAuthority(u32),
+	SetMicrosoftAuthority(Authority),
+	SignInWithBrowser,
+	SignInWithCode,
+	SetMicrosoftSignIn(MicrosoftSignIn),
 }
 
 #[derive(Debug)]
 pub enum PreferencesComponentOutput {
 	ServiceDisabled(Service),
 	ExpandSubTasks(bool),
+	DatabaseRestored,
+	DatabaseMoved,
+	WorkspaceImported(Service),
+	ListImported(Service),
+	ServiceConnected(Service),
 }
 
 #[relm4::component(pub async)]
@@ -97,6 +185,196 @@ impl AsyncComponent for PreferencesComponentModel {
 									connect_active_notify => PreferencesComponentInput::ExpandSubTasks
 								}
 							},
+							add = &adw::PreferencesGroup {
+								set_title: fl!("focus"),
+								adw::SpinRow::with_range(1.0, 120.0, 1.0) {
+									set_title: fl!("focus-work"),
+									set_subtitle: fl!("minutes"),
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::TIMER),
+									},
+									set_value: model.preferences.pomodoro.work_minutes as f64,
+									connect_value_notify[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetFocusDuration(FocusPhase::Work, row.value() as u32))
+									}
+								},
+								adw::SpinRow::with_range(1.0, 60.0, 1.0) {
+									set_title: fl!("focus-short-break"),
+									set_subtitle: fl!("minutes"),
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::PAUSE),
+									},
+									set_value: model.preferences.pomodoro.short_break_minutes as f64,
+									connect_value_notify[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetFocusDuration(FocusPhase::ShortBreak, row.value() as u32))
+									}
+								},
+								adw::SpinRow::with_range(1.0, 60.0, 1.0) {
+									set_title: fl!("focus-long-break"),
+									set_subtitle: fl!("minutes"),
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::PAUSE),
+									},
+									set_value: model.preferences.pomodoro.long_break_minutes as f64,
+									connect_value_notify[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetFocusDuration(FocusPhase::LongBreak, row.value() as u32))
+									}
+								},
+								adw::SpinRow::with_range(1.0, 12.0, 1.0) {
+									set_title: fl!("focus-long-break-interval"),
+									set_subtitle: fl!("focus-long-break-interval-desc"),
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::UPDATE),
+									},
+									set_value: model.preferences.pomodoro.sessions_before_long_break as f64,
+									connect_value_notify[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetSessionsBeforeLongBreak(row.value() as u32))
+									}
+								},
+							},
+							add = &adw::PreferencesGroup {
+								set_title: fl!("backups"),
+								adw::SwitchRow {
+									set_title: fl!("automatic-backups"),
+									set_subtitle: fl!("automatic-backups-desc"),
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::FLOPPY),
+									},
+									set_active: model.preferences.backups.enabled,
+									connect_active_notify => PreferencesComponentInput::ToggleBackups
+								},
+								adw::SpinRow::with_range(1.0, 168.0, 1.0) {
+									set_title: fl!("backup-interval"),
+									set_subtitle: fl!("hours"),
+									#[watch]
+									set_sensitive: model.preferences.backups.enabled,
+									set_value: model.preferences.backups.interval_hours as f64,
+									connect_value_notify[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetBackupInterval(row.value() as u32))
+									}
+								},
+								adw::SpinRow::with_range(1.0, 100.0, 1.0) {
+									set_title: fl!("backups-kept"),
+									set_subtitle: fl!("backups-kept-desc"),
+									#[watch]
+									set_sensitive: model.preferences.backups.enabled,
+									set_value: model.preferences.backups.keep as f64,
+									connect_value_notify[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetBackupsKept(row.value() as u32))
+									}
+								},
+								adw::ActionRow {
+									set_title: fl!("back-up-now"),
+									add_suffix = &gtk::Button {
+										set_label: fl!("back-up"),
+										set_valign: gtk::Align::Center,
+										connect_clicked => PreferencesComponentInput::BackUpNow,
+									},
+								},
+								adw::ActionRow {
+									set_title: fl!("restore-backup"),
+									set_subtitle: fl!("restore-backup-desc"),
+									add_suffix = &gtk::Button {
+										set_label: fl!("restore"),
+										set_valign: gtk::Align::Center,
+										connect_clicked => PreferencesComponentInput::OpenBackups,
+									},
+								},
+							},
+							add = &adw::PreferencesGroup {
+								set_title: fl!("local-database"),
+								adw::ActionRow {
+									set_title: fl!("database-location"),
+									#[watch]
+									set_subtitle: &model
+										.database_location
+										.as_ref()
+										.map(|folder| folder.display().to_string())
+										.unwrap_or_else(|| fl!("default-location").clone()),
+									set_tooltip: fl!("database-location-desc"),
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::FILE_CABINET),
+									},
+									add_suffix = &gtk::Button {
+										set_label: fl!("reset"),
+										set_valign: gtk::Align::Center,
+										#[watch]
+										set_visible: model.database_location.is_some(),
+										connect_clicked => PreferencesComponentInput::SetDatabaseLocation(None),
+									},
+									add_suffix = &gtk::Button {
+										set_label: fl!("choose"),
+										set_valign: gtk::Align::Center,
+										connect_clicked => PreferencesComponentInput::ChooseDatabaseLocation,
+									},
+								},
+								adw::ActionRow {
+									#[watch]
+									set_visible: model.database_locked_by.is_some(),
+									#[watch]
+									set_title: &model
+										.database_locked_by
+										.as_ref()
+										.map(|host| fl!("database-read-only", host = host.as_str()).clone())
+										.unwrap_or_default(),
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::WARNING),
+									},
+									add_suffix = &gtk::Button {
+										set_label: fl!("take-over"),
+										set_valign: gtk::Align::Center,
+										connect_clicked => PreferencesComponentInput::TakeOverDatabase,
+									},
+								},
+								adw::SwitchRow {
+									set_title: fl!("encrypt-database"),
+									set_subtitle: if Encryption::available() {
+										fl!("encrypt-database-desc")
+									} else {
+										fl!("encrypt-database-unavailable")
+									},
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::LOCK_CLOSED_REGULAR),
+									},
+									set_sensitive: Encryption::available(),
+									#[watch]
+									set_active: model.encrypted,
+									connect_active_notify[sender] => move |switch| {
+										sender.input(PreferencesComponentInput::SetEncryption(switch.is_active()))
+									}
+								},
+							},
+							add = &adw::PreferencesGroup {
+								set_title: fl!("workspace"),
+								adw::ActionRow {
+									set_title: fl!("export-workspace"),
+									set_subtitle: fl!("export-workspace-desc"),
+									add_suffix = &gtk::Button {
+										set_label: fl!("export"),
+										set_valign: gtk::Align::Center,
+										connect_clicked => PreferencesComponentInput::ExportWorkspace,
+									},
+								},
+								adw::ActionRow {
+									set_title: fl!("import-workspace"),
+									set_subtitle: fl!("import-workspace-desc"),
+									add_suffix = &gtk::Button {
+										set_label: fl!("import"),
+										set_valign: gtk::Align::Center,
+										connect_clicked => PreferencesComponentInput::ImportWorkspace,
+									},
+								},
+								adw::ActionRow {
+									set_title: fl!("import-list"),
+									set_subtitle: fl!("import-list-desc"),
+									add_suffix = &gtk::Button {
+										set_label: fl!("import"),
+										set_valign: gtk::Align::Center,
+										connect_clicked => PreferencesComponentInput::ImportList,
+									},
+								},
+							},
+							#[name = "services_group"]
 							add = &adw::PreferencesGroup {
 								set_title: fl!("services"),
 								adw::SwitchRow {
@@ -113,7 +391,72 @@ impl AsyncComponent for PreferencesComponentModel {
 											sender.input_sender().send(PreferencesComponentInput::MicrosoftLogout).unwrap();
 										}
 									}
+								},
+								adw::ComboRow {
+									set_title: fl!("msft-account-type"),
+									set_subtitle: fl!("msft-account-type-description"),
+									set_model: Some(&gtk::StringList::new(&[
+										fl!("msft-personal"),
+										fl!("msft-work-or-school"),
+										fl!("msft-any-account"),
+										fl!("msft-single-organization"),
+									])),
+									set_selected: match model.authority {
+										Authority::Consumers => 0,
+										Authority::Organizations => 1,
+										Authority::Common => 2,
+										Authority::Tenant(_) => 3,
+									},
+									connect_selected_notify[sender] => move |combo_row| {
+										sender.input(PreferencesComponentInput::SelectMicrosoftAuthority(combo_row.selected()))
+									},
+								},
+								adw::EntryRow {
+									set_title: fl!("msft-tenant"),
+									#[watch]
+									set_visible: matches!(model.authority, Authority::Tenant(_)),
+									set_text: match &model.authority {
+										Authority::Tenant(tenant) => tenant.as_str(),
+										_ => "",
+									},
+									set_show_apply_button: true,
+									connect_apply[sender] => move |entry| {
+										let tenant = entry.text().trim().to_string();
+										sender.input(PreferencesComponentInput::SetMicrosoftAuthority(Authority::Tenant(tenant)))
+									},
+								},
+								adw::ActionRow {
+									set_title: fl!("msft-sign-in"),
+									#[watch]
+									set_subtitle: &model.sign_in_subtitle(),
+									set_subtitle_selectable: true,
+									add_suffix = &gtk::Button {
+										set_label: fl!("msft-browser"),
+										set_valign: gtk::Align::Center,
+										#[watch]
+										set_sensitive: !model.signing_in(),
+										connect_clicked => PreferencesComponentInput::SignInWithBrowser,
+									},
+									add_suffix = &gtk::Button {
+										set_label: fl!("msft-code"),
+										set_valign: gtk::Align::Center,
+										#[watch]
+										set_sensitive: !model.signing_in(),
+										connect_clicked => PreferencesComponentInput::SignInWithCode,
+									},
 								}
+							},
+							add = &adw::PreferencesGroup {
+								set_title: fl!("troubleshooting"),
+								adw::ActionRow {
+									set_title: fl!("error-log"),
+									set_subtitle: fl!("error-log-desc"),
+									add_suffix = &gtk::Button {
+										set_label: fl!("view"),
+										set_valign: gtk::Align::Center,
+										connect_clicked => PreferencesComponentInput::OpenErrorLog,
+									},
+								},
 							}
 						}
 					}
@@ -127,16 +470,49 @@ impl AsyncComponent for PreferencesComponentModel {
 		root: Self::Root,
 		sender: AsyncComponentSender<Self>,
 	) -> AsyncComponentParts<Self> {
-		let preferences = if let Ok(config) = Config::new(APP_ID, 1, None) {
-			config.get_json("preferences").unwrap_or(Preferences::new())
-		} else {
-			Preferences::new()
-		};
+		let preferences =
+			if let Ok(config) = Config::new(&Services::app_id(), 1, None) {
+				config.get_json("preferences").unwrap_or(Preferences::new())
+			} else {
+				Preferences::new()
+			};
 
-		let model = Self { preferences };
+		let mut model = Self {
+			preferences,
+			backups: BackupsModel::builder().launch(()).forward(
+				sender.input_sender(),
+				|message| match message {
+					BackupsOutput::Restored => {
+						PreferencesComponentInput::DatabaseRestored
+					},
+				},
+			),
+			encrypted: Encryption::new().enabled().unwrap_or_default(),
+			database_location: Database::new(Services::app_id()).location(),
+			database_locked_by: Database::new(Services::app_id()).locked_by(),
+			error_log: ErrorLogModel::builder().launch(()).detach(),
+			workspace: None,
+			bundle: None,
+			service_rows: vec![],
+			archive_rows: vec![],
+			authority: Authority::get(),
+			sign_in: MicrosoftSignIn::Idle,
+		};
 
 		let widgets = view_output!();
 
+		for service in Service::list().into_iter().filter(Service::file_backed) {
+			let (row, disconnect) = service_row(service, &sender);
+			widgets.services_group.add(&row);
+			model.service_rows.push((service, row, disconnect));
+			if service.has_archive() {
+				let (row, reset) = archive_row(service, &sender);
+				widgets.services_group.add(&row);
+				model.archive_rows.push((service, row, reset));
+			}
+		}
+		model.update_service_rows();
+
 		AsyncComponentParts { model, widgets }
 	}
 
@@ -145,7 +521,7 @@ impl AsyncComponent for PreferencesComponentModel {
 		widgets: &mut Self::Widgets,
 		message: Self::Input,
 		sender: AsyncComponentSender<Self>,
-		_root: &Self::Root,
+		root: &Self::Root,
 	) {
 		match message {
 			PreferencesComponentInput::SetColorScheme(color_scheme) => {
@@ -182,16 +558,458 @@ impl AsyncComponent for PreferencesComponentModel {
 					))
 					.unwrap();
 			},
+			PreferencesComponentInput::SetFocusDuration(phase, minutes) => {
+				let pomodoro = &mut self.preferences.pomodoro;
+				match phase {
+					FocusPhase::Work => pomodoro.work_minutes = minutes,
+					FocusPhase::ShortBreak => pomodoro.short_break_minutes = minutes,
+					FocusPhase::LongBreak => pomodoro.long_break_minutes = minutes,
+				}
+				if let Err(err) = update_preferences(&self.preferences) {
+					tracing::error!("{err}")
+				}
+			},
+			PreferencesComponentInput::SetSessionsBeforeLongBreak(sessions) => {
+				self.preferences.pomodoro.sessions_before_long_break = sessions;
+				if let Err(err) = update_preferences(&self.preferences) {
+					tracing::error!("{err}")
+				}
+			},
+			PreferencesComponentInput::ToggleBackups => {
+				self.preferences.backups.enabled = !self.preferences.backups.enabled;
+				if let Err(err) = update_preferences(&self.preferences) {
+					tracing::error!("{err}")
+				}
+			},
+			PreferencesComponentInput::SetBackupInterval(hours) => {
+				self.preferences.backups.interval_hours = hours;
+				if let Err(err) = update_preferences(&self.preferences) {
+					tracing::error!("{err}")
+				}
+			},
+			PreferencesComponentInput::SetBackupsKept(keep) => {
+				self.preferences.backups.keep = keep;
+				if let Err(err) = update_preferences(&self.preferences) {
+					tracing::error!("{err}")
+				}
+			},
+			PreferencesComponentInput::BackUpNow => {
+				match Backups::new().and_then(|mut backups| backups.create()) {
+					Ok(_) => widgets
+						.overlay
+						.add_toast(adw::Toast::new(fl!("backup-created"))),
+					Err(err) => widgets.overlay.add_toast(failure(
+						&sender,
+						fl!("backup-not-created"),
+						err,
+						|| PreferencesComponentInput::BackUpNow,
+					)),
+				}
+			},
+			PreferencesComponentInput::OpenBackups => self
+				.backups
+				.sender()
+				.send(BackupsInput::Open)
+				.unwrap_or_default(),
+			PreferencesComponentInput::SetEncryption(encrypted) => {
+				if encrypted != self.encrypted {
+					let result = relm4::tokio::task::spawn_blocking(move || {
+						let encryption = Encryption::new();
+						if encrypted {
+							encryption.enable()
+						} else {
+							encryption.disable()
+						}
+					})
+					.await
+					.map_err(anyhow::Error::from)
+					.and_then(|result| result);
+					match result {
+						Ok(()) => {
+							self.encrypted = encrypted;
+							widgets.overlay.add_toast(adw::Toast::new(if encrypted {
+								fl!("database-encrypted")
+							} else {
+								fl!("database-decrypted")
+							}));
+						},
+						Err(err) => widgets.overlay.add_toast(failure(
+							&sender,
+							fl!("encryption-not-changed"),
+							err,
+							move || PreferencesComponentInput::SetEncryption(encrypted),
+						)),
+					}
+				}
+			},
+			PreferencesComponentInput::ChooseDatabaseLocation => {
+				let dialog = gtk::FileDialog::new();
+				let sender = sender.clone();
+				dialog.select_folder(
+					Some(root),
+					gtk::gio::Cancellable::NONE,
+					move |file| {
+						if let Some(path) = file.ok().and_then(|file| file.path()) {
+							sender.input(PreferencesComponentInput::SetDatabaseLocation(
+								Some(path),
+							));
+						}
+					},
+				);
+			},
+			PreferencesComponentInput::SetDatabaseLocation(folder) => {
+				let database = Database::new(Services::app_id());
+				match database.set_location(folder.clone()) {
+					Ok(()) => {
+						self.database_location = database.location();
+						self.database_locked_by = database.locked_by();
+						widgets
+							.overlay
+							.add_toast(adw::Toast::new(fl!("database-moved")));
+						sender
+							.output(PreferencesComponentOutput::DatabaseMoved)
+							.unwrap_or_default();
+					},
+					Err(err) => widgets.overlay.add_toast(failure(
+						&sender,
+						fl!("database-not-moved"),
+						err,
+						move || {
+							PreferencesComponentInput::SetDatabaseLocation(folder.clone())
+						},
+					)),
+				}
+			},
+			PreferencesComponentInput::TakeOverDatabase => {
+				let database = Database::new(Services::app_id());
+				match database.take_over() {
+					Ok(()) => {
+						self.database_locked_by = database.locked_by();
+						sender
+							.output(PreferencesComponentOutput::DatabaseMoved)
+							.unwrap_or_default();
+					},
+					Err(err) => widgets.overlay.add_toast(failure(
+						&sender,
+						fl!("database-not-taken-over"),
+						err,
+						|| PreferencesComponentInput::TakeOverDatabase,
+					)),
+				}
+			},
+			PreferencesComponentInput::DatabaseChanged => {
+				self.database_locked_by = Database::new(Services::app_id()).locked_by();
+			},
+			PreferencesComponentInput::OpenErrorLog => self
+				.error_log
+				.sender()
+				.send(ErrorLogInput::Open)
+				.unwrap_or_default(),
+			PreferencesComponentInput::DatabaseRestored => {
+				widgets
+					.overlay
+					.add_toast(adw::Toast::new(fl!("backup-restored")));
+				sender
+					.output(PreferencesComponentOutput::DatabaseRestored)
+					.unwrap_or_default();
+			},
+			PreferencesComponentInput::ExportWorkspace => {
+				let dialog = gtk::FileDialog::new();
+				dialog.set_initial_name(Some("done-workspace.json"));
+				let sender = sender.clone();
+				dialog.save(Some(root), gtk::gio::Cancellable::NONE, move |file| {
+					if let Some(path) = file.ok().and_then(|file| file.path()) {
+						sender.input(PreferencesComponentInput::ExportWorkspaceTo(path));
+					}
+				});
+			},
+			PreferencesComponentInput::ExportWorkspaceTo(path) => {
+				let workspace = Workspace::export(&Service::list()).await;
+				match workspace
+					.to_json()
+					.and_then(|json| Ok(std::fs::write(&path, json)?))
+				{
+					Ok(_) if workspace.skipped.is_empty() => widgets
+						.overlay
+						.add_toast(adw::Toast::new(fl!("workspace-exported"))),
+					Ok(_) => widgets.overlay.add_toast(adw::Toast::new(fl!(
+						"workspace-exported-without",
+						services = service_names(&workspace.skipped)
+					))),
+					Err(err) => widgets.overlay.add_toast(failure(
+						&sender,
+						fl!("workspace-not-exported"),
+						err,
+						move || PreferencesComponentInput::ExportWorkspaceTo(path.clone()),
+					)),
+				}
+			},
+			PreferencesComponentInput::ImportWorkspace => {
+				let dialog = gtk::FileDialog::new();
+				let sender = sender.clone();
+				dialog.open(Some(root), gtk::gio::Cancellable::NONE, move |file| {
+					if let Some(path) = file.ok().and_then(|file| file.path()) {
+						sender.input(PreferencesComponentInput::ImportWorkspaceFrom(path));
+					}
+				});
+			},
+			PreferencesComponentInput::ImportWorkspaceFrom(path) => {
+				let workspace = std::fs::read_to_string(&path)
+					.map_err(anyhow::Error::from)
+					.and_then(|json| Workspace::from_json(&json));
+				match workspace {
+					Ok(workspace) => {
+						match workspace.import(Service::Computer, true).await {
+							Ok(report) => {
+								let sender = sender.clone();
+								import_dialog(
+									root,
+									fl!("import-workspace"),
+									&import_summary(&report),
+									move |service| {
+										sender.input(
+											PreferencesComponentInput::ImportWorkspaceInto(service),
+										)
+									},
+								);
+								self.workspace = Some(workspace);
+							},
+							Err(err) => widgets.overlay.add_toast(failure(
+								&sender,
+								fl!("workspace-not-read"),
+								err,
+								move || {
+									PreferencesComponentInput::ImportWorkspaceFrom(path.clone())
+								},
+							)),
+						}
+					},
+					Err(err) => widgets.overlay.add_toast(failure(
+						&sender,
+						fl!("workspace-not-read"),
+						err,
+						move || {
+							PreferencesComponentInput::ImportWorkspaceFrom(path.clone())
+						},
+					)),
+				}
+			},
+			PreferencesComponentInput::ImportWorkspaceInto(service) => {
+				if let Some(workspace) = self.workspace.take() {
+					match workspace.import(service, false).await {
+						Ok(report) => {
+							tracing::info!("{}", import_summary(&report));
+							widgets
+								.overlay
+								.add_toast(adw::Toast::new(fl!("workspace-imported")));
+							sender
+								.output(PreferencesComponentOutput::WorkspaceImported(service))
+								.unwrap_or_default();
+						},
+						Err(err) => {
+							// Kept so the import can be tried again.
+							self.workspace = Some(workspace);
+							widgets.overlay.add_toast(failure(
+								&sender,
+								fl!("workspace-not-imported"),
+								err,
+								move || PreferencesComponentInput::ImportWorkspaceInto(service),
+							));
+						},
+					}
+				}
+			},
+			PreferencesComponentInput::ImportList => {
+				let dialog = gtk::FileDialog::new();
+				let sender = sender.clone();
+				dialog.open(Some(root), gtk::gio::Cancellable::NONE, move |file| {
+					if let Some(path) = file.ok().and_then(|file| file.path()) {
+						sender.input(PreferencesComponentInput::ImportListFrom(path));
+					}
+				});
+			},
+			PreferencesComponentInput::ImportListFrom(path) => {
+				let bundle = std::fs::read_to_string(&path)
+					.map_err(anyhow::Error::from)
+					.and_then(|json| ListBundle::from_json(&json));
+				match bundle {
+					Ok(bundle) => {
+						let sender = sender.clone();
+						import_dialog(
+							root,
+							fl!("import-list"),
+							bundle.list.name.as_str(),
+							move |service| {
+								sender.input(PreferencesComponentInput::ImportListInto(service))
+							},
+						);
+						self.bundle = Some(bundle);
+					},
+					Err(err) => widgets.overlay.add_toast(failure(
+						&sender,
+						fl!("list-not-read"),
+						err,
+						move || PreferencesComponentInput::ImportListFrom(path.clone()),
+					)),
+				}
+			},
+			PreferencesComponentInput::ImportListInto(service) => {
+				match &self.bundle {
+					Some(bundle) if bundle.imported(service).await => {
+						// A newer bundle of a list imported before, ask whether to
+						// update it or keep both.
+						let dialog = adw::MessageDialog::new(
+							Some(root),
+							Some(fl!("import-list")),
+							Some(fl!(
+								"list-already-imported",
+								name = bundle.list.name.as_str()
+							)),
+						);
+						dialog.add_response("cancel", fl!("cancel"));
+						dialog.add_response("copy", fl!("import-as-copy"));
+						dialog.add_response("update", fl!("update"));
+						dialog.set_response_appearance(
+							"update",
+							adw::ResponseAppearance::Suggested,
+						);
+						dialog.set_close_response("cancel");
+						let sender = sender.clone();
+						dialog.connect_response(None, move |_, response| {
+							let mode = match response {
+								"update" => BundleImport::Update,
+								"copy" => BundleImport::Copy,
+								_ => return,
+							};
+							sender
+								.input(PreferencesComponentInput::ImportListAs(service, mode));
+						});
+						dialog.present();
+					},
+					Some(_) => sender.input(PreferencesComponentInput::ImportListAs(
+						service,
+						BundleImport::Update,
+					)),
+					None => (),
+				}
+			},
+			PreferencesComponentInput::ImportListAs(service, mode) => {
+				if let Some(bundle) = self.bundle.take() {
+					match bundle.import(service, mode).await {
+						Ok(report) => {
+							widgets
+								.overlay
+								.add_toast(adw::Toast::new(&bundle_summary(&report)));
+							sender
+								.output(PreferencesComponentOutput::ListImported(service))
+								.unwrap_or_default();
+						},
+						Err(err) => {
+							// Kept so the import can be tried again.
+							self.bundle = Some(bundle);
+							widgets.overlay.add_toast(failure(
+								&sender,
+								fl!("list-not-imported"),
+								err,
+								move || PreferencesComponentInput::ImportListAs(service, mode),
+							));
+						},
+					}
+				}
+			},
+			PreferencesComponentInput::ChooseServicePath(service) => {
+				let dialog = gtk::FileDialog::new();
+				let sender = sender.clone();
+				let callback = move |file: Result<gtk::gio::File, gtk::glib::Error>| {
+					if let Some(path) = file.ok().and_then(|file| file.path()) {
+						sender
+							.input(PreferencesComponentInput::SetServicePath(service, path));
+					}
+				};
+				if service.path_is_directory() {
+					dialog.select_folder(
+						Some(root),
+						gtk::gio::Cancellable::NONE,
+						callback,
+					);
+				} else {
+					dialog.open(Some(root), gtk::gio::Cancellable::NONE, callback);
+				}
+			},
+			PreferencesComponentInput::SetServicePath(service, path) => {
+				match service.set_path(path.clone()) {
+					Ok(_) => {
+						self.update_service_rows();
+						sender
+							.output(PreferencesComponentOutput::ServiceConnected(service))
+							.unwrap_or_default();
+					},
+					Err(err) => widgets.overlay.add_toast(failure(
+						&sender,
+						fl!("service-not-connected", service = service.to_string()),
+						err,
+						move || {
+							PreferencesComponentInput::SetServicePath(service, path.clone())
+						},
+					)),
+				}
+			},
+			PreferencesComponentInput::ClearServicePath(service) => {
+				match service.clear_path() {
+					Ok(_) => {
+						self.update_service_rows();
+						sender
+							.output(PreferencesComponentOutput::ServiceDisabled(service))
+							.unwrap_or_default();
+					},
+					Err(err) => widgets.overlay.add_toast(failure(
+						&sender,
+						fl!("service-not-disconnected", service = service.to_string()),
+						err,
+						move || PreferencesComponentInput::ClearServicePath(service),
+					)),
+				}
+			},
+			PreferencesComponentInput::ChooseArchivePath(service) => {
+				let dialog = gtk::FileDialog::new();
+				let sender = sender.clone();
+				dialog.open(Some(root), gtk::gio::Cancellable::NONE, move |file| {
+					if let Some(path) = file.ok().and_then(|file| file.path()) {
+						sender.input(PreferencesComponentInput::SetArchivePath(
+							service,
+							Some(path),
+						));
+					}
+				});
+			},
+			PreferencesComponentInput::SetArchivePath(service, path) => {
+				match service.set_archive_path(path.clone()) {
+					Ok(_) => {
+						self.update_service_rows();
+						sender
+							.output(PreferencesComponentOutput::ServiceConnected(service))
+							.unwrap_or_default();
+					},
+					Err(err) => widgets.overlay.add_toast(failure(
+						&sender,
+						fl!("archive-not-changed", service = service.to_string()),
+						err,
+						move || {
+							PreferencesComponentInput::SetArchivePath(service, path.clone())
+						},
+					)),
+				}
+			},
 			PreferencesComponentInput::MicrosoftLogin => {
 				let service = Service::Microsoft.get_service();
-				match service.login() {
+				match service.login().await {
 					Ok(_) => println!("Login started"),
 					Err(err) => eprintln!("{err}"),
 				};
 			},
 			PreferencesComponentInput::MicrosoftLogout => {
 				let service = Service::Microsoft.get_service();
-				match service.logout() {
+				match service.logout().await {
 					Ok(_) => {
 						println!("Logout completed");
 						sender
@@ -203,13 +1021,312 @@ impl AsyncComponent for PreferencesComponentModel {
 					Err(err) => eprintln!("{err}"),
 				};
 			},
+			PreferencesComponentInput::SelectMicrosoftAuthority(index) => {
+				let authority = match index {
+					0 => Authority::Consumers,
+					1 => Authority::Organizations,
+					2 => Authority::Common,
+					_ => {
+						// The tenant is saved once it is entered.
+						if !matches!(self.authority, Authority::Tenant(_)) {
+							self.authority = Authority::Tenant(String::new());
+						}
+						self.update_view(widgets, sender);
+						return;
+					},
+				};
+				sender
+					.input(PreferencesComponentInput::SetMicrosoftAuthority(authority));
+			},
+			PreferencesComponentInput::SignInWithBrowser => {
+				self.sign_in = MicrosoftSignIn::WaitingForBrowser;
+				let sender = sender.clone();
+				relm4::tokio::spawn(async move {
+					sender.input(PreferencesComponentInput::SetMicrosoftSignIn(
+						match sign_in_with_loopback().await {
+							Ok(()) => MicrosoftSignIn::SignedIn,
+							Err(err) => MicrosoftSignIn::Failed(err.to_string()),
+						},
+					));
+				});
+			},
+			PreferencesComponentInput::SignInWithCode => {
+				self.sign_in = MicrosoftSignIn::WaitingForBrowser;
+				let sender = sender.clone();
+				relm4::tokio::spawn(async move {
+					let result = match DeviceCode::request().await {
+						Ok(code) => {
+							sender.input(PreferencesComponentInput::SetMicrosoftSignIn(
+								MicrosoftSignIn::WaitingForCode(
+									code.user_code.clone(),
+									code.verification_uri.clone(),
+								),
+							));
+							code.sign_in().await
+						},
+						Err(err) => Err(err),
+					};
+					sender.input(PreferencesComponentInput::SetMicrosoftSignIn(
+						match result {
+							Ok(()) => MicrosoftSignIn::SignedIn,
+							Err(err) => MicrosoftSignIn::Failed(err.to_string()),
+						},
+					));
+				});
+			},
+			PreferencesComponentInput::SetMicrosoftSignIn(sign_in) => {
+				if let MicrosoftSignIn::Failed(err) = &sign_in {
+					tracing::error!("Failed to sign in to Microsoft: {err}");
+				}
+				if matches!(sign_in, MicrosoftSignIn::SignedIn) {
+					sender
+						.output(PreferencesComponentOutput::ServiceConnected(
+							Service::Microsoft,
+						))
+						.unwrap_or_default();
+				}
+				self.sign_in = sign_in;
+			},
+			PreferencesComponentInput::SetMicrosoftAuthority(authority) => {
+				match authority.clone().set() {
+					Ok(()) => self.authority = authority,
+					Err(err) => tracing::error!("{err}"),
+				}
+			},
 		}
 		self.update_view(widgets, sender);
 	}
 }
 
+impl PreferencesComponentModel {
+	fn signing_in(&self) -> bool {
+		matches!(
+			self.sign_in,
+			MicrosoftSignIn::WaitingForBrowser | MicrosoftSignIn::WaitingForCode(..)
+		)
+	}
+
+	fn sign_in_subtitle(&self) -> String {
+		match &self.sign_in {
+			MicrosoftSignIn::Idle => fl!("msft-sign-in-description").clone(),
+			MicrosoftSignIn::WaitingForBrowser => fl!("msft-waiting").clone(),
+			MicrosoftSignIn::WaitingForCode(code, uri) => {
+				fl!("msft-enter-code", code = code.as_str(), uri = uri.as_str()).clone()
+			},
+			MicrosoftSignIn::SignedIn => fl!("msft-signed-in").clone(),
+			MicrosoftSignIn::Failed(err) => {
+				fl!("msft-sign-in-failed", error = err.as_str()).clone()
+			},
+		}
+	}
+
+	fn update_service_rows(&self) {
+		for (service, row, disconnect) in &self.service_rows {
+			let path = service.path();
+			row.set_subtitle(
+				&path
+					.as_ref()
+					.map(|path| path.display().to_string())
+					.unwrap_or_else(|| no_path_chosen(service)),
+			);
+			disconnect.set_visible(path.is_some());
+		}
+		for (service, row, reset) in &self.archive_rows {
+			let path = service.archive_path();
+			row.set_visible(service.path().is_some());
+			row.set_subtitle(
+				&path
+					.as_ref()
+					.map(|path| path.display().to_string())
+					.unwrap_or_else(|| fl!("default-archive").clone()),
+			);
+			reset.set_visible(path.is_some());
+		}
+	}
+}
+
+/// What importing a workspace created, or would create on a dry run.
+fn import_summary(report: &ImportReport) -> String {
+	let (lists, tasks, sub_tasks) =
+		(report.lists.len(), report.tasks, report.sub_tasks);
+	let mut summary = if report.dry_run {
+		fl!(
+			"workspace-import-preview",
+			lists = lists,
+			tasks = tasks,
+			sub_tasks = sub_tasks
+		)
+		.clone()
+	} else {
+		fl!(
+			"workspace-import-done",
+			lists = lists,
+			tasks = tasks,
+			sub_tasks = sub_tasks
+		)
+		.clone()
+	};
+	for (name, tasks) in &report.lists {
+		summary.push('\n');
+		summary.push_str(fl!(
+			"workspace-import-list",
+			name = name.as_str(),
+			tasks = *tasks
+		));
+	}
+	if !report.skipped.is_empty() {
+		summary.push_str("\n\n");
+		summary.push_str(fl!(
+			"workspace-import-skipped",
+			services = service_names(&report.skipped)
+		));
+	}
+	summary
+}
+
+fn service_names(services: &[Service]) -> String {
+	services
+		.iter()
+		.map(ToString::to_string)
+		.collect::<Vec<String>>()
+		.join(", ")
+}
+
+/// Asks which service to import into, `choose` is called with the one
+/// picked.
+fn import_dialog(
+	root: &adw::PreferencesWindow,
+	heading: &str,
+	body: &str,
+	choose: impl Fn(Service) + 'static,
+) {
+	let dialog = adw::MessageDialog::new(Some(root), Some(heading), Some(body));
+	dialog.add_response("cancel", fl!("cancel"));
+	let services: Vec<Service> = Service::list()
+		.into_iter()
+		.filter(|service| {
+			*service != Service::Smart && service.get_service().available()
+		})
+		.collect();
+	for (index, service) in services.iter().enumerate() {
+		dialog.add_response(
+			&index.to_string(),
+			fl!("import-into", service = service.to_string()),
+		);
+	}
+	dialog.set_close_response("cancel");
+	dialog.connect_response(None, move |_, response| {
+		if let Some(service) = response
+			.parse::<usize>()
+			.ok()
+			.and_then(|index| services.get(index))
+		{
+			choose(*service);
+		}
+	});
+	dialog.present();
+}
+
+/// What importing a list did.
+fn bundle_summary(report: &BundleReport) -> String {
+	fl!(
+		"list-import-done",
+		name = report.list.as_str(),
+		created = report.created,
+		updated = report.updated,
+		unchanged = report.unchanged
+	)
+	.clone()
+}
+
+fn no_path_chosen(service: &Service) -> String {
+	if service.path_is_directory() {
+		fl!("no-folder-chosen").clone()
+	} else {
+		fl!("no-file-chosen").clone()
+	}
+}
+
+/// A row to choose the file or folder of a file backed service.
+fn service_row(
+	service: Service,
+	sender: &AsyncComponentSender<PreferencesComponentModel>,
+) -> (adw::ActionRow, gtk::Button) {
+	let row = adw::ActionRow::new();
+	row.set_title(&service.to_string());
+	row.add_prefix(&gtk::Image::from_resource(service.icon()));
+	let path = service.path();
+	row.set_subtitle(
+		&path
+			.as_ref()
+			.map(|path| path.display().to_string())
+			.unwrap_or_else(|| no_path_chosen(&service)),
+	);
+
+	let disconnect = gtk::Button::with_label(fl!("disconnect"));
+	disconnect.set_valign(gtk::Align::Center);
+	disconnect.set_visible(path.is_some());
+	let disconnect_sender = sender.clone();
+	disconnect.connect_clicked(move |_| {
+		disconnect_sender
+			.input(PreferencesComponentInput::ClearServicePath(service));
+	});
+	row.add_suffix(&disconnect);
+
+	let choose = gtk::Button::with_label(fl!("choose"));
+	choose.set_valign(gtk::Align::Center);
+	let choose_sender = sender.clone();
+	choose.connect_clicked(move |_| {
+		choose_sender.input(PreferencesComponentInput::ChooseServicePath(service));
+	});
+	row.add_suffix(&choose);
+
+	(row, disconnect)
+}
+
+/// A row to choose where a file backed service archives completed tasks.
+fn archive_row(
+	service: Service,
+	sender: &AsyncComponentSender<PreferencesComponentModel>,
+) -> (adw::ActionRow, gtk::Button) {
+	let row = adw::ActionRow::new();
+	row.set_title(fl!("archive"));
+
+	let reset = gtk::Button::with_label(fl!("reset"));
+	reset.set_valign(gtk::Align::Center);
+	let reset_sender = sender.clone();
+	reset.connect_clicked(move |_| {
+		reset_sender
+			.input(PreferencesComponentInput::SetArchivePath(service, None));
+	});
+	row.add_suffix(&reset);
+
+	let choose = gtk::Button::with_label(fl!("choose"));
+	choose.set_valign(gtk::Align::Center);
+	let choose_sender = sender.clone();
+	choose.connect_clicked(move |_| {
+		choose_sender.input(PreferencesComponentInput::ChooseArchivePath(service));
+	});
+	row.add_suffix(&choose);
+
+	(row, reset)
+}
+
 fn update_preferences(preferences: &Preferences) -> Result<()> {
-	Config::new(APP_ID, 1, None)?
+	Config::new(&Services::app_id(), 1, None)?
 		.set_json::<Preferences>("preferences", preferences.to_owned())?;
 	Ok(())
 }
+
+/// A toast for a failure, retrying sends the input `retry` builds.
+fn failure(
+	sender: &AsyncComponentSender<PreferencesComponentModel>,
+	message: &str,
+	error: impl Display,
+	retry: impl Fn() -> PreferencesComponentInput + Send + Sync + 'static,
+) -> adw::Toast {
+	let input = sender.input_sender().clone();
+	Failure::new(message, error)
+		.with_retry(move || input.emit(retry()))
+		.toast()
+}
@@ -0,0 +1,253 @@
+use relm4::{
+	adw,
+	adw::prelude::{ActionRowExt, EntryRowExt, PreferencesRowExt},
+	adw::traits::MessageDialogExt,
+	component::{AsyncComponent, AsyncComponentParts},
+	gtk::{
+		self,
+		prelude::{BoxExt, ButtonExt, EditableExt, GtkWindowExt, WidgetExt},
+		traits::OrientableExt,
+	},
+	AsyncComponentSender, RelmWidgetExt,
+};
+
+use done_core::{
+	models::profile::Profile, service::Services, services::profiles::Profiles,
+};
+
+use crate::app::models::failure::Failure;
+use crate::fl;
+
+pub struct ProfilesModel {
+	profiles: Vec<Profile>,
+	rows: gtk::ListBox,
+	overlay: adw::ToastOverlay,
+}
+
+#[derive(Debug)]
+pub enum ProfilesInput {
+	Open,
+	Refresh,
+	Add(String),
+	Switch(usize),
+	Remove(usize),
+	ConfirmRemove(usize),
+}
+
+#[derive(Debug)]
+pub enum ProfilesOutput {
+	/// Another profile was opened in its own window, this one closes.
+	Switched,
+}
+
+#[relm4::component(pub async)]
+impl AsyncComponent for ProfilesModel {
+	type CommandOutput = ();
+	type Input = ProfilesInput;
+	type Output = ProfilesOutput;
+	type Init = ();
+
+	view! {
+		#[root]
+		adw::Window {
+			set_hide_on_close: true,
+			set_default_width: 420,
+			set_default_height: 500,
+			set_modal: true,
+
+			#[local_ref]
+			overlay -> adw::ToastOverlay {
+				#[wrap(Some)]
+				set_child = &gtk::Box {
+					set_orientation: gtk::Orientation::Vertical,
+					adw::HeaderBar {
+						set_show_end_title_buttons: true,
+						set_css_classes: &["flat"],
+						set_title_widget: Some(&gtk::Label::new(Some(fl!("profiles")))),
+					},
+					gtk::Box {
+						set_orientation: gtk::Orientation::Vertical,
+						set_margin_all: 20,
+						set_spacing: 10,
+						gtk::Label {
+							set_css_classes: &["dim-label"],
+							set_wrap: true,
+							set_label: fl!("profiles-desc"),
+						},
+						gtk::ListBox {
+							set_css_classes: &["boxed-list"],
+							set_selection_mode: gtk::SelectionMode::None,
+							adw::EntryRow {
+								set_title: fl!("new-profile"),
+								set_show_apply_button: true,
+								connect_apply[sender] => move |entry| {
+									sender.input(ProfilesInput::Add(entry.text().to_string()));
+									entry.set_text("");
+								},
+							},
+						},
+						gtk::ScrolledWindow {
+							set_vexpand: true,
+							#[local_ref]
+							rows -> gtk::ListBox {
+								set_css_classes: &["boxed-list"],
+								set_selection_mode: gtk::SelectionMode::None,
+								set_valign: gtk::Align::Start,
+							},
+						},
+					}
+				}
+			}
+		}
+	}
+
+	async fn init(
+		_init: Self::Init,
+		root: Self::Root,
+		sender: AsyncComponentSender<Self>,
+	) -> AsyncComponentParts<Self> {
+		let model = Self {
+			profiles: vec![],
+			rows: gtk::ListBox::default(),
+			overlay: adw::ToastOverlay::default(),
+		};
+
+		let rows = &model.rows;
+		let overlay = &model.overlay;
+
+		let widgets = view_output!();
+
+		AsyncComponentParts { model, widgets }
+	}
+
+	async fn update(
+		&mut self,
+		message: Self::Input,
+		sender: AsyncComponentSender<Self>,
+		root: &Self::Root,
+	) {
+		match message {
+			ProfilesInput::Open => {
+				sender.input(ProfilesInput::Refresh);
+				root.present();
+			},
+			ProfilesInput::Refresh => {
+				self.profiles = Profiles::list();
+				self.rows.remove_all();
+				let current = Services::profile();
+				for (index, profile) in self.profiles.iter().enumerate() {
+					let row = adw::ActionRow::new();
+					row.set_title(&profile_name(profile));
+					if profile.slug() == current.slug() {
+						row.set_subtitle(fl!("current-profile"));
+					} else {
+						if !profile.is_default() {
+							let remove = gtk::Button::with_label(fl!("remove"));
+							remove.set_valign(gtk::Align::Center);
+							remove.add_css_class("destructive-action");
+							let sender = sender.clone();
+							remove.connect_clicked(move |_| {
+								sender.input(ProfilesInput::ConfirmRemove(index));
+							});
+							row.add_suffix(&remove);
+						}
+						let open = gtk::Button::with_label(fl!("open"));
+						open.set_valign(gtk::Align::Center);
+						let sender = sender.clone();
+						open.connect_clicked(move |_| {
+							sender.input(ProfilesInput::Switch(index));
+						});
+						row.add_suffix(&open);
+					}
+					self.rows.append(&row);
+				}
+			},
+			ProfilesInput::Add(name) => {
+				if let Err(err) = Profiles::add(&name) {
+					let retry = sender.input_sender().clone();
+					self.overlay.add_toast(
+						Failure::new(fl!("profile-not-added"), err)
+							.with_retry(move || retry.emit(ProfilesInput::Add(name.clone())))
+							.toast(),
+					);
+				}
+				sender.input(ProfilesInput::Refresh);
+			},
+			ProfilesInput::Switch(index) => {
+				let Some(profile) = self.profiles.get(index) else {
+					return;
+				};
+				let opened = std::env::current_exe().and_then(|executable| {
+					std::process::Command::new(executable)
+						.arg("--profile")
+						.arg(&profile.name)
+						.spawn()
+				});
+				match opened {
+					Ok(_) => {
+						root.close();
+						sender.output(ProfilesOutput::Switched).unwrap_or_default();
+					},
+					Err(err) => {
+						let retry = sender.input_sender().clone();
+						self.overlay.add_toast(
+							Failure::new(
+								fl!("profile-not-opened", profile = profile_name(profile)),
+								err,
+							)
+							.with_retry(move || retry.emit(ProfilesInput::Switch(index)))
+							.toast(),
+						);
+					},
+				}
+			},
+			ProfilesInput::ConfirmRemove(index) => {
+				let Some(profile) = self.profiles.get(index) else {
+					return;
+				};
+				let dialog = adw::MessageDialog::new(
+					Some(root),
+					Some(fl!("remove-profile", name = profile.name.as_str())),
+					Some(fl!("remove-profile-desc")),
+				);
+				dialog.add_response("cancel", fl!("cancel"));
+				dialog.add_response("remove", fl!("remove"));
+				dialog.set_response_appearance(
+					"remove",
+					adw::ResponseAppearance::Destructive,
+				);
+				dialog.set_close_response("cancel");
+				let sender = sender.clone();
+				dialog.connect_response(Some("remove"), move |_, _| {
+					sender.input(ProfilesInput::Remove(index));
+				});
+				dialog.present();
+			},
+			ProfilesInput::Remove(index) => {
+				if let Some(profile) = self.profiles.get(index) {
+					if let Err(err) = Profiles::remove(profile) {
+						let retry = sender.input_sender().clone();
+						self.overlay.add_toast(
+							Failure::new(
+								fl!("profile-not-removed", profile = profile_name(profile)),
+								err,
+							)
+							.with_retry(move || retry.emit(ProfilesInput::Remove(index)))
+							.toast(),
+						);
+					}
+				}
+				sender.input(ProfilesInput::Refresh);
+			},
+		}
+	}
+}
+
+/// The name of a profile, the default one is translated.
+pub fn profile_name(profile: &Profile) -> String {
+	if profile.is_default() {
+		fl!("default-profile").clone()
+	} else {
+		profile.name.clone()
+	}
+}
@@ -1,13 +1,17 @@
 use glib::Cast;
-use libadwaita::prelude::{FlowBoxChildExt, ToggleButtonExt};
+use libadwaita::prelude::{
+	BoxExt, ButtonExt, CheckButtonExt, FlowBoxChildExt, ToggleButtonExt,
+	WidgetExt,
+};
 use relm4::{
 	component::{AsyncComponent, AsyncComponentParts},
 	factory::{AsyncFactoryVecDeque, DynamicIndex},
 	gtk::{self, prelude::OrientableExt},
-	AsyncComponentSender, RelmIterChildrenExt, RelmWidgetExt,
+	AsyncComponentSender, RelmIterChildrenExt, RelmRemoveAllExt, RelmWidgetExt,
 };
+use relm4_icons::icon_name;
 
-use done_core::service::Service;
+use done_core::{service::Service, services::accounts::Accounts};
 
 use crate::{
 	app::factories::service::{ServiceFactoryModel, ServiceFactoryOutput},
@@ -16,18 +20,24 @@ use crate::{
 
 pub struct ServicesModel {
 	services_factory: AsyncFactoryVecDeque<ServiceFactoryModel>,
+	service: Service,
+	accounts: gtk::Box,
 }
 
 #[derive(Debug)]
 pub enum ServicesInput {
 	ServiceSelected(DynamicIndex, Service),
 	ReloadServices(Service),
+	AddAccount,
+	SetAccountEnabled(String, bool),
+	RemoveAccount(String),
 }
 
 #[derive(Debug)]
 pub enum ServicesOutput {
 	ServiceSelected(Service),
 	ServiceDisabled(Service),
+	AccountsChanged(Service),
 }
 
 #[relm4::component(pub async)]
@@ -40,6 +50,7 @@ impl AsyncComponent for ServicesModel {
 	view! {
 		#[root]
 		gtk::Box {
+			set_orientation: gtk::Orientation::Vertical,
 			#[local_ref]
 			flow_box -> gtk::FlowBox {
 				set_margin_all: 10,
@@ -59,6 +70,16 @@ impl AsyncComponent for ServicesModel {
 					2
 				},
 			},
+			#[local_ref]
+			accounts -> gtk::Box {
+				set_orientation: gtk::Orientation::Vertical,
+				set_margin_start: 10,
+				set_margin_end: 10,
+				set_margin_bottom: 10,
+				set_spacing: 5,
+				#[watch]
+				set_visible: model.service.has_accounts(),
+			},
 		}
 	}
 
@@ -89,9 +110,14 @@ impl AsyncComponent for ServicesModel {
 			}
 		}
 
-		let model = ServicesModel { services_factory };
+		let model = ServicesModel {
+			services_factory,
+			service: Service::Smart,
+			accounts: gtk::Box::default(),
+		};
 
 		let flow_box = model.services_factory.widget();
+		let accounts = &model.accounts;
 
 		let selected_child = flow_box
 			.child_at_index(0)
@@ -123,6 +149,10 @@ impl AsyncComponent for ServicesModel {
 						guard.push_back(service);
 					}
 				}
+				if self.service == service {
+					self.service = Service::Smart;
+					self.update_accounts(&sender);
+				}
 				sender
 					.output(ServicesOutput::ServiceDisabled(service))
 					.unwrap()
@@ -140,10 +170,79 @@ impl AsyncComponent for ServicesModel {
 					}
 				}
 
+				self.service = service;
+				self.update_accounts(&sender);
 				sender
 					.output(ServicesOutput::ServiceSelected(service))
 					.unwrap();
 			},
+			ServicesInput::AddAccount => {
+				if let Err(err) = self.service.get_service().login().await {
+					tracing::error!("Failed to start signing in: {err}");
+				}
+			},
+			ServicesInput::SetAccountEnabled(id, enabled) => {
+				match Accounts::set_enabled(self.service, &id, enabled) {
+					Ok(()) => sender
+						.output(ServicesOutput::AccountsChanged(self.service))
+						.unwrap(),
+					Err(err) => tracing::error!("Failed to update the account: {err}"),
+				}
+			},
+			ServicesInput::RemoveAccount(id) => {
+				if let Err(err) = self.service.remove_account(&id) {
+					tracing::error!("Failed to sign out: {err}");
+					return;
+				}
+				if self.service.accounts().is_empty() {
+					sender.input(ServicesInput::ReloadServices(self.service));
+				} else {
+					self.update_accounts(&sender);
+					sender
+						.output(ServicesOutput::AccountsChanged(self.service))
+						.unwrap();
+				}
+			},
+		}
+	}
+}
+
+impl ServicesModel {
+	/// Lists the accounts of the selected service, each can be hidden or
+	/// signed out of.
+	fn update_accounts(&self, sender: &AsyncComponentSender<Self>) {
+		self.accounts.remove_all();
+		if !self.service.has_accounts() {
+			return;
+		}
+		for account in self.service.accounts() {
+			let row = gtk::Box::new(gtk::Orientation::Horizontal, 5);
+			let enabled = gtk::CheckButton::with_label(&account.display_name);
+			enabled.set_active(account.enabled);
+			enabled.set_hexpand(true);
+			let id = account.id.clone();
+			let input = sender.input_sender().clone();
+			enabled.connect_toggled(move |button| {
+				input.emit(ServicesInput::SetAccountEnabled(
+					id.clone(),
+					button.is_active(),
+				))
+			});
+			let remove = gtk::Button::from_icon_name(icon_name::X_CIRCULAR);
+			remove.set_tooltip(fl!("sign-out"));
+			remove.add_css_class("flat");
+			let input = sender.input_sender().clone();
+			remove.connect_clicked(move |_| {
+				input.emit(ServicesInput::RemoveAccount(account.id.clone()))
+			});
+			row.append(&enabled);
+			row.append(&remove);
+			self.accounts.append(&row);
 		}
+		let add = gtk::Button::with_label(fl!("add-account"));
+		add.add_css_class("flat");
+		let input = sender.input_sender().clone();
+		add.connect_clicked(move |_| input.emit(ServicesInput::AddAccount));
+		self.accounts.append(&add);
 	}
 }
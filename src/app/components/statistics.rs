@@ -0,0 +1,207 @@
+use relm4::{
+	adw,
+	adw::prelude::{ActionRowExt, PreferencesGroupExt, PreferencesRowExt},
+	component::{AsyncComponent, AsyncComponentParts},
+	gtk::{
+		self,
+		prelude::{BoxExt, ButtonExt, GtkWindowExt, WidgetExt},
+		traits::OrientableExt,
+	},
+	AsyncComponentSender, RelmRemoveAllExt, RelmWidgetExt,
+};
+use relm4_icons::icon_name;
+
+use done_core::{
+	models::statistics::{Breakdown, Statistics},
+	service::Service,
+};
+
+use crate::{app::components::time_report::format_duration, fl};
+
+pub struct StatisticsModel {
+	statistics: Option<Statistics>,
+	sections: gtk::Box,
+}
+
+#[derive(Debug)]
+pub enum StatisticsInput {
+	Open,
+	Refresh,
+}
+
+#[relm4::component(pub async)]
+impl AsyncComponent for StatisticsModel {
+	type CommandOutput = ();
+	type Input = StatisticsInput;
+	type Output = ();
+	type Init = ();
+
+	view! {
+		#[root]
+		adw::Window {
+			set_hide_on_close: true,
+			set_default_width: 480,
+			set_default_height: 640,
+			set_modal: true,
+
+			gtk::Box {
+				set_orientation: gtk::Orientation::Vertical,
+				adw::HeaderBar {
+					set_show_end_title_buttons: true,
+					set_css_classes: &["flat"],
+					set_title_widget: Some(&gtk::Label::new(Some(fl!("statistics")))),
+					pack_start = &gtk::Button {
+						set_icon_name: icon_name::UPDATE,
+						set_tooltip: fl!("refresh"),
+						set_css_classes: &["flat", "image-button"],
+						connect_clicked => StatisticsInput::Refresh,
+					},
+				},
+				gtk::ScrolledWindow {
+					set_vexpand: true,
+					adw::Clamp {
+						gtk::Box {
+							set_orientation: gtk::Orientation::Vertical,
+							set_margin_all: 20,
+							set_spacing: 20,
+							#[local_ref]
+							sections -> gtk::Box {
+								set_orientation: gtk::Orientation::Vertical,
+								set_spacing: 20,
+							},
+						}
+					}
+				}
+			}
+		}
+	}
+
+	async fn init(
+		_init: Self::Init,
+		root: Self::Root,
+		sender: AsyncComponentSender<Self>,
+	) -> AsyncComponentParts<Self> {
+		let model = Self {
+			statistics: None,
+			sections: gtk::Box::default(),
+		};
+
+		let sections = &model.sections;
+
+		let widgets = view_output!();
+
+		AsyncComponentParts { model, widgets }
+	}
+
+	async fn update(
+		&mut self,
+		message: Self::Input,
+		sender: AsyncComponentSender<Self>,
+		root: &Self::Root,
+	) {
+		match message {
+			StatisticsInput::Open => {
+				root.present();
+				sender.input(StatisticsInput::Refresh);
+			},
+			StatisticsInput::Refresh => {
+				self.statistics = Some(Statistics::collect(&Service::list()).await);
+				self.sections.remove_all();
+				if let Some(statistics) = &self.statistics {
+					self.sections.append(&summary_group(statistics));
+					self.sections.append(&completed_group(statistics));
+					for (title, breakdowns) in [
+						(fl!("by-list"), &statistics.by_list),
+						(fl!("by-priority"), &statistics.by_priority),
+						(fl!("by-tag"), &statistics.by_tag),
+					] {
+						if !breakdowns.is_empty() {
+							self.sections.append(&breakdown_group(title, breakdowns));
+						}
+					}
+				}
+			},
+		}
+	}
+}
+
+fn summary_group(statistics: &Statistics) -> adw::PreferencesGroup {
+	let group = adw::PreferencesGroup::new();
+	group.set_title(fl!("summary"));
+	if !statistics.skipped.is_empty() {
+		let services: Vec<String> =
+			statistics.skipped.iter().map(ToString::to_string).collect();
+		group.set_description(Some(fl!(
+			"statistics-skipped",
+			services = services.join(", ")
+		)));
+	}
+	let lead_time = statistics
+		.average_lead_time
+		.map(format_duration)
+		.unwrap_or_else(|| "-".into());
+	for (title, value) in [
+		(fl!("total-tasks"), statistics.total.to_string()),
+		(fl!("completed-tasks"), statistics.completed.to_string()),
+		(fl!("overdue-tasks"), statistics.overdue.to_string()),
+		(fl!("average-lead-time"), lead_time),
+	] {
+		let row = adw::ActionRow::new();
+		row.set_title(title);
+		row.add_suffix(&gtk::Label::new(Some(&value)));
+		group.add(&row);
+	}
+	group
+}
+
+fn completed_group(statistics: &Statistics) -> adw::PreferencesGroup {
+	let group = adw::PreferencesGroup::new();
+	group.set_title(fl!("completed-per-day"));
+	let days = statistics.last_days(7);
+	let max = days
+		.iter()
+		.map(|(_, count)| *count)
+		.max()
+		.unwrap_or(0)
+		.max(1);
+	for (day, count) in days {
+		let row = adw::ActionRow::new();
+		row.set_title(&day.format("%a %d").to_string());
+		let bar = gtk::LevelBar::for_interval(0.0, max as f64);
+		bar.set_value(count as f64);
+		bar.set_hexpand(true);
+		bar.set_valign(gtk::Align::Center);
+		row.add_suffix(&bar);
+		row.add_suffix(&gtk::Label::new(Some(&count.to_string())));
+		group.add(&row);
+	}
+	for (week, count) in statistics.completed_per_week.iter().rev().take(4) {
+		let row = adw::ActionRow::new();
+		row.set_title(&format!("{} {}", fl!("week-of"), week.format("%m/%d/%Y")));
+		row.add_suffix(&gtk::Label::new(Some(&count.to_string())));
+		group.add(&row);
+	}
+	group
+}
+
+fn breakdown_group(
+	title: &str,
+	breakdowns: &[Breakdown],
+) -> adw::PreferencesGroup {
+	let group = adw::PreferencesGroup::new();
+	group.set_title(title);
+	for breakdown in breakdowns {
+		let row = adw::ActionRow::new();
+		row.set_title(&breakdown.name);
+		row.set_subtitle(&format!(
+			"{} {} · {} {}",
+			breakdown.completed,
+			fl!("completed"),
+			breakdown.overdue,
+			fl!("overdue")
+		));
+		row.add_suffix(&gtk::Label::new(Some(&breakdown.total.to_string())));
+		group.add(&row);
+	}
+	group
+}
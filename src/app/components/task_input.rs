@@ -1,11 +1,15 @@
 use adw::traits::{EntryRowExt, PreferencesRowExt};
-use gtk::traits::{EditableExt, ListBoxRowExt};
+use chrono::Utc;
+use gtk::traits::{BoxExt, ButtonExt, EditableExt, ListBoxRowExt, PopoverExt};
 use relm4::{
 	adw, gtk, gtk::prelude::WidgetExt, Component, ComponentParts,
-	ComponentSender, RelmWidgetExt,
+	ComponentSender, RelmRemoveAllExt, RelmWidgetExt,
 };
+use relm4_icons::icon_name;
 
 use done_core::models::task::Task;
+use done_core::models::template::{Template, TemplateContent};
+use done_core::services::local::templates::TemplateStore;
 
 use crate::{app::models::sidebar_list::SidebarList, fl};
 
@@ -14,6 +18,9 @@ pub struct TaskInputModel {
 	pub task: Task,
 	pub parent_list: SidebarList,
 	pub buffer: gtk::EntryBuffer,
+	templates: Vec<Template>,
+	templates_box: gtk::Box,
+	templates_popover: gtk::Popover,
 }
 
 #[derive(Debug)]
@@ -22,6 +29,8 @@ pub enum TaskInputInput {
 	AddTask,
 	Rename(String),
 	CleanTaskEntry,
+	LoadTemplates,
+	AddTaskFromTemplate(usize),
 }
 
 #[derive(Debug)]
@@ -58,6 +67,13 @@ impl Component for TaskInputModel {
 				let text = entry.text().to_string();
 				sender.input(TaskInputInput::Rename(text));
 			},
+			add_suffix = &gtk::MenuButton {
+				set_tooltip: fl!("templates"),
+				set_icon_name: icon_name::FILE_CABINET,
+				set_css_classes: &["flat", "image-button"],
+				set_valign: gtk::Align::Center,
+				set_popover: Some(&model.templates_popover),
+			},
 		}
 	}
 
@@ -70,8 +86,21 @@ impl Component for TaskInputModel {
 			task: Task::new(String::new(), String::new()),
 			parent_list: init,
 			buffer: gtk::EntryBuffer::new(None::<String>),
+			templates: vec![],
+			templates_box: gtk::Box::new(gtk::Orientation::Vertical, 5),
+			templates_popover: gtk::Popover::new(),
 		};
 
+		model
+			.templates_popover
+			.set_child(Some(&model.templates_box));
+		{
+			let sender = sender.clone();
+			model
+				.templates_popover
+				.connect_show(move |_| sender.input(TaskInputInput::LoadTemplates));
+		}
+
 		let widgets = view_output!();
 		ComponentParts { model, widgets }
 	}
@@ -105,6 +134,46 @@ impl Component for TaskInputModel {
 			TaskInputInput::SetParentList(list) => {
 				self.parent_list = list;
 			},
+			TaskInputInput::LoadTemplates => {
+				self.templates = match TemplateStore::new().read_templates() {
+					Ok(templates) => templates
+						.into_iter()
+						.filter(|template| template.is_task())
+						.collect(),
+					Err(err) => {
+						tracing::error!("Failed to load templates: {err}");
+						vec![]
+					},
+				};
+				self.templates_box.remove_all();
+				if self.templates.is_empty() {
+					let label = gtk::Label::new(Some(fl!("no-templates")));
+					label.set_margin_all(5);
+					self.templates_box.append(&label);
+				}
+				for (index, template) in self.templates.iter().enumerate() {
+					let button = gtk::Button::with_label(&template.name);
+					button.add_css_class("flat");
+					let sender = sender.clone();
+					button.connect_clicked(move |_| {
+						sender.input(TaskInputInput::AddTaskFromTemplate(index))
+					});
+					self.templates_box.append(&button);
+				}
+			},
+			TaskInputInput::AddTaskFromTemplate(index) => {
+				self.templates_popover.popdown();
+				if let (SidebarList::Custom(list), Some(template)) =
+					(&self.parent_list, self.templates.get(index))
+				{
+					if let TemplateContent::Task(template) = &template.content {
+						let task = template.instantiate(list.id.clone(), Utc::now());
+						sender
+							.output(TaskInputOutput::AddTask(task))
+							.unwrap_or_default();
+					}
+				}
+			},
 		}
 	}
 }
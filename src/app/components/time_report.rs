@@ -0,0 +1,271 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc};
+use relm4::{
+	adw,
+	adw::prelude::{ActionRowExt, PreferencesRowExt},
+	component::{AsyncComponent, AsyncComponentParts},
+	gtk::{
+		self,
+		prelude::{BoxExt, ButtonExt, FileExt, GtkWindowExt, WidgetExt},
+		traits::OrientableExt,
+	},
+	AsyncComponentSender, RelmWidgetExt,
+};
+use relm4_icons::icon_name;
+
+use done_core::{
+	models::{list::List, task::Task, time_entry::TimeReport},
+	service::Service,
+	services::local::time_tracking::TimeTracker,
+};
+
+use crate::app::models::failure::Failure;
+use crate::fl;
+
+pub struct TimeReportModel {
+	list: Option<List>,
+	tasks: Vec<Task>,
+	range: ReportRange,
+	report: TimeReport,
+	rows: gtk::ListBox,
+	overlay: adw::ToastOverlay,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportRange {
+	Today,
+	Week,
+	Month,
+	AllTime,
+}
+
+impl ReportRange {
+	fn start(&self) -> Option<DateTime<Utc>> {
+		let today = Utc::now().date_naive();
+		let day = match self {
+			ReportRange::Today => today,
+			ReportRange::Week => {
+				today - Duration::days(today.weekday().num_days_from_monday() as i64)
+			},
+			ReportRange::Month => today.with_day(1)?,
+			ReportRange::AllTime => return None,
+		};
+		Some(day.and_time(NaiveTime::MIN).and_utc())
+	}
+}
+
+impl From<u32> for ReportRange {
+	fn from(value: u32) -> Self {
+		match value {
+			0 => ReportRange::Today,
+			1 => ReportRange::Week,
+			2 => ReportRange::Month,
+			_ => ReportRange::AllTime,
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum TimeReportInput {
+	Open(Service, List),
+	SetRange(u32),
+	Refresh,
+	Export,
+	ExportTo(PathBuf),
+}
+
+#[relm4::component(pub async)]
+impl AsyncComponent for TimeReportModel {
+	type CommandOutput = ();
+	type Input = TimeReportInput;
+	type Output = ();
+	type Init = ();
+
+	view! {
+		#[root]
+		adw::Window {
+			set_hide_on_close: true,
+			set_default_width: 420,
+			set_default_height: 500,
+			set_modal: true,
+
+			#[local_ref]
+			overlay -> adw::ToastOverlay {
+				#[wrap(Some)]
+				set_child = &gtk::Box {
+					set_orientation: gtk::Orientation::Vertical,
+					adw::HeaderBar {
+						set_show_end_title_buttons: true,
+						set_css_classes: &["flat"],
+						set_title_widget: Some(&gtk::Label::new(Some(fl!("time-report")))),
+						pack_start = &gtk::Button {
+							set_icon_name: icon_name::FLOPPY,
+							set_tooltip: fl!("export-csv"),
+							set_css_classes: &["flat", "image-button"],
+							connect_clicked => TimeReportInput::Export,
+						},
+					},
+					gtk::Box {
+						set_orientation: gtk::Orientation::Vertical,
+						set_margin_all: 20,
+						set_spacing: 10,
+						gtk::Label {
+							set_css_classes: &["title-4"],
+							#[watch]
+							set_label: model.list.as_ref().map(|list| list.name.as_str()).unwrap_or_default(),
+						},
+						gtk::DropDown {
+							set_model: Some(&gtk::StringList::new(&[
+								fl!("today").as_str(),
+								fl!("this-week").as_str(),
+								fl!("this-month").as_str(),
+								fl!("all-time").as_str(),
+							])),
+							set_selected: 1,
+							connect_selected_notify[sender] => move |dropdown| {
+								sender.input(TimeReportInput::SetRange(dropdown.selected()));
+							}
+						},
+						gtk::ScrolledWindow {
+							set_vexpand: true,
+							#[local_ref]
+							rows -> gtk::ListBox {
+								set_css_classes: &["boxed-list"],
+								set_selection_mode: gtk::SelectionMode::None,
+								set_valign: gtk::Align::Start,
+							},
+						},
+						gtk::Label {
+							set_css_classes: &["heading"],
+							set_halign: gtk::Align::End,
+							#[watch]
+							set_label: &format!("{}: {}", fl!("total-tracked"), format_duration(model.report.total())),
+						},
+					}
+				}
+			}
+		}
+	}
+
+	async fn init(
+		_init: Self::Init,
+		root: Self::Root,
+		sender: AsyncComponentSender<Self>,
+	) -> AsyncComponentParts<Self> {
+		let model = Self {
+			list: None,
+			tasks: vec![],
+			range: ReportRange::Week,
+			report: TimeReport::default(),
+			rows: gtk::ListBox::default(),
+			overlay: adw::ToastOverlay::default(),
+		};
+
+		let rows = &model.rows;
+		let overlay = &model.overlay;
+
+		let widgets = view_output!();
+
+		AsyncComponentParts { model, widgets }
+	}
+
+	async fn update(
+		&mut self,
+		message: Self::Input,
+		sender: AsyncComponentSender<Self>,
+		root: &Self::Root,
+	) {
+		match message {
+			TimeReportInput::Open(service, list) => {
+				match service
+					.get_service()
+					.read_tasks_from_list(list.id.clone())
+					.await
+				{
+					Ok(tasks) => self.tasks = tasks,
+					Err(err) => {
+						self.tasks = vec![];
+						let retry = sender.input_sender().clone();
+						let list = list.clone();
+						self.overlay.add_toast(
+							Failure::new(fl!("tasks-not-loaded"), err)
+								.with_retry(move || {
+									retry.emit(TimeReportInput::Open(service, list.clone()))
+								})
+								.toast(),
+						);
+					},
+				}
+				self.list = Some(list);
+				sender.input(TimeReportInput::Refresh);
+				root.present();
+			},
+			TimeReportInput::SetRange(range) => {
+				self.range = range.into();
+				sender.input(TimeReportInput::Refresh);
+			},
+			TimeReportInput::Refresh => {
+				let list_id = self.list.as_ref().map(|list| list.id.clone());
+				match TimeTracker::new().and_then(|mut tracker| {
+					tracker.report(list_id, self.range.start(), None)
+				}) {
+					Ok(report) => self.report = report,
+					Err(err) => {
+						let retry = sender.input_sender().clone();
+						self.overlay.add_toast(
+							Failure::new(fl!("time-report-not-loaded"), err)
+								.with_retry(move || retry.emit(TimeReportInput::Refresh))
+								.toast(),
+						);
+					},
+				}
+				self.rows.remove_all();
+				for (task_id, duration) in self.report.totals_by_task() {
+					let title = self
+						.tasks
+						.iter()
+						.find(|task| task.id == task_id)
+						.map(|task| task.title.clone())
+						.unwrap_or(task_id);
+					let row = adw::ActionRow::new();
+					row.set_title(&title);
+					row.add_suffix(&gtk::Label::new(Some(&format_duration(duration))));
+					self.rows.append(&row);
+				}
+			},
+			TimeReportInput::Export => {
+				let dialog = gtk::FileDialog::new();
+				dialog.set_initial_name(Some("time-report.csv"));
+				dialog.save(Some(root), gtk::gio::Cancellable::NONE, move |file| {
+					if let Some(path) = file.ok().and_then(|file| file.path()) {
+						sender.input(TimeReportInput::ExportTo(path));
+					}
+				});
+			},
+			TimeReportInput::ExportTo(path) => {
+				if let Err(err) = std::fs::write(&path, self.report.to_csv(&self.tasks))
+				{
+					let retry = sender.input_sender().clone();
+					self.overlay.add_toast(
+						Failure::new(fl!("time-report-not-exported"), err)
+							.with_retry(move || {
+								retry.emit(TimeReportInput::ExportTo(path.clone()))
+							})
+							.toast(),
+					);
+				}
+			},
+		}
+	}
+}
+
+/// Formats a duration as hours and minutes, e.g. `1h 05m`.
+pub fn format_duration(duration: Duration) -> String {
+	let minutes = duration.num_minutes().max(0);
+	if minutes >= 60 {
+		format!("{}h {:02}m", minutes / 60, minutes % 60)
+	} else {
+		format!("{minutes}m")
+	}
+}
@@ -3,7 +3,9 @@ use libset::Config;
 use relm4::adw;
 use serde::{Deserialize, Serialize};
 
-use super::{info::APP_ID, preferences::Preferences};
+use done_core::service::Services;
+
+use super::preferences::Preferences;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum ColorScheme {
@@ -19,7 +21,7 @@ impl Default for ColorScheme {
 }
 
 pub(crate) fn init() -> Result<()> {
-	let project = Config::new(APP_ID, 1, None).unwrap();
+	let project = Config::new(&Services::app_id(), 1, None).unwrap();
 	match project.get_json::<Preferences>("preferences") {
 		Ok(preferences) => {
 			let color_scheme = match preferences.color_scheme {
@@ -1,3 +1,4 @@
+use done_core::models::{backup::BackupSettings, focus::PomodoroSettings};
 use serde::{Deserialize, Serialize};
 
 use super::appearance::ColorScheme;
@@ -6,6 +7,10 @@ use super::appearance::ColorScheme;
 pub struct Preferences {
 	pub color_scheme: ColorScheme,
 	pub expand_subtasks: bool,
+	#[serde(default)]
+	pub pomodoro: PomodoroSettings,
+	#[serde(default)]
+	pub backups: BackupSettings,
 }
 
 impl Preferences {
@@ -13,6 +18,8 @@ impl Preferences {
 		Self {
 			color_scheme: ColorScheme::Default,
 			expand_subtasks: false,
+			pomodoro: PomodoroSettings::default(),
+			backups: BackupSettings::default(),
 		}
 	}
 }
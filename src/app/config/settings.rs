@@ -1,26 +1,61 @@
 use anyhow::Result;
 use libset::{Config, FileType};
 
-use done_core::service::Services;
+use done_core::{
+	service::Services,
+	services::{local::backups::Backups, plugins::registry::PluginRegistry},
+};
 
 use super::{info::APP_ID, preferences::Preferences};
 
 pub(crate) fn init() -> Result<()> {
+	if let Err(err) = PluginRegistry::load() {
+		tracing::error!("Failed to load plugins: {err}");
+	}
 	migrate_old_database()?;
+	Services::open_database()?;
 	ensure_app_config_exists()?;
-	Services::init(APP_ID);
 	Ok(())
 }
 
+/// Checks every hour whether an automatic backup of the local database is
+/// due and takes it.
+pub(crate) fn schedule_backups() {
+	relm4::tokio::spawn(async move {
+		let mut interval =
+			relm4::tokio::time::interval(std::time::Duration::from_secs(60 * 60));
+		loop {
+			interval.tick().await;
+			let preferences =
+				if let Ok(config) = Config::new(&Services::app_id(), 1, None) {
+					config.get_json("preferences").unwrap_or(Preferences::new())
+				} else {
+					Preferences::new()
+				};
+			if let Err(err) = Backups::new()
+				.and_then(|mut backups| backups.run_scheduled(&preferences.backups))
+			{
+				tracing::error!("Failed to back up database: {err}");
+			}
+		}
+	});
+}
+
 fn ensure_app_config_exists() -> Result<()> {
-	let app_config = Config::new(APP_ID, 1, None)?;
+	let app_config = Config::new(&Services::app_id(), 1, None)?;
 	if !app_config.path("preferences", FileType::Json)?.exists() {
 		app_config.set_json("preferences", Preferences::new())?;
 	}
 	Ok(())
 }
 
+/// Moves the database from where older versions kept it into the default
+/// profile.
 fn migrate_old_database() -> Result<()> {
+	if !Services::profile().is_default() {
+		return Ok(());
+	}
+
 	let database_config = Config::new(APP_ID, 1, Some("database"))?;
 
 	let database_path =
@@ -32,6 +67,12 @@ fn migrate_old_database() -> Result<()> {
 		.join("dev.edfloreshz.Done.db");
 
 	if old_database_path.exists() {
+		if database_path
+			.metadata()
+			.is_ok_and(|metadata| metadata.len() > 0)
+		{
+			Backups::new()?.create()?;
+		}
 		let db = std::fs::read(&old_database_path)?;
 		std::fs::write(database_path, db)?;
 		std::fs::remove_dir_all(old_database_path.parent().unwrap())?;
@@ -40,6 +81,6 @@ fn migrate_old_database() -> Result<()> {
 }
 
 pub(crate) fn refresh() -> Result<()> {
-	Config::new(APP_ID, 1, None)?.clean()?;
+	Config::new(&Services::app_id(), 1, None)?.clean()?;
 	init()
 }
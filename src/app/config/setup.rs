@@ -1,10 +1,44 @@
 use super::appearance;
+use super::info::APP_ID;
 use super::{actions, gettext, localization, resources, settings};
-use anyhow::Result;
+use anyhow::{bail, Result};
+use done_core::models::profile::Profile;
+use done_core::service::Services;
+use done_core::services::{microsoft::login::DeviceCode, profiles::Profiles};
 use relm4::gtk::gio::ApplicationFlags;
 use relm4::gtk::prelude::{ApplicationExt, ApplicationExtManual};
 use relm4::{gtk, main_adw_application};
 
+/// Selects the profile named with `--profile`, or the one opened last, and
+/// returns the arguments left for GTK. Naming a new profile creates it.
+pub fn select_profile(args: Vec<String>) -> Result<(Profile, Vec<String>)> {
+	Services::init(APP_ID);
+	let mut name = None;
+	let mut rest = vec![];
+	let mut args = args.into_iter();
+	while let Some(arg) = args.next() {
+		if arg == "--profile" {
+			let Some(value) = args.next() else {
+				bail!("--profile needs the name of a profile.");
+			};
+			name = Some(value);
+		} else if let Some(value) = arg.strip_prefix("--profile=") {
+			name = Some(value.to_string());
+		} else {
+			rest.push(arg);
+		}
+	}
+	let profile = match name {
+		Some(name) => Profiles::open(&name)?,
+		None => Profiles::last(),
+	};
+	if let Err(err) = Profiles::set_last(&profile) {
+		tracing::error!("Failed to remember the profile: {err}");
+	}
+	Services::select_profile(profile.clone());
+	Ok((profile, rest))
+}
+
 pub fn init() -> Result<()> {
 	gtk::init()?;
 	gettext::init();
@@ -29,9 +63,22 @@ pub fn connect_signals() {
 
 pub fn init_services() -> Result<()> {
 	settings::init()?;
+	settings::schedule_backups();
 	appearance::init()
 }
 
+/// Signs in to Microsoft To Do with a code entered on another device,
+/// without starting the app. `show_code` gets the instructions for the user
+/// before the sign in is awaited.
+pub fn sign_in_with_device_code(show_code: impl FnOnce(&str)) -> Result<()> {
+	settings::init()?;
+	relm4::tokio::runtime::Runtime::new()?.block_on(async {
+		let code = DeviceCode::request().await?;
+		show_code(&code.message);
+		code.sign_in().await
+	})
+}
+
 pub fn refresh() -> Result<()> {
 	settings::refresh()
 }
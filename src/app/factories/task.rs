@@ -7,14 +7,17 @@ use adw::prelude::{
 use adw::traits::{EntryRowExt, PreferencesRowExt};
 use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
 use libset::Config;
-use relm4::factory::{AsyncFactoryComponent, FactoryVecDeque};
-use relm4::factory::{AsyncFactorySender, DynamicIndex, FactoryView};
+use relm4::component::{
+	AsyncComponent, AsyncComponentParts, AsyncComponentSender,
+};
+use relm4::factory::{DynamicIndex, FactoryVecDeque};
 use relm4::{
 	adw, gtk,
 	gtk::prelude::{
-		ButtonExt, CheckButtonExt, EditableExt, ListBoxRowExt, WidgetExt,
+		ButtonExt, CheckButtonExt, EditableExt, ListBoxRowExt, PopoverExt,
+		WidgetExt,
 	},
-	RelmWidgetExt,
+	tokio, JoinHandle, RelmWidgetExt,
 };
 use relm4_icons::icon_name;
 
@@ -23,9 +26,15 @@ use done_core::models::priority::Priority;
 use done_core::models::recurrence::Day;
 use done_core::models::status::Status;
 use done_core::models::task::Task;
+use done_core::models::template::Template;
+use done_core::models::time_entry::TaskTime;
+use done_core::service::Services;
+use done_core::services::local::templates::TemplateStore;
+use done_core::services::local::time_tracking::TimeTracker;
 
-use crate::app::config::info::APP_ID;
+use crate::app::components::time_report::format_duration;
 use crate::app::config::preferences::Preferences;
+use crate::app::models::failure::Failure;
 use crate::fl;
 
 use super::sub_tasks::messages::SubTaskOutput;
@@ -34,26 +43,42 @@ use super::sub_tasks::model::{SubTaskInit, SubTaskModel};
 #[derive(Debug)]
 pub struct TaskModel {
 	pub task: Task,
-	pub sub_tasks: FactoryVecDeque<SubTaskModel>,
-	pub parent_list: List,
-	pub index: DynamicIndex,
+	sub_tasks: FactoryVecDeque<SubTaskModel>,
+	parent_list: List,
 	notes_buffer: gtk::TextBuffer,
 	preferences: Preferences,
+	time: TaskTime,
+	/// Refreshes the tracked time while the timer runs.
+	ticker: Option<JoinHandle<()>>,
+	/// Whether the last change reached the service.
+	saved: bool,
+	/// Set while the widgets are updated for another task, the changes they
+	/// report until then aren't edits.
+	binding: bool,
 }
 
-#[derive(derive_new::new)]
-pub struct TaskInit {
+/// A task of the list shown in the content, rows are bound to the items
+/// scrolled into view and reused for others.
+#[derive(Debug, Clone, derive_new::new)]
+pub struct TaskItem {
 	pub task: Task,
 	pub parent_list: List,
+	pub time: TaskTime,
+	#[new(value = "true")]
+	pub saved: bool,
 }
 
 #[derive(Debug)]
 pub enum TaskInput {
+	/// Shows another task in the row.
+	Bind(Box<TaskItem>),
+	/// The widgets show the task bound last.
+	Bound,
 	SetCompleted(bool),
 	ModifyTitle(String),
 	Favorite,
 	SetNotes,
-	SetPriority(i32),
+	SetPriority(Priority),
 	SetDueDate(Option<DateTime<Utc>>),
 	SetReminderDate(Option<DateTime<Utc>>),
 	SetReminderHour(u32),
@@ -64,12 +89,30 @@ pub enum TaskInput {
 	RemoveSubTask(DynamicIndex),
 	ExpandSubTask(bool),
 	CreateSubTask,
+	SaveAsTemplate,
+	ToggleTimer,
+	SetEstimate(i32),
+	Tick,
+	/// Whether the last change of a task reached the service, rows not
+	/// showing the task ignore it.
+	SetSaved(String, bool),
+	Remove,
+	/// Done editing, the edits don't have to wait to be saved.
+	Flush,
+	Focus,
 }
 
 #[derive(Debug)]
 pub enum TaskOutput {
-	Remove(DynamicIndex),
+	Remove(Task),
 	UpdateTask(Task),
+	/// The tracked time or estimate of a task changed.
+	Time(String, TaskTime),
+	Focus(Task),
+	Flush(String),
+	/// Shown on the toast overlay of the content.
+	Error(Failure),
+	Notice(String),
 }
 
 #[derive(Debug)]
@@ -85,40 +128,67 @@ pub enum DateDay {
 	None,
 }
 
-#[relm4::factory(pub async)]
-impl AsyncFactoryComponent for TaskModel {
-	type ParentWidget = adw::PreferencesGroup;
+#[relm4::component(pub async)]
+impl AsyncComponent for TaskModel {
 	type CommandOutput = ();
 	type Input = TaskInput;
 	type Output = TaskOutput;
-	type Init = TaskInit;
-	type Widgets = TaskWidgets;
+	type Init = ();
 
 	view! {
-		root = adw::ExpanderRow {
+		#[root]
+		adw::ExpanderRow {
+			add_css_class: "card",
 			#[watch]
-			set_title: self.task.title.as_str(),
+			set_title: model.task.title.as_str(),
 			#[watch]
-			set_subtitle: &if let Some(reminder_date) = self.task.reminder_date {
+			set_subtitle: &if let Some(reminder_date) = model.task.reminder_date {
 				format!("Reminder: {}", reminder_date.format("%m/%d/%Y %H:%M"))
-			} else if let Some(due_date) = self.task.due_date {
+			} else if let Some(due_date) = model.task.due_date {
 				format!("Due: {}", due_date.format("%m/%d/%Y"))
 			}  else {
-				format!("Sub tasks: {}", self.task.sub_tasks.len())
+				format!("Sub tasks: {}", model.task.sub_tasks.len())
 			},
 			#[watch]
-			set_expanded: self.preferences.expand_subtasks && !self.task.sub_tasks.is_empty(),
+			set_expanded: model.preferences.expand_subtasks && !model.task.sub_tasks.is_empty(),
 			#[watch]
-			set_enable_expansion: !self.task.sub_tasks.is_empty(),
+			set_enable_expansion: !model.task.sub_tasks.is_empty(),
 			#[name(check_button)]
 			add_prefix = &gtk::CheckButton {
 				set_tooltip: fl!("completed-tooltip"),
 				#[watch]
-				set_active: self.task.status == Status::Completed,
+				set_active: model.task.status == Status::Completed,
 				connect_toggled[sender] => move |checkbox| {
 					sender.input(TaskInput::SetCompleted(checkbox.is_active()));
 				}
 			},
+			#[name(not_saved)]
+			add_suffix = &gtk::Image {
+				add_css_class: "warning",
+				set_icon_name: Some(icon_name::WARNING),
+				set_tooltip: fl!("task-not-saved"),
+				#[watch]
+				set_visible: !model.saved,
+			},
+			#[name(tracked_time)]
+			add_suffix = &gtk::Label {
+				add_css_class: "caption",
+				#[watch]
+				set_visible: model.time.total() > Duration::zero() || model.time.estimate.is_some(),
+				#[watch]
+				set_text: &tracked_time_label(model.time.total(), model.time.estimate),
+			},
+			#[name(timer)]
+			add_suffix = &gtk::Button {
+				add_css_class: "circular",
+				#[watch]
+				set_class_active: ("accent", model.time.running_since.is_some()),
+				#[watch]
+				set_icon_name: if model.time.running_since.is_some() { icon_name::STOP } else { icon_name::PLAY },
+				set_tooltip: fl!("track-time"),
+				set_valign: gtk::Align::Center,
+				connect_clicked => TaskInput::ToggleTimer,
+			},
 			#[name(delete)]
 			add_suffix = &gtk::Button {
 				add_css_class: "error",
@@ -126,9 +196,7 @@ impl AsyncFactoryComponent for TaskModel {
 				set_icon_name: icon_name::X_CIRCULAR,
 				set_tooltip: fl!("remove-task"),
 				set_valign: gtk::Align::Center,
-				connect_clicked[sender, index] => move |_| {
-					sender.output(TaskOutput::Remove(index.clone())).unwrap()
-				}
+				connect_clicked => TaskInput::Remove,
 			},
 			#[name(sub_tasks_button)]
 			add_suffix = &gtk::MenuButton {
@@ -139,13 +207,14 @@ impl AsyncFactoryComponent for TaskModel {
 				set_tooltip: fl!("details"),
 				#[wrap(Some)]
 				set_popover = &gtk::Popover {
+					connect_closed => TaskInput::Flush,
 					adw::PreferencesGroup {
 						set_margin_all: 10,
 						set_title: fl!("details"),
 						#[name(title)]
 						add = &adw::EntryRow {
 							set_title: fl!("title"),
-							set_text: self.task.title.as_str(),
+							set_text: model.task.title.as_str(),
 							set_show_apply_button: true,
 							set_enable_emoji_completion: true,
 							connect_changed[sender] => move |entry| {
@@ -172,7 +241,7 @@ impl AsyncFactoryComponent for TaskModel {
 								add_css_class: "opaque",
 								add_css_class: "circular",
 								#[watch]
-								set_class_active: ("favorite", self.task.favorite),
+								set_class_active: ("favorite", model.task.favorite),
 								set_icon_name: icon_name::STAR_FILLED_ROUNDED,
 								set_valign: gtk::Align::Center,
 								set_tooltip: fl!("favorite"),
@@ -194,46 +263,67 @@ impl AsyncFactoryComponent for TaskModel {
 									set_tooltip: fl!("low"),
 									set_css_classes: &["flat", "image-button"],
 									set_valign: gtk::Align::Center,
-									set_active: self.task.priority == Priority::Low,
+									set_active: model.task.priority == Priority::Low,
 									connect_toggled[sender] => move |toggle| {
 										if toggle.is_active() {
-											sender.input(TaskInput::SetPriority(Priority::Low as i32));
+											sender.input(TaskInput::SetPriority(Priority::Low));
 										}
 									}
 								},
+								#[name(normal_importance)]
 								gtk::ToggleButton {
 									set_icon_name: icon_name::FLAG_OUTLINE_THICK,
 									set_tooltip: fl!("medium"),
 									set_css_classes: &["flat", "image-button"],
 									set_valign: gtk::Align::Center,
 									set_group: Some(&low_importance),
-									set_active: self.task.priority == Priority::Normal,
+									set_active: model.task.priority == Priority::Normal,
 									connect_toggled[sender] => move |toggle| {
 										if toggle.is_active() {
-											sender.input(TaskInput::SetPriority(Priority::Normal as i32));
+											sender.input(TaskInput::SetPriority(Priority::Normal));
 										}
 									}
 								},
+								#[name(high_importance)]
 								gtk::ToggleButton {
 									set_icon_name: icon_name::FLAG_FILLED,
 									set_tooltip: fl!("high"),
 									set_css_classes: &["flat", "image-button"],
 									set_valign: gtk::Align::Center,
 									set_group: Some(&low_importance),
-									set_active: self.task.priority == Priority::High,
+									set_active: model.task.priority == Priority::High,
 									connect_toggled[sender] => move |toggle| {
 										if toggle.is_active() {
-											sender.input(TaskInput::SetPriority(Priority::High as i32));
+											sender.input(TaskInput::SetPriority(Priority::High));
 										}
 									}
 								}
 							}
 						},
+						add = &adw::ActionRow {
+							set_title: fl!("estimate"),
+							set_subtitle: fl!("estimate-desc"),
+							add_prefix = &gtk::Image {
+								set_icon_name: Some(icon_name::TIMER)
+							},
+							#[name(estimate)]
+							add_suffix = &gtk::SpinButton {
+								set_adjustment: &gtk::Adjustment::new(
+									model.time.estimate.unwrap_or_default() as f64, 0.0, 10000.0, 5.0, 15.0, 0.0
+								),
+								set_numeric: true,
+								set_valign: gtk::Align::Center,
+								set_tooltip: fl!("estimate-desc"),
+								connect_value_changed[sender] => move |spin| {
+									sender.input(TaskInput::SetEstimate(spin.value() as i32))
+								},
+							},
+						},
 						#[name(reminder)]
 						add = &adw::ActionRow {
 							set_title: fl!("reminder"),
 							#[watch]
-							set_subtitle: &self.task.reminder_date.map(|date| date.format("%m/%d/%Y %H:%M").to_string()).unwrap_or(fl!("no-date-set").to_string()),
+							set_subtitle: &model.task.reminder_date.map(|date| date.format("%m/%d/%Y %H:%M").to_string()).unwrap_or(fl!("no-date-set").to_string()),
 							add_prefix = &gtk::Image {
 								set_icon_name: Some(icon_name::ALARM)
 							},
@@ -249,9 +339,9 @@ impl AsyncFactoryComponent for TaskModel {
 										gtk::Calendar {
 											set_margin_all: 10,
 											add_css_class: "card",
-											set_day: self.task.reminder_date.unwrap_or(Utc::now()).day() as i32,
-											set_month: self.task.reminder_date.unwrap_or(Utc::now()).month() as i32 - 1,
-											set_year: self.task.reminder_date.unwrap_or(Utc::now()).year(),
+											set_day: model.task.reminder_date.unwrap_or(Utc::now()).day() as i32,
+											set_month: model.task.reminder_date.unwrap_or(Utc::now()).month() as i32 - 1,
+											set_year: model.task.reminder_date.unwrap_or(Utc::now()).year(),
 											connect_day_selected[sender] => move |calendar| {
 												if let Ok(date) = calendar.date().format("%Y-%m-%dT%H:%M:%SZ") {
 													if let Ok(date) = DateTime::<Utc>::from_str(date.to_string().as_str()) {
@@ -298,9 +388,10 @@ impl AsyncFactoryComponent for TaskModel {
 										set_orientation: gtk::Orientation::Horizontal,
 										set_spacing: 10,
 										set_halign: gtk::Align::Center,
+										#[name(reminder_hour)]
 										gtk::SpinButton {
 											set_adjustment: &gtk::Adjustment::new(
-												self.task.reminder_date.unwrap_or_default().time().hour() as f64, 0.0, 23.0, 1.0, 1.0, 0.0
+												model.task.reminder_date.unwrap_or_default().time().hour() as f64, 0.0, 23.0, 1.0, 1.0, 0.0
 											),
 											set_orientation: gtk::Orientation::Horizontal,
 											set_wrap: true,
@@ -316,9 +407,10 @@ impl AsyncFactoryComponent for TaskModel {
 										gtk::Label {
 											set_text: ":",
 										},
+										#[name(reminder_minute)]
 										gtk::SpinButton {
 											set_adjustment: &gtk::Adjustment::new(
-												self.task.reminder_date.unwrap_or_default().time().minute() as f64, 0.0, 59.0, 1.0, 1.0, 0.0
+												model.task.reminder_date.unwrap_or_default().time().minute() as f64, 0.0, 59.0, 1.0, 1.0, 0.0
 											),
 											set_orientation: gtk::Orientation::Horizontal,
 											set_wrap: true,
@@ -354,49 +446,49 @@ impl AsyncFactoryComponent for TaskModel {
 												set_label: fl!("mon"),
 												set_tooltip: fl!("monday"),
 												#[watch]
-												set_active: self.task.recurrence.monday,
+												set_active: model.task.recurrence.monday,
 												connect_toggled[sender] => move |toggled_button| sender.input(TaskInput::SetDayInRecurrence((toggled_button.is_active(), Day::Monday)))
 											},
 											gtk::ToggleButton {
 												set_label: fl!("tue"),
 												set_tooltip: fl!("tuesday"),
 												#[watch]
-												set_active: self.task.recurrence.tuesday,
+												set_active: model.task.recurrence.tuesday,
 												connect_toggled[sender] => move |toggled_button| sender.input(TaskInput::SetDayInRecurrence((toggled_button.is_active(), Day::Tuesday)))
 											},
 											gtk::ToggleButton {
 												set_label: fl!("wed"),
 												set_tooltip: fl!("wednesday"),
 												#[watch]
-												set_active: self.task.recurrence.wednesday,
+												set_active: model.task.recurrence.wednesday,
 												connect_toggled[sender] => move |toggled_button| sender.input(TaskInput::SetDayInRecurrence((toggled_button.is_active(), Day::Wednesday)))
 											},
 											gtk::ToggleButton {
 												set_label: fl!("thu"),
 												set_tooltip: fl!("thursday"),
 												#[watch]
-												set_active: self.task.recurrence.thursday,
+												set_active: model.task.recurrence.thursday,
 												connect_toggled[sender] => move |toggled_button| sender.input(TaskInput::SetDayInRecurrence((toggled_button.is_active(), Day::Thursday)))
 											},
 											gtk::ToggleButton {
 												set_label: fl!("fri"),
 												set_tooltip: fl!("friday"),
 												#[watch]
-												set_active: self.task.recurrence.friday,
+												set_active: model.task.recurrence.friday,
 												connect_toggled[sender] => move |toggled_button| sender.input(TaskInput::SetDayInRecurrence((toggled_button.is_active(), Day::Friday)))
 											},
 											gtk::ToggleButton {
 												set_label: fl!("sat"),
 												set_tooltip: fl!("saturday"),
 												#[watch]
-												set_active: self.task.recurrence.saturday,
+												set_active: model.task.recurrence.saturday,
 												connect_toggled[sender] => move |toggled_button| sender.input(TaskInput::SetDayInRecurrence((toggled_button.is_active(), Day::Saturday)))
 											},
 											gtk::ToggleButton {
 												set_label: fl!("sun"),
 												set_tooltip: fl!("sunday"),
 												#[watch]
-												set_active: self.task.recurrence.sunday,
+												set_active: model.task.recurrence.sunday,
 												connect_toggled[sender] => move |toggled_button| sender.input(TaskInput::SetDayInRecurrence((toggled_button.is_active(), Day::Sunday)))
 											},
 										},
@@ -411,7 +503,7 @@ impl AsyncFactoryComponent for TaskModel {
 							},
 							set_title: fl!("due-date"),
 							#[watch]
-							set_subtitle: &self.task.due_date.map(|date| date.format("%m/%d/%Y").to_string()).unwrap_or(fl!("no-date-set").to_string()),
+							set_subtitle: &model.task.due_date.map(|date| date.format("%m/%d/%Y").to_string()).unwrap_or(fl!("no-date-set").to_string()),
 							#[name(due_date_label)]
 							add_suffix = &gtk::MenuButton {
 								set_tooltip: fl!("due-date"),
@@ -425,9 +517,9 @@ impl AsyncFactoryComponent for TaskModel {
 										gtk::Calendar {
 											set_margin_all: 10,
 											add_css_class: "card",
-											set_day: self.task.due_date.unwrap_or(Utc::now()).day() as i32,
-											set_month: self.task.due_date.unwrap_or(Utc::now()).month() as i32 - 1,
-											set_year: self.task.due_date.unwrap_or(Utc::now()).year(),
+											set_day: model.task.due_date.unwrap_or(Utc::now()).day() as i32,
+											set_month: model.task.due_date.unwrap_or(Utc::now()).month() as i32 - 1,
+											set_year: model.task.due_date.unwrap_or(Utc::now()).year(),
 											connect_day_selected[sender] => move |calendar| {
 												if let Ok(date) = calendar.date().format("%Y-%m-%dT%H:%M:%SZ") {
 													println!("{date}");
@@ -472,6 +564,36 @@ impl AsyncFactoryComponent for TaskModel {
 								}
 							},
 						},
+						#[name(focus)]
+						add = &adw::ActionRow {
+							set_title: fl!("focus"),
+							set_subtitle: fl!("focus-desc"),
+							add_prefix = &gtk::Image {
+								set_icon_name: Some(icon_name::TARGET_REGULAR)
+							},
+							add_suffix = &gtk::Button {
+								add_css_class: "flat",
+								set_icon_name: icon_name::PLAY,
+								set_valign: gtk::Align::Center,
+								set_tooltip: fl!("start-focus"),
+								connect_clicked => TaskInput::Focus,
+							},
+						},
+						#[name(save_template)]
+						add = &adw::ActionRow {
+							set_title: fl!("save-as-template"),
+							set_subtitle: fl!("save-as-template-desc"),
+							add_prefix = &gtk::Image {
+								set_icon_name: Some(icon_name::FILE_CABINET)
+							},
+							add_suffix = &gtk::Button {
+								add_css_class: "flat",
+								set_icon_name: icon_name::FLOPPY,
+								set_valign: gtk::Align::Center,
+								set_tooltip: fl!("save-as-template"),
+								connect_clicked => TaskInput::SaveAsTemplate,
+							},
+						},
 						#[name(notes)]
 						add = &gtk::ListBoxRow {
 							set_activatable: false,
@@ -485,7 +607,7 @@ impl AsyncFactoryComponent for TaskModel {
 									set_left_margin: 10,
 									set_right_margin: 10,
 									set_height_request: 100,
-									set_buffer: Some(&self.notes_buffer),
+									set_buffer: Some(&model.notes_buffer),
 								}
 							}
 						},
@@ -510,17 +632,13 @@ impl AsyncFactoryComponent for TaskModel {
 		}
 	}
 
-	async fn init_model(
-		init: Self::Init,
-		index: &DynamicIndex,
-		sender: AsyncFactorySender<Self>,
-	) -> Self {
-		let mut task = init.task.clone();
-		let notes_buffer = gtk::TextBuffer::default();
-		notes_buffer.set_text(&task.notes);
-		task.parent = init.parent_list.id.clone();
-		let mut model = Self {
-			task,
+	async fn init(
+		_init: Self::Init,
+		root: Self::Root,
+		sender: AsyncComponentSender<Self>,
+	) -> AsyncComponentParts<Self> {
+		let model = Self {
+			task: Task::default(),
 			sub_tasks: FactoryVecDeque::builder()
 				.launch(adw::PreferencesGroup::default())
 				.forward(sender.input_sender(), |output| match output {
@@ -529,48 +647,62 @@ impl AsyncFactoryComponent for TaskModel {
 					},
 					SubTaskOutput::Remove(index) => TaskInput::RemoveSubTask(index),
 				}),
-			parent_list: init.parent_list,
-			index: index.clone(),
-			notes_buffer,
-			preferences: if let Ok(config) = Config::new(APP_ID, 1, None) {
+			parent_list: List::default(),
+			notes_buffer: gtk::TextBuffer::default(),
+			preferences: if let Ok(config) = Config::new(&Services::app_id(), 1, None)
+			{
 				config.get_json("preferences").unwrap_or(Preferences::new())
 			} else {
 				Preferences::new()
 			},
+			time: TaskTime::default(),
+			ticker: None,
+			saved: true,
+			binding: false,
 		};
 
+		let notes_sender = sender.clone();
 		model
 			.notes_buffer
-			.connect_changed(move |_| sender.input(TaskInput::SetNotes));
-
-		{
-			let mut sub_tasks_guard = model.sub_tasks.guard();
-			for sub_task in init.task.sub_tasks {
-				sub_tasks_guard.push_back(SubTaskInit::new(sub_task));
-			}
-		}
-		model
-	}
+			.connect_changed(move |_| notes_sender.input(TaskInput::SetNotes));
 
-	fn init_widgets(
-		&mut self,
-		index: &DynamicIndex,
-		root: &Self::Root,
-		_returned_widget: &<Self::ParentWidget as FactoryView>::ReturnedWidget,
-		sender: AsyncFactorySender<Self>,
-	) -> Self::Widgets {
-		let sub_tasks = self.sub_tasks.widget();
+		let sub_tasks = model.sub_tasks.widget();
 		let widgets = view_output!();
-		widgets
+		AsyncComponentParts { model, widgets }
 	}
 
 	async fn update_with_view(
 		&mut self,
 		widgets: &mut Self::Widgets,
 		message: Self::Input,
-		sender: AsyncFactorySender<Self>,
+		sender: AsyncComponentSender<Self>,
+		_root: &Self::Root,
 	) {
+		// Changes reported by the widgets while they are set to another task.
+		if self.binding
+			&& !matches!(
+				message,
+				TaskInput::Bind(_)
+					| TaskInput::Bound
+					| TaskInput::Tick
+					| TaskInput::SetSaved(..)
+					| TaskInput::ExpandSubTask(_)
+			) {
+			return;
+		}
+		// Only edits are saved, setting a widget to the value it has isn't one.
+		let task =
+			(!matches!(message, TaskInput::Bind(_))).then(|| self.task.clone());
 		match message {
+			TaskInput::Bind(item) => {
+				self.bind(*item, widgets, &sender);
+				sender.input(TaskInput::Bound);
+			},
+			TaskInput::Bound => self.binding = false,
+			TaskInput::Remove => sender
+				.output_sender()
+				.send(TaskOutput::Remove(self.task.clone()))
+				.unwrap_or_default(),
 			TaskInput::SetNotes => {
 				self.task.notes = self
 					.notes_buffer
@@ -587,7 +719,7 @@ impl AsyncFactoryComponent for TaskModel {
 				self.preferences.expand_subtasks = expand
 			},
 			TaskInput::SetPriority(priority) => {
-				self.task.priority = priority.into();
+				self.task.priority = priority;
 			},
 			TaskInput::SetCompleted(toggled) => {
 				self.task.status = if toggled {
@@ -705,6 +837,74 @@ impl AsyncFactoryComponent for TaskModel {
 						}
 					});
 			},
+			TaskInput::SaveAsTemplate => {
+				match TemplateStore::new()
+					.create_template(Template::from_task(&self.task))
+				{
+					Ok(_) => sender
+						.output_sender()
+						.send(TaskOutput::Notice(fl!("template-saved").clone()))
+						.unwrap_or_default(),
+					Err(err) => self.fail(&sender, fl!("template-not-saved"), err),
+				}
+			},
+			TaskInput::ToggleTimer => match TimeTracker::new() {
+				Ok(mut tracker) => {
+					if self.time.running_since.is_some() {
+						match tracker.stop(self.task.id.clone()) {
+							Ok(entry) => {
+								if let Some(entry) = entry {
+									self.time.tracked = self.time.tracked + entry.duration();
+								}
+								self.time.running_since = None;
+								if let Some(ticker) = self.ticker.take() {
+									ticker.abort();
+								}
+								self.send_time(&sender);
+							},
+							Err(err) => self.fail(&sender, fl!("timer-not-changed"), err),
+						}
+					} else {
+						match tracker.start(&self.task) {
+							Ok(entry) => {
+								self.time.running_since = Some(entry.start_date_time);
+								self.start_ticker(&sender);
+								self.send_time(&sender);
+							},
+							Err(err) => self.fail(&sender, fl!("timer-not-changed"), err),
+						}
+					}
+				},
+				Err(err) => self.fail(&sender, fl!("timer-not-changed"), err),
+			},
+			TaskInput::SetEstimate(minutes) => {
+				let estimate = (minutes > 0).then_some(minutes);
+				if estimate != self.time.estimate {
+					match TimeTracker::new().and_then(|mut tracker| {
+						tracker.set_estimate(self.task.id.clone(), estimate)
+					}) {
+						Ok(_) => {
+							self.time.estimate = estimate;
+							self.send_time(&sender);
+						},
+						Err(err) => self.fail(&sender, fl!("estimate-not-saved"), err),
+					}
+				}
+			},
+			TaskInput::Tick => {},
+			TaskInput::SetSaved(id, saved) => {
+				if id == self.task.id {
+					self.saved = saved;
+				}
+			},
+			TaskInput::Flush => sender
+				.output_sender()
+				.send(TaskOutput::Flush(self.task.id.clone()))
+				.unwrap_or_default(),
+			TaskInput::Focus => sender
+				.output_sender()
+				.send(TaskOutput::Focus(self.task.clone()))
+				.unwrap_or_default(),
 			TaskInput::RemoveSubTask(index) => {
 				self.task.sub_tasks.remove(index.current_index());
 				self
@@ -714,10 +914,125 @@ impl AsyncFactoryComponent for TaskModel {
 					.unwrap();
 			},
 		}
+		if task.is_some_and(|task| task != self.task) {
+			sender
+				.output_sender()
+				.send(TaskOutput::UpdateTask(self.task.clone()))
+				.unwrap_or_default();
+		}
+		self.update_view(widgets, sender);
+	}
+}
+
+impl TaskModel {
+	/// Shows another task, the widgets that aren't updated on every change
+	/// are set here.
+	fn bind(
+		&mut self,
+		item: TaskItem,
+		widgets: &mut <Self as AsyncComponent>::Widgets,
+		sender: &AsyncComponentSender<Self>,
+	) {
+		self.binding = true;
+		self.task = item.task;
+		self.task.parent = item.parent_list.id.clone();
+		self.parent_list = item.parent_list;
+		self.time = item.time;
+		self.saved = item.saved;
+
+		if let Some(ticker) = self.ticker.take() {
+			ticker.abort();
+		}
+		if self.time.running_since.is_some() {
+			self.start_ticker(sender);
+		}
+
+		self.notes_buffer.set_text(&self.task.notes);
+		{
+			let mut sub_tasks = self.sub_tasks.guard();
+			sub_tasks.clear();
+			for sub_task in &self.task.sub_tasks {
+				sub_tasks.push_back(SubTaskInit::new(sub_task.clone()));
+			}
+		}
+
+		widgets.title.set_text(&self.task.title);
+		widgets
+			.low_importance
+			.set_active(self.task.priority == Priority::Low);
+		widgets
+			.normal_importance
+			.set_active(self.task.priority == Priority::Normal);
+		widgets
+			.high_importance
+			.set_active(self.task.priority == Priority::High);
+		widgets
+			.estimate
+			.set_value(self.time.estimate.unwrap_or_default() as f64);
+		let reminder = self.task.reminder_date.unwrap_or_default();
+		widgets.reminder_hour.set_value(reminder.hour() as f64);
+		widgets.reminder_minute.set_value(reminder.minute() as f64);
+		for (calendar, date) in [
+			(&widgets.reminder_calendar, self.task.reminder_date),
+			(&widgets.due_date_calendar, self.task.due_date),
+		] {
+			let date = date.unwrap_or(Utc::now());
+			calendar.set_year(date.year());
+			calendar.set_month(date.month() as i32 - 1);
+			calendar.set_day(date.day() as i32);
+		}
+	}
+
+	/// Keeps the tracked time of the item, the row may show another task
+	/// by the time it is scrolled back into view.
+	fn send_time(&self, sender: &AsyncComponentSender<Self>) {
 		sender
 			.output_sender()
-			.send(TaskOutput::UpdateTask(self.task.clone()))
+			.send(TaskOutput::Time(self.task.id.clone(), self.time.clone()))
 			.unwrap_or_default();
-		self.update_view(widgets, sender);
+	}
+
+	/// Shows a failure on the content. There is no retry, the row may show
+	/// another task by the time it would run.
+	fn fail(
+		&self,
+		sender: &AsyncComponentSender<Self>,
+		message: &str,
+		error: impl std::fmt::Display,
+	) {
+		sender
+			.output_sender()
+			.send(TaskOutput::Error(Failure::new(message, error)))
+			.unwrap_or_default();
+	}
+
+	fn start_ticker(&mut self, sender: &AsyncComponentSender<Self>) {
+		if self.ticker.is_some() {
+			return;
+		}
+		let sender = sender.input_sender().clone();
+		self.ticker = Some(tokio::spawn(async move {
+			let mut interval =
+				tokio::time::interval(std::time::Duration::from_secs(10));
+			interval.tick().await;
+			loop {
+				interval.tick().await;
+				// The row is gone once nobody listens anymore.
+				if sender.send(TaskInput::Tick).is_err() {
+					break;
+				}
+			}
+		}));
+	}
+}
+
+fn tracked_time_label(tracked_time: Duration, estimate: Option<i32>) -> String {
+	match estimate {
+		Some(estimate) => format!(
+			"{} / {}",
+			format_duration(tracked_time),
+			format_duration(Duration::minutes(estimate as i64))
+		),
+		None => format!("{} {}", format_duration(tracked_time), fl!("tracked")),
 	}
 }
@@ -1,14 +1,22 @@
+use std::{fmt::Display, path::PathBuf};
+
 use relm4::actions::{ActionGroupName, RelmAction, RelmActionGroup};
 use relm4::factory::AsyncFactoryComponent;
 use relm4::factory::{DynamicIndex, FactoryView};
-use relm4::gtk::prelude::{ListBoxRowExt, WidgetExt};
+use relm4::gtk::prelude::{
+	FileExt, GtkApplicationExt, ListBoxRowExt, WidgetExt,
+};
 use relm4::gtk::traits::{BoxExt, GtkWindowExt};
 use relm4::{
 	gtk, AsyncFactorySender, Component, ComponentController, Controller,
 	RelmWidgetExt,
 };
 
+use done_core::models::bundle::ListBundle;
+use done_core::models::list::{List, Sharing};
+use done_core::models::template::Template;
 use done_core::service::Service;
+use done_core::services::local::templates::TemplateStore;
 
 use crate::app::components::delete::{
 	DeleteComponent, DeleteInit, DeleteOutput,
@@ -16,6 +24,7 @@ use crate::app::components::delete::{
 use crate::app::components::list_dialog::{
 	ListDialogComponent, ListDialogOutput,
 };
+use crate::app::models::failure::Failure;
 use crate::app::models::sidebar_list::SidebarList;
 use crate::fl;
 
@@ -34,23 +43,40 @@ pub struct TaskListFactoryInit {
 	pub list: SidebarList,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum TaskListFactoryInput {
 	Select,
 	Delete,
 	RenameList(String),
 	ChangeIcon(String),
+	SaveAsTemplate,
+	Export,
+	ExportTo(PathBuf),
 }
 
 #[derive(Debug)]
 pub enum TaskListFactoryOutput {
 	Select(SidebarList),
 	DeleteTaskList(DynamicIndex),
+	TimeReport(List),
+	Error(Failure),
+	Notice(String),
 }
 
 relm4::new_action_group!(pub(super) TaskListActionGroup, "win");
 relm4::new_stateless_action!(RenameAction, TaskListActionGroup, "rename");
 relm4::new_stateless_action!(DeleteAction, TaskListActionGroup, "delete");
+relm4::new_stateless_action!(
+	TimeReportAction,
+	TaskListActionGroup,
+	"time-report"
+);
+relm4::new_stateless_action!(
+	SaveAsTemplateAction,
+	TaskListActionGroup,
+	"save-as-template"
+);
+relm4::new_stateless_action!(ExportAction, TaskListActionGroup, "export");
 
 #[relm4::factory(pub async)]
 impl AsyncFactoryComponent for TaskListFactoryModel {
@@ -65,6 +91,9 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 		primary_menu: {
 			section! {
 				"Rename" => RenameAction,
+				"Save as template" => SaveAsTemplateAction,
+				"Time report" => TimeReportAction,
+				"Export" => ExportAction,
 				"Delete" => DeleteAction,
 			}
 		}
@@ -97,6 +126,8 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 						set_label: self.list.icon().unwrap_or_default(),
 						#[watch]
 						set_visible: !self.list.smart(),
+						#[watch]
+						set_sensitive: self.list.sharing() != Sharing::SharedWithMe,
 						set_css_classes: &["flat", "image-button"],
 						set_valign: gtk::Align::Center,
 						#[wrap(Some)]
@@ -118,6 +149,16 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 						set_text: self.list.name().as_str(),
 						set_margin_all: 5,
 					},
+					gtk::Image {
+						#[watch]
+						set_visible: self.list.sharing() != Sharing::Private,
+						set_icon_name: Some("emblem-shared-symbolic"),
+						#[watch]
+						set_tooltip: match self.list.sharing() {
+							Sharing::SharedWithMe => fl!("shared-with-me"),
+							_ => fl!("shared-by-me"),
+						},
+					},
 					#[name(list_actions)]
 					gtk::MenuButton {
 						#[watch]
@@ -140,7 +181,8 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 		let rename = ListDialogComponent::builder()
 			.launch(Some(init.list.name()))
 			.forward(sender.input_sender(), |message| match message {
-				ListDialogOutput::AddTaskListToSidebar(_) => {
+				ListDialogOutput::AddTaskListToSidebar(_)
+				| ListDialogOutput::AddTaskListFromTemplate(_, _) => {
 					TaskListFactoryInput::Select
 				},
 				ListDialogOutput::RenameList(name) => {
@@ -189,8 +231,41 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 			})
 		};
 
-		actions.add_action(rename_action);
-		actions.add_action(delete_action);
+		let save_as_template_action = {
+			let sender = sender.clone();
+			RelmAction::<SaveAsTemplateAction>::new_stateless(move |_| {
+				sender.input(TaskListFactoryInput::SaveAsTemplate)
+			})
+		};
+
+		let export_action = {
+			let sender = sender.clone();
+			RelmAction::<ExportAction>::new_stateless(move |_| {
+				sender.input(TaskListFactoryInput::Export)
+			})
+		};
+
+		let time_report_action = {
+			let sender = sender.clone();
+			let list = self.list.clone();
+			RelmAction::<TimeReportAction>::new_stateless(move |_| {
+				if let SidebarList::Custom(list) = &list {
+					sender
+						.output(TaskListFactoryOutput::TimeReport(list.clone()))
+						.unwrap_or_default();
+				}
+			})
+		};
+
+		// Lists shared by someone else can only be renamed or deleted by them,
+		// leaving their actions out disables the menu items.
+		if self.list.sharing() != Sharing::SharedWithMe {
+			actions.add_action(rename_action);
+			actions.add_action(delete_action);
+		}
+		actions.add_action(save_as_template_action);
+		actions.add_action(time_report_action);
+		actions.add_action(export_action);
 
 		widgets.list_actions.insert_action_group(
 			TaskListActionGroup::NAME,
@@ -241,6 +316,69 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 					}
 				}
 			},
+			TaskListFactoryInput::SaveAsTemplate => {
+				if let SidebarList::Custom(list) = &self.list {
+					let mut service = self.service.get_service();
+					match service.read_tasks_from_list(list.id.clone()).await {
+						Ok(tasks) => {
+							let template = Template::from_list(list, &tasks);
+							match TemplateStore::new().create_template(template) {
+								Ok(_) => sender
+									.output(TaskListFactoryOutput::Notice(
+										fl!("template-saved").clone(),
+									))
+									.unwrap_or_default(),
+								Err(err) => fail(
+									&sender,
+									fl!("template-not-saved"),
+									err,
+									TaskListFactoryInput::SaveAsTemplate,
+								),
+							}
+						},
+						Err(err) => fail(
+							&sender,
+							fl!("template-not-saved"),
+							err,
+							TaskListFactoryInput::SaveAsTemplate,
+						),
+					}
+				}
+			},
+			TaskListFactoryInput::Export => {
+				if let SidebarList::Custom(list) = &self.list {
+					let dialog = gtk::FileDialog::new();
+					dialog
+						.set_initial_name(Some(&format!("{}.done-list.json", list.name)));
+					let window = relm4::main_application().active_window();
+					dialog.save(
+						window.as_ref(),
+						gtk::gio::Cancellable::NONE,
+						move |file| {
+							if let Some(path) = file.ok().and_then(|file| file.path()) {
+								sender.input(TaskListFactoryInput::ExportTo(path));
+							}
+						},
+					);
+				}
+			},
+			TaskListFactoryInput::ExportTo(path) => {
+				if let SidebarList::Custom(list) = &self.list {
+					match ListBundle::export(list)
+						.await
+						.and_then(|bundle| bundle.to_json())
+						.and_then(|json| Ok(std::fs::write(&path, json)?))
+					{
+						Ok(_) => tracing::info!("List {} exported.", list.name),
+						Err(err) => fail(
+							&sender,
+							fl!("list-not-exported"),
+							err,
+							TaskListFactoryInput::ExportTo(path.clone()),
+						),
+					}
+				}
+			},
 			TaskListFactoryInput::ChangeIcon(icon) => {
 				if let SidebarList::Custom(list) = &self.list {
 					let mut list = list.clone();
@@ -257,3 +395,18 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 		}
 	}
 }
+
+/// Shows a failure on the content, retrying sends `retry` again.
+fn fail(
+	sender: &AsyncFactorySender<TaskListFactoryModel>,
+	message: &str,
+	error: impl Display,
+	retry: TaskListFactoryInput,
+) {
+	let input = sender.input_sender().clone();
+	let failure =
+		Failure::new(message, error).with_retry(move || input.emit(retry.clone()));
+	sender
+		.output(TaskListFactoryOutput::Error(failure))
+		.unwrap_or_default();
+}
@@ -0,0 +1,46 @@
+use std::{
+	collections::VecDeque,
+	sync::{Mutex, OnceLock},
+};
+
+use chrono::{DateTime, Local};
+
+/// How many errors are kept, the oldest are dropped first.
+const CAPACITY: usize = 100;
+
+/// Errors shown to the user since the app started, for the log in
+/// preferences.
+pub struct ErrorLog;
+
+#[derive(Debug, Clone)]
+pub struct LoggedError {
+	pub date_time: DateTime<Local>,
+	pub message: String,
+}
+
+fn entries() -> &'static Mutex<VecDeque<LoggedError>> {
+	static ENTRIES: OnceLock<Mutex<VecDeque<LoggedError>>> = OnceLock::new();
+	ENTRIES.get_or_init(Default::default)
+}
+
+impl ErrorLog {
+	pub fn record(message: String) {
+		let mut entries = entries().lock().unwrap();
+		if entries.len() == CAPACITY {
+			entries.pop_back();
+		}
+		entries.push_front(LoggedError {
+			date_time: Local::now(),
+			message,
+		});
+	}
+
+	/// The logged errors, newest first.
+	pub fn entries() -> Vec<LoggedError> {
+		entries().lock().unwrap().iter().cloned().collect()
+	}
+
+	pub fn clear() {
+		entries().lock().unwrap().clear();
+	}
+}
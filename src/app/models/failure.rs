@@ -0,0 +1,57 @@
+use std::{
+	fmt::{Debug, Display},
+	sync::Arc,
+};
+
+use relm4::adw;
+
+use crate::fl;
+
+use super::error_log::ErrorLog;
+
+/// An operation that failed, shown to the user as a toast that can try it
+/// again.
+#[derive(Clone)]
+pub struct Failure {
+	message: String,
+	retry: Option<Arc<dyn Fn() + Send + Sync>>,
+}
+
+impl Failure {
+	/// Logs the error, `message` tells the user what couldn't be done.
+	pub fn new(message: &str, error: impl Display) -> Self {
+		tracing::error!("{message}: {error}");
+		ErrorLog::record(format!("{message}: {error}"));
+		Self {
+			message: message.to_string(),
+			retry: None,
+		}
+	}
+
+	pub fn with_retry(
+		mut self,
+		retry: impl Fn() + Send + Sync + 'static,
+	) -> Self {
+		self.retry = Some(Arc::new(retry));
+		self
+	}
+
+	pub fn toast(&self) -> adw::Toast {
+		let toast = adw::Toast::new(&self.message);
+		if let Some(retry) = self.retry.clone() {
+			toast.set_button_label(Some(fl!("retry")));
+			toast.set_priority(adw::ToastPriority::High);
+			toast.connect_button_clicked(move |_| retry());
+		}
+		toast
+	}
+}
+
+impl Debug for Failure {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Failure")
+			.field("message", &self.message)
+			.field("retry", &self.retry.is_some())
+			.finish()
+	}
+}
@@ -1 +1,3 @@
+pub mod error_log;
+pub mod failure;
 pub mod sidebar_list;
@@ -2,7 +2,8 @@ use relm4_icons::icon_name;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-use done_core::models::list::List;
+use done_core::models::list::{List, Sharing};
+use done_core::models::smart_list::SmartList;
 
 use crate::fl;
 
@@ -57,6 +58,10 @@ impl SidebarList {
 			SidebarList::Starred => starred_desc.clone(),
 			SidebarList::Next7Days => next_7_days_desc.clone(),
 			SidebarList::Done => completed_list_desc.clone(),
+			// Lists of services with several accounts show whose they are.
+			SidebarList::Custom(list) if list.description.is_empty() => {
+				list.account.clone().unwrap_or_default()
+			},
 			SidebarList::Custom(list) => list.description.clone(),
 		}
 	}
@@ -72,7 +77,25 @@ impl SidebarList {
 		}
 	}
 
+	pub fn sharing(&self) -> Sharing {
+		match self {
+			SidebarList::Custom(list) => list.sharing,
+			_ => Sharing::Private,
+		}
+	}
+
 	pub fn smart(&self) -> bool {
 		!matches!(self, SidebarList::Custom(_))
 	}
+
+	pub fn smart_list(&self) -> Option<SmartList> {
+		match self {
+			SidebarList::All => Some(SmartList::All),
+			SidebarList::Today => Some(SmartList::Today),
+			SidebarList::Starred => Some(SmartList::Starred),
+			SidebarList::Next7Days => Some(SmartList::Next7Days),
+			SidebarList::Done => Some(SmartList::Done),
+			SidebarList::Custom(_) => None,
+		}
+	}
 }
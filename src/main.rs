@@ -7,7 +7,14 @@ use app::Done;
 mod app;
 
 fn main() -> Result<()> {
-	let app = RelmApp::new(APP_ID);
+	let (profile, args) = setup::select_profile(std::env::args().collect())?;
+	if args.iter().any(|arg| arg == "--microsoft-sign-in") {
+		setup::sign_in_with_device_code(|message| println!("{message}"))?;
+		println!("Signed in to Microsoft To Do.");
+		return Ok(());
+	}
+	// Profiles run as separate applications, so they can be open together.
+	let app = RelmApp::new(&profile.app_id(APP_ID)).with_args(args);
 	setup::init()?;
 	app.run_async::<Done>(());
 	Ok(())